@@ -1,19 +1,28 @@
-use std::{path::PathBuf, process::Command};
+use std::path::{Path, PathBuf};
 
-use clap::{ArgEnum, Parser};
+use clap::{ArgEnum, Parser, Subcommand};
 use image::{ImageBuffer, Rgba};
-use space_thumbnails::{SpaceThumbnailsRenderer, RendererBackend};
+use space_thumbnails::{
+    check_step_shells, convert_step_to_glb, diff_step_files, mesh_mass_properties,
+    read_step_products, step_caption, step_diff_to_glb, GlbOptions, RendererBackend,
+    SpaceThumbnailsRenderer, StepData, DEFAULT_GLB_RESOLUTION,
+};
 
 /// A command line tool for generating thumbnails for 3D model files.
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+
     /// The output file
-    output: PathBuf,
+    #[clap(required = true)]
+    output: Option<PathBuf>,
 
     // The 3D model file for which you want to generate thumbnail.
-    #[clap(short, long)]
-    input: PathBuf,
+    #[clap(short, long, required = true)]
+    input: Option<PathBuf>,
 
     // Specify the backend API
     #[clap(short, long, arg_enum, default_value_t)]
@@ -26,6 +35,79 @@ struct Args {
     // Generated thumbnail height
     #[clap(short, long, default_value_t = 800)]
     height: u32,
+
+    // Write the caption, the products and the mass properties to this JSON file
+    #[clap(long)]
+    metadata: Option<PathBuf>,
+
+    // View STEP files along the principal axes of the model instead of Z-up
+    #[clap(long)]
+    auto_orient: bool,
+
+    // Draw the graphical PMI of AP242 files over the model
+    #[clap(long)]
+    pmi: bool,
+
+    // Draw the name and the revision of the top-level product of STEP files
+    #[clap(long)]
+    caption: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Validate the B-rep shells of a STEP file
+    Check {
+        /// The STEP file to validate
+        input: PathBuf,
+
+        // The tolerance of the distances, the uncertainty of the file by default
+        #[clap(short, long)]
+        tolerance: Option<f64>,
+    },
+    /// Convert a STEP file into a binary glTF file with one node per part
+    Glb {
+        /// The STEP file to convert
+        input: PathBuf,
+
+        /// The output glTF binary file
+        output: PathBuf,
+
+        // The meshes are no finer than the pixels of a square image of this size
+        #[clap(short, long, default_value_t = DEFAULT_GLB_RESOLUTION)]
+        resolution: u32,
+
+        // Turn the model along its principal axes instead of Z-up
+        #[clap(long)]
+        auto_orient: bool,
+
+        // Draw the graphical PMI of AP242 files in a node named PMI
+        #[clap(long)]
+        pmi: bool,
+    },
+    /// Compare two STEP files by the geometry of their shells and faces
+    Diff {
+        /// The old STEP file
+        old: PathBuf,
+
+        /// The new STEP file
+        new: PathBuf,
+
+        // The tolerance of the lengths, the larger uncertainty of the files by default
+        #[clap(short, long)]
+        tolerance: Option<f64>,
+
+        // Render the added faces in green and the removed faces in red to this image
+        #[clap(long)]
+        thumbnail: Option<PathBuf>,
+
+        // The thumbnail width
+        #[clap(long, default_value_t = 800)]
+        width: u32,
+
+        // The thumbnail height
+        #[clap(long, default_value_t = 800)]
+        height: u32,
+    },
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
@@ -42,39 +124,186 @@ impl Default for BackendApi {
     }
 }
 
+fn check(input: &Path, tolerance: Option<f64>) {
+    let validations = match check_step_shells(input, tolerance) {
+        Some(validations) => validations,
+        None => std::process::exit(2),
+    };
+    let mut valid = true;
+    for (idx, validation) in validations {
+        match validation {
+            Ok(validation) => {
+                valid &= validation.is_valid();
+                print!("shell #{}: {}", idx, validation);
+            }
+            Err(e) => {
+                valid = false;
+                println!("shell #{}: failed to convert: {}", idx, e);
+            }
+        }
+    }
+    if !valid {
+        std::process::exit(1);
+    }
+}
+
+fn diff(
+    old: &Path,
+    new: &Path,
+    tolerance: Option<f64>,
+    thumbnail: Option<&Path>,
+    size: (u32, u32),
+) {
+    let diff = match diff_step_files(old, new, tolerance) {
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("Failed to compare STEP files: {}", e);
+            std::process::exit(2);
+        }
+    };
+    print!("{}", diff);
+    if let Some(thumbnail) = thumbnail {
+        let (width, height) = size;
+        let glb = step_diff_to_glb(&diff, "diff");
+        let mut renderer = SpaceThumbnailsRenderer::new(RendererBackend::Default, width, height);
+        if renderer.load_asset_from_memory(&glb, "diff.glb").is_none() {
+            eprintln!("Failed to load the differences");
+            std::process::exit(2);
+        }
+        let mut screenshot_buffer = vec![0; renderer.get_screenshot_size_in_byte()];
+        renderer.take_screenshot_sync(screenshot_buffer.as_mut_slice());
+        let image = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, screenshot_buffer).unwrap();
+        image.save(thumbnail).unwrap();
+    }
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 fn main() {
     let args = Args::parse();
 
-    let input = match args
-        .input
+    match &args.command {
+        Some(Commands::Check { input, tolerance }) => {
+            check(input, *tolerance);
+            return;
+        }
+        Some(Commands::Glb {
+            input,
+            output,
+            resolution,
+            auto_orient,
+            pmi,
+        }) => {
+            let options = GlbOptions {
+                resolution: *resolution,
+                auto_orient: *auto_orient,
+                pmi: *pmi,
+            };
+            if let Err(e) = convert_step_to_glb(input, output, &options) {
+                eprintln!("Failed to convert STEP file to glTF: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Diff {
+            old,
+            new,
+            tolerance,
+            thumbnail,
+            width,
+            height,
+        }) => {
+            diff(old, new, *tolerance, thumbnail.as_deref(), (*width, *height));
+            return;
+        }
+        None => {}
+    }
+    let (output, input) = (args.output.unwrap(), args.input.unwrap());
+    let mut caption = None;
+
+    let input = match input
         .extension()
         .and_then(|s| s.to_str())
         .map(|s| s.to_ascii_lowercase())
         .as_deref()
     {
         Some("stp") | Some("step") => {
-            let mut converted = args.input.clone();
-            converted.set_extension("obj");
-
-            let mut cmd = Command::new("cmd");
-            cmd.arg("/C")
-                .arg("step2obj.bat")
-                .env("STEP2OBJ_INPUT", &args.input)
-                .env("STEP2OBJ_OUTPUT", &converted);
-            
-            let status = cmd.status().expect("failed to execute step2obj command");
-
-            if !status.success() {
-                eprintln!(
-                    "Failed to convert STEP file with step2obj, exit code: {:?}",
-                    status.code()
-                );
-                std::process::exit(1);
+            // the file is parsed once for the metadata and the conversion into glTF.
+            let data = match args.metadata.is_some() || args.auto_orient || args.pmi {
+                true => StepData::read(&input),
+                false => None,
+            };
+            let products = match (&data, args.caption) {
+                (Some(data), _) => data.products(),
+                (None, true) => read_step_products(&input),
+                (None, false) => Vec::new(),
+            };
+            if args.caption {
+                caption = step_caption(&products);
+            }
+            if let Some(metadata) = &args.metadata {
+                let mass_properties = data.as_ref().map(|data| {
+                    let properties = data.mass_properties();
+                    serde_json::json!({
+                        "total": properties.total,
+                        "products": properties.products,
+                        "instances": properties.instances,
+                    })
+                });
+                let pmi = data.as_ref().map(|data| {
+                    let pmi = data.pmi();
+                    serde_json::json!({
+                        "tolerances": pmi.tolerances,
+                        "dimensions": pmi.dimensions,
+                        "datums": pmi.datums,
+                    })
+                });
+                let json = serde_json::json!({
+                    "caption": step_caption(&products),
+                    "products": products,
+                    "mass_properties": mass_properties,
+                    "pmi": pmi,
+                });
+                std::fs::write(metadata, serde_json::to_string_pretty(&json).unwrap()).unwrap();
             }
 
-            converted
+            if args.auto_orient || args.pmi {
+                let mut converted = input.clone();
+                converted.set_extension("glb");
+                let options = GlbOptions {
+                    auto_orient: args.auto_orient,
+                    pmi: args.pmi,
+                    ..Default::default()
+                };
+                let Some(data) = &data else {
+                    eprintln!("Failed to convert STEP file to glTF");
+                    std::process::exit(1);
+                };
+                let name = input
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default();
+                if let Err(e) = std::fs::write(&converted, data.to_glb(name, &options)) {
+                    eprintln!("Failed to convert STEP file to glTF: {}", e);
+                    std::process::exit(1);
+                }
+                converted
+            } else {
+                // the renderer triangulates the B-rep shells of the STEP file itself
+                input
+            }
+        }
+        Some("obj") | Some("stl") => {
+            if let Some(metadata) = &args.metadata {
+                let json = serde_json::json!({
+                    "mass_properties": mesh_mass_properties(&input),
+                });
+                std::fs::write(metadata, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+            }
+            input
         }
-        _ => args.input.clone(),
+        _ => input,
     };
 
     let mut renderer = SpaceThumbnailsRenderer::new(
@@ -88,9 +317,10 @@ fn main() {
         args.height,
     );
     renderer.load_asset_from_file(&input).expect("Failed to load converted asset");
+    renderer.set_caption(caption);
     let mut screenshot_buffer = vec![0; renderer.get_screenshot_size_in_byte()];
     renderer.take_screenshot_sync(screenshot_buffer.as_mut_slice());
 
     let image = ImageBuffer::<Rgba<u8>, _>::from_raw(args.width, args.height, screenshot_buffer).unwrap();
-    image.save(args.output).unwrap();
+    image.save(output).unwrap();
 }
@@ -0,0 +1,55 @@
+//! Draws the captions on the screenshots with the 8x8 bitmap font.
+//!
+//! The caption is written in white on a darkened band along the bottom of the image.
+//! The characters out of the basic Latin block are drawn as `?`, and the captions
+//! longer than the image are cut with `...`.
+
+use font8x8::{UnicodeFonts, BASIC_FONTS};
+
+/// The side of a glyph of the font in pixels
+const GLYPH_SIZE: usize = 8;
+
+/// The opacity of the band behind the caption.
+const BAND_ALPHA: u8 = 160;
+
+/// Draws `caption` on the RGBA image of `width` x `height` pixels, rows from the top.
+pub(crate) fn draw_caption(image: &mut [u8], width: u32, height: u32, caption: &str) {
+    let (width, height) = (width as usize, height as usize);
+    let scale = usize::max(width.min(height) / 256, 1);
+    let (glyph, margin) = (GLYPH_SIZE * scale, 2 * scale);
+    let band = glyph + 2 * margin;
+    if band > height || band > width || image.len() < width * height * 4 {
+        return;
+    }
+
+    let mut chars: Vec<char> = caption.chars().collect();
+    let max_chars = (width - 2 * margin) / glyph;
+    if chars.len() > max_chars {
+        chars.truncate(max_chars.saturating_sub(3));
+        chars.extend("...".chars());
+        chars.truncate(max_chars);
+    }
+
+    let top = height - band;
+    for pixel in image[top * width * 4..height * width * 4].chunks_exact_mut(4) {
+        pixel[..3].iter_mut().for_each(|c| *c /= 3);
+        pixel[3] = pixel[3].max(BAND_ALPHA);
+    }
+
+    for (i, c) in chars.into_iter().enumerate() {
+        let rows = BASIC_FONTS
+            .get(c)
+            .or_else(|| BASIC_FONTS.get('?'))
+            .unwrap_or_default();
+        let left = margin + i * glyph;
+        for (y, row) in rows.iter().enumerate() {
+            for x in (0..GLYPH_SIZE).filter(|x| row & (1 << x) != 0) {
+                for (dy, dx) in (0..scale).flat_map(|dy| (0..scale).map(move |dx| (dy, dx))) {
+                    let (px, py) = (left + x * scale + dx, top + margin + y * scale + dy);
+                    let idx = (py * width + px) * 4;
+                    image[idx..idx + 4].copy_from_slice(&[255, 255, 255, 255]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,799 @@
+//! Converts STEP files into binary glTF.
+//!
+//! Each product becomes a node named after it, the components of the assemblies become
+//! the child nodes placed by the transforms of the occurrences, and the shells of each part
+//! are triangulated into one mesh with a PBR material per STEP colour.
+//! The graphical PMI of AP242 files is optionally drawn as unlit lines and triangles.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde_json::{json, Value};
+use truck_meshalgo::{
+    filters::{NormalFilters, OptimizingFilter},
+    tessellation::MeshedShape,
+};
+use truck_stepio::r#in::{
+    alias::{Matrix4, PolygonMesh, SquareMatrix, StandardVertex},
+    diff::{ShellSignature, StepDiff},
+    mass::MassProperties,
+    product::{product_instances, StepProduct, StepProductOccurrence},
+    style::StepStyledItem,
+    tessellation::{triangulate_converted_shells, ViewCamera, ViewTolerance},
+    Table,
+};
+
+use crate::{mass::placed_meshes, StepData, DEFAULT_GLB_RESOLUTION};
+
+/// The colour of the shells without STEP colours, the default grey of CAD systems.
+const DEFAULT_COLOUR: [f64; 3] = [0.8, 0.8, 0.8];
+
+/// The colour of the PMI, dark blue as drawn by CAD systems.
+const PMI_COLOUR: [f64; 3] = [0.1, 0.2, 0.7];
+
+/// The colours of the overlays of the differences: the unchanged faces are translucent.
+const UNCHANGED: Material = Material {
+    colour: DEFAULT_COLOUR,
+    transparency: 0.7,
+};
+const ADDED: Material = Material {
+    colour: [0.2, 0.8, 0.2],
+    transparency: 0.0,
+};
+const REMOVED: Material = Material {
+    colour: [0.9, 0.2, 0.2],
+    transparency: 0.0,
+};
+
+/// The relative difference of the principal moments below which the axes are not used.
+const PRINCIPAL_RATIO: f64 = 1.0e-3;
+
+/// The depth of the assemblies at which the nodes are cut, in case of cyclic occurrences.
+const MAX_DEPTH: usize = 64;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const LINES: u32 = 1;
+const TRIANGLES: u32 = 4;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+/// Material of the glTF file, made from a STEP colour
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+struct Material {
+    colour: [f64; 3],
+    transparency: f64,
+}
+
+impl Material {
+    fn from_style(style: Option<&StepStyledItem>) -> Self {
+        Self {
+            colour: style
+                .and_then(|style| style.colour)
+                .unwrap_or(DEFAULT_COLOUR),
+            transparency: style.and_then(|style| style.transparency).unwrap_or(0.0),
+        }
+    }
+
+    /// the key for grouping, since `f64` is not `Ord`
+    fn key(&self) -> [u64; 4] {
+        let [r, g, b] = self.colour;
+        [r, g, b, self.transparency].map(f64::to_bits)
+    }
+
+    fn to_json(self) -> Value {
+        // STEP colours are in sRGB, the base colours of glTF are linear.
+        let [r, g, b] = self.colour.map(srgb_to_linear);
+        let alpha = 1.0 - self.transparency.clamp(0.0, 1.0);
+        let mut material = json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": [r, g, b, alpha],
+                "metallicFactor": 0.0,
+                "roughnessFactor": 0.5,
+            },
+        });
+        if alpha < 1.0 {
+            material["alphaMode"] = json!("BLEND");
+            material["doubleSided"] = json!(true);
+        }
+        material
+    }
+}
+
+fn srgb_to_linear(c: f64) -> f64 {
+    let c = c.clamp(0.0, 1.0);
+    match c <= 0.04045 {
+        true => c / 12.92,
+        false => ((c + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+/// Accumulates the buffers, the accessors and the meshes of the glTF file.
+#[derive(Debug, Default)]
+struct GlbBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    materials: Vec<Value>,
+    material_indices: HashMap<[u64; 4], usize>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    /// `KHR_materials_unlit` is used
+    unlit: bool,
+}
+
+impl GlbBuilder {
+    fn push_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(bytes);
+        // all components are 4 bytes, so the views are kept aligned.
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer_views.len() - 1
+    }
+
+    fn push_vec3s(&mut self, vectors: &[[f32; 3]], bounds: bool) -> usize {
+        let bytes: Vec<u8> = vectors
+            .iter()
+            .flatten()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let view = self.push_view(&bytes, ARRAY_BUFFER);
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": vectors.len(),
+            "type": "VEC3",
+        });
+        // the bounds are required for the positions.
+        if bounds {
+            let (mut min, mut max) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+            vectors.iter().for_each(|v| {
+                (0..3).for_each(|i| {
+                    min[i] = f32::min(min[i], v[i]);
+                    max[i] = f32::max(max[i], v[i]);
+                })
+            });
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn material(&mut self, material: Material) -> usize {
+        let materials = &mut self.materials;
+        *self
+            .material_indices
+            .entry(material.key())
+            .or_insert_with(|| {
+                materials.push(material.to_json());
+                materials.len() - 1
+            })
+    }
+
+    /// Pushes the triangles of `mesh` as a primitive, `None` if there are no triangles.
+    fn primitive(&mut self, mesh: &PolygonMesh, material: Material) -> Option<Value> {
+        let (positions, normals) = (mesh.positions(), mesh.normals());
+        let mut vertex_map = HashMap::<(usize, Option<usize>), u32>::new();
+        let (mut points, mut vectors, mut indices) = (Vec::new(), Vec::new(), Vec::new());
+        let mut push_vertex = |v: StandardVertex| {
+            let nor = v.nor.filter(|nor| *nor < normals.len());
+            *vertex_map.entry((v.pos, nor)).or_insert_with(|| {
+                let p = positions[v.pos];
+                let n = nor
+                    .map(|nor| normals[nor])
+                    .unwrap_or_else(|| [0.0; 3].into());
+                points.push([p[0] as f32, p[1] as f32, p[2] as f32]);
+                vectors.push([n[0] as f32, n[1] as f32, n[2] as f32]);
+                points.len() as u32 - 1
+            })
+        };
+        mesh.faces()
+            .triangle_iter()
+            .filter(|[a, b, c]| a.pos != b.pos && b.pos != c.pos && c.pos != a.pos)
+            .for_each(|tri| indices.extend(tri.map(&mut push_vertex)));
+        if indices.is_empty() {
+            return None;
+        }
+        let position = self.push_vec3s(&points, true);
+        let normal = self.push_vec3s(&vectors, false);
+        let indices = self.push_indices(&indices);
+        Some(json!({
+            "attributes": { "POSITION": position, "NORMAL": normal },
+            "indices": indices,
+            "material": self.material(material),
+        }))
+    }
+
+    /// Pushes a mesh with one primitive per material, `None` if there are no triangles.
+    fn mesh(&mut self, name: &str, meshes: Vec<(Material, PolygonMesh)>) -> Option<usize> {
+        let mut groups = BTreeMap::<[u64; 4], (Material, PolygonMesh)>::new();
+        meshes.into_iter().for_each(|(material, mesh)| {
+            groups
+                .entry(material.key())
+                .or_insert_with(|| (material, PolygonMesh::default()))
+                .1
+                .merge(mesh)
+        });
+        let primitives: Vec<Value> = groups
+            .into_values()
+            .filter_map(|(material, mut mesh)| {
+                // the tessellated shells may have no normals.
+                if mesh
+                    .faces()
+                    .triangle_iter()
+                    .flatten()
+                    .any(|v| v.nor.is_none())
+                {
+                    mesh.add_naive_normals(true);
+                }
+                self.primitive(&mesh, material)
+            })
+            .collect();
+        if primitives.is_empty() {
+            return None;
+        }
+        self.meshes
+            .push(json!({ "name": name, "primitives": primitives }));
+        Some(self.meshes.len() - 1)
+    }
+
+    /// Pushes the segments of the polylines and the triangles with an unlit material,
+    /// `None` if there are neither.
+    fn annotation_mesh(
+        &mut self,
+        name: &str,
+        polylines: &[Vec<[f64; 3]>],
+        triangles: &[[[f64; 3]; 3]],
+        colour: [f64; 3],
+    ) -> Option<usize> {
+        if polylines.iter().all(|polyline| polyline.len() < 2) && triangles.is_empty() {
+            return None;
+        }
+        let [r, g, b] = colour.map(srgb_to_linear);
+        self.materials.push(json!({
+            "pbrMetallicRoughness": { "baseColorFactor": [r, g, b, 1.0] },
+            "doubleSided": true,
+            "extensions": { "KHR_materials_unlit": {} },
+        }));
+        self.unlit = true;
+        let material = self.materials.len() - 1;
+        let to_f32 = |p: &[f64; 3]| p.map(|x| x as f32);
+
+        let mut primitives = Vec::new();
+        let points: Vec<[f32; 3]> = polylines.iter().flatten().map(to_f32).collect();
+        let mut indices = Vec::new();
+        let mut offset = 0;
+        polylines.iter().for_each(|polyline| {
+            let len = polyline.len() as u32;
+            (1..len).for_each(|i| indices.extend([offset + i - 1, offset + i]));
+            offset += len;
+        });
+        if !indices.is_empty() {
+            let position = self.push_vec3s(&points, true);
+            let indices = self.push_indices(&indices);
+            primitives.push(json!({
+                "attributes": { "POSITION": position },
+                "indices": indices,
+                "material": material,
+                "mode": LINES,
+            }));
+        }
+        if !triangles.is_empty() {
+            let points: Vec<[f32; 3]> = triangles.iter().flatten().map(to_f32).collect();
+            let position = self.push_vec3s(&points, true);
+            primitives.push(json!({
+                "attributes": { "POSITION": position },
+                "material": material,
+                "mode": TRIANGLES,
+            }));
+        }
+        self.meshes
+            .push(json!({ "name": name, "primitives": primitives }));
+        Some(self.meshes.len() - 1)
+    }
+
+    fn node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Assembles the JSON chunk and the binary chunk into a GLB file.
+    fn into_glb(self, root: usize) -> Vec<u8> {
+        let mut gltf = json!({
+            "asset": { "version": "2.0", "generator": "space-thumbnails" },
+            "scene": 0,
+            "scenes": [{ "nodes": [root] }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+        });
+        if !self.buffer.is_empty() {
+            gltf["buffers"] = json!([{ "byteLength": self.buffer.len() }]);
+        }
+        if self.unlit {
+            gltf["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+        }
+        glb_container(&serde_json::to_vec(&gltf).unwrap(), &self.buffer)
+    }
+}
+
+/// Writes the JSON chunk and the binary chunk with the header of GLB.
+///
+/// The JSON chunk is padded with spaces and the binary chunk with zeros to 4 bytes.
+fn glb_container(json: &[u8], bin: &[u8]) -> Vec<u8> {
+    let pad = |len: usize| (4 - len % 4) % 4;
+    let json_len = json.len() + pad(json.len());
+    let bin_len = bin.len() + pad(bin.len());
+    let mut total = 12 + 8 + json_len;
+    if !bin.is_empty() {
+        total += 8 + bin_len;
+    }
+    let mut glb = Vec::with_capacity(total);
+    [
+        GLB_MAGIC,
+        GLB_VERSION,
+        total as u32,
+        json_len as u32,
+        CHUNK_JSON,
+    ]
+    .iter()
+    .for_each(|x| glb.extend(x.to_le_bytes()));
+    glb.extend_from_slice(json);
+    glb.resize(glb.len() + pad(json.len()), b' ');
+    if !bin.is_empty() {
+        [bin_len as u32, CHUNK_BIN]
+            .iter()
+            .for_each(|x| glb.extend(x.to_le_bytes()));
+        glb.extend_from_slice(bin);
+        glb.resize(glb.len() + pad(bin.len()), 0);
+    }
+    glb
+}
+
+/// The nodes of the products, reached from the top-level products through the occurrences.
+struct ProductTree<'a> {
+    products: HashMap<u64, &'a StepProduct>,
+    occurrences: &'a [StepProductOccurrence],
+    /// the meshes of the products with their own shells
+    meshes: HashMap<u64, usize>,
+}
+
+impl ProductTree<'_> {
+    fn node(&self, builder: &mut GlbBuilder, product: u64, name: String, depth: usize) -> usize {
+        let children: Vec<usize> = match depth < MAX_DEPTH {
+            true => self
+                .occurrences
+                .iter()
+                .filter(|occurrence| occurrence.parent == product)
+                .map(|occurrence| {
+                    let name = match occurrence.name.is_empty() {
+                        true => self.caption(occurrence.child),
+                        false => occurrence.name.clone(),
+                    };
+                    let child = self.node(builder, occurrence.child, name, depth + 1);
+                    if occurrence.transform != Matrix4::identity() {
+                        let matrix: &[f64; 16] = occurrence.transform.as_ref();
+                        builder.nodes[child]["matrix"] = json!(matrix);
+                    }
+                    child
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+        let mut node = json!({ "name": name });
+        if let Some(mesh) = self.meshes.get(&product) {
+            node["mesh"] = json!(mesh);
+        }
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        builder.node(node)
+    }
+
+    fn caption(&self, product: u64) -> String {
+        self.products
+            .get(&product)
+            .map(|product| product.caption())
+            .unwrap_or_default()
+    }
+}
+
+/// Triangulates the B-rep shells for the view and reads the tessellated shells.
+///
+/// The B-rep shells of the products with the tessellated shells are skipped not to be drawn
+/// twice. The shells failed to be converted are reported and skipped.
+pub(crate) fn shell_meshes(table: &Table, view: &ViewTolerance) -> HashMap<u64, PolygonMesh> {
+    let tessellated: HashSet<u64> = table.shells_with_tessellation().into_iter().collect();
+    let mut shells: Vec<_> = table
+        .shell
+        .iter()
+        .filter(|(idx, _)| !tessellated.contains(idx))
+        .collect();
+    shells.sort_by_key(|(idx, _)| **idx);
+    let shells = shells
+        .into_iter()
+        .map(|(idx, shell)| (*idx, table.to_compressed_shell(shell)))
+        .collect();
+    let mut meshes: HashMap<u64, PolygonMesh> = triangulate_converted_shells(shells, view)
+        .into_iter()
+        .filter_map(|(idx, shell)| match shell {
+            Ok(shell) => {
+                let mut mesh = shell.to_polygon();
+                mesh.put_together_same_attrs(1.0e-7)
+                    .remove_degenerate_faces()
+                    .remove_unused_attrs();
+                Some((idx, mesh))
+            }
+            Err(e) => {
+                eprintln!("the shell #{idx} is skipped: {e}");
+                None
+            }
+        })
+        .collect();
+    table
+        .tessellated_shell
+        .iter()
+        .for_each(|(idx, shell)| match table.to_polygon_mesh(shell) {
+            Ok(mesh) => {
+                meshes.insert(*idx, mesh);
+            }
+            Err(e) => eprintln!("the tessellated shell #{idx} is skipped: {e}"),
+        });
+    meshes
+}
+
+/// The quaternion, `[x, y, z, w]` as glTF, of the rotation given by the matrix.
+fn quaternion(m: [[f64; 3]; 3]) -> [f64; 4] {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    if trace > 0.0 {
+        let s = 2.0 * f64::sqrt(trace + 1.0);
+        [
+            (m[2][1] - m[1][2]) / s,
+            (m[0][2] - m[2][0]) / s,
+            (m[1][0] - m[0][1]) / s,
+            s / 4.0,
+        ]
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = 2.0 * f64::sqrt(1.0 + m[0][0] - m[1][1] - m[2][2]);
+        [
+            s / 4.0,
+            (m[0][1] + m[1][0]) / s,
+            (m[0][2] + m[2][0]) / s,
+            (m[2][1] - m[1][2]) / s,
+        ]
+    } else if m[1][1] > m[2][2] {
+        let s = 2.0 * f64::sqrt(1.0 + m[1][1] - m[0][0] - m[2][2]);
+        [
+            (m[0][1] + m[1][0]) / s,
+            s / 4.0,
+            (m[1][2] + m[2][1]) / s,
+            (m[0][2] - m[2][0]) / s,
+        ]
+    } else {
+        let s = 2.0 * f64::sqrt(1.0 + m[2][2] - m[0][0] - m[1][1]);
+        [
+            (m[0][2] + m[2][0]) / s,
+            (m[1][2] + m[2][1]) / s,
+            s / 4.0,
+            (m[1][0] - m[0][1]) / s,
+        ]
+    }
+}
+
+/// The rotation taking the principal axes of the least and the largest moments to X and Y,
+/// `None` if the moments are too close to tell the axes, e.g. for cubes and spheres.
+pub(crate) fn principal_rotation(properties: &MassProperties) -> Option<[f64; 4]> {
+    let [least, _, largest] = properties.principal_moments;
+    if largest - least <= PRINCIPAL_RATIO * largest {
+        return None;
+    }
+    let [x, _, y] = properties.principal_axes;
+    let z = [
+        x[1] * y[2] - x[2] * y[1],
+        x[2] * y[0] - x[0] * y[2],
+        x[0] * y[1] - x[1] * y[0],
+    ];
+    // the rows are the images of the axes, so the matrix takes them to X, Y and Z.
+    Some(quaternion([x, y, z]))
+}
+
+/// Options of the conversion into GLB files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlbOptions {
+    /// the meshes are no finer than the pixels of a square image of this size
+    pub resolution: u32,
+    /// turns the model along its principal axes, see [`step_to_glb`]
+    pub auto_orient: bool,
+    /// draws the graphical PMI of AP242 files
+    pub pmi: bool,
+}
+
+impl Default for GlbOptions {
+    fn default() -> Self {
+        Self {
+            resolution: DEFAULT_GLB_RESOLUTION,
+            auto_orient: false,
+            pmi: false,
+        }
+    }
+}
+
+/// Converts the STEP data into a GLB file.
+///
+/// The shells are triangulated no finer than the pixels of a square image of `resolution` pixels.
+/// The model is converted to metres and Y-up, as glTF requires.
+/// With `auto_orient`, the model is turned so that the principal axes of the least and the largest
+/// moments of inertia of the assembled model, with all the instances of the parts, become X and Y,
+/// e.g. a plate lies flat with its long side across the view.
+/// With `pmi`, the annotations are drawn in a node named `PMI`.
+pub fn step_to_glb(step_string: &str, name: &str, options: &GlbOptions) -> Result<Vec<u8>, String> {
+    Ok(StepData::from_step(step_string)?.to_glb(name, options))
+}
+
+impl StepData {
+    /// Converts the parsed STEP file into a GLB file, see [`step_to_glb`].
+    pub fn to_glb(&self, name: &str, options: &GlbOptions) -> Vec<u8> {
+        let table = &self.table;
+        let units = self.units.clone().unwrap_or_default();
+        let styles = table.shell_styles();
+        let material = |idx: u64| Material::from_style(styles.get(&idx));
+
+        let resolution = options.resolution;
+        let view = ViewTolerance::new((resolution, resolution), ViewCamera::Fit);
+        let meshes = shell_meshes(table, &view);
+        let products = table.products();
+        let occurrences = table.product_occurrences();
+        // Z-up to Y-up: the rotation by -90 degrees around the X axis.
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let mut rotation = [-half, 0.0, 0.0, half];
+        if options.auto_orient {
+            let instances = product_instances(&products, &occurrences);
+            let placed = placed_meshes(&meshes, &products, &instances);
+            let properties = MassProperties::from_placed_meshes(placed);
+            if let Some(principal) = principal_rotation(&properties) {
+                rotation = principal;
+            }
+        }
+        let shells: HashMap<u64, (Material, PolygonMesh)> = meshes
+            .into_iter()
+            .map(|(idx, mesh)| (idx, (material(idx), mesh)))
+            .collect();
+
+        let mut builder = GlbBuilder::default();
+        let mut used = HashSet::new();
+        let meshes = products
+            .iter()
+            .filter_map(|product| {
+                let meshes: Vec<_> = product
+                    .shells
+                    .iter()
+                    .chain(&product.tessellated_shells)
+                    .filter_map(|idx| {
+                        used.insert(*idx);
+                        shells.get(idx).cloned()
+                    })
+                    .collect();
+                let mesh = builder.mesh(&product.caption(), meshes)?;
+                Some((product.entity_id, mesh))
+            })
+            .collect();
+        let tree = ProductTree {
+            products: products
+                .iter()
+                .map(|product| (product.entity_id, product))
+                .collect(),
+            occurrences: &occurrences,
+            meshes,
+        };
+        let mut children: Vec<usize> = products
+            .iter()
+            .filter(|product| product.top_level)
+            .map(|product| tree.node(&mut builder, product.entity_id, product.caption(), 0))
+            .collect();
+        // the shells out of the product structure, e.g. in files without products.
+        let mut rest: Vec<(u64, (Material, PolygonMesh))> = shells
+            .into_iter()
+            .filter(|(idx, _)| !used.contains(idx))
+            .collect();
+        rest.sort_by_key(|(idx, _)| *idx);
+        let rest = rest.into_iter().map(|(_, mesh)| mesh).collect();
+        if let Some(mesh) = builder.mesh(name, rest) {
+            children.push(builder.node(json!({ "name": name, "mesh": mesh })));
+        }
+        if options.pmi {
+            let pmi = self.pmi();
+            let polylines: Vec<_> = pmi
+                .annotations
+                .iter()
+                .flat_map(|annotation| annotation.polylines.iter().cloned())
+                .collect();
+            let triangles: Vec<_> = pmi
+                .annotations
+                .iter()
+                .flat_map(|annotation| annotation.triangles.iter().copied())
+                .collect();
+            if let Some(mesh) = builder.annotation_mesh("PMI", &polylines, &triangles, PMI_COLOUR) {
+                children.push(builder.node(json!({ "name": "PMI", "mesh": mesh })));
+            }
+        }
+
+        let scale = [units.length; 3];
+        let root = builder.node(json!({
+            "name": name,
+            "rotation": rotation,
+            "scale": scale,
+            "children": children,
+        }));
+        builder.into_glb(root)
+    }
+}
+
+/// Converts the difference of two STEP files into a GLB file overlaying the two models.
+///
+/// The added faces are green, the removed faces are red and the unchanged faces are translucent.
+/// The model stays in the length unit of the files, turned to Y-up.
+pub fn step_diff_to_glb(diff: &StepDiff, name: &str) -> Vec<u8> {
+    let mut meshes = Vec::new();
+    let mut push = |shell: &ShellSignature, faces: &mut dyn Iterator<Item = usize>, material| {
+        faces.for_each(|i| meshes.push((material, shell.faces[i].mesh.clone())))
+    };
+    for pair in &diff.unchanged {
+        let shell = &diff.new[pair.new];
+        push(shell, &mut (0..shell.faces.len()), UNCHANGED);
+    }
+    for pair in &diff.modified {
+        let (old, new) = (&diff.old[pair.old], &diff.new[pair.new]);
+        push(
+            new,
+            &mut pair.faces.matched.iter().map(|(_, j)| *j),
+            UNCHANGED,
+        );
+        push(new, &mut pair.faces.added.iter().copied(), ADDED);
+        push(old, &mut pair.faces.removed.iter().copied(), REMOVED);
+    }
+    for i in &diff.added {
+        let shell = &diff.new[*i];
+        push(shell, &mut (0..shell.faces.len()), ADDED);
+    }
+    for i in &diff.removed {
+        let shell = &diff.old[*i];
+        push(shell, &mut (0..shell.faces.len()), REMOVED);
+    }
+
+    let mut builder = GlbBuilder::default();
+    let children: Vec<usize> = builder
+        .mesh(name, meshes)
+        .map(|mesh| builder.node(json!({ "name": name, "mesh": mesh })))
+        .into_iter()
+        .collect();
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let root = builder.node(json!({
+        "name": name,
+        "rotation": [-half, 0.0, 0.0, half],
+        "children": children,
+    }));
+    builder.into_glb(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glb_chunks_are_aligned() {
+        let json = br#"{"asset":{"version":"2.0"}}"#;
+        let bin = [1u8, 2, 3, 4, 5];
+        let glb = glb_container(json, &bin);
+        let word = |i: usize| u32::from_le_bytes(glb[i..i + 4].try_into().unwrap());
+        assert_eq!(word(0), GLB_MAGIC);
+        assert_eq!(word(4), GLB_VERSION);
+        assert_eq!(word(8) as usize, glb.len());
+        let json_len = word(12) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(word(16), CHUNK_JSON);
+        assert_eq!(&glb[20..20 + json.len()], json);
+        let bin_start = 20 + json_len;
+        assert_eq!(word(bin_start) as usize, 8);
+        assert_eq!(word(bin_start + 4), CHUNK_BIN);
+        assert_eq!(&glb[bin_start + 8..bin_start + 13], &bin);
+        assert_eq!(glb.len(), bin_start + 16);
+    }
+
+    #[test]
+    fn colours_are_linearized() {
+        let material = Material {
+            colour: [1.0, 0.5, 0.0],
+            transparency: 0.25,
+        };
+        let json = material.to_json();
+        let factor = &json["pbrMetallicRoughness"]["baseColorFactor"];
+        assert_eq!(factor[0], 1.0);
+        assert!((factor[1].as_f64().unwrap() - 0.214).abs() < 1.0e-3);
+        assert_eq!(factor[2], 0.0);
+        assert_eq!(factor[3], 0.75);
+        assert_eq!(json["alphaMode"], "BLEND");
+    }
+
+    #[test]
+    fn principal_axes_to_glb_axes() {
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let z_up = quaternion([[1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]]);
+        assert_eq!(z_up, [-half, 0.0, 0.0, half]);
+
+        // a plate standing in the YZ plane, long along Z: Z becomes X and -X becomes Y.
+        let properties = MassProperties {
+            volume: 1.0,
+            area: 1.0,
+            centre_of_mass: [0.0; 3],
+            inertia: [[0.0; 3]; 3],
+            principal_moments: [1.0, 4.0, 5.0],
+            principal_axes: [[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]],
+        };
+        let [x, y, z, w] = principal_rotation(&properties).unwrap();
+        assert!([x + 0.5, y - 0.5, z + 0.5, w - 0.5]
+            .iter()
+            .all(|d| d.abs() < 1.0e-12));
+
+        let properties = MassProperties {
+            principal_moments: [1.0, 1.0, 1.0],
+            ..properties
+        };
+        assert_eq!(principal_rotation(&properties), None);
+    }
+
+    #[test]
+    fn annotations_are_unlit_lines_and_triangles() {
+        let mut builder = GlbBuilder::default();
+        assert_eq!(builder.annotation_mesh("PMI", &[], &[], PMI_COLOUR), None);
+
+        let polylines = [
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+            vec![[0.0, 2.0, 0.0], [0.0, 3.0, 0.0]],
+        ];
+        let triangles = [[[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]]];
+        let mesh = builder.annotation_mesh("PMI", &polylines, &triangles, PMI_COLOUR);
+        assert_eq!(mesh, Some(0));
+        let primitives = &builder.meshes[0]["primitives"];
+        assert_eq!(primitives[0]["mode"], LINES);
+        let indices = primitives[0]["indices"].as_u64().unwrap() as usize;
+        assert_eq!(builder.accessors[indices]["count"], 6);
+        assert_eq!(primitives[1]["mode"], TRIANGLES);
+        assert!(primitives[1].get("indices").is_none());
+        let material = &builder.materials[0];
+        assert!(material["extensions"].get("KHR_materials_unlit").is_some());
+        let gltf: Value = {
+            let root = builder.node(json!({ "name": "PMI", "mesh": 0 }));
+            let glb = builder.into_glb(root);
+            let len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+            serde_json::from_slice(&glb[20..20 + len]).unwrap()
+        };
+        assert_eq!(gltf["extensionsUsed"][0], "KHR_materials_unlit");
+    }
+}
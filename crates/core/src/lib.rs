@@ -18,10 +18,29 @@ use filament_bindings::{
 
 // use truck_meshalgo::tessellation::{MeshedShape, RobustMeshableShape};
 // use truck_polymesh::PolygonMesh;
-// use truck_stepio::r#in::Table;
+use truck_stepio::r#in::{
+    alias::PolygonMesh,
+    ruststep::{self, ast::DataSection},
+    stream::{StreamOptions, PRODUCT_STRUCTURE_TYPES},
+    units::StepUnitContext,
+    Table,
+};
+use truck_stepio::iges::{obj, IgesFile, ViewCamera, ViewTolerance};
 use rayon::prelude::*;
 use std::panic::{self, AssertUnwindSafe};
 
+mod caption;
+mod glb;
+mod mass;
+
+pub use glb::{step_diff_to_glb, step_to_glb, GlbOptions};
+pub use mass::{mesh_mass_properties, step_mass_properties, StepMassProperties};
+pub use truck_stepio::r#in::mass::MassProperties;
+pub use truck_stepio::r#in::pmi::StepPmi;
+pub use truck_stepio::r#in::diff::StepDiff;
+pub use truck_stepio::r#in::product::StepProduct;
+pub use truck_stepio::r#in::validation::ShellValidation;
+
 const IDL_TEXTURE_DATA: &'static [u8] = include_bytes!("lightroom_14b_ibl.ktx");
 
 const ASSIMP_FLAGS: u32 = post_process::GEN_SMOOTH_NORMALS
@@ -45,6 +64,9 @@ pub struct SpaceThumbnailsRenderer {
     sunlight_entity: Entity,
     view: View,
     destory_asset: Option<Box<dyn FnOnce(&mut Engine, &mut Scene)>>,
+    step_products: Vec<StepProduct>,
+    draw_caption: bool,
+    caption: Option<String>,
 
     viewport: Viewport,
 }
@@ -142,6 +164,9 @@ impl SpaceThumbnailsRenderer {
                 sunlight_entity,
                 view,
                 destory_asset: None,
+                step_products: Vec::new(),
+                draw_caption: false,
+                caption: None,
                 viewport,
             }
         }
@@ -160,6 +185,8 @@ impl SpaceThumbnailsRenderer {
              let ext = filepath.as_ref().extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase());
              if matches!(ext.as_deref(), Some("stp") | Some("step")) {
                  self.load_step_asset(filepath)
+             } else if matches!(ext.as_deref(), Some("igs") | Some("iges")) {
+                 self.load_iges_asset(filepath)
              } else {
                 eprintln!("DEBUG: Fallback to Assimp for {:?}", filepath.as_ref());
                 let asset_res = AssimpAsset::from_file_with_flags(&mut self.engine, filepath, ASSIMP_FLAGS);
@@ -177,49 +204,98 @@ impl SpaceThumbnailsRenderer {
     pub fn load_step_asset(&mut self, filepath: impl AsRef<Path>) -> Option<&mut Self> {
         eprintln!("Start reading file: {:?}", filepath.as_ref());
         let start = std::time::Instant::now();
-
-        // Temporary file for OBJ output
-        let out_path = std::env::temp_dir().join(format!("space_thumbnails_{}.obj", uuid::Uuid::new_v4()));
-        let out_path_str = out_path.to_str()?;
-        let in_path_str = filepath.as_ref().to_str()?;
-
-        // Absolute path to the bat script (HARDCODED for this environment as requested)
-        let bat_script = r"d:\Users\Shomn\OneDrive - MSFT\Source\Repos\space-thumbnails\tools\step2obj.bat";
-
-        eprintln!("Converting STEP to OBJ using FreeCAD...");
-        let status = std::process::Command::new("cmd")
-            .arg("/C")
-            .arg(bat_script)
-            .env("STEP2OBJ_INPUT", in_path_str)
-            .env("STEP2OBJ_OUTPUT", out_path_str)
-            .status();
-
-        match status {
-            Ok(s) if s.success() => {
-                eprintln!("Conversion successful in {:?}.", start.elapsed());
-                // Load the generated OBJ
-                let obj_bytes = match fs::read(&out_path) {
-                    Ok(b) => b,
-                    Err(e) => {
-                        eprintln!("Failed to read generated OBJ: {:?}", e);
-                        return None;
-                    }
+        // the products and their shells are read in the same pass as the pre-tessellated shapes
+        let options =
+            StreamOptions::tessellated_preview().with_entity_types(&PRODUCT_STRUCTURE_TYPES);
+        let table = fs::File::open(filepath.as_ref()).and_then(|file| {
+            Table::from_reader_with_options(std::io::BufReader::new(file), &options)
+        });
+        let (step_products, tessellation) = match &table {
+            Ok(table) => {
+                let products = match self.draw_caption {
+                    true => table.products(),
+                    false => Vec::new(),
                 };
-                
-                // Cleanup temp file
-                let _ = fs::remove_file(&out_path);
+                // the B-rep shells of the products with pre-tessellated shapes need no tessellation
+                let covered = table.shells_with_tessellation().len() == table.shell.len();
+                let tessellation = match covered {
+                    true => step_tessellation_obj(table),
+                    false => None,
+                };
+                (products, tessellation)
+            }
+            Err(e) => {
+                eprintln!("Failed to read STEP file: {:?}", e);
+                (Vec::new(), None)
+            }
+        };
+
+        if let Some(obj_bytes) = tessellation {
+            eprintln!("Read the pre-tessellated shapes in {:?}.", start.elapsed());
+            self.load_asset_from_memory(&obj_bytes, "converted.obj")?;
+            self.set_step_products(step_products);
+            return Some(self);
+        }
 
-                self.load_asset_from_memory(&obj_bytes, "converted.obj")
+        // the file is read in full only when some B-rep shell has no pre-tessellated counterpart,
+        // and the shells are triangulated no finer than the pixels of the thumbnail
+        let view = ViewTolerance::new((self.viewport.width, self.viewport.height), ViewCamera::Fit);
+        let table = fs::File::open(filepath.as_ref())
+            .and_then(|file| Table::from_reader(std::io::BufReader::new(file)));
+        let table = match table {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Failed to read STEP file: {:?}", e);
+                return None;
             }
-            Ok(s) => {
-                eprintln!("Conversion failed with exit code: {:?}", s.code());
-                None
+        };
+        let obj_bytes = match step_shells_obj(&table, &view) {
+            Some(obj_bytes) => obj_bytes,
+            None => {
+                eprintln!("The STEP file has no shell to be drawn");
+                return None;
             }
+        };
+        eprintln!("Conversion successful in {:?}.", start.elapsed());
+
+        self.load_asset_from_memory(&obj_bytes, "converted.obj")?;
+        self.set_step_products(step_products);
+        Some(self)
+    }
+
+    pub fn load_iges_asset(&mut self, filepath: impl AsRef<Path>) -> Option<&mut Self> {
+        eprintln!("Start reading file: {:?}", filepath.as_ref());
+        let start = std::time::Instant::now();
+        let file = match fs::File::open(filepath.as_ref()) {
+            Ok(file) => file,
             Err(e) => {
-                eprintln!("Failed to execute conversion script: {:?}", e);
-                None
+                eprintln!("Failed to open IGES file: {:?}", e);
+                return None;
             }
+        };
+        let iges = match IgesFile::from_reader(std::io::BufReader::new(file)) {
+            Ok(iges) => iges,
+            Err(e) => {
+                eprintln!("Failed to parse IGES file: {}", e);
+                return None;
+            }
+        };
+
+        // Triangulate no finer than the pixels of the thumbnail
+        let view = ViewTolerance::new((self.viewport.width, self.viewport.height), ViewCamera::Fit);
+        let (polygon, skipped) = iges.to_polygon_mesh_for_view_with_skipped(&view);
+        for (pointer, e) in skipped {
+            eprintln!("The IGES entity {} is skipped: {}", pointer, e);
+        }
+        let mut obj_bytes = Vec::new();
+        if let Err(e) = obj::write(&polygon, &mut obj_bytes) {
+            eprintln!("Failed to write OBJ: {:?}", e);
+            return None;
         }
+        eprintln!("Conversion successful in {:?}.", start.elapsed());
+
+        self.load_asset_from_memory(&obj_bytes, "converted.obj")?;
+        Some(self)
     }
 
     pub fn load_asset_from_memory(
@@ -417,6 +493,11 @@ impl SpaceThumbnailsRenderer {
             }
         }
 
+        if let Some(caption) = &self.caption {
+            let (width, height) = self.get_size();
+            caption::draw_caption(output_memory, width, height, caption);
+        }
+
         byte_count
     }
 
@@ -428,7 +509,39 @@ impl SpaceThumbnailsRenderer {
         (self.viewport.width * self.viewport.height * 4) as usize
     }
 
+    /// Products of the loaded STEP file, empty for the other formats or without the caption.
+    pub fn get_step_products(&self) -> &[StepProduct] {
+        &self.step_products
+    }
+
+    /// Reads the products of the STEP files and draws the caption on the screenshots.
+    ///
+    /// Off by default: the product structure is then not read at all.
+    pub fn set_draw_caption(&mut self, draw_caption: bool) -> &mut Self {
+        self.draw_caption = draw_caption;
+        self
+    }
+
+    /// Draws `caption` on the screenshots of the loaded asset, e.g. the caption of the STEP file
+    /// from which the asset was converted.
+    pub fn set_caption(&mut self, caption: Option<String>) -> &mut Self {
+        self.caption = caption;
+        self
+    }
+
+    /// Caption drawn on the screenshots: the name and the revision of the top-level product.
+    pub fn get_caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    fn set_step_products(&mut self, step_products: Vec<StepProduct>) {
+        self.caption = step_caption(&step_products);
+        self.step_products = step_products;
+    }
+
     pub fn destory_opened_asset(&mut self) -> &mut Self {
+        self.step_products.clear();
+        self.caption = None;
         let destory_asset = self.destory_asset.take();
         if let Some(destory) = destory_asset {
             destory(&mut self.engine, &mut self.scene)
@@ -481,6 +594,223 @@ fn fit_into_unit_cube(bounds: &Aabb) -> Mat4f {
         * Mat4f::translation(center * -1.0)
 }
 
+/// Reads the products of the STEP file without reading the geometry.
+pub fn read_step_products(filepath: impl AsRef<Path>) -> Vec<StepProduct> {
+    let table = fs::File::open(filepath).and_then(|file| {
+        Table::from_reader_with_options(file, &StreamOptions::product_structure())
+    });
+    match table {
+        Ok(table) => table.products(),
+        Err(e) => {
+            eprintln!("Failed to read STEP products: {:?}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Reads the pre-tessellated shapes of the AP242 file into OBJ,
+/// `None` if the file has no tessellated shell.
+///
+/// Only the tessellated entities are read, so the B-rep shapes are neither parsed nor meshed.
+pub fn read_step_tessellation(filepath: impl AsRef<Path>) -> Option<Vec<u8>> {
+    let table = fs::File::open(filepath)
+        .and_then(|file| {
+            Table::from_reader_with_options(
+                std::io::BufReader::new(file),
+                &StreamOptions::tessellated_preview(),
+            )
+        })
+        .ok()?;
+    step_tessellation_obj(&table)
+}
+
+fn step_tessellation_obj(table: &Table) -> Option<Vec<u8>> {
+    let mut shells: Vec<_> = table.tessellated_shell.iter().collect();
+    shells.sort_by_key(|(idx, _)| **idx);
+    let meshes = shells
+        .into_iter()
+        .filter_map(|(idx, shell)| match table.to_polygon_mesh(shell) {
+            Ok(mesh) => Some(mesh),
+            Err(e) => {
+                eprintln!("The tessellated shell #{idx} is skipped: {e}");
+                None
+            }
+        });
+    meshes_obj(meshes)
+}
+
+/// Triangulates the B-rep shells for `view` and reads the tessellated shells into OBJ,
+/// `None` if the table has no shell to be drawn.
+fn step_shells_obj(table: &Table, view: &ViewTolerance) -> Option<Vec<u8>> {
+    let mut meshes: Vec<_> = glb::shell_meshes(table, view).into_iter().collect();
+    meshes.sort_by_key(|(idx, _)| *idx);
+    meshes_obj(meshes.into_iter().map(|(_, mesh)| mesh))
+}
+
+/// Merges the meshes into OBJ, `None` if there is no vertex.
+fn meshes_obj(meshes: impl IntoIterator<Item = PolygonMesh>) -> Option<Vec<u8>> {
+    let mut meshes = meshes.into_iter();
+    let mut polygon = meshes.next()?;
+    meshes.for_each(|mesh| polygon.merge(mesh));
+    if polygon.positions().is_empty() {
+        return None;
+    }
+    let mut obj_bytes = Vec::new();
+    obj::write(&polygon, &mut obj_bytes).ok()?;
+    Some(obj_bytes)
+}
+
+/// The caption of the top-level product, e.g. `gearbox rev. C`.
+pub fn step_caption(products: &[StepProduct]) -> Option<String> {
+    products
+        .iter()
+        .find(|product| product.top_level)
+        .map(StepProduct::caption)
+}
+
+/// The tolerance of the validation when the STEP file has no uncertainty.
+pub const DEFAULT_STEP_TOLERANCE: f64 = 1.0e-6;
+
+/// A STEP file parsed once, so that the products, the mass properties, the PMI
+/// and the glTF conversion are read from the same data.
+#[derive(Clone, Debug)]
+pub struct StepData {
+    /// the first data section of the file
+    pub data_section: DataSection,
+    /// the entities of the data section
+    pub table: Table,
+    /// the units and the uncertainty of the geometry, `None` if the file has no context
+    pub units: Option<StepUnitContext>,
+}
+
+impl StepData {
+    /// Reads the table and the units of the data section.
+    pub fn from_data_section(data_section: DataSection) -> Self {
+        let units = StepUnitContext::from_data_section(&data_section);
+        let table = Table::from_data_section(&data_section);
+        Self {
+            data_section,
+            table,
+            units,
+        }
+    }
+
+    /// Parses the STEP string and reads its first data section.
+    pub fn from_step(step_string: &str) -> Result<Self, String> {
+        let exchange = ruststep::parser::parse(step_string).map_err(|e| e.to_string())?;
+        let data_section = exchange.data.into_iter().next().ok_or("no data section")?;
+        Ok(Self::from_data_section(data_section))
+    }
+
+    /// Reads and parses the STEP file, reporting the failures.
+    pub fn read(filepath: impl AsRef<Path>) -> Option<Self> {
+        let step_string = match fs::read_to_string(filepath) {
+            Ok(step_string) => step_string,
+            Err(e) => {
+                eprintln!("Failed to read STEP file: {:?}", e);
+                return None;
+            }
+        };
+        match Self::from_step(&step_string) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                eprintln!("Failed to parse STEP file: {}", e);
+                None
+            }
+        }
+    }
+
+    /// The products with their shells, sorted by the entity ids.
+    pub fn products(&self) -> Vec<StepProduct> {
+        self.table.products()
+    }
+
+    /// The annotations, the tolerances, the dimensions and the datums of AP242.
+    pub fn pmi(&self) -> StepPmi {
+        StepPmi::from_data_section(&self.data_section)
+    }
+}
+
+/// Reads the annotations, the tolerances, the dimensions and the datums of the AP242 file.
+///
+/// Returns `None` if the file cannot be read.
+pub fn read_step_pmi(filepath: impl AsRef<Path>) -> Option<StepPmi> {
+    Some(StepData::read(filepath)?.pmi())
+}
+
+/// Validates the B-rep shells of the STEP file, sorted by the entity ids of the shells.
+///
+/// The distances are checked against `tolerance`, or the uncertainty of the file if it is `None`.
+/// Returns `None` if the file cannot be read.
+pub fn check_step_shells(
+    filepath: impl AsRef<Path>,
+    tolerance: Option<f64>,
+) -> Option<Vec<(u64, Result<ShellValidation, String>)>> {
+    let StepData { table, units, .. } = StepData::read(filepath)?;
+    let tolerance = tolerance
+        .or_else(|| units?.uncertainty)
+        .unwrap_or(DEFAULT_STEP_TOLERANCE);
+    let validations = table
+        .validate_shells(tolerance)
+        .into_iter()
+        .map(|(idx, validation)| (idx, validation.map_err(|e| e.to_string())))
+        .collect();
+    Some(validations)
+}
+
+/// Compares the shapes of two revisions of a STEP file, matching them by the geometry.
+///
+/// The lengths are compared within `tolerance`, or the larger uncertainty of the files
+/// if it is `None`. Fails if either file cannot be read, or if the length units differ.
+pub fn diff_step_files(
+    old: impl AsRef<Path>,
+    new: impl AsRef<Path>,
+    tolerance: Option<f64>,
+) -> Result<StepDiff, String> {
+    let read = |filepath: &Path| -> Result<StepData, String> {
+        let step_string = fs::read_to_string(filepath).map_err(|e| e.to_string())?;
+        StepData::from_step(&step_string)
+    };
+    let (old, new) = (read(old.as_ref())?, read(new.as_ref())?);
+    let old_units = old.units.clone().unwrap_or_default();
+    let new_units = new.units.clone().unwrap_or_default();
+    // the geometry of the table cannot be rescaled, and shapes in different units never match
+    if old_units.length != new_units.length {
+        return Err(format!(
+            "the length units differ: {} and {}",
+            old_units.length_name, new_units.length_name
+        ));
+    }
+    let tolerance = tolerance
+        .or_else(|| match (old_units.uncertainty, new_units.uncertainty) {
+            (Some(a), Some(b)) => Some(f64::max(a, b)),
+            (a, b) => a.or(b),
+        })
+        .unwrap_or(DEFAULT_STEP_TOLERANCE);
+    Ok(old.table.diff(&new.table, tolerance))
+}
+
+/// The resolution for which the meshes of glTF files are triangulated.
+pub const DEFAULT_GLB_RESOLUTION: u32 = 2048;
+
+/// Converts the STEP file into a binary glTF file with one node per part.
+///
+/// See [`step_to_glb`] for the options.
+pub fn convert_step_to_glb(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &GlbOptions,
+) -> Result<(), String> {
+    let step_string = fs::read_to_string(&input).map_err(|e| e.to_string())?;
+    let data = StepData::from_step(&step_string)?;
+    let name = input
+        .as_ref()
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default();
+    fs::write(output, data.to_glb(name, options)).map_err(|e| e.to_string())
+}
+
 fn is_base64_data_uri(uri: &str) -> bool {
     uri.starts_with("data:") && uri.find(";base64,").is_some()
 }
@@ -0,0 +1,177 @@
+//! Reads the mass properties of the parts, in metres and with the unit density.
+//!
+//! The STEP shells are triangulated as for the glTF files. The shells of the parts are counted
+//! at every placement of the parts in the assemblies, moved by the placements.
+//! The mesh files are merged at their shared vertices and must be closed.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use truck_meshalgo::filters::OptimizingFilter;
+use truck_stepio::r#in::{
+    alias::{obj, stl, Matrix4, PolygonMesh, SquareMatrix},
+    mass::MassProperties,
+    product::{product_instances, StepProduct},
+    tessellation::{ViewCamera, ViewTolerance},
+};
+
+use crate::{glb::shell_meshes, StepData, DEFAULT_GLB_RESOLUTION};
+
+/// The distance within which the vertices of the mesh files are merged.
+const MERGE_TOLERANCE: f64 = 1.0e-7;
+
+/// The mass properties of a STEP file
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepMassProperties {
+    /// the whole model with all the instances of the parts
+    pub total: MassProperties,
+    /// one instance of each product with its own shells, by the entity ids of the products
+    pub products: BTreeMap<u64, MassProperties>,
+    /// the numbers of the instances of the products in the model, by the entity ids
+    pub instances: BTreeMap<u64, usize>,
+}
+
+/// the shells and the tessellated shells of the product itself
+fn own_shells(product: &StepProduct) -> impl Iterator<Item = &u64> {
+    product.shells.iter().chain(&product.tessellated_shells)
+}
+
+/// The meshes placed in the model: the shells of the products at each of the `instances`,
+/// and the shells out of the product structure as they are.
+pub(crate) fn placed_meshes<'a>(
+    meshes: &'a HashMap<u64, PolygonMesh>,
+    products: &[StepProduct],
+    instances: &[(u64, Matrix4)],
+) -> Vec<(&'a PolygonMesh, Matrix4)> {
+    let products: HashMap<u64, &StepProduct> = products
+        .iter()
+        .map(|product| (product.entity_id, product))
+        .collect();
+    let mut placed: Vec<(&PolygonMesh, Matrix4)> = instances
+        .iter()
+        .filter_map(|(idx, transform)| Some((*products.get(idx)?, *transform)))
+        .flat_map(|(product, transform)| {
+            own_shells(product)
+                .filter_map(|idx| meshes.get(idx))
+                .map(move |mesh| (mesh, transform))
+        })
+        .collect();
+    let used: HashSet<u64> = products
+        .values()
+        .flat_map(|p| own_shells(p))
+        .copied()
+        .collect();
+    let mut rest: Vec<(&u64, &PolygonMesh)> = meshes
+        .iter()
+        .filter(|(idx, _)| !used.contains(idx))
+        .collect();
+    rest.sort_by_key(|(idx, _)| **idx);
+    placed.extend(
+        rest.into_iter()
+            .map(|(_, mesh)| (mesh, Matrix4::identity())),
+    );
+    placed
+}
+
+/// Computes the mass properties of the STEP file, converted into metres.
+///
+/// Returns `None` if the file cannot be read.
+pub fn step_mass_properties(filepath: impl AsRef<Path>) -> Option<StepMassProperties> {
+    Some(StepData::read(filepath)?.mass_properties())
+}
+
+impl StepData {
+    /// Computes the mass properties of the parsed STEP file, converted into metres.
+    pub fn mass_properties(&self) -> StepMassProperties {
+        let table = &self.table;
+        let length = self.units.clone().unwrap_or_default().length;
+        let view = ViewTolerance::new(
+            (DEFAULT_GLB_RESOLUTION, DEFAULT_GLB_RESOLUTION),
+            ViewCamera::Fit,
+        );
+        let meshes = shell_meshes(table, &view);
+        let products = table.products();
+        let instances = product_instances(&products, &table.product_occurrences());
+        let own_properties = products
+            .iter()
+            .filter_map(|product| {
+                let meshes: Vec<&PolygonMesh> = own_shells(product)
+                    .filter_map(|idx| meshes.get(idx))
+                    .collect();
+                match meshes.is_empty() {
+                    true => None,
+                    false => {
+                        let properties = MassProperties::from_meshes(meshes).scaled(length);
+                        Some((product.entity_id, properties))
+                    }
+                }
+            })
+            .collect();
+        let mut counts = BTreeMap::new();
+        instances
+            .iter()
+            .for_each(|(idx, _)| *counts.entry(*idx).or_default() += 1);
+        let total =
+            MassProperties::from_placed_meshes(placed_meshes(&meshes, &products, &instances));
+        StepMassProperties {
+            total: total.scaled(length),
+            products: own_properties,
+            instances: counts,
+        }
+    }
+}
+
+/// Returns `true` if every edge of the triangles is shared by exactly two triangles
+/// running it in the opposite directions.
+fn is_closed(mesh: &PolygonMesh) -> bool {
+    let mut edges = HashMap::<(usize, usize), i32>::new();
+    mesh.faces().triangle_iter().for_each(|tri| {
+        (0..3).for_each(|i| {
+            let (a, b) = (tri[i].pos, tri[(i + 1) % 3].pos);
+            match a < b {
+                true => *edges.entry((a, b)).or_default() += 1,
+                false => *edges.entry((b, a)).or_default() -= 1,
+            }
+        })
+    });
+    !edges.is_empty() && edges.values().all(|count| *count == 0)
+}
+
+/// Computes the mass properties of the OBJ or STL file, in the unit of the file.
+///
+/// Returns `None` if the file cannot be read or the mesh is not closed.
+pub fn mesh_mass_properties(filepath: impl AsRef<Path>) -> Option<MassProperties> {
+    let filepath = filepath.as_ref();
+    let extension = filepath
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase());
+    let file = match fs::File::open(filepath) {
+        Ok(file) => std::io::BufReader::new(file),
+        Err(e) => {
+            eprintln!("Failed to open mesh file: {:?}", e);
+            return None;
+        }
+    };
+    let mesh = match extension.as_deref() {
+        Some("obj") => obj::read(file).map_err(|e| e.to_string()),
+        Some("stl") => stl::read(file, stl::StlType::Automatic).map_err(|e| e.to_string()),
+        _ => Err("only OBJ and STL files are supported".to_string()),
+    };
+    let mut mesh = match mesh {
+        Ok(mesh) => mesh,
+        Err(e) => {
+            eprintln!("Failed to read mesh file: {}", e);
+            return None;
+        }
+    };
+    mesh.put_together_same_attrs(MERGE_TOLERANCE);
+    if !is_closed(&mesh) {
+        eprintln!("The mesh is not closed, the mass properties are not computed");
+        return None;
+    }
+    Some(MassProperties::from_polygon_mesh(&mesh))
+}
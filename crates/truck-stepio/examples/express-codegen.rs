@@ -0,0 +1,488 @@
+//! Generate the entity holders of `truck_stepio::in` from the bundled EXPRESS schema.
+//!
+//! The holders of the entities listed in `express/holders.txt` are written to
+//! `src/in/generated.rs` together with the dispatch of `Table::push_instance`,
+//! and their fields of `Table` are written into the marked region of `src/in/mod.rs`.
+//! The entities with hand-written holders are referred to, not generated.
+//! The schema is parsed by `espr`, the EXPRESS compiler of `ruststep`.
+
+use clap::Parser;
+use espr::ast::{AttributeDecl, Entity, EntityAttribute, SimpleType, SyntaxTree, Type};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// only check that the generated sources are up to date
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() {
+    let Args { check } = Args::parse();
+    let files = generate_files(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(2)
+    });
+    let mut outdated = false;
+    for (path, content) in files {
+        if std::fs::read_to_string(&path).ok().as_ref() == Some(&content) {
+            continue;
+        }
+        match check {
+            true => {
+                eprintln!("{} is outdated", path.display());
+                outdated = true;
+            }
+            false => {
+                std::fs::write(&path, content).unwrap();
+                println!("{} is updated", path.display());
+            }
+        }
+    }
+    if outdated {
+        std::process::exit(1);
+    }
+}
+
+/// the EXPRESS schema
+pub const SCHEMA_PATH: &str = "express/10303-04x.exp";
+/// the list of the generated entities
+pub const CONFIG_PATH: &str = "express/holders.txt";
+/// the module of the generated holders
+pub const MODULE_PATH: &str = "src/in/generated.rs";
+/// the module which has the hand-written holders and `Table`
+pub const TABLE_PATH: &str = "src/in/mod.rs";
+
+const TABLE_BEGIN: &str = "    // generated holders, see `generated.rs`";
+const TABLE_END: &str = "    // end of generated holders";
+
+/// Generates the sources in the crate directory `dir`.
+///
+/// Returns the pairs of the paths and the contents to be written.
+pub fn generate_files(dir: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let read = |path: &str| {
+        std::fs::read_to_string(dir.join(path)).map_err(|e| format!("failed to read {path}: {e}"))
+    };
+    let (module, table) = generate(&read(SCHEMA_PATH)?, &read(CONFIG_PATH)?, &read(TABLE_PATH)?)?;
+    Ok(vec![
+        (dir.join(MODULE_PATH), module),
+        (dir.join(TABLE_PATH), table),
+    ])
+}
+
+/// Generates the holders module and the table module with the generated fields.
+///
+/// - `schema`: the EXPRESS schema
+/// - `config`: the entity names, one per line. `#` starts a comment.
+/// - `table`: the module with the hand-written holders and `Table`
+pub fn generate(schema: &str, config: &str, table: &str) -> Result<(String, String), String> {
+    let syntax_tree =
+        SyntaxTree::parse(schema).map_err(|e| format!("failed to parse the schema: {e:?}"))?;
+    let schema = Schema::new(&syntax_tree);
+    let handwritten = Handwritten::scan(table);
+    let entities: Vec<&str> = config
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let mut generator = Generator {
+        schema: &schema,
+        handwritten: &handwritten,
+        entities: entities.iter().map(|name| name.to_string()).collect(),
+        enums: BTreeSet::new(),
+    };
+    let mut structs = Vec::new();
+    for name in &entities {
+        structs.push(generator.holder(name)?);
+    }
+    let module = generator.module(&structs)?;
+    let table = splice_table(table, &entities)?;
+    Ok((module, table))
+}
+
+/// the entities and the types of all the schemas by the names
+#[derive(Clone, Debug, Default)]
+struct Schema<'a> {
+    entities: BTreeMap<String, &'a Entity>,
+    types: BTreeMap<String, &'a Type>,
+}
+
+impl<'a> Schema<'a> {
+    fn new(syntax_tree: &'a SyntaxTree) -> Self {
+        let mut schema = Schema::default();
+        for s in &syntax_tree.schemas {
+            let entities = s.entities.iter().map(|e| (e.name.to_lowercase(), e));
+            schema.entities.extend(entities);
+            let types = s
+                .types
+                .iter()
+                .map(|t| (t.type_id.to_lowercase(), &t.underlying_type));
+            schema.types.extend(types);
+        }
+        schema
+    }
+
+    fn entity(&self, name: &str) -> Result<&'a Entity, String> {
+        self.entities
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("entity `{name}` is not found in the schema"))
+    }
+
+    /// The explicit attributes of `name` including the inherited ones, supertypes first,
+    /// with the names of the entities which declare them.
+    ///
+    /// The redeclarations of the inherited attributes, `SELF\entity.attribute`, are skipped.
+    fn attributes(&self, name: &str) -> Result<Vec<(String, &'a EntityAttribute)>, String> {
+        fn collect<'a>(
+            schema: &Schema<'a>,
+            name: &str,
+            visited: &mut BTreeSet<String>,
+            attributes: &mut Vec<(String, &'a EntityAttribute)>,
+        ) -> Result<(), String> {
+            if !visited.insert(name.to_string()) {
+                return Ok(());
+            }
+            let entity = schema.entity(name)?;
+            if let Some(subtype) = &entity.subtype {
+                for supertype in &subtype.entity_references {
+                    collect(schema, &supertype.to_lowercase(), visited, attributes)?;
+                }
+            }
+            let explicit = entity
+                .attributes
+                .iter()
+                .filter_map(|attr| match &attr.name {
+                    AttributeDecl::Reference(_) => Some((name.to_string(), attr)),
+                    AttributeDecl::Qualified { .. } => None,
+                });
+            attributes.extend(explicit);
+            Ok(())
+        }
+        let mut attributes = Vec::new();
+        collect(self, name, &mut BTreeSet::new(), &mut attributes)?;
+        match self.rederived(name, &mut BTreeSet::new())? {
+            Some((owner, attr)) => Err(format!(
+                "`{name}` redeclares `{owner}.{attr}` as derived, which is not supported"
+            )),
+            None => Ok(attributes),
+        }
+    }
+
+    /// the first inherited attribute which is redeclared as derived by `name` or its supertypes
+    fn rederived(
+        &self,
+        name: &str,
+        visited: &mut BTreeSet<String>,
+    ) -> Result<Option<(String, String)>, String> {
+        if !visited.insert(name.to_string()) {
+            return Ok(None);
+        }
+        let entity = self.entity(name)?;
+        let derived = entity
+            .derive_clause
+            .iter()
+            .flat_map(|clause| &clause.attributes);
+        for derived in derived {
+            if let AttributeDecl::Qualified {
+                group, attribute, ..
+            } = &derived.attr
+            {
+                return Ok(Some((group.to_lowercase(), attribute.to_lowercase())));
+            }
+        }
+        for supertype in entity.subtype.iter().flat_map(|s| &s.entity_references) {
+            if let Some(rederived) = self.rederived(&supertype.to_lowercase(), visited)? {
+                return Ok(Some(rederived));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// the name of an explicit attribute
+fn attribute_name(attr: &EntityAttribute) -> String {
+    match &attr.name {
+        AttributeDecl::Reference(name) => name.to_lowercase(),
+        AttributeDecl::Qualified { attribute, .. } => attribute.to_lowercase(),
+    }
+}
+
+/// the hand-written holders and enums
+#[derive(Clone, Debug, Default)]
+struct Handwritten {
+    /// the holder structs by the table fields
+    holders: BTreeMap<String, String>,
+    enums: BTreeSet<String>,
+}
+
+impl Handwritten {
+    fn scan(source: &str) -> Self {
+        let mut handwritten = Handwritten::default();
+        let mut field = None;
+        for line in source.lines() {
+            if let Some(rest) = line.strip_prefix("#[holder(field = ") {
+                field = Some(rest.trim_end_matches(")]").to_string());
+            } else if let Some(rest) = line.strip_prefix("pub struct ") {
+                let name = rest.split(|c: char| !c.is_alphanumeric()).next();
+                if let (Some(field), Some(name)) = (field.take(), name) {
+                    handwritten.holders.insert(field, name.to_string());
+                }
+            } else if let Some(rest) = line.strip_prefix("pub enum ") {
+                let name = rest.split(|c: char| !c.is_alphanumeric()).next();
+                handwritten.enums.extend(name.map(str::to_string));
+            }
+        }
+        handwritten
+    }
+}
+
+/// `composite_curve_segment` -> `CompositeCurveSegment`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn field_name(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "box", "const", "crate", "dyn", "enum", "fn", "impl", "in", "loop", "match", "mod",
+        "move", "mut", "ref", "self", "static", "struct", "super", "trait", "type", "use", "where",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("{name}_"),
+        false => name.to_string(),
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema<'a>,
+    handwritten: &'a Handwritten,
+    /// the generated entities
+    entities: BTreeSet<String>,
+    /// the enumerations which should be generated
+    enums: BTreeSet<String>,
+}
+
+/// the generated type of an attribute, with whether it needs `use_place_holder`
+type RustType = (String, bool);
+
+impl Generator<'_> {
+    fn resolve(&mut self, ty: &Type) -> Result<RustType, String> {
+        let name = match ty {
+            Type::Set { base, .. }
+            | Type::Bag { base, .. }
+            | Type::List { base, .. }
+            | Type::Array { base, .. } => {
+                let (element, place_holder) = self.resolve(base)?;
+                return Ok((format!("Vec<{element}>"), place_holder));
+            }
+            Type::Simple(simple) => {
+                let simple = match simple {
+                    SimpleType::Real | SimpleType::Number => "f64",
+                    SimpleType::Integer => "i64",
+                    SimpleType::String_ => "String",
+                    SimpleType::Boolen => "bool",
+                    SimpleType::Logical => "Logical",
+                    SimpleType::Binary => return Err("`BINARY` is not supported".to_string()),
+                };
+                return Ok((simple.to_string(), false));
+            }
+            Type::Named(name) => name.to_lowercase(),
+            _ => return Err(format!("the type `{ty:?}` is not supported")),
+        };
+        let schema = self.schema;
+        if let Some(underlying) = schema.types.get(&name) {
+            return match underlying {
+                Type::Enumeration { items, .. } if items.is_empty() => Err(format!(
+                    "the extensible enumeration `{name}` is not supported"
+                )),
+                Type::Enumeration { .. } => {
+                    let enum_name = camel_case(&name);
+                    if !self.handwritten.enums.contains(&enum_name) {
+                        self.enums.insert(name);
+                    }
+                    Ok((enum_name, false))
+                }
+                Type::Select { .. } => match self.is_entity_select(&name) {
+                    true => Ok(("Dummy".to_string(), true)),
+                    false => Err(format!(
+                        "the select `{name}` has values which are not entities"
+                    )),
+                },
+                ty => self.resolve(ty),
+            };
+        }
+        if !schema.entities.contains_key(&name) {
+            return Err(format!("unknown type `{name}`"));
+        }
+        if self.entities.contains(&name) {
+            return Ok((camel_case(&name), true));
+        }
+        if let Some(holder) = self.handwritten.holders.get(&name) {
+            return Ok((holder.clone(), true));
+        }
+        let any = camel_case(&name) + "Any";
+        match self.handwritten.enums.contains(&any) {
+            true => Ok((any, true)),
+            false => Ok(("Dummy".to_string(), true)),
+        }
+    }
+
+    /// whether all items of the select are entities
+    fn is_entity_select(&self, name: &str) -> bool {
+        match self.schema.types.get(name) {
+            Some(Type::Select { types, .. }) => types.iter().all(|item| {
+                let item = item.to_lowercase();
+                self.schema.entities.contains_key(&item) || self.is_entity_select(&item)
+            }),
+            _ => false,
+        }
+    }
+
+    /// The holder struct of the entity `name`.
+    fn holder(&mut self, name: &str) -> Result<String, String> {
+        if self.handwritten.holders.contains_key(name) {
+            return Err(format!("`{name}` already has the hand-written holder"));
+        }
+        let mut fields = String::new();
+        for (owner, attr) in self.schema.attributes(name)? {
+            let attr_name = attribute_name(attr);
+            let err = |e: String| format!("`{name}.{attr_name}`: {e}");
+            let (field, (ty, place_holder)) = match (owner.as_str(), attr_name.as_str()) {
+                // the name of an item is `label` as the hand-written holders.
+                ("representation_item", "name") => {
+                    ("label".to_string(), ("String".to_string(), false))
+                }
+                _ => (field_name(&attr_name), self.resolve(&attr.ty).map_err(err)?),
+            };
+            let optional = attr.optional || field == "label";
+            if place_holder {
+                fields += "    #[holder(use_place_holder)]\n";
+            }
+            fields += &match optional {
+                true => format!("    pub {field}: Option<{ty}>,\n"),
+                false => format!("    pub {field}: {ty},\n"),
+            };
+        }
+        let struct_name = camel_case(name);
+        Ok(format!(
+            "/// `{name}`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = {name})]
+#[holder(generate_deserialize)]
+pub struct {struct_name} {{
+{fields}}}
+"
+        ))
+    }
+
+    fn module(&self, structs: &[String]) -> Result<String, String> {
+        let mut module = format!(
+            "//! Holders generated from the EXPRESS schema `{SCHEMA_PATH}`.
+//!
+//! Do not edit this file by hand: list the entities in `{CONFIG_PATH}`
+//! and run `cargo run --example express-codegen`.
+
+use super::*;
+"
+        );
+        for name in &self.enums {
+            let Some(Type::Enumeration { items, .. }) = self.schema.types.get(name) else {
+                unreachable!()
+            };
+            let variants: String = items
+                .iter()
+                .map(|item| format!("    {},\n", camel_case(&item.to_lowercase())))
+                .collect();
+            module += &format!(
+                "
+/// `{name}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum {} {{
+{variants}}}
+",
+                camel_case(name)
+            );
+        }
+        for holder in structs {
+            module += "\n";
+            module += holder;
+        }
+        let arms: String = self
+            .entities
+            .iter()
+            .map(|name| {
+                let insert = format!("self.{name}.insert(id, Deserialize::deserialize(record)?);");
+                let insert = match insert.len() <= 61 {
+                    true => format!("                {insert}\n"),
+                    false => format!(
+                        "                self.{name}\n                    .insert(id, Deserialize::deserialize(record)?);\n"
+                    ),
+                };
+                format!(
+                    "            \"{}\" => {{\n{insert}            }}\n",
+                    name.to_uppercase()
+                )
+            })
+            .collect();
+        module += &format!(
+            "
+impl Table {{
+    /// Pushes the instance of a generated entity. Returns `false` if the entity is not generated.
+    pub(super) fn push_generated_instance(
+        &mut self,
+        id: u64,
+        record: &Record,
+    ) -> ruststep::error::Result<bool> {{
+        match record.name.as_str() {{
+{arms}            _ => return Ok(false),
+        }}
+        Ok(true)
+    }}
+}}
+"
+        );
+        Ok(module)
+    }
+}
+
+/// Replaces the fields between the markers in `Table` with the ones of `entities`.
+fn splice_table(table: &str, entities: &[&str]) -> Result<String, String> {
+    let crlf = table.contains("\r\n");
+    let table = table.replace("\r\n", "\n");
+    let marker = |marker: &str| {
+        table
+            .find(&format!("{marker}\n"))
+            .map(|pos| pos + marker.len() + 1)
+            .ok_or_else(|| format!("the marker `{}` is not found", marker.trim()))
+    };
+    let begin = marker(TABLE_BEGIN)?;
+    let end = marker(TABLE_END)? - TABLE_END.len() - 1;
+    if end < begin {
+        return Err("the markers of the generated holders are swapped".to_string());
+    }
+    let fields: String = entities
+        .iter()
+        .map(|name| {
+            let ty = format!("HashMap<u64, {}Holder>", camel_case(name));
+            match format!("    pub {name}: {ty},").len() <= 100 {
+                true => format!("    pub {name}: {ty},\n"),
+                false => format!("    pub {name}:\n        {ty},\n"),
+            }
+        })
+        .collect();
+    let spliced = format!("{}{fields}{}", &table[..begin], &table[end..]);
+    match crlf {
+        true => Ok(spliced.replace('\n', "\r\n")),
+        false => Ok(spliced),
+    }
+}
@@ -3,7 +3,7 @@
 use clap::Parser;
 use std::path::Path;
 use truck_meshalgo::prelude::*;
-use truck_stepio::r#in::*;
+use truck_stepio::r#in::{tessellation::*, *};
 use truck_topology::compress::*;
 
 #[derive(Parser, Debug)]
@@ -25,6 +25,10 @@ struct Args {
     /// When the string "output" is entered, the registered `#i` shell is output "output-i.json".
     #[arg(long("shape-json"))]
     shape_json: Option<String>,
+    /// size of the image in pixels for which the shapes are meshed
+    /// If omitted, the tolerance is 0.1% of the diameter of each shell.
+    #[arg(long("image-size"))]
+    image_size: Option<u32>,
 }
 
 fn main() {
@@ -34,36 +38,91 @@ fn main() {
         only_edge,
         condition_check,
         shape_json,
+        image_size,
     } = Args::parse();
 
     println!("reading file...");
     let step_file = std::fs::read_to_string(input_step_file).unwrap();
     let exchange = ruststep::parser::parse(&step_file).unwrap();
     let table = Table::from_data_section(&exchange.data[0]);
-    println!("meshing...");
-    let polyshells = table
-        .shell
-        .iter()
-        .map(|(idx, shell)| {
-            let shell = table.to_compressed_shell(shell).unwrap();
-            if let Some(output) = &shape_json {
-                let content = serde_json::to_string_pretty(&shell).unwrap();
+    if let Some(output) = &shape_json {
+        table
+            .to_compressed_shells()
+            .into_iter()
+            .for_each(|(idx, shell)| {
+                let content = serde_json::to_string_pretty(&shell.unwrap()).unwrap();
                 let file_name = format!("{output}-{idx}.json");
                 std::fs::write(file_name, content).unwrap();
+            });
+    }
+    let path: &Path = output_mesh_file.as_ref();
+    let extension = path.extension().and_then(|e| e.to_str());
+
+    // pre-tessellated shapes do not need meshing
+    let mut tessellated_shells = table.tessellated_shell.iter().collect::<Vec<_>>();
+    tessellated_shells.sort_by_key(|(idx, _)| **idx);
+    let meshes = tessellated_shells
+        .into_iter()
+        .filter_map(|(idx, shell)| match table.to_polygon_mesh(shell) {
+            Ok(mesh) => Some(mesh),
+            Err(e) => {
+                eprintln!("the tessellated shell #{idx} is skipped: {e}");
+                None
             }
-            let pre = shell.robust_triangulation(0.01).to_polygon();
-            let bdd = pre.bounding_box();
-            shell.robust_triangulation(bdd.diameter() * 0.001)
         })
         .collect::<Vec<_>>();
+    // the B-rep shells of the products with pre-tessellated shapes need no meshing
+    let tessellated = table.shells_with_tessellation();
+    let mut shells = table
+        .shell
+        .iter()
+        .filter(|(idx, _)| !tessellated.contains(idx))
+        .collect::<Vec<_>>();
+    shells.sort_by_key(|(idx, _)| **idx);
+    let shells = shells
+        .into_iter()
+        .map(|(idx, shell)| (*idx, table.to_compressed_shell(shell)))
+        .collect::<Vec<_>>();
+    println!("meshing...");
+    let polyshells = match image_size {
+        // shells and faces are meshed in parallel
+        Some(size) => {
+            let view = ViewTolerance::new((size, size), ViewCamera::Fit);
+            triangulate_converted_shells(shells, &view)
+        }
+        None => shells
+            .into_iter()
+            .map(|(idx, shell)| {
+                let meshed = shell.map(|shell| {
+                    let tol = control_point_bounding_box(&shell).diameter() * 0.001;
+                    shell.robust_triangulation(tol)
+                });
+                (idx, meshed)
+            })
+            .collect(),
+    };
+    let mut polyshells = polyshells
+        .into_iter()
+        .map(|(_, shell)| shell.unwrap())
+        .collect::<Vec<_>>();
 
-    let path: &Path = output_mesh_file.as_ref();
-    let extension = path.extension().and_then(|e| e.to_str());
     match extension {
-        Some("obj") => output_obj(&polyshells, path, condition_check),
+        Some("obj") => output_obj(&polyshells, meshes, path, condition_check),
         Some("vtu") => match only_edge {
             true => output_vtk_edge(polyshells, path),
-            false => output_vtk(polyshells, path),
+            false => {
+                // each pre-tessellated shape is output as a face without boundaries
+                polyshells.extend(meshes.into_iter().map(|mesh| CompressedShell {
+                    vertices: Vec::new(),
+                    edges: Vec::new(),
+                    faces: vec![CompressedFace {
+                        boundaries: Vec::new(),
+                        orientation: true,
+                        surface: Some(mesh),
+                    }],
+                }));
+                output_vtk(polyshells, path)
+            }
         },
         _ => {}
     }
@@ -71,10 +130,12 @@ fn main() {
 
 fn output_obj(
     polyshells: &[CompressedShell<Point3, PolylineCurve<Point3>, Option<PolygonMesh>>],
+    meshes: Vec<PolygonMesh>,
     path: &Path,
     condition_check: bool,
 ) {
     let mut polymesh = PolygonMesh::default();
+    meshes.into_iter().for_each(|mesh| polymesh.merge(mesh));
     polyshells.iter().for_each(|shell| {
         let mut poly = shell.to_polygon();
         poly.put_together_same_attrs(TOLERANCE * 50.0)
@@ -0,0 +1,368 @@
+use super::*;
+use std::f64::consts::PI;
+
+/// the maximum depth of the chains of the transformations and of the nested composite curves
+const MAX_DEPTH: usize = 16;
+
+/// the parameters of `126` rational B-spline curve
+struct BSplineCurveData {
+    knots: Vec<f64>,
+    weights: Vec<f64>,
+    points: Vec<Point3>,
+    polynomial: bool,
+    range: (f64, f64),
+}
+
+impl BSplineCurveData {
+    fn new(entity: &IgesEntity) -> Result<Self, IgesError> {
+        let upper = entity.integer(0)? as usize;
+        let degree = entity.integer(1)? as usize;
+        let polynomial = entity.integer_or(4, 0) == 1;
+        let knots_start = 6;
+        let weights_start = knots_start + upper + degree + 2;
+        let points_start = weights_start + upper + 1;
+        let range_start = points_start + 3 * (upper + 1);
+        let knots = (knots_start..weights_start)
+            .map(|i| entity.real(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights = (weights_start..points_start)
+            .map(|i| entity.real(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let points = (0..=upper)
+            .map(|i| {
+                let j = points_start + 3 * i;
+                Ok(Point3::new(
+                    entity.real(j)?,
+                    entity.real(j + 1)?,
+                    entity.real(j + 2)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, IgesError>>()?;
+        let range = (
+            entity.real_or(range_start, knots[degree]),
+            entity.real_or(range_start + 1, knots[upper + 1]),
+        );
+        Ok(Self {
+            knots,
+            weights,
+            points,
+            polynomial,
+            range,
+        })
+    }
+
+    fn is_polynomial(&self) -> bool {
+        self.polynomial
+            || self
+                .weights
+                .iter()
+                .all(|w| f64::abs(w - self.weights[0]) < TOLERANCE)
+    }
+
+    fn to_curve_3d(&self) -> Result<Curve3D, IgesError> {
+        let knot_vec = KnotVec::from(self.knots.clone());
+        let curve = match self.is_polynomial() {
+            true => Curve3D::BSplineCurve(BSplineCurve::try_new(knot_vec, self.points.clone())?),
+            false => {
+                let points = self
+                    .points
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(p, w)| Vector4::new(p.x * w, p.y * w, p.z * w, *w))
+                    .collect();
+                Curve3D::NurbsCurve(NurbsCurve::new(BSplineCurve::try_new(knot_vec, points)?))
+            }
+        };
+        Ok(trimmed(curve, self.range))
+    }
+
+    fn to_curve_2d(&self) -> Result<Curve2D, IgesError> {
+        let knot_vec = KnotVec::from(self.knots.clone());
+        let curve = match self.is_polynomial() {
+            true => Curve2D::BSplineCurve(BSplineCurve::try_new(
+                knot_vec,
+                self.points.iter().map(|p| Point2::new(p.x, p.y)).collect(),
+            )?),
+            false => {
+                let points = self
+                    .points
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(p, w)| Vector3::new(p.x * w, p.y * w, *w))
+                    .collect();
+                Curve2D::NurbsCurve(NurbsCurve::new(BSplineCurve::try_new(knot_vec, points)?))
+            }
+        };
+        Ok(trimmed(curve, self.range))
+    }
+}
+
+/// Cuts `curve` at the ends of `(t0, t1)` if they are in the parameter range.
+fn trimmed<C: Cut>(mut curve: C, (t0, t1): (f64, f64)) -> C {
+    let (front, back) = curve.range_tuple();
+    if front < t1 && t1 < back - TOLERANCE {
+        curve.cut(t1);
+    }
+    if front + TOLERANCE < t0 && t0 < t1 {
+        curve = curve.cut(t0);
+    }
+    curve
+}
+
+/// the center, the radius and the angles of the ends of `100` circular arc
+fn arc_data(entity: &IgesEntity) -> Result<(Point2, f64, (f64, f64)), IgesError> {
+    let point = |i: usize| -> Result<Point2, IgesError> {
+        Ok(Point2::new(entity.real(i)?, entity.real(i + 1)?))
+    };
+    let (center, start, end) = (point(1)?, point(3)?, point(5)?);
+    let radius = start.distance(center);
+    let angle = |p: Point2| f64::atan2(p.y - center.y, p.x - center.x);
+    let t0 = angle(start);
+    // arcs are counterclockwise, the arc whose ends coincide is the whole circle.
+    let mut t1 = angle(end);
+    if start.distance(end) < TOLERANCE * radius.max(1.0) || t1 <= t0 {
+        t1 += 2.0 * PI;
+    }
+    Ok((center, radius, (t0, t1)))
+}
+
+fn curve_3d(entity: &IgesEntity) -> Result<Curve3D, IgesError> {
+    let point = |i: usize| -> Result<Point3, IgesError> {
+        Ok(Point3::new(
+            entity.real(i)?,
+            entity.real(i + 1)?,
+            entity.real(i + 2)?,
+        ))
+    };
+    match entity.directory.entity_type {
+        100 => {
+            let (center, radius, range) = arc_data(entity)?;
+            let zt = entity.real_or(0, 0.0);
+            let matrix = Matrix4::from_translation(Vector3::new(center.x, center.y, zt))
+                * Matrix4::from_scale(radius);
+            let ellipse: Ellipse<Point3, Matrix4> =
+                Processor::new(TrimmedCurve::new(UnitCircle::new(), range)).transformed(matrix);
+            Ok(Curve3D::Conic(Conic3D::Ellipse(ellipse)))
+        }
+        110 => Ok(Curve3D::Line(Line(point(0)?, point(3)?))),
+        126 => BSplineCurveData::new(entity)?.to_curve_3d(),
+        x => Err(format!("the entity {x} is not supported as a curve.").into()),
+    }
+}
+
+fn curve_2d(entity: &IgesEntity) -> Result<Curve2D, IgesError> {
+    let point = |i: usize| -> Result<Point2, IgesError> {
+        Ok(Point2::new(entity.real(i)?, entity.real(i + 1)?))
+    };
+    match entity.directory.entity_type {
+        100 => {
+            let (center, radius, range) = arc_data(entity)?;
+            let matrix = Matrix3::from_translation(center.to_vec()) * Matrix3::from_scale(radius);
+            let ellipse: Ellipse<Point2, Matrix3> =
+                Processor::new(TrimmedCurve::new(UnitCircle::new(), range)).transformed(matrix);
+            Ok(Curve2D::Conic(Conic2D::Ellipse(ellipse)))
+        }
+        110 => Ok(Curve2D::Line(Line(point(0)?, point(3)?))),
+        126 => BSplineCurveData::new(entity)?.to_curve_2d(),
+        x => Err(format!("the entity {x} is not supported as a curve.").into()),
+    }
+}
+
+/// `124` transformation matrix
+fn transformation_matrix(entity: &IgesEntity) -> Result<Matrix4, IgesError> {
+    let r = |i: usize| entity.real(i);
+    Ok(Matrix4::new(
+        r(0)?,
+        r(4)?,
+        r(8)?,
+        0.0,
+        r(1)?,
+        r(5)?,
+        r(9)?,
+        0.0,
+        r(2)?,
+        r(6)?,
+        r(10)?,
+        0.0,
+        r(3)?,
+        r(7)?,
+        r(11)?,
+        1.0,
+    ))
+}
+
+/// the affine transformation of the parameter plane given by the one of the model space
+fn plane_transformation(matrix: Matrix4) -> Matrix3 {
+    Matrix3::new(
+        matrix.x.x, matrix.x.y, 0.0, matrix.y.x, matrix.y.y, 0.0, matrix.w.x, matrix.w.y, 1.0,
+    )
+}
+
+impl IgesFile {
+    /// The transformation of the entity, composed along the chain of `124` transformation matrices.
+    pub fn transformation(&self, pointer: usize) -> Result<Matrix4, IgesError> {
+        let mut matrix = Matrix4::identity();
+        let mut next = self.entity(pointer)?.directory.transformation;
+        for _ in 0..MAX_DEPTH {
+            if next == 0 {
+                return Ok(matrix);
+            }
+            let entity = self.entity(next)?;
+            if entity.directory.entity_type != 124 {
+                return Err(format!("the transformation {next} is not a matrix.").into());
+            }
+            matrix = transformation_matrix(entity)? * matrix;
+            next = entity.directory.transformation;
+        }
+        Err(format!("the transformations of the entity {pointer} are cyclic.").into())
+    }
+
+    fn composite_segments<C>(
+        &self,
+        pointer: usize,
+        depth: usize,
+        convert: &impl Fn(&IgesEntity) -> Result<C, IgesError>,
+        transform: &impl Fn(&mut C, Matrix4),
+    ) -> Result<Vec<C>, IgesError> {
+        let entity = self.entity(pointer)?;
+        let mut segments = match entity.directory.entity_type {
+            102 if depth < MAX_DEPTH => {
+                let len = entity.integer(0)? as usize;
+                (1..=len)
+                    .map(|i| {
+                        self.composite_segments(entity.pointer(i), depth + 1, convert, transform)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat()
+            }
+            102 => return Err(format!("the composite curve {pointer} is nested too deep.").into()),
+            _ => vec![convert(entity)?],
+        };
+        let matrix = self.transformation(pointer)?;
+        if matrix != Matrix4::identity() {
+            segments
+                .iter_mut()
+                .for_each(|segment| transform(segment, matrix));
+        }
+        Ok(segments)
+    }
+
+    /// Converts the curve entity to the curves in the model space.
+    ///
+    /// `102` composite curves are split into their segments.
+    pub fn curve_segments(&self, pointer: usize) -> Result<Vec<Curve3D>, IgesError> {
+        self.composite_segments(pointer, 0, &curve_3d, &|curve: &mut Curve3D, matrix| {
+            curve.transform_by(matrix)
+        })
+    }
+
+    /// Converts the curve entity in the parameter space of a surface to the curves in 2D.
+    ///
+    /// `102` composite curves are split into their segments, and `z` coordinates are ignored.
+    pub fn curve_2d_segments(&self, pointer: usize) -> Result<Vec<Curve2D>, IgesError> {
+        self.composite_segments(pointer, 0, &curve_2d, &|curve: &mut Curve2D, matrix| {
+            curve.transform_by(plane_transformation(matrix))
+        })
+    }
+
+    /// Converts the curve entity to a curve in the model space.
+    ///
+    /// Errors if the curve is a composite curve of several segments.
+    pub fn curve(&self, pointer: usize) -> Result<Curve3D, IgesError> {
+        let mut segments = self.curve_segments(pointer)?;
+        match segments.len() {
+            1 => Ok(segments.pop().unwrap()),
+            len => Err(format!("the curve {pointer} consists of {len} segments.").into()),
+        }
+    }
+
+    /// Converts the surface entity to a surface in the model space.
+    pub fn surface(&self, pointer: usize) -> Result<Surface, IgesError> {
+        let entity = self.entity(pointer)?;
+        let mut surface = match entity.directory.entity_type {
+            128 => bspline_surface(entity)?,
+            x => return Err(format!("the entity {x} is not supported as a surface.").into()),
+        };
+        let matrix = self.transformation(pointer)?;
+        if matrix != Matrix4::identity() {
+            surface.transform_by(matrix);
+        }
+        Ok(surface)
+    }
+
+    /// The parameter range of the surface entity, `((u0, u1), (v0, v1))`.
+    pub fn surface_domain(&self, pointer: usize) -> Result<((f64, f64), (f64, f64)), IgesError> {
+        let entity = self.entity(pointer)?;
+        match entity.directory.entity_type {
+            128 => {
+                let start = bspline_surface_range_start(entity)?;
+                Ok((
+                    (entity.real(start)?, entity.real(start + 1)?),
+                    (entity.real(start + 2)?, entity.real(start + 3)?),
+                ))
+            }
+            x => Err(format!("the entity {x} is not supported as a surface.").into()),
+        }
+    }
+}
+
+/// the index of `U0` of `128` rational B-spline surface
+fn bspline_surface_range_start(entity: &IgesEntity) -> Result<usize, IgesError> {
+    let (upper0, upper1) = (entity.integer(0)? as usize, entity.integer(1)? as usize);
+    let (degree0, degree1) = (entity.integer(2)? as usize, entity.integer(3)? as usize);
+    let len = (upper0 + 1) * (upper1 + 1);
+    Ok(9 + (upper0 + degree0 + 2) + (upper1 + degree1 + 2) + 4 * len)
+}
+
+/// `128` rational B-spline surface
+fn bspline_surface(entity: &IgesEntity) -> Result<Surface, IgesError> {
+    let (upper0, upper1) = (entity.integer(0)? as usize, entity.integer(1)? as usize);
+    let (degree0, degree1) = (entity.integer(2)? as usize, entity.integer(3)? as usize);
+    let reals = |start: usize, len: usize| {
+        (start..start + len)
+            .map(|i| entity.real(i))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let uknots_start = 9;
+    let vknots_start = uknots_start + upper0 + degree0 + 2;
+    let weights_start = vknots_start + upper1 + degree1 + 2;
+    let len = (upper0 + 1) * (upper1 + 1);
+    let points_start = weights_start + len;
+    let uknots = KnotVec::from(reals(uknots_start, upper0 + degree0 + 2)?);
+    let vknots = KnotVec::from(reals(vknots_start, upper1 + degree1 + 2)?);
+    let weights = reals(weights_start, len)?;
+    let points = reals(points_start, 3 * len)?;
+    // the control points are ordered with the first index varying fastest.
+    let control_point = |i: usize, j: usize| {
+        let k = j * (upper0 + 1) + i;
+        (
+            Point3::new(points[3 * k], points[3 * k + 1], points[3 * k + 2]),
+            weights[k],
+        )
+    };
+    let polynomial = entity.integer_or(6, 0) == 1
+        || weights.iter().all(|w| f64::abs(w - weights[0]) < TOLERANCE);
+    match polynomial {
+        true => {
+            let control_points = (0..=upper0)
+                .map(|i| (0..=upper1).map(|j| control_point(i, j).0).collect())
+                .collect();
+            let surface = BSplineSurface::try_new((uknots, vknots), control_points)?;
+            Ok(Surface::BSplineSurface(Box::new(surface)))
+        }
+        false => {
+            let control_points = (0..=upper0)
+                .map(|i| {
+                    (0..=upper1)
+                        .map(|j| {
+                            let (p, w) = control_point(i, j);
+                            Vector4::new(p.x * w, p.y * w, p.z * w, w)
+                        })
+                        .collect()
+                })
+                .collect();
+            let surface = BSplineSurface::try_new((uknots, vknots), control_points)?;
+            Ok(Surface::NurbsSurface(Box::new(NurbsSurface::new(surface))))
+        }
+    }
+}
@@ -0,0 +1,598 @@
+//! Reads IGES files into `truck`.
+//!
+//! IGES files consist of the fixed-column sections Start (`S`), Global (`G`),
+//! Directory Entry (`D`), Parameter Data (`P`) and Terminate (`T`).
+//! [`IgesFile`] keeps the directory entries with their parameters,
+//! and the entities are converted to the curves and the surfaces of [`alias`](crate::r#in::alias)
+//! and to `CompressedShell` on demand.
+//!
+//! The supported entities are
+//! - curves: `100` circular arc, `102` composite curve, `110` line, `126` rational B-spline curve,
+//! - surfaces: `128` rational B-spline surface, `142` curve on a parametric surface, `144` trimmed surface,
+//! - B-rep: `186` manifold solid B-rep, `502` vertex list, `504` edge list, `508` loop, `510` face, `514` shell,
+//! - `124` transformation matrix.
+//!
+//! # Example
+//! ```
+//! use truck_stepio::iges::*;
+//! let iges_str = "                                                                        S      1
+//! 1H,,1H;,,,,,,,,,,,1.,2,2HMM,,,,0.001,,,,,;                              G      1
+//!      110       1       0       0       0       0       0       000000000D      1
+//!      110       0       0       1       0                               0D      2
+//! 110,0.,0.,0.,10.,0.,0.;                                                1P      1
+//! S      1G      1D      2P      1                                        T      1";
+//! let iges: IgesFile = iges_str.parse().unwrap();
+//! assert_eq!(iges.global.units_name, "MM");
+//! let curve = iges.curve(1).unwrap();
+//! assert_eq!(curve.back(), Point3::new(10.0, 0.0, 0.0));
+//! ```
+
+use crate::r#in::alias::*;
+use std::{collections::BTreeMap, io::Read, str::FromStr};
+
+mod geometry;
+#[cfg(feature = "tessellation")]
+mod tessellation;
+mod topology;
+
+pub use crate::r#in::alias::{re_exports::*, Curve2D, Curve3D, Surface};
+#[cfg(feature = "tessellation")]
+pub use crate::r#in::tessellation::{MeshedShell, ViewCamera, ViewTolerance};
+pub use topology::IgesCompressedShell;
+
+/// Errors that occur when reading IGES files
+pub type IgesError = Box<dyn std::error::Error + Send + Sync>;
+
+/// the column where the section letter is
+const SECTION_COLUMN: usize = 72;
+/// the width of the fields of the directory entries
+const FIELD_WIDTH: usize = 8;
+/// the width of the parameters in a line of the parameter data section
+const PARAMETER_WIDTH: usize = 64;
+
+/// a parameter in the Global and the Parameter Data sections
+#[derive(Clone, Debug, PartialEq)]
+pub enum IgesParameter {
+    /// integer, also used for the pointers to the directory entries
+    Integer(i64),
+    /// real number
+    Real(f64),
+    /// Hollerith string
+    String(String),
+    /// omitted parameter, the default value is used
+    Default,
+}
+
+impl IgesParameter {
+    /// Returns the number, integers are also converted.
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            IgesParameter::Integer(x) => Some(*x as f64),
+            IgesParameter::Real(x) => Some(*x),
+            _ => None,
+        }
+    }
+    /// Returns the integer, the reals with no fractional part are also converted.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            IgesParameter::Integer(x) => Some(*x),
+            IgesParameter::Real(x) if x.fract() == 0.0 => Some(*x as i64),
+            _ => None,
+        }
+    }
+    /// Returns the string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            IgesParameter::String(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// The Global section
+#[derive(Clone, Debug, PartialEq)]
+pub struct IgesGlobal {
+    /// the delimiter of the parameters, `,` by default
+    pub parameter_delimiter: char,
+    /// the delimiter of the records, `;` by default
+    pub record_delimiter: char,
+    /// the product identification from the sender
+    pub sender_id: String,
+    /// the file name
+    pub file_name: String,
+    /// the native system identification
+    pub system_id: String,
+    /// the preprocessor version
+    pub preprocessor_version: String,
+    /// the scale of the model space
+    pub model_scale: f64,
+    /// the unit flag, e.g. `1` for inch and `2` for millimetre
+    pub units_flag: i64,
+    /// the name of the unit, e.g. `IN` or `MM`
+    pub units_name: String,
+    /// the date and time of the generation of the file
+    pub date: String,
+    /// the minimum resolution of the model
+    pub resolution: f64,
+    /// the name of the author
+    pub author: String,
+    /// the organization of the author
+    pub organization: String,
+}
+
+impl Default for IgesGlobal {
+    fn default() -> Self {
+        Self {
+            parameter_delimiter: ',',
+            record_delimiter: ';',
+            sender_id: String::new(),
+            file_name: String::new(),
+            system_id: String::new(),
+            preprocessor_version: String::new(),
+            model_scale: 1.0,
+            units_flag: 1,
+            units_name: "IN".to_string(),
+            date: String::new(),
+            resolution: 0.0,
+            author: String::new(),
+            organization: String::new(),
+        }
+    }
+}
+
+impl IgesGlobal {
+    /// The length unit in metres, e.g. `0.0254` for inch.
+    ///
+    /// The units flag `3` is resolved by the name of the unit.
+    pub fn unit_in_metres(&self) -> f64 {
+        let flag = match self.units_flag {
+            3 => match self.units_name.to_uppercase().as_str() {
+                "IN" | "INCH" => 1,
+                "MM" => 2,
+                "FT" => 4,
+                "MI" => 5,
+                "M" => 6,
+                "KM" => 7,
+                "MIL" => 8,
+                "UM" => 9,
+                "CM" => 10,
+                "UIN" => 11,
+                _ => 1,
+            },
+            flag => flag,
+        };
+        match flag {
+            2 => 0.001,
+            4 => 0.3048,
+            5 => 1609.344,
+            6 => 1.0,
+            7 => 1000.0,
+            8 => 2.54e-5,
+            9 => 1.0e-6,
+            10 => 0.01,
+            11 => 2.54e-8,
+            _ => 0.0254,
+        }
+    }
+
+    fn from_parameters(
+        parameter_delimiter: char,
+        record_delimiter: char,
+        params: &[IgesParameter],
+    ) -> Self {
+        let string = |i: usize| {
+            params
+                .get(i)
+                .and_then(IgesParameter::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let real = |i: usize| params.get(i).and_then(IgesParameter::as_real);
+        let default = Self::default();
+        Self {
+            parameter_delimiter,
+            record_delimiter,
+            sender_id: string(0),
+            file_name: string(1),
+            system_id: string(2),
+            preprocessor_version: string(3),
+            model_scale: real(10).unwrap_or(default.model_scale),
+            units_flag: params
+                .get(11)
+                .and_then(IgesParameter::as_integer)
+                .unwrap_or(default.units_flag),
+            units_name: match string(12) {
+                name if name.is_empty() => default.units_name,
+                name => name,
+            },
+            date: string(15),
+            resolution: real(16).unwrap_or_default(),
+            author: string(18),
+            organization: string(19),
+        }
+    }
+}
+
+/// The status number of a directory entry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IgesStatus {
+    /// `0` if visible, `1` if blanked
+    pub blank: u32,
+    /// `0` if independent, `1` if physically dependent, `2` if logically dependent, `3` if both
+    pub subordinate: u32,
+    /// `0` for geometry, `1` for annotation, `2` for definition, `3` for other, ...
+    pub entity_use: u32,
+    /// `0` if the global top-down, `1` if the global defer, `2` if the hierarchy entity is used
+    pub hierarchy: u32,
+}
+
+/// A directory entry, the first 20 fields of an entity
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IgesDirectoryEntry {
+    /// the entity type number, e.g. `110` for line
+    pub entity_type: u32,
+    /// the sequence number of the first line of the parameters
+    pub parameter_pointer: usize,
+    /// the pointer to the transformation matrix, `0` if the entity is not transformed
+    pub transformation: usize,
+    /// the status number
+    pub status: IgesStatus,
+    /// the colour number, negative values are the pointers to the colour definitions
+    pub colour: i64,
+    /// the form number
+    pub form: u32,
+    /// the entity label
+    pub label: String,
+    /// the entity subscript number
+    pub subscript: i64,
+}
+
+/// An entity, the directory entry and the parameters
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IgesEntity {
+    /// the directory entry
+    pub directory: IgesDirectoryEntry,
+    /// the parameters without the leading entity type number
+    pub parameters: Vec<IgesParameter>,
+}
+
+impl IgesEntity {
+    /// the `i`-th parameter as a number, errors if it is lacked
+    pub fn real(&self, i: usize) -> Result<f64, IgesError> {
+        self.parameters
+            .get(i)
+            .and_then(IgesParameter::as_real)
+            .ok_or_else(|| self.parameter_error(i, "a number"))
+    }
+
+    /// the `i`-th parameter as a number, `default` if it is omitted
+    pub fn real_or(&self, i: usize, default: f64) -> f64 {
+        self.parameters
+            .get(i)
+            .and_then(IgesParameter::as_real)
+            .unwrap_or(default)
+    }
+
+    /// the `i`-th parameter as an integer, errors if it is lacked
+    pub fn integer(&self, i: usize) -> Result<i64, IgesError> {
+        self.parameters
+            .get(i)
+            .and_then(IgesParameter::as_integer)
+            .ok_or_else(|| self.parameter_error(i, "an integer"))
+    }
+
+    /// the `i`-th parameter as an integer, `default` if it is omitted
+    pub fn integer_or(&self, i: usize, default: i64) -> i64 {
+        self.parameters
+            .get(i)
+            .and_then(IgesParameter::as_integer)
+            .unwrap_or(default)
+    }
+
+    /// the `i`-th parameter as a pointer to a directory entry, `0` if it is omitted
+    ///
+    /// Negative pointers, which are used by some systems, are converted to the positive ones.
+    pub fn pointer(&self, i: usize) -> usize { self.integer_or(i, 0).unsigned_abs() as usize }
+
+    fn parameter_error(&self, i: usize, kind: &str) -> IgesError {
+        format!(
+            "the parameter {} of the entity {} is not {kind}.",
+            i + 1,
+            self.directory.entity_type,
+        )
+        .into()
+    }
+}
+
+/// The contents of IGES file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IgesFile {
+    /// the Start section
+    pub start: String,
+    /// the Global section
+    pub global: IgesGlobal,
+    /// the entities by the sequence numbers of their directory entries
+    pub entities: BTreeMap<usize, IgesEntity>,
+}
+
+/// the lines of a section and their sequence numbers
+#[derive(Clone, Debug, Default)]
+struct Sections {
+    start: Vec<String>,
+    global: Vec<String>,
+    directory: Vec<(usize, String)>,
+    parameter: Vec<String>,
+}
+
+fn columns(line: &[char], start: usize, end: usize) -> String {
+    line.iter()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
+}
+
+fn split_sections(iges_str: &str) -> Result<Sections, IgesError> {
+    let mut sections = Sections::default();
+    let lines = iges_str.lines().flat_map(|line| {
+        let chars: Vec<char> = line
+            .trim_end_matches(&['\r', '\n', '\u{1a}'][..])
+            .chars()
+            .collect();
+        // some systems write the whole file in a line
+        match chars.len() > 80 && chars.len() % 80 == 0 {
+            true => chars.chunks(80).map(<[char]>::to_vec).collect::<Vec<_>>(),
+            false => vec![chars],
+        }
+    });
+    for (i, line) in lines.enumerate() {
+        if line.iter().all(|c| c.is_whitespace()) {
+            continue;
+        }
+        let Some(section) = line.get(SECTION_COLUMN) else {
+            return Err(format!("the line {} is shorter than 73 columns.", i + 1).into());
+        };
+        let body = columns(&line, 0, SECTION_COLUMN);
+        match section {
+            'S' => sections.start.push(body),
+            'G' => sections.global.push(body),
+            'D' => {
+                let sequence = columns(&line, SECTION_COLUMN + 1, 80);
+                let sequence = sequence
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("the sequence number of the line {} is broken.", i + 1))?;
+                sections.directory.push((sequence, body));
+            }
+            'P' => sections.parameter.push(body),
+            'T' => break,
+            'C' | 'B' => return Err("compressed and binary IGES files are not supported.".into()),
+            _ => return Err(format!("the section of the line {} is unknown.", i + 1).into()),
+        }
+    }
+    Ok(sections)
+}
+
+fn parse_number(token: &str) -> Result<IgesParameter, IgesError> {
+    let token: String = token.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Ok(x) = token.parse::<i64>() {
+        return Ok(IgesParameter::Integer(x));
+    }
+    token
+        .replace(&['D', 'd'][..], "E")
+        .parse::<f64>()
+        .map(IgesParameter::Real)
+        .map_err(|_| format!("`{token}` is not a number.").into())
+}
+
+/// Parses the free-formatted parameters until the record delimiter.
+fn parse_parameters(
+    record: &str,
+    parameter_delimiter: char,
+    record_delimiter: char,
+) -> Result<Vec<IgesParameter>, IgesError> {
+    let chars: Vec<char> = record.chars().collect();
+    let skip_spaces = |mut i: usize| {
+        while i < chars.len() && chars[i] == ' ' {
+            i += 1;
+        }
+        i
+    };
+    let mut params = Vec::new();
+    let mut i = skip_spaces(0);
+    while i < chars.len() && chars[i] != record_delimiter {
+        let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && chars.get(i + digits) == Some(&'H') {
+            let len: usize = columns(&chars, i, i + digits).parse()?;
+            let start = i + digits + 1;
+            if start + len > chars.len() {
+                return Err("a Hollerith string is broken.".into());
+            }
+            params.push(IgesParameter::String(columns(&chars, start, start + len)));
+            i = start + len;
+        } else {
+            let len = chars[i..]
+                .iter()
+                .take_while(|c| **c != parameter_delimiter && **c != record_delimiter)
+                .count();
+            let token = columns(&chars, i, i + len);
+            params.push(match token.trim().is_empty() {
+                true => IgesParameter::Default,
+                false => parse_number(&token)?,
+            });
+            i += len;
+        }
+        i = skip_spaces(i);
+        if chars.get(i) == Some(&parameter_delimiter) {
+            i = skip_spaces(i + 1);
+            // the parameter just before the record delimiter is omitted.
+            if i == chars.len() || chars[i] == record_delimiter {
+                params.push(IgesParameter::Default);
+            }
+        } else if i < chars.len() && chars[i] != record_delimiter {
+            return Err(format!("unexpected character `{}` in parameters.", chars[i]).into());
+        }
+    }
+    Ok(params)
+}
+
+/// Takes the delimiter given by a Hollerith string, `default` if it is omitted.
+fn take_delimiter(rest: &mut &str, default: char) -> char {
+    *rest = rest.trim_start();
+    match rest.strip_prefix("1H").and_then(|s| s.chars().next()) {
+        Some(c) => {
+            *rest = &rest[2 + c.len_utf8()..];
+            c
+        }
+        None => default,
+    }
+}
+
+fn skip_delimiter(rest: &mut &str, delimiter: char) {
+    *rest = rest.trim_start();
+    if let Some(stripped) = rest.strip_prefix(delimiter) {
+        *rest = stripped;
+    }
+}
+
+fn parse_global(global: &str) -> Result<IgesGlobal, IgesError> {
+    // the delimiters are given by the first two parameters, which may be omitted.
+    let mut rest = global;
+    let parameter_delimiter = take_delimiter(&mut rest, ',');
+    skip_delimiter(&mut rest, parameter_delimiter);
+    let record_delimiter = take_delimiter(&mut rest, ';');
+    skip_delimiter(&mut rest, parameter_delimiter);
+    let params = parse_parameters(rest, parameter_delimiter, record_delimiter)?;
+    Ok(IgesGlobal::from_parameters(
+        parameter_delimiter,
+        record_delimiter,
+        &params,
+    ))
+}
+
+fn parse_status(field: &str) -> IgesStatus {
+    let field = format!("{field:0>8}");
+    let number = |i: usize| {
+        field
+            .get(i..i + 2)
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or_default()
+    };
+    IgesStatus {
+        blank: number(0),
+        subordinate: number(2),
+        entity_use: number(4),
+        hierarchy: number(6),
+    }
+}
+
+fn parse_directory(
+    (sequence, line0): &(usize, String),
+    (_, line1): &(usize, String),
+) -> Result<IgesDirectoryEntry, IgesError> {
+    let field = |line: &str, i: usize| {
+        let chars: Vec<char> = line.chars().collect();
+        columns(&chars, i * FIELD_WIDTH, (i + 1) * FIELD_WIDTH)
+    };
+    let integer = |line: &str, i: usize| -> Result<i64, IgesError> {
+        match field(line, i).trim() {
+            "" => Ok(0),
+            x => x.parse().map_err(|_| {
+                format!("the directory entry {sequence} has a broken field `{x}`.").into()
+            }),
+        }
+    };
+    Ok(IgesDirectoryEntry {
+        entity_type: integer(line0, 0)? as u32,
+        parameter_pointer: integer(line0, 1)? as usize,
+        transformation: integer(line0, 6)?.unsigned_abs() as usize,
+        status: parse_status(field(line0, 8).trim()),
+        colour: integer(line1, 2)?,
+        form: integer(line1, 4)? as u32,
+        label: field(line1, 7).trim().to_string(),
+        subscript: integer(line1, 8)?,
+    })
+}
+
+impl FromStr for IgesFile {
+    type Err = IgesError;
+    fn from_str(iges_str: &str) -> Result<Self, Self::Err> {
+        let sections = split_sections(iges_str)?;
+        let start = sections
+            .start
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let global = parse_global(&sections.global.concat())?;
+        // the parameters of an entity are the lines with the back pointer to its directory entry.
+        let mut records = BTreeMap::<usize, String>::new();
+        for line in &sections.parameter {
+            let chars: Vec<char> = line.chars().collect();
+            let pointer = columns(&chars, PARAMETER_WIDTH, SECTION_COLUMN);
+            let pointer: usize = pointer
+                .trim()
+                .parse()
+                .map_err(|_| format!("the back pointer `{}` is broken.", pointer.trim()))?;
+            let record = records.entry(pointer).or_default();
+            record.push_str(&columns(&chars, 0, PARAMETER_WIDTH));
+        }
+        let entities = sections
+            .directory
+            .chunks(2)
+            .filter(|lines| lines.len() == 2)
+            .map(|lines| {
+                let directory = parse_directory(&lines[0], &lines[1])?;
+                let sequence = lines[0].0;
+                let mut parameters = match records.get(&sequence) {
+                    Some(record) => parse_parameters(
+                        record,
+                        global.parameter_delimiter,
+                        global.record_delimiter,
+                    )?,
+                    None => Vec::new(),
+                };
+                // the first parameter is the entity type number.
+                if !parameters.is_empty() {
+                    parameters.remove(0);
+                }
+                Ok((
+                    sequence,
+                    IgesEntity {
+                        directory,
+                        parameters,
+                    },
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>, IgesError>>()?;
+        Ok(Self {
+            start,
+            global,
+            entities,
+        })
+    }
+}
+
+impl IgesFile {
+    /// Reads IGES file from `reader`.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, IgesError> {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        // IGES files are ASCII, but the strings in them are often written by the local encodings.
+        String::from_utf8_lossy(&buffer).parse()
+    }
+
+    /// Returns the entity whose directory entry is at the sequence number `pointer`.
+    pub fn entity(&self, pointer: usize) -> Result<&IgesEntity, IgesError> {
+        self.entities
+            .get(&pointer)
+            .ok_or_else(|| format!("the directory entry {pointer} is not found.").into())
+    }
+
+    /// Returns the pointers of the entities whose type is `entity_type`, in the order of the file.
+    pub fn pointers(&self, entity_type: u32) -> impl Iterator<Item = usize> + '_ {
+        self.entities
+            .iter()
+            .filter(move |(_, entity)| entity.directory.entity_type == entity_type)
+            .map(|(pointer, _)| *pointer)
+    }
+}
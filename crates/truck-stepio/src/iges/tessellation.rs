@@ -0,0 +1,45 @@
+use super::*;
+use crate::r#in::tessellation::{triangulate_converted_shells, MeshedShell, ViewTolerance};
+use truck_meshalgo::tessellation::MeshedShape;
+
+impl IgesFile {
+    /// Converts all shells in IGES file and triangulates them for the image given by `view`.
+    ///
+    /// The shells are given by [`IgesFile::to_compressed_shells`],
+    /// and the tolerances are derived as [`Table::triangulate_shells_for_view`] does.
+    ///
+    /// [`Table::triangulate_shells_for_view`]: crate::r#in::Table::triangulate_shells_for_view
+    pub fn triangulate_shells_for_view(
+        &self,
+        view: &ViewTolerance,
+    ) -> Vec<(usize, Result<MeshedShell, IgesError>)> {
+        triangulate_converted_shells(self.to_compressed_shells(), view)
+    }
+
+    /// Triangulates all shells for the image given by `view` and merges them into a mesh.
+    ///
+    /// The shells and the faces which fail to be converted are skipped, see
+    /// [`IgesFile::to_polygon_mesh_for_view_with_skipped`].
+    pub fn to_polygon_mesh_for_view(&self, view: &ViewTolerance) -> PolygonMesh {
+        self.to_polygon_mesh_for_view_with_skipped(view).0
+    }
+
+    /// Triangulates and merges the shells as [`IgesFile::to_polygon_mesh_for_view`] does,
+    /// returning also the shells and the faces which are skipped with their pointers,
+    /// sorted by the pointers.
+    pub fn to_polygon_mesh_for_view_with_skipped(
+        &self,
+        view: &ViewTolerance,
+    ) -> (PolygonMesh, Vec<(usize, IgesError)>) {
+        let (shells, mut skipped) = self.to_compressed_shells_with_skipped_faces();
+        let mut polygon = PolygonMesh::default();
+        triangulate_converted_shells(shells, view)
+            .into_iter()
+            .for_each(|(pointer, shell)| match shell {
+                Ok(shell) => polygon.merge(shell.to_polygon()),
+                Err(e) => skipped.push((pointer, e)),
+            });
+        skipped.sort_by_key(|(pointer, _)| *pointer);
+        (polygon, skipped)
+    }
+}
@@ -0,0 +1,375 @@
+use super::*;
+use crate::r#in::alias::PCurve;
+use std::collections::{HashMap, HashSet};
+use truck_topology::compress::*;
+
+/// The shell read from IGES file, realized in `truck`
+pub type IgesCompressedShell = CompressedShell<Point3, Curve3D, Surface>;
+
+/// the shell under construction with the vertices and the edges shared by the faces
+#[derive(Debug, Default)]
+struct ShellBuilder {
+    vertices: Vec<Point3>,
+    edges: Vec<CompressedEdge<Curve3D>>,
+    faces: Vec<CompressedFace<Surface>>,
+    /// the vertices by the pointers to `502` vertex lists and the indices in them
+    vertex_map: HashMap<(usize, usize), usize>,
+    /// the edges by the pointers to `504` edge lists and the indices in them
+    edge_map: HashMap<(usize, usize), Vec<usize>>,
+}
+
+impl ShellBuilder {
+    fn push_vertex(&mut self, point: Point3) -> usize {
+        self.vertices.push(point);
+        self.vertices.len() - 1
+    }
+
+    /// Pushes the closed loop of the curves, each of which ends at the start of the next one.
+    fn push_loop(&mut self, curves: Vec<Curve3D>) -> Vec<CompressedEdgeIndex> {
+        let (first, len) = (self.vertices.len(), curves.len());
+        self.vertices
+            .extend(curves.iter().map(|curve| curve.front()));
+        curves
+            .into_iter()
+            .enumerate()
+            .map(|(i, curve)| {
+                self.edges.push(CompressedEdge {
+                    vertices: (first + i, first + (i + 1) % len),
+                    curve,
+                });
+                CompressedEdgeIndex {
+                    index: self.edges.len() - 1,
+                    orientation: true,
+                }
+            })
+            .collect()
+    }
+
+    fn build(self) -> IgesCompressedShell {
+        CompressedShell {
+            vertices: self.vertices,
+            edges: self.edges,
+            faces: self.faces,
+        }
+    }
+}
+
+fn pcurve(curve: Curve2D, surface: &Surface) -> Curve3D {
+    Curve3D::PCurve(PCurve::new(Box::new(curve), Box::new(surface.clone())))
+}
+
+fn expect_type(entity: &IgesEntity, pointer: usize, entity_type: u32) -> Result<(), IgesError> {
+    match entity.directory.entity_type == entity_type {
+        true => Ok(()),
+        false => Err(format!(
+            "the entity {pointer} is {}, not {entity_type}.",
+            entity.directory.entity_type
+        )
+        .into()),
+    }
+}
+
+impl IgesFile {
+    fn is_independent(&self, pointer: usize) -> bool {
+        let status = self.entities[&pointer].directory.status;
+        status.blank == 0 && status.subordinate == 0
+    }
+
+    /// the loop along the boundary of the parameter range of the surface
+    fn domain_boundary(
+        &self,
+        pointer: usize,
+        surface: &Surface,
+    ) -> Result<Vec<Curve3D>, IgesError> {
+        let ((u0, u1), (v0, v1)) = self.surface_domain(pointer)?;
+        let corners = [
+            Point2::new(u0, v0),
+            Point2::new(u1, v0),
+            Point2::new(u1, v1),
+            Point2::new(u0, v1),
+        ];
+        Ok((0..4)
+            .map(|i| {
+                pcurve(
+                    Curve2D::Line(Line(corners[i], corners[(i + 1) % 4])),
+                    surface,
+                )
+            })
+            .collect())
+    }
+
+    /// the curves of `142` curve on a parametric surface, the pcurves are preferred.
+    fn curve_on_surface(
+        &self,
+        pointer: usize,
+        surface: &Surface,
+    ) -> Result<Vec<Curve3D>, IgesError> {
+        let entity = self.entity(pointer)?;
+        expect_type(entity, pointer, 142)?;
+        let (parameter_curve, model_curve) = (entity.pointer(2), entity.pointer(3));
+        let preference = entity.integer_or(4, 0);
+        let curves = match parameter_curve != 0 && (preference != 2 || model_curve == 0) {
+            true => self
+                .curve_2d_segments(parameter_curve)?
+                .into_iter()
+                .map(|curve| pcurve(curve, surface))
+                .collect(),
+            false => self.curve_segments(model_curve)?,
+        };
+        match curves.is_empty() {
+            true => Err(format!("the curve on surface {pointer} is empty.").into()),
+            false => Ok(curves),
+        }
+    }
+
+    /// `144` trimmed surface or `128` rational B-spline surface as a face
+    fn surface_face(&self, pointer: usize) -> Result<IgesCompressedShell, IgesError> {
+        let mut builder = ShellBuilder::default();
+        let entity = self.entity(pointer)?;
+        let (surface_pointer, outer, inners) = match entity.directory.entity_type {
+            144 => {
+                let len = entity.integer_or(2, 0) as usize;
+                let outer = match entity.integer_or(1, 0) {
+                    0 => None,
+                    _ => Some(entity.pointer(3)),
+                };
+                let inners = (0..len).map(|i| entity.pointer(4 + i)).collect();
+                (entity.pointer(0), outer, inners)
+            }
+            _ => (pointer, None, Vec::new()),
+        };
+        let surface = self.surface(surface_pointer)?;
+        let outer = match outer {
+            Some(outer) => self.curve_on_surface(outer, &surface)?,
+            None => self.domain_boundary(surface_pointer, &surface)?,
+        };
+        let mut boundaries = vec![builder.push_loop(outer)];
+        for inner in inners {
+            let curves = self.curve_on_surface(inner, &surface)?;
+            boundaries.push(builder.push_loop(curves));
+        }
+        builder.faces.push(CompressedFace {
+            surface,
+            boundaries,
+            orientation: true,
+        });
+        Ok(builder.build())
+    }
+
+    fn vertex(
+        &self,
+        builder: &mut ShellBuilder,
+        list: usize,
+        index: usize,
+    ) -> Result<usize, IgesError> {
+        if let Some(idx) = builder.vertex_map.get(&(list, index)) {
+            return Ok(*idx);
+        }
+        let entity = self.entity(list)?;
+        expect_type(entity, list, 502)?;
+        if index == 0 {
+            return Err(format!("the vertex 0 of the vertex list {list} is referred.").into());
+        }
+        let i = 1 + 3 * (index - 1);
+        let point = Point3::new(entity.real(i)?, entity.real(i + 1)?, entity.real(i + 2)?);
+        let point = self.transformation(list)?.transform_point(point);
+        let idx = builder.push_vertex(point);
+        builder.vertex_map.insert((list, index), idx);
+        Ok(idx)
+    }
+
+    /// the edges of an edge in `504` edge list, composite curves are split into several edges.
+    fn edge(
+        &self,
+        builder: &mut ShellBuilder,
+        list: usize,
+        index: usize,
+    ) -> Result<Vec<usize>, IgesError> {
+        if let Some(edges) = builder.edge_map.get(&(list, index)) {
+            return Ok(edges.clone());
+        }
+        let entity = self.entity(list)?;
+        expect_type(entity, list, 504)?;
+        if index == 0 {
+            return Err(format!("the edge 0 of the edge list {list} is referred.").into());
+        }
+        let i = 1 + 5 * (index - 1);
+        let curves = self.curve_segments(entity.pointer(i))?;
+        let Some((last, segments)) = curves.split_last() else {
+            return Err(format!("the edge {index} of the edge list {list} has no curves.").into());
+        };
+        let front = self.vertex(builder, entity.pointer(i + 1), entity.pointer(i + 2))?;
+        let back = self.vertex(builder, entity.pointer(i + 3), entity.pointer(i + 4))?;
+        let mut vertices = vec![front];
+        vertices.extend(
+            segments
+                .iter()
+                .map(|curve| builder.push_vertex(curve.back())),
+        );
+        vertices.push(back);
+        let edges: Vec<usize> = segments
+            .iter()
+            .chain(Some(last))
+            .zip(vertices.windows(2))
+            .map(|(curve, ends)| {
+                builder.edges.push(CompressedEdge {
+                    vertices: (ends[0], ends[1]),
+                    curve: curve.clone(),
+                });
+                builder.edges.len() - 1
+            })
+            .collect();
+        builder.edge_map.insert((list, index), edges.clone());
+        Ok(edges)
+    }
+
+    /// `508` loop
+    fn boundary(
+        &self,
+        builder: &mut ShellBuilder,
+        pointer: usize,
+    ) -> Result<Vec<CompressedEdgeIndex>, IgesError> {
+        let entity = self.entity(pointer)?;
+        expect_type(entity, pointer, 508)?;
+        let len = entity.integer(0)? as usize;
+        let mut boundary = Vec::new();
+        let mut cursor = 1;
+        for _ in 0..len {
+            let is_vertex = entity.integer_or(cursor, 0) == 1;
+            let (list, index) = (entity.pointer(cursor + 1), entity.pointer(cursor + 2));
+            let orientation = entity.integer_or(cursor + 3, 1) == 1;
+            // the parameter space curves are skipped, the pcurves are made on tessellation.
+            cursor += 5 + 2 * entity.integer_or(cursor + 4, 0) as usize;
+            if is_vertex {
+                continue;
+            }
+            let edges = self.edge(builder, list, index)?;
+            match orientation {
+                true => boundary.extend(edges.into_iter().map(|index| CompressedEdgeIndex {
+                    index,
+                    orientation: true,
+                })),
+                false => {
+                    boundary.extend(edges.into_iter().rev().map(|index| CompressedEdgeIndex {
+                        index,
+                        orientation: false,
+                    }))
+                }
+            }
+        }
+        Ok(boundary)
+    }
+
+    /// `510` face
+    fn face(
+        &self,
+        builder: &mut ShellBuilder,
+        pointer: usize,
+        orientation: bool,
+    ) -> Result<(), IgesError> {
+        let entity = self.entity(pointer)?;
+        expect_type(entity, pointer, 510)?;
+        let surface_pointer = entity.pointer(0);
+        let surface = self.surface(surface_pointer)?;
+        let len = entity.integer(1)? as usize;
+        let mut boundaries = Vec::new();
+        // the boundary of the parameter range is the outer loop if it is not given.
+        if entity.integer_or(2, 1) == 0 {
+            let curves = self.domain_boundary(surface_pointer, &surface)?;
+            boundaries.push(builder.push_loop(curves));
+        }
+        for i in 0..len {
+            boundaries.push(self.boundary(builder, entity.pointer(3 + i))?);
+        }
+        builder.faces.push(CompressedFace {
+            surface,
+            boundaries,
+            orientation,
+        });
+        Ok(())
+    }
+
+    /// `514` shell, the faces which fail to be converted are skipped and pushed to `skipped`.
+    fn shell(
+        &self,
+        pointer: usize,
+        orientation: bool,
+        skipped: &mut Vec<(usize, IgesError)>,
+    ) -> Result<IgesCompressedShell, IgesError> {
+        let entity = self.entity(pointer)?;
+        expect_type(entity, pointer, 514)?;
+        let len = entity.integer(0)? as usize;
+        let mut builder = ShellBuilder::default();
+        for i in 0..len {
+            let face = entity.pointer(1 + 2 * i);
+            let face_orientation = entity.integer_or(2 + 2 * i, 1) == 1;
+            if let Err(e) = self.face(&mut builder, face, face_orientation == orientation) {
+                skipped.push((face, e));
+            }
+        }
+        Ok(builder.build())
+    }
+
+    /// Converts the shells and the independent surfaces in IGES file to `CompressedShell`s.
+    ///
+    /// - `514` shells are converted with the faces, oriented by the referring `186` B-rep solids,
+    /// - independent `144` trimmed surfaces and `128` B-spline surfaces are converted to the shells
+    ///   with a face.
+    ///
+    /// The blanked entities are ignored.
+    /// The results are sorted by the pointers of the converted entities.
+    /// The faces which fail to be converted are skipped, see
+    /// [`IgesFile::to_compressed_shells_with_skipped_faces`].
+    pub fn to_compressed_shells(&self) -> Vec<(usize, Result<IgesCompressedShell, IgesError>)> {
+        self.to_compressed_shells_with_skipped_faces().0
+    }
+
+    /// Converts the shells as [`IgesFile::to_compressed_shells`] does, returning also
+    /// the faces which fail to be converted with their pointers, sorted by the pointers.
+    pub fn to_compressed_shells_with_skipped_faces(
+        &self,
+    ) -> (
+        Vec<(usize, Result<IgesCompressedShell, IgesError>)>,
+        Vec<(usize, IgesError)>,
+    ) {
+        let mut skipped = Vec::new();
+        let mut shells = Vec::new();
+        let mut solid_shells = HashSet::new();
+        for pointer in self.pointers(186) {
+            let entity = &self.entities[&pointer];
+            if entity.directory.status.blank != 0 {
+                continue;
+            }
+            // the outer shell and the voids with their orientations
+            let len = entity.integer_or(2, 0) as usize;
+            let list = std::iter::once((entity.pointer(0), entity.integer_or(1, 1) == 1)).chain(
+                (0..len).map(|i| {
+                    (
+                        entity.pointer(3 + 2 * i),
+                        entity.integer_or(4 + 2 * i, 1) == 1,
+                    )
+                }),
+            );
+            for (shell, orientation) in list {
+                if solid_shells.insert(shell) {
+                    shells.push((shell, self.shell(shell, orientation, &mut skipped)));
+                }
+            }
+        }
+        let independents = [514, 144, 128]
+            .into_iter()
+            .flat_map(|entity_type| self.pointers(entity_type))
+            .filter(|pointer| !solid_shells.contains(pointer) && self.is_independent(*pointer))
+            .collect::<Vec<_>>();
+        independents.into_iter().for_each(|pointer| {
+            let shell = match self.entities[&pointer].directory.entity_type {
+                514 => self.shell(pointer, true, &mut skipped),
+                _ => self.surface_face(pointer),
+            };
+            shells.push((pointer, shell));
+        });
+        shells.sort_by_key(|(pointer, _)| *pointer);
+        skipped.sort_by_key(|(pointer, _)| *pointer);
+        (shells, skipped)
+    }
+}
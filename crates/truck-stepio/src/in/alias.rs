@@ -11,7 +11,9 @@ pub mod re_exports {
 pub use re_exports::*;
 
 /// Errors that occur when converting STEP format
-pub type StepConvertingError = Box<dyn std::error::Error>;
+///
+/// `Send` and `Sync` so that the conversions can run on worker threads.
+pub type StepConvertingError = Box<dyn std::error::Error + Send + Sync>;
 
 /// `ellipse`, realized in `truck`
 pub type Ellipse<P, M> = Processor<TrimmedCurve<UnitCircle<P>>, M>;
@@ -215,6 +217,7 @@ pub enum Surface {
     SweptCurve(Box<SweptCurve>),
     BSplineSurface(Box<BSplineSurface<Point3>>),
     NurbsSurface(Box<NurbsSurface<Vector4>>),
+    OffsetSurface(Box<OffsetSurface<Surface>>),
 }
 
 impl truck_stepio::out::DisplayByStep for Surface {
@@ -225,6 +228,7 @@ impl truck_stepio::out::DisplayByStep for Surface {
             SweptCurve(x) => x.fmt(idx, f),
             BSplineSurface(x) => x.fmt(idx, f),
             NurbsSurface(x) => x.fmt(idx, f),
+            OffsetSurface(x) => x.fmt(idx, f),
         }
     }
 }
@@ -383,6 +387,220 @@ impl truck_stepio::out::DisplayByStep for Processor<Sphere, Matrix4> {
     }
 }
 
+/// `offset_surface`, realized in `truck`
+///
+/// The point at `(u, v)` is the point of the basis surface moved by `distance` along its unit normal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OffsetSurface<S> {
+    surface: S,
+    distance: f64,
+}
+
+impl<S> OffsetSurface<S> {
+    /// constructor
+    #[inline(always)]
+    pub const fn new(surface: S, distance: f64) -> Self { Self { surface, distance } }
+    /// basis surface
+    #[inline(always)]
+    pub const fn surface(&self) -> &S { &self.surface }
+    /// signed distance along the normal of the basis surface
+    #[inline(always)]
+    pub const fn distance(&self) -> f64 { self.distance }
+}
+
+mod offset_surface {
+    use super::*;
+    use truck_geotrait::algo;
+
+    /// step of the central difference used for the second derivatives
+    const DIFF_STEP: f64 = 1.0e-4;
+    const PRESEARCH_DIVISION: usize = 50;
+
+    impl<S: ParametricSurface3D> OffsetSurface<S> {
+        fn normal_derivatives(&self, u: f64, v: f64) -> (Vector3, Vector3) {
+            let surface = &self.surface;
+            let (uder, vder) = (surface.uder(u, v), surface.vder(u, v));
+            let n = uder.cross(vder);
+            let len2 = n.magnitude2();
+            let len = f64::sqrt(len2);
+            if len.so_small() {
+                return (Vector3::zero(), Vector3::zero());
+            }
+            let uvder = surface.uvder(u, v);
+            let nu = surface.uuder(u, v).cross(vder) + uder.cross(uvder);
+            let nv = uvder.cross(vder) + uder.cross(surface.vvder(u, v));
+            (
+                (nu - n * (n.dot(nu) / len2)) / len,
+                (nv - n * (n.dot(nv) / len2)) / len,
+            )
+        }
+    }
+
+    impl<S: ParametricSurface3D> ParametricSurface for OffsetSurface<S> {
+        type Point = Point3;
+        type Vector = Vector3;
+        #[inline]
+        fn subs(&self, u: f64, v: f64) -> Point3 {
+            self.surface.subs(u, v) + self.distance * self.surface.normal(u, v)
+        }
+        #[inline]
+        fn uder(&self, u: f64, v: f64) -> Vector3 {
+            self.surface.uder(u, v) + self.distance * self.normal_derivatives(u, v).0
+        }
+        #[inline]
+        fn vder(&self, u: f64, v: f64) -> Vector3 {
+            self.surface.vder(u, v) + self.distance * self.normal_derivatives(u, v).1
+        }
+        #[inline]
+        fn uuder(&self, u: f64, v: f64) -> Vector3 {
+            (self.uder(u + DIFF_STEP, v) - self.uder(u - DIFF_STEP, v)) / (2.0 * DIFF_STEP)
+        }
+        #[inline]
+        fn uvder(&self, u: f64, v: f64) -> Vector3 {
+            (self.uder(u, v + DIFF_STEP) - self.uder(u, v - DIFF_STEP)) / (2.0 * DIFF_STEP)
+        }
+        #[inline]
+        fn vvder(&self, u: f64, v: f64) -> Vector3 {
+            (self.vder(u, v + DIFF_STEP) - self.vder(u, v - DIFF_STEP)) / (2.0 * DIFF_STEP)
+        }
+        #[inline]
+        fn parameter_range(&self) -> (ParameterRange, ParameterRange) {
+            self.surface.parameter_range()
+        }
+        #[inline]
+        fn u_period(&self) -> Option<f64> { self.surface.u_period() }
+        #[inline]
+        fn v_period(&self) -> Option<f64> { self.surface.v_period() }
+    }
+
+    impl<S: ParametricSurface3D> ParametricSurface3D for OffsetSurface<S> {
+        #[inline]
+        fn normal(&self, u: f64, v: f64) -> Vector3 { self.surface.normal(u, v) }
+    }
+
+    impl<S> SearchNearestParameter<D2> for OffsetSurface<S>
+    where S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>
+    {
+        type Point = Point3;
+        fn search_nearest_parameter<H: Into<SPHint2D>>(
+            &self,
+            point: Point3,
+            hint: H,
+            trials: usize,
+        ) -> Option<(f64, f64)> {
+            let hint = match hint.into() {
+                SPHint2D::Parameter(x, y) => (x, y),
+                SPHint2D::Range(range0, range1) => {
+                    algo::surface::presearch(self, point, (range0, range1), PRESEARCH_DIVISION)
+                }
+                // The foot of the perpendicular on the basis surface is also the one on the offset.
+                SPHint2D::None => self.surface.search_nearest_parameter(point, None, trials)?,
+            };
+            algo::surface::search_nearest_parameter(self, point, hint, trials)
+        }
+    }
+
+    impl<S> SearchParameter<D2> for OffsetSurface<S>
+    where S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>
+    {
+        type Point = Point3;
+        fn search_parameter<H: Into<SPHint2D>>(
+            &self,
+            point: Point3,
+            hint: H,
+            trials: usize,
+        ) -> Option<(f64, f64)> {
+            let hint = match hint.into() {
+                SPHint2D::Parameter(x, y) => (x, y),
+                SPHint2D::Range(range0, range1) => {
+                    algo::surface::presearch(self, point, (range0, range1), PRESEARCH_DIVISION)
+                }
+                SPHint2D::None => self.surface.search_nearest_parameter(point, None, trials)?,
+            };
+            algo::surface::search_parameter(self, point, hint, trials)
+        }
+    }
+
+    impl<S: ParametricSurface3D> ParameterDivision2D for OffsetSurface<S> {
+        #[inline]
+        fn parameter_division(
+            &self,
+            range: ((f64, f64), (f64, f64)),
+            tol: f64,
+        ) -> (Vec<f64>, Vec<f64>) {
+            algo::surface::parameter_division(self, range, tol)
+        }
+    }
+
+    impl<S: Invertible> Invertible for OffsetSurface<S> {
+        #[inline]
+        fn invert(&mut self) {
+            // inverting the basis flips its normal, so the sign of the distance is also flipped.
+            self.surface.invert();
+            self.distance = -self.distance;
+        }
+    }
+
+    impl<S: Transformed<Matrix4>> Transformed<Matrix4> for OffsetSurface<S> {
+        #[inline]
+        fn transform_by(&mut self, trans: Matrix4) {
+            self.surface.transform_by(trans);
+            // exact for similar transformations only
+            self.distance *= trans.determinant().cbrt();
+        }
+    }
+
+    impl<S: truck_stepio::out::StepLength> truck_stepio::out::StepLength for OffsetSurface<S> {
+        #[inline]
+        fn step_length(&self) -> usize { 1 + self.surface.step_length() }
+    }
+
+    impl<S> truck_stepio::out::DisplayByStep for OffsetSurface<S>
+    where S: truck_stepio::out::DisplayByStep + truck_stepio::out::StepSurface
+    {
+        fn fmt(&self, idx: usize, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            use truck_stepio::out::{FloatDisplay, StepSurface};
+            let basis_idx = idx + 1;
+            // the distance in STEP is measured along the normal of the output basis surface.
+            let distance = match self.surface.same_sense() {
+                true => self.distance,
+                false => -self.distance,
+            };
+            f.write_fmt(format_args!(
+                "#{idx} = OFFSET_SURFACE('', #{basis_idx}, {distance}, .F.);\n",
+                distance = FloatDisplay(distance),
+            ))?;
+            self.surface.fmt(basis_idx, f)
+        }
+    }
+
+    impl<S: truck_stepio::out::StepSurface> truck_stepio::out::StepSurface for OffsetSurface<S> {
+        #[inline(always)]
+        fn same_sense(&self) -> bool { self.surface.same_sense() }
+    }
+
+    #[cfg(test)]
+    proptest::proptest! {
+        #[test]
+        fn offset_sphere(
+            radius in 0.1f64..100.0f64,
+            distance in -0.09f64..100.0f64,
+            (u, v) in (0.0..=2.0 * std::f64::consts::PI, -1.5f64..=1.5f64),
+        ) {
+            const EPS: f64 = 1.0e-3;
+            let sphere = Sphere(truck_geometry::prelude::Sphere::new(Point3::origin(), radius));
+            let offset = OffsetSurface::new(sphere, distance);
+            let big = Sphere(truck_geometry::prelude::Sphere::new(Point3::origin(), radius + distance));
+            let (p0, p1) = (offset.subs(u, v), big.subs(u, v));
+            assert!((p0 - p1).magnitude2() < EPS, "subs failed: {p0:?}, {p1:?}");
+            let (d0, d1) = (offset.uder(u, v), big.uder(u, v));
+            assert!((d0 - d1).magnitude2() < EPS, "uder failed: {d0:?}, {d1:?}");
+            let (d0, d1) = (offset.vder(u, v), big.vder(u, v));
+            assert!((d0 - d1).magnitude2() < EPS, "vder failed: {d0:?}, {d1:?}");
+        }
+    }
+}
+
 /// Implementation required to apply a closed surface division to a shape parsed from a STEP file.
 mod from_pcurve {
     use super::{Curve2D, Curve3D, Surface};
@@ -0,0 +1,158 @@
+//! Binary cache of the shells converted from STEP files.
+//!
+//! Parsing and converting are the slow parts of reading large STEP files.
+//! The cache keeps the converted B-rep shells and the pre-tessellated meshes in a compact binary
+//! file, so that the shapes are triangulated again for other images without parsing the file.
+//! The cache remembers the hash of the STEP file and is rebuilt when the file changes.
+
+use super::{tessellation::*, *};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// the first bytes of the cache files
+const MAGIC: &[u8; 8] = b"TRKSTEPC";
+/// incremented when the layout of [`StepCache`] changes
+const VERSION: u32 = 1;
+
+/// The 64-bit FNV-1a hash of the STEP file, stable across the platforms and the builds.
+pub fn source_hash(step_bytes: &[u8]) -> u64 {
+    step_bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// The shells converted from a STEP file, to be saved and reloaded
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StepCache {
+    /// the hash of the STEP file by [`source_hash`]
+    pub source_hash: u64,
+    /// the converted B-rep shells sorted by the entity ids, with the messages of the failures
+    pub shells: Vec<(u64, Result<StepCompressedShell, String>)>,
+    /// the meshes of the tessellated shells sorted by the entity ids
+    pub tessellated_shells: Vec<(u64, Result<PolygonMesh, String>)>,
+}
+
+fn invalid_data(message: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn bincode_error(error: bincode::Error) -> io::Error {
+    match *error {
+        bincode::ErrorKind::Io(error) => error,
+        error => invalid_data(error),
+    }
+}
+
+impl StepCache {
+    /// Converts the shells of `table` read from the STEP file whose hash is `source_hash`.
+    pub fn from_table(table: &Table, source_hash: u64) -> Self {
+        let shells = table
+            .to_compressed_shells()
+            .into_iter()
+            .map(|(idx, shell)| (idx, shell.map_err(|e| e.to_string())))
+            .collect();
+        let mut tessellated_shells: Vec<_> = table
+            .tessellated_shell
+            .iter()
+            .map(|(idx, shell)| {
+                let mesh = table.to_polygon_mesh(shell).map_err(|e| e.to_string());
+                (*idx, mesh)
+            })
+            .collect();
+        tessellated_shells.sort_by_key(|(idx, _)| *idx);
+        Self {
+            source_hash,
+            shells,
+            tessellated_shells,
+        }
+    }
+
+    /// Parses the STEP file and converts its shells.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::cache::StepCache;
+    /// let step_string = include_str!(concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/../resources/step/occt-cube.step",
+    /// ));
+    /// let cache = StepCache::from_step(step_string).unwrap();
+    /// let mut bytes = Vec::new();
+    /// cache.write(&mut bytes).unwrap();
+    /// let reloaded = StepCache::read(bytes.as_slice()).unwrap();
+    /// assert_eq!(reloaded.shells.len(), 1);
+    /// assert!(reloaded.is_cache_of(step_string.as_bytes()));
+    /// ```
+    pub fn from_step(step_str: &str) -> Option<Self> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        let table = Table::from_data_section(exchange.data.first()?);
+        Some(Self::from_table(&table, source_hash(step_str.as_bytes())))
+    }
+
+    /// Returns `true` if the cache was made from `step_bytes`.
+    #[inline]
+    pub fn is_cache_of(&self, step_bytes: &[u8]) -> bool {
+        self.source_hash == source_hash(step_bytes)
+    }
+
+    /// Writes the cache in the binary format.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, self).map_err(bincode_error)?;
+        writer.flush()
+    }
+
+    /// Reads the cache written by [`StepCache::write`].
+    ///
+    /// The files of the other formats or the other versions are [`io::ErrorKind::InvalidData`].
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_data("not a cache of STEP file"));
+        }
+        let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported cache version: {version}"
+            )));
+        }
+        bincode::deserialize_from(reader).map_err(bincode_error)
+    }
+
+    /// Reloads the cache of the STEP file at `step_path` from `cache_path`,
+    /// or parses the STEP file and saves the cache if it is missing, broken or outdated.
+    pub fn load_or_create(
+        step_path: impl AsRef<Path>,
+        cache_path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let step_bytes = std::fs::read(step_path)?;
+        let cache_path = cache_path.as_ref();
+        if let Ok(file) = std::fs::File::open(cache_path) {
+            match Self::read(io::BufReader::new(file)) {
+                Ok(cache) if cache.is_cache_of(&step_bytes) => return Ok(cache),
+                _ => {}
+            }
+        }
+        let step_str = std::str::from_utf8(&step_bytes).map_err(invalid_data)?;
+        let cache =
+            Self::from_step(step_str).ok_or_else(|| invalid_data("failed to parse STEP file"))?;
+        let file = std::fs::File::create(cache_path)?;
+        cache.write(io::BufWriter::new(file))?;
+        Ok(cache)
+    }
+
+    /// Triangulates the B-rep shells for the image given by `view`,
+    /// as [`Table::triangulate_shells_for_view`] does.
+    pub fn triangulate_shells_for_view(
+        &self,
+        view: &ViewTolerance,
+    ) -> Vec<(u64, Result<MeshedShell, String>)> {
+        let shells = self
+            .shells
+            .iter()
+            .map(|(idx, shell)| (*idx, shell.as_ref().map_err(Clone::clone)))
+            .collect();
+        triangulate_converted_shells(shells, view)
+    }
+}
@@ -0,0 +1,485 @@
+//! Compares the shapes of two tables structurally.
+//!
+//! The entity ids change on every export, so the shells and the faces are matched by their
+//! geometry signatures: the kinds of the surfaces, the areas, the centroids and the volumes,
+//! all computed from the triangulations with a common tolerance.
+//!
+//! The shells whose faces and volumes all match are unchanged.
+//! The rest are paired by the overlaps of their bounding boxes as modified shells,
+//! and the shells left without pairs are removed or added.
+
+use super::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use tessellation::{control_point_bounding_box, StepCompressedShell};
+use truck_meshalgo::tessellation::RobustMeshableShape;
+
+/// the tolerance of the triangulations relative to the diameter of the models
+const MESH_RATIO: f64 = 1.0e-3;
+
+/// Kind of the surface of a face
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SurfaceKind {
+    Plane,
+    Sphere,
+    Cylinder,
+    Torus,
+    Cone,
+    Extrusion,
+    Revolution,
+    BSpline,
+    Nurbs,
+    Offset,
+}
+
+impl From<&Surface> for SurfaceKind {
+    fn from(surface: &Surface) -> Self {
+        match surface {
+            Surface::ElementarySurface(surface) => match **surface {
+                ElementarySurface::Plane(_) => SurfaceKind::Plane,
+                ElementarySurface::Sphere(_) => SurfaceKind::Sphere,
+                ElementarySurface::CylindricalSurface(_) => SurfaceKind::Cylinder,
+                ElementarySurface::ToroidalSurface(_) => SurfaceKind::Torus,
+                ElementarySurface::ConicalSurface(_) => SurfaceKind::Cone,
+            },
+            Surface::SweptCurve(surface) => match **surface {
+                SweptCurve::ExtrudedCurve(_) => SurfaceKind::Extrusion,
+                SweptCurve::RevolutedCurve(_) => SurfaceKind::Revolution,
+            },
+            Surface::BSplineSurface(_) => SurfaceKind::BSpline,
+            Surface::NurbsSurface(_) => SurfaceKind::Nurbs,
+            Surface::OffsetSurface(_) => SurfaceKind::Offset,
+        }
+    }
+}
+
+/// Geometry signature of a face
+#[derive(Clone, Debug)]
+pub struct FaceSignature {
+    /// the kind of the surface
+    pub kind: SurfaceKind,
+    /// the area of the triangulation
+    pub area: f64,
+    /// the centroid of the triangulation
+    pub centroid: Point3,
+    /// the triangulation, oriented as the shell
+    pub mesh: PolygonMesh,
+}
+
+/// Geometry signature of a shell
+#[derive(Clone, Debug)]
+pub struct ShellSignature {
+    /// the entity id of the shell
+    pub id: u64,
+    /// the signatures of the faces
+    pub faces: Vec<FaceSignature>,
+    /// the sum of the areas of the faces
+    pub area: f64,
+    /// the enclosed volume, meaningful only for closed shells
+    pub volume: f64,
+    /// the bounding box of the triangulation
+    pub bounding_box: BoundingBox<Point3>,
+}
+
+fn triangles(mesh: &PolygonMesh) -> impl Iterator<Item = [Point3; 3]> + '_ {
+    let positions = mesh.positions();
+    mesh.faces()
+        .triangle_iter()
+        .map(move |tri| tri.map(|v| positions[v.pos]))
+}
+
+/// the allowed difference of the areas of the faces whose boundaries are within `length`
+fn area_tolerance(length: f64, area: f64) -> f64 { 4.0 * length * area.sqrt() + length * length }
+
+impl FaceSignature {
+    fn new(kind: SurfaceKind, mesh: PolygonMesh) -> Self {
+        let (mut area, mut moment) = (0.0, Vector3::zero());
+        triangles(&mesh).for_each(|[a, b, c]| {
+            let s = (b - a).cross(c - a).magnitude() / 2.0;
+            area += s;
+            moment += (a.to_vec() + b.to_vec() + c.to_vec()) * (s / 3.0);
+        });
+        let centroid = match area > 0.0 {
+            true => Point3::from_vec(moment / area),
+            false => Point3::origin(),
+        };
+        Self {
+            kind,
+            area,
+            centroid,
+            mesh,
+        }
+    }
+
+    /// Returns `true` if the faces are the same within `tolerance`.
+    pub fn matches(&self, other: &FaceSignature, tolerance: f64) -> bool {
+        self.kind == other.kind
+            && f64::abs(self.area - other.area)
+                <= area_tolerance(tolerance, f64::max(self.area, other.area))
+            && self.centroid.distance(other.centroid) <= tolerance
+    }
+}
+
+impl ShellSignature {
+    /// Triangulates `shell` with `mesh_tolerance` and computes the signature.
+    pub fn new(id: u64, shell: &StepCompressedShell, mesh_tolerance: f64) -> Self {
+        let meshed = shell.robust_triangulation(mesh_tolerance);
+        let faces: Vec<FaceSignature> = shell
+            .faces
+            .iter()
+            .zip(meshed.faces)
+            .map(|(face, meshed)| {
+                let mut mesh = meshed.surface.unwrap_or_default();
+                if !meshed.orientation {
+                    mesh.invert();
+                }
+                FaceSignature::new(SurfaceKind::from(&face.surface), mesh)
+            })
+            .collect();
+        let volume = faces
+            .iter()
+            .flat_map(|face| triangles(&face.mesh))
+            .map(|[a, b, c]| a.to_vec().dot(b.to_vec().cross(c.to_vec())) / 6.0)
+            .sum::<f64>();
+        Self {
+            id,
+            area: faces.iter().map(|face| face.area).sum(),
+            volume: volume.abs(),
+            bounding_box: faces
+                .iter()
+                .flat_map(|face| face.mesh.positions())
+                .collect(),
+            faces,
+        }
+    }
+
+    /// the allowed difference of the areas of the shells
+    fn area_tolerance(&self, tolerance: f64) -> f64 {
+        self.faces
+            .iter()
+            .map(|face| area_tolerance(tolerance, face.area))
+            .sum()
+    }
+}
+
+/// Pairs of the faces matched by the signatures
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FaceMatching {
+    /// the pairs of the indices of the faces in the old shell and the new shell
+    pub matched: Vec<(usize, usize)>,
+    /// the faces of the old shell without pairs
+    pub removed: Vec<usize>,
+    /// the faces of the new shell without pairs
+    pub added: Vec<usize>,
+}
+
+impl FaceMatching {
+    /// Matches the faces greedily, each face with the nearest one among the matching faces.
+    pub fn new(old: &[FaceSignature], new: &[FaceSignature], tolerance: f64) -> Self {
+        let mut used = vec![false; new.len()];
+        let mut matching = FaceMatching::default();
+        old.iter().enumerate().for_each(|(i, face)| {
+            let found = new
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| !used[*j] && face.matches(other, tolerance))
+                .min_by(|(_, a), (_, b)| {
+                    let da = face.centroid.distance2(a.centroid);
+                    da.total_cmp(&face.centroid.distance2(b.centroid))
+                });
+            match found {
+                Some((j, _)) => {
+                    used[j] = true;
+                    matching.matched.push((i, j));
+                }
+                None => matching.removed.push(i),
+            }
+        });
+        matching.added = (0..new.len()).filter(|j| !used[*j]).collect();
+        matching
+    }
+
+    /// Returns `true` if all faces are matched.
+    pub fn is_complete(&self) -> bool { self.removed.is_empty() && self.added.is_empty() }
+}
+
+/// Shells paired between the old and the new tables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShellPair {
+    /// the index of the old shell in [`StepDiff::old`]
+    pub old: usize,
+    /// the index of the new shell in [`StepDiff::new`]
+    pub new: usize,
+    /// the matching of the faces
+    pub faces: FaceMatching,
+}
+
+/// Structural difference between the shapes of two tables
+#[derive(Clone, Debug)]
+pub struct StepDiff {
+    /// the tolerance of the lengths, including the one of the triangulations
+    pub tolerance: f64,
+    /// the signatures of the old shells
+    pub old: Vec<ShellSignature>,
+    /// the signatures of the new shells
+    pub new: Vec<ShellSignature>,
+    /// the shells whose faces and volumes all match
+    pub unchanged: Vec<ShellPair>,
+    /// the shells paired by the overlaps of their bounding boxes
+    pub modified: Vec<ShellPair>,
+    /// the indices of the old shells without pairs
+    pub removed: Vec<usize>,
+    /// the indices of the new shells without pairs
+    pub added: Vec<usize>,
+    /// the ids of the old shells which failed to be converted, with the errors
+    pub old_failed: Vec<(u64, String)>,
+    /// the ids of the new shells which failed to be converted, with the errors
+    pub new_failed: Vec<(u64, String)>,
+}
+
+/// the overlap of the bounding boxes expanded by `tolerance`, `0.0` if they are disjoint
+///
+/// It is the diagonal of the intersection relative to the larger diagonal of the boxes.
+fn overlap(a: &BoundingBox<Point3>, b: &BoundingBox<Point3>, tolerance: f64) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let (min, max) = (
+        Point3::new(
+            f64::max(a.min().x, b.min().x),
+            f64::max(a.min().y, b.min().y),
+            f64::max(a.min().z, b.min().z),
+        ),
+        Point3::new(
+            f64::min(a.max().x, b.max().x),
+            f64::min(a.max().y, b.max().y),
+            f64::min(a.max().z, b.max().z),
+        ),
+    );
+    let diagonal = max - min;
+    if (0..3).any(|i| diagonal[i] < -2.0 * tolerance) {
+        return 0.0;
+    }
+    let diagonal = diagonal.map(|x| f64::max(x, 0.0)).magnitude() + tolerance;
+    diagonal / (f64::max(a.diameter(), b.diameter()) + tolerance)
+}
+
+impl StepDiff {
+    /// Compares the signatures of the shells with `tolerance` of the lengths.
+    ///
+    /// The signatures should be triangulated with the same tolerance,
+    /// which is included in `tolerance`.
+    pub fn new(old: Vec<ShellSignature>, new: Vec<ShellSignature>, tolerance: f64) -> Self {
+        let mut new_used = vec![false; new.len()];
+        let mut old_used = vec![false; old.len()];
+        let mut unchanged = Vec::new();
+        old.iter().enumerate().for_each(|(i, shell)| {
+            let found = new.iter().enumerate().find_map(|(j, other)| {
+                let candidate = !new_used[j]
+                    && shell.faces.len() == other.faces.len()
+                    && f64::abs(shell.volume - other.volume) <= shell.area * tolerance
+                    && f64::abs(shell.area - other.area) <= shell.area_tolerance(tolerance)
+                    && shell.bounding_box.min().distance(other.bounding_box.min()) <= tolerance
+                    && shell.bounding_box.max().distance(other.bounding_box.max()) <= tolerance;
+                if !candidate {
+                    return None;
+                }
+                let faces = FaceMatching::new(&shell.faces, &other.faces, tolerance);
+                faces.is_complete().then_some((j, faces))
+            });
+            if let Some((j, faces)) = found {
+                (old_used[i], new_used[j]) = (true, true);
+                unchanged.push(ShellPair {
+                    old: i,
+                    new: j,
+                    faces,
+                });
+            }
+        });
+
+        let mut overlaps: Vec<(f64, usize, usize)> = (0..old.len())
+            .filter(|i| !old_used[*i])
+            .flat_map(|i| (0..new.len()).map(move |j| (i, j)))
+            .filter(|(_, j)| !new_used[*j])
+            .map(|(i, j)| {
+                let overlap = overlap(&old[i].bounding_box, &new[j].bounding_box, tolerance);
+                (overlap, i, j)
+            })
+            .filter(|(overlap, _, _)| *overlap > 0.0)
+            .collect();
+        overlaps.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        let mut modified = Vec::new();
+        overlaps.into_iter().for_each(|(_, i, j)| {
+            if old_used[i] || new_used[j] {
+                return;
+            }
+            (old_used[i], new_used[j]) = (true, true);
+            let faces = FaceMatching::new(&old[i].faces, &new[j].faces, tolerance);
+            modified.push(ShellPair {
+                old: i,
+                new: j,
+                faces,
+            });
+        });
+        modified.sort_by_key(|pair| pair.old);
+
+        Self {
+            tolerance,
+            removed: (0..old.len()).filter(|i| !old_used[*i]).collect(),
+            added: (0..new.len()).filter(|j| !new_used[*j]).collect(),
+            old,
+            new,
+            unchanged,
+            modified,
+            old_failed: Vec::new(),
+            new_failed: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if no shell is modified, removed or added.
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// The change of the volume of the paired shells, the new one minus the old one.
+    pub fn volume_change(&self, pair: &ShellPair) -> f64 {
+        self.new[pair.new].volume - self.old[pair.old].volume
+    }
+
+    /// The change of the area of the paired shells, the new one minus the old one.
+    pub fn area_change(&self, pair: &ShellPair) -> f64 {
+        self.new[pair.new].area - self.old[pair.old].area
+    }
+}
+
+fn fmt_bounding_box(bdd: &BoundingBox<Point3>) -> String {
+    match bdd.is_empty() {
+        true => "empty".to_string(),
+        false => {
+            let (min, max) = (bdd.min(), bdd.max());
+            format!(
+                "({:.6}, {:.6}, {:.6})-({:.6}, {:.6}, {:.6})",
+                min.x, min.y, min.z, max.x, max.y, max.z,
+            )
+        }
+    }
+}
+
+impl std::fmt::Display for StepDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "unchanged: {}, modified: {}, removed: {}, added: {}, tolerance: {:e}",
+            self.unchanged.len(),
+            self.modified.len(),
+            self.removed.len(),
+            self.added.len(),
+            self.tolerance,
+        )?;
+        for pair in &self.modified {
+            let (old, new) = (&self.old[pair.old], &self.new[pair.new]);
+            writeln!(
+                f,
+                "modified shell #{} -> #{}: volume {:+.6}, area {:+.6}, faces: {} matched, {} removed, {} added",
+                old.id,
+                new.id,
+                self.volume_change(pair),
+                self.area_change(pair),
+                pair.faces.matched.len(),
+                pair.faces.removed.len(),
+                pair.faces.added.len(),
+            )?;
+            let (old_bdd, new_bdd) = (&old.bounding_box, &new.bounding_box);
+            if old_bdd.min().distance(new_bdd.min()) > self.tolerance
+                || old_bdd.max().distance(new_bdd.max()) > self.tolerance
+            {
+                writeln!(
+                    f,
+                    "  bounding box {} -> {}",
+                    fmt_bounding_box(old_bdd),
+                    fmt_bounding_box(new_bdd),
+                )?;
+            }
+        }
+        let shells = [
+            ("removed", &self.removed, &self.old),
+            ("added", &self.added, &self.new),
+        ];
+        for (label, indices, signatures) in shells {
+            for shell in indices.iter().map(|i| &signatures[*i]) {
+                writeln!(
+                    f,
+                    "{label} shell #{}: volume {:.6}, area {:.6}, {} faces, bounding box {}",
+                    shell.id,
+                    shell.volume,
+                    shell.area,
+                    shell.faces.len(),
+                    fmt_bounding_box(&shell.bounding_box),
+                )?;
+            }
+        }
+        let failed = [("old", &self.old_failed), ("new", &self.new_failed)];
+        for (label, failed) in failed {
+            for (id, error) in failed {
+                writeln!(f, "failed {label} shell #{id}: {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the signatures of the converted shells.
+///
+/// Returns the signatures and the ids of the shells failed to be converted with the errors.
+fn shell_signatures(
+    shells: Vec<(u64, Result<StepCompressedShell, StepConvertingError>)>,
+    mesh_tolerance: f64,
+) -> (Vec<ShellSignature>, Vec<(u64, String)>) {
+    #[cfg(not(feature = "parallel"))]
+    let shells = shells.into_iter();
+    #[cfg(feature = "parallel")]
+    let shells = shells.into_par_iter();
+    let results: Vec<_> = shells
+        .map(|(idx, shell)| match shell {
+            Ok(shell) => Ok(ShellSignature::new(idx, &shell, mesh_tolerance)),
+            Err(e) => Err((idx, e.to_string())),
+        })
+        .collect();
+    let (mut signatures, mut failed) = (Vec::new(), Vec::new());
+    results.into_iter().for_each(|result| match result {
+        Ok(signature) => signatures.push(signature),
+        Err(failure) => failed.push(failure),
+    });
+    (signatures, failed)
+}
+
+impl Table {
+    /// Compares the shapes with the ones of `new`, the next revision of the model.
+    ///
+    /// `tolerance` is the tolerance of the lengths, e.g. the uncertainty of the files.
+    /// The two tables should be in the same length unit.
+    /// Both are triangulated with the tolerance relative to the diameter of the models,
+    /// which is added to `tolerance` on matching the signatures.
+    pub fn diff(&self, new: &Table, tolerance: f64) -> StepDiff {
+        let (old_shells, new_shells) = (self.to_compressed_shells(), new.to_compressed_shells());
+        let model = old_shells
+            .iter()
+            .chain(&new_shells)
+            .filter_map(|(_, shell)| shell.as_ref().ok())
+            .fold(BoundingBox::new(), |mut model, shell| {
+                model += &control_point_bounding_box(shell);
+                model
+            });
+        let mesh_tolerance = match model.is_empty() {
+            true => 0.01,
+            false => f64::max(model.diameter() * MESH_RATIO, 10.0 * TOLERANCE),
+        };
+        let (old, old_failed) = shell_signatures(old_shells, mesh_tolerance);
+        let (new, new_failed) = shell_signatures(new_shells, mesh_tolerance);
+        StepDiff {
+            old_failed,
+            new_failed,
+            ..StepDiff::new(old, new, tolerance + mesh_tolerance)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Holders generated from the EXPRESS schema `express/10303-04x.exp`.
+//!
+//! Do not edit this file by hand: list the entities in `express/holders.txt`
+//! and run `cargo run --example express-codegen`.
+
+use super::*;
+
+/// `transition_code`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransitionCode {
+    Discontinuous,
+    Continuous,
+    ContSameGradient,
+    ContSameGradientSameCurvature,
+}
+
+/// `composite_curve`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = composite_curve)]
+#[holder(generate_deserialize)]
+pub struct CompositeCurve {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub segments: Vec<CompositeCurveSegment>,
+    pub self_intersect: Logical,
+}
+
+/// `composite_curve_segment`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = composite_curve_segment)]
+#[holder(generate_deserialize)]
+pub struct CompositeCurveSegment {
+    pub transition: TransitionCode,
+    pub same_sense: bool,
+    #[holder(use_place_holder)]
+    pub parent_curve: CurveAny,
+}
+
+/// `offset_curve_3d`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = offset_curve_3d)]
+#[holder(generate_deserialize)]
+pub struct OffsetCurve3d {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub basis_curve: CurveAny,
+    pub distance: f64,
+    pub self_intersect: Logical,
+    #[holder(use_place_holder)]
+    pub ref_direction: Direction,
+}
+
+/// `point_on_curve`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = point_on_curve)]
+#[holder(generate_deserialize)]
+pub struct PointOnCurve {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub basis_curve: CurveAny,
+    pub point_parameter: f64,
+}
+
+/// `point_on_surface`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = point_on_surface)]
+#[holder(generate_deserialize)]
+pub struct PointOnSurface {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub basis_surface: SurfaceAny,
+    pub point_parameter_u: f64,
+    pub point_parameter_v: f64,
+}
+
+/// `poly_loop`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = poly_loop)]
+#[holder(generate_deserialize)]
+pub struct PolyLoop {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub polygon: Vec<CartesianPoint>,
+}
+
+/// `vertex_loop`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = vertex_loop)]
+#[holder(generate_deserialize)]
+pub struct VertexLoop {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub loop_vertex: Dummy,
+}
+
+impl Table {
+    /// Pushes the instance of a generated entity. Returns `false` if the entity is not generated.
+    pub(super) fn push_generated_instance(
+        &mut self,
+        id: u64,
+        record: &Record,
+    ) -> ruststep::error::Result<bool> {
+        match record.name.as_str() {
+            "COMPOSITE_CURVE" => {
+                self.composite_curve
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            "COMPOSITE_CURVE_SEGMENT" => {
+                self.composite_curve_segment
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            "OFFSET_CURVE_3D" => {
+                self.offset_curve_3d
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            "POINT_ON_CURVE" => {
+                self.point_on_curve
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            "POINT_ON_SURFACE" => {
+                self.point_on_surface
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            "POLY_LOOP" => {
+                self.poly_loop.insert(id, Deserialize::deserialize(record)?);
+            }
+            "VERTEX_LOOP" => {
+                self.vertex_loop
+                    .insert(id, Deserialize::deserialize(record)?);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,195 @@
+//! Reads the HEADER section of STEP files.
+//!
+//! The output module writes the header from [`StepHeaderDescriptor`](crate::out::StepHeaderDescriptor).
+//! [`StepHeader`] is the counterpart read from `FILE_DESCRIPTION`, `FILE_NAME` and `FILE_SCHEMA`.
+
+use super::stream::{is_data_keyword, StatementReader};
+use super::*;
+use std::io::{self, Read};
+
+/// Application protocols of STEP files
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StepSchema {
+    /// AP203, configuration controlled 3D design
+    Ap203,
+    /// AP214, core data for automotive mechanical design processes
+    Ap214,
+    /// AP242, managed model based 3D engineering
+    Ap242,
+    /// other schemas, with the identifier in `FILE_SCHEMA`
+    Other(String),
+}
+
+impl StepSchema {
+    /// Detects the application protocol from the schema identifier in `FILE_SCHEMA`.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::header::StepSchema;
+    /// let schema = StepSchema::from_identifier("AUTOMOTIVE_DESIGN { 1 0 10303 214 3 1 1 }");
+    /// assert_eq!(schema, StepSchema::Ap214);
+    /// ```
+    pub fn from_identifier(identifier: &str) -> Self {
+        let upper = identifier.to_uppercase();
+        let contains = |keys: &[&str]| keys.iter().any(|key| upper.contains(key));
+        if contains(&["AP242", "10303 442", "MANAGED_MODEL_BASED"]) {
+            StepSchema::Ap242
+        } else if contains(&["AP214", "10303 214", "AUTOMOTIVE_DESIGN"]) {
+            StepSchema::Ap214
+        } else if contains(&["AP203", "10303 203", "CONFIG_CONTROL_DESIGN"]) {
+            StepSchema::Ap203
+        } else {
+            StepSchema::Other(identifier.to_string())
+        }
+    }
+}
+
+/// The HEADER section of STEP file
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepHeader {
+    /// description in `FILE_DESCRIPTION`
+    pub description: Vec<String>,
+    /// implementation level in `FILE_DESCRIPTION`, e.g. `2;1`
+    pub implementation_level: String,
+    /// file name
+    pub file_name: String,
+    /// time stamp
+    pub time_stamp: String,
+    /// authors
+    pub authors: Vec<String>,
+    /// organization
+    pub organization: Vec<String>,
+    /// preprocessor version, i.e. the system which wrote the file
+    pub preprocessor_version: String,
+    /// originating system, i.e. the CAD tool which made the shape
+    pub originating_system: String,
+    /// authorization
+    pub authorization: String,
+    /// schema identifiers in `FILE_SCHEMA`
+    pub schema_identifiers: Vec<String>,
+}
+
+fn parameter_to_string(parameter: Option<&Parameter>) -> String {
+    match parameter {
+        Some(Parameter::String(string)) => string.clone(),
+        Some(Parameter::Typed { parameter, .. }) => parameter_to_string(Some(parameter)),
+        _ => String::new(),
+    }
+}
+
+fn parameter_to_strings(parameter: Option<&Parameter>) -> Vec<String> {
+    match parameter {
+        Some(Parameter::List(list)) => list
+            .iter()
+            .map(|parameter| parameter_to_string(Some(parameter)))
+            .filter(|string| !string.is_empty())
+            .collect(),
+        Some(Parameter::String(string)) if !string.is_empty() => vec![string.clone()],
+        _ => Vec::new(),
+    }
+}
+
+impl StepHeader {
+    /// Reads the header from the records in the HEADER section.
+    ///
+    /// Unknown records and broken parameters are ignored, and the lacked fields are left empty.
+    pub fn from_records(records: &[Record]) -> Self {
+        let mut header = StepHeader::default();
+        for record in records {
+            let params = match &record.parameter {
+                Parameter::List(params) => params.as_slice(),
+                _ => &[],
+            };
+            match record.name.as_str() {
+                "FILE_DESCRIPTION" => {
+                    header.description = parameter_to_strings(params.first());
+                    header.implementation_level = parameter_to_string(params.get(1));
+                }
+                "FILE_NAME" => {
+                    header.file_name = parameter_to_string(params.first());
+                    header.time_stamp = parameter_to_string(params.get(1));
+                    header.authors = parameter_to_strings(params.get(2));
+                    header.organization = parameter_to_strings(params.get(3));
+                    header.preprocessor_version = parameter_to_string(params.get(4));
+                    header.originating_system = parameter_to_string(params.get(5));
+                    header.authorization = parameter_to_string(params.get(6));
+                }
+                "FILE_SCHEMA" => header.schema_identifiers = parameter_to_strings(params.first()),
+                _ => {}
+            }
+        }
+        header
+    }
+
+    /// Reads the header of the STEP file.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::header::*;
+    /// let step_str = "ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION(('a cube'), '2;1');
+    /// FILE_NAME('cube.step', '2024-01-01T00:00:00', ('author'), ('org'), 'pre 1.0', 'cad 2.0', '');
+    /// FILE_SCHEMA(('AUTOMOTIVE_DESIGN { 1 0 10303 214 3 1 1 }'));
+    /// ENDSEC;
+    /// DATA;
+    /// ENDSEC;
+    /// END-ISO-10303-21;";
+    /// let header = StepHeader::from_step(step_str).unwrap();
+    /// assert_eq!(header.file_name, "cube.step");
+    /// assert_eq!(header.originating_system, "cad 2.0");
+    /// assert_eq!(header.schema(), Some(StepSchema::Ap214));
+    /// ```
+    pub fn from_step(step_str: &str) -> Option<Self> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        Some(StepHeader::from_records(&exchange.header))
+    }
+
+    /// Reads only the header from `reader`, stopping at the end of the HEADER section.
+    ///
+    /// This is much cheaper than [`StepHeader::from_step`] for large files.
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Self> {
+        let mut statements = Vec::new();
+        let mut in_header = false;
+        for statement in StatementReader::new(reader) {
+            let statement = statement?;
+            match (in_header, statement.as_str()) {
+                (false, "HEADER") => in_header = true,
+                (false, statement) if is_data_keyword(statement) => break,
+                (true, "ENDSEC") => break,
+                (true, _) => statements.push(statement),
+                _ => {}
+            }
+        }
+        let step_str = format!(
+            "ISO-10303-21;\nHEADER;\n{}\nENDSEC;\nDATA;\nENDSEC;\nEND-ISO-10303-21;\n",
+            statements
+                .iter()
+                .map(|statement| format!("{statement};"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        let exchange = ruststep::parser::parse(&step_str)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(StepHeader::from_records(&exchange.header))
+    }
+
+    /// The application protocol of the first schema identifier.
+    #[inline]
+    pub fn schema(&self) -> Option<StepSchema> {
+        self.schema_identifiers
+            .first()
+            .map(|identifier| StepSchema::from_identifier(identifier))
+    }
+}
+
+impl Table {
+    /// Reads the STEP file with its header.
+    #[inline]
+    pub fn from_step_with_header(step_str: &str) -> Option<(Table, StepHeader)> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        let table = match exchange.data.first() {
+            Some(data_section) => Table::from_data_section(data_section),
+            None => Table::default(),
+        };
+        Some((table, StepHeader::from_records(&exchange.header)))
+    }
+}
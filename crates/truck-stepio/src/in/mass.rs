@@ -0,0 +1,203 @@
+//! Computes the mass properties of the triangulated shells.
+//!
+//! The volume integrals are summed over the tetrahedra spanned by the origin and the triangles,
+//! so the volume, the centre of mass and the inertia are exact for the closed meshes
+//! and meaningless for the open ones. The density is one throughout.
+
+use super::*;
+use tessellation::MeshedShell;
+
+/// the number of the sweeps of the Jacobi method, enough for 3x3 matrices
+const JACOBI_SWEEPS: usize = 32;
+
+/// Mass properties of a mesh with the unit density
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MassProperties {
+    /// the enclosed volume
+    pub volume: f64,
+    /// the surface area
+    pub area: f64,
+    /// the centre of mass
+    pub centre_of_mass: [f64; 3],
+    /// the inertia tensor about the centre of mass
+    pub inertia: [[f64; 3]; 3],
+    /// the principal moments of inertia, in ascending order
+    pub principal_moments: [f64; 3],
+    /// the unit principal axes corresponding to the moments, forming a right-handed frame
+    pub principal_axes: [[f64; 3]; 3],
+}
+
+/// the integrals over the volume and the surface, accumulated triangle by triangle
+#[derive(Clone, Copy, Debug, Default)]
+struct Moments {
+    volume: f64,
+    area: f64,
+    /// the integral of the position over the volume
+    first: [f64; 3],
+    /// the integral of the products of the coordinates over the volume
+    second: [[f64; 3]; 3],
+}
+
+impl Moments {
+    /// adds the triangles of `mesh` placed by `transform`
+    fn add_mesh(&mut self, mesh: &PolygonMesh, transform: &Matrix4) {
+        let positions = mesh.positions();
+        mesh.faces().triangle_iter().for_each(|tri| {
+            let [a, b, c] = tri.map(|v| transform.transform_point(positions[v.pos]).to_vec());
+            self.area += (b - a).cross(c - a).magnitude() / 2.0;
+            let volume = a.dot(b.cross(c)) / 6.0;
+            let sum = a + b + c;
+            self.volume += volume;
+            (0..3).for_each(|i| {
+                self.first[i] += volume * sum[i] / 4.0;
+                (0..3).for_each(|j| {
+                    let products = a[i] * a[j] + b[i] * b[j] + c[i] * c[j];
+                    self.second[i][j] += volume * (products + sum[i] * sum[j]) / 20.0;
+                })
+            });
+        });
+    }
+}
+
+/// the eigenvalues and the eigenvectors, as the columns, of the symmetric matrix
+fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let scale = (0..3).map(|i| a[i][i].abs()).fold(0.0, f64::max);
+    for _ in 0..JACOBI_SWEEPS {
+        let off = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+        if off <= f64::EPSILON * scale {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + f64::sqrt(theta * theta + 1.0));
+            let c = 1.0 / f64::sqrt(t * t + 1.0);
+            let s = t * c;
+            for row in a.iter_mut().chain(v.iter_mut()) {
+                let (kp, kq) = (row[p], row[q]);
+                row[p] = c * kp - s * kq;
+                row[q] = s * kp + c * kq;
+            }
+            let (rp, rq) = (a[p], a[q]);
+            (0..3).for_each(|k| {
+                a[p][k] = c * rp[k] - s * rq[k];
+                a[q][k] = s * rp[k] + c * rq[k];
+            });
+        }
+    }
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+impl From<Moments> for MassProperties {
+    fn from(mut moments: Moments) -> Self {
+        // the inverted meshes give the negative volumes.
+        if moments.volume < 0.0 {
+            moments.volume = -moments.volume;
+            moments.first = moments.first.map(|x| -x);
+            moments.second = moments.second.map(|row| row.map(|x| -x));
+        }
+        let Moments {
+            volume,
+            area,
+            first,
+            second,
+        } = moments;
+        let centre_of_mass = match volume > 0.0 {
+            true => first.map(|x| x / volume),
+            false => [0.0; 3],
+        };
+        let mut covariance = [[0.0; 3]; 3];
+        (0..3).for_each(|i| {
+            (0..3).for_each(|j| {
+                covariance[i][j] = second[i][j] - volume * centre_of_mass[i] * centre_of_mass[j]
+            })
+        });
+        let trace = covariance[0][0] + covariance[1][1] + covariance[2][2];
+        let mut inertia = covariance.map(|row| row.map(|x| -x));
+        (0..3).for_each(|i| inertia[i][i] += trace);
+
+        let (values, vectors) = symmetric_eigen(inertia);
+        let mut order = [0, 1, 2];
+        order.sort_by(|i, j| values[*i].total_cmp(&values[*j]));
+        let axis = |i: usize| {
+            let axis = Vector3::new(vectors[0][i], vectors[1][i], vectors[2][i]);
+            // the largest component is made positive, so that the axes are reproducible.
+            let largest = (0..3).fold(0, |k, l| match axis[l].abs() > axis[k].abs() {
+                true => l,
+                false => k,
+            });
+            match axis[largest] < 0.0 {
+                true => -axis,
+                false => axis,
+            }
+        };
+        let (x, y) = (axis(order[0]), axis(order[1]));
+        let z = x.cross(y);
+        Self {
+            volume,
+            area,
+            centre_of_mass,
+            inertia,
+            principal_moments: order.map(|i| values[i]),
+            principal_axes: [x, y, z].map(Into::into),
+        }
+    }
+}
+
+impl MassProperties {
+    /// Computes the mass properties of the triangles of the meshes altogether.
+    pub fn from_meshes<'a>(meshes: impl IntoIterator<Item = &'a PolygonMesh>) -> Self {
+        Self::from_placed_meshes(meshes.into_iter().map(|mesh| (mesh, Matrix4::identity())))
+    }
+
+    /// Computes the mass properties of the meshes placed by the transforms altogether,
+    /// e.g. the instances of the parts in an assembly.
+    ///
+    /// A mesh placed twice is counted twice.
+    pub fn from_placed_meshes<'a>(
+        meshes: impl IntoIterator<Item = (&'a PolygonMesh, Matrix4)>,
+    ) -> Self {
+        let mut moments = Moments::default();
+        meshes
+            .into_iter()
+            .for_each(|(mesh, transform)| moments.add_mesh(mesh, &transform));
+        moments.into()
+    }
+
+    /// Computes the mass properties of the mesh.
+    pub fn from_polygon_mesh(mesh: &PolygonMesh) -> Self { Self::from_meshes([mesh]) }
+
+    /// Computes the mass properties of the triangulated shell.
+    ///
+    /// The faces failed to be triangulated are left out.
+    pub fn from_meshed_shell(shell: &MeshedShell) -> Self {
+        let meshes: Vec<PolygonMesh> = shell
+            .faces
+            .iter()
+            .filter_map(|face| {
+                let mut mesh = face.surface.clone()?;
+                if !face.orientation {
+                    mesh.invert();
+                }
+                Some(mesh)
+            })
+            .collect();
+        Self::from_meshes(&meshes)
+    }
+
+    /// The mass properties of the model scaled by `length`, e.g. converted to metres.
+    pub fn scaled(&self, length: f64) -> Self {
+        let (area, volume) = (length * length, length * length * length);
+        Self {
+            volume: self.volume * volume,
+            area: self.area * area,
+            centre_of_mass: self.centre_of_mass.map(|x| x * length),
+            inertia: self.inertia.map(|row| row.map(|x| x * volume * area)),
+            principal_moments: self.principal_moments.map(|x| x * volume * area),
+            principal_axes: self.principal_axes,
+        }
+    }
+}
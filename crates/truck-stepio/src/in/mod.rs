@@ -4,21 +4,61 @@
 pub use ruststep;
 
 use ruststep::{
-    ast::{DataSection, EntityInstance, Name, Parameter, SubSuperRecord},
+    ast::{DataSection, EntityInstance, Name, Parameter, Record, SubSuperRecord},
     primitive::Logical,
     tables::{EntityTable, IntoOwned, PlaceHolder},
     Holder,
 };
 use serde::{Deserialize, Serialize};
 use std::result::Result;
-use std::{collections::HashMap, f64::consts::PI};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    f64::consts::PI,
+};
 use truck_geometry::prelude as truck;
 use truck_topology::compress::*;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /// type alias
 pub mod alias;
 use alias::*;
 
+/// header section
+pub mod header;
+
+/// streaming reader
+pub mod stream;
+
+/// product manufacturing information of AP242 files
+pub mod pmi;
+/// product structure
+pub mod product;
+/// colours of the shapes
+pub mod style;
+/// units of the geometry
+pub mod units;
+/// validation of the converted shells
+pub mod validation;
+
+// holders generated from the EXPRESS schema
+mod generated;
+pub use generated::*;
+
+/// binary cache of the converted shells
+#[cfg(feature = "cache")]
+pub mod cache;
+/// structural comparison of the shapes of two tables
+#[cfg(feature = "tessellation")]
+pub mod diff;
+/// mass properties of the triangulated shells
+#[cfg(feature = "tessellation")]
+pub mod mass;
+/// tessellation of the shells in [`Table`]
+#[cfg(feature = "tessellation")]
+pub mod tessellation;
+
 /// the exchange structure corresponds to a graph in STEP file
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Table {
@@ -59,6 +99,10 @@ pub struct Table {
     pub rational_b_spline_surface: HashMap<u64, RationalBSplineSurfaceHolder>,
     pub surface_of_linear_extrusion: HashMap<u64, SurfaceOfLinearExtrusionHolder>,
     pub surface_of_revolution: HashMap<u64, SurfaceOfRevolutionHolder>,
+    pub degenerate_toroidal_surface: HashMap<u64, DegenerateToroidalSurfaceHolder>,
+    pub offset_surface: HashMap<u64, OffsetSurfaceHolder>,
+    pub rectangular_trimmed_surface: HashMap<u64, RectangularTrimmedSurfaceHolder>,
+    pub curve_bounded_surface: HashMap<u64, CurveBoundedSurfaceHolder>,
 
     // topology
     pub vertex_point: HashMap<u64, VertexPointHolder>,
@@ -71,9 +115,55 @@ pub struct Table {
     pub shell: HashMap<u64, ShellHolder>,
     pub oriented_shell: HashMap<u64, OrientedShellHolder>,
 
+    // tessellation
+    pub coordinates_list: HashMap<u64, CoordinatesListHolder>,
+    pub triangulated_face: HashMap<u64, TriangulatedFaceHolder>,
+    pub complex_triangulated_face: HashMap<u64, ComplexTriangulatedFaceHolder>,
+    pub tessellated_shell: HashMap<u64, TessellatedShellHolder>,
+
+    // product structure
+    pub product: HashMap<u64, ProductHolder>,
+    pub product_definition_formation: HashMap<u64, ProductDefinitionFormationHolder>,
+    pub product_definition: HashMap<u64, ProductDefinitionHolder>,
+    pub product_definition_shape: HashMap<u64, ProductDefinitionShapeHolder>,
+    pub next_assembly_usage_occurrence: HashMap<u64, NextAssemblyUsageOccurrenceHolder>,
+    pub shape_definition_representation: HashMap<u64, ShapeDefinitionRepresentationHolder>,
+    pub shape_representation: HashMap<u64, ShapeRepresentationHolder>,
+    pub shape_representation_relationship: HashMap<u64, ShapeRepresentationRelationshipHolder>,
+    pub manifold_solid_brep: HashMap<u64, ManifoldSolidBrepHolder>,
+    pub shell_based_surface_model: HashMap<u64, ShellBasedSurfaceModelHolder>,
+    pub item_defined_transformation: HashMap<u64, ItemDefinedTransformationHolder>,
+    pub representation_relationship_with_transformation:
+        HashMap<u64, RepresentationRelationshipWithTransformationHolder>,
+    pub context_dependent_shape_representation:
+        HashMap<u64, ContextDependentShapeRepresentationHolder>,
+
+    // presentation
+    pub styled_item: HashMap<u64, StyledItemHolder>,
+    pub presentation_style_assignment: HashMap<u64, PresentationStyleAssignmentHolder>,
+    pub surface_style_usage: HashMap<u64, SurfaceStyleUsageHolder>,
+    pub surface_side_style: HashMap<u64, SurfaceSideStyleHolder>,
+    pub surface_style_fill_area: HashMap<u64, SurfaceStyleFillAreaHolder>,
+    pub fill_area_style: HashMap<u64, FillAreaStyleHolder>,
+    pub fill_area_style_colour: HashMap<u64, FillAreaStyleColourHolder>,
+    pub colour_rgb: HashMap<u64, ColourRgbHolder>,
+    pub surface_style_rendering_with_properties:
+        HashMap<u64, SurfaceStyleRenderingWithPropertiesHolder>,
+    pub surface_style_transparent: HashMap<u64, SurfaceStyleTransparentHolder>,
+
     // others
     pub definitional_representation: HashMap<u64, DefinitionalRepresentationHolder>,
 
+    // generated holders, see `generated.rs`
+    pub composite_curve: HashMap<u64, CompositeCurveHolder>,
+    pub composite_curve_segment: HashMap<u64, CompositeCurveSegmentHolder>,
+    pub offset_curve_3d: HashMap<u64, OffsetCurve3dHolder>,
+    pub point_on_curve: HashMap<u64, PointOnCurveHolder>,
+    pub point_on_surface: HashMap<u64, PointOnSurfaceHolder>,
+    pub poly_loop: HashMap<u64, PolyLoopHolder>,
+    pub vertex_loop: HashMap<u64, VertexLoopHolder>,
+    // end of generated holders
+
     // dummy
     pub dummy: HashMap<u64, DummyHolder>,
 }
@@ -156,6 +246,10 @@ impl Table {
                     self.surface_curve
                         .insert(*id, Deserialize::deserialize(&record.parameter)?);
                 }
+                "INTERSECTION_CURVE" => {
+                    self.surface_curve
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
                 "PLANE" => {
                     self.plane.insert(*id, Deserialize::deserialize(record)?);
                 }
@@ -199,6 +293,22 @@ impl Table {
                     self.surface_of_revolution
                         .insert(*id, Deserialize::deserialize(record)?);
                 }
+                "DEGENERATE_TOROIDAL_SURFACE" => {
+                    self.degenerate_toroidal_surface
+                        .insert(*id, Deserialize::deserialize(record)?);
+                }
+                "OFFSET_SURFACE" => {
+                    self.offset_surface
+                        .insert(*id, Deserialize::deserialize(record)?);
+                }
+                "RECTANGULAR_TRIMMED_SURFACE" => {
+                    self.rectangular_trimmed_surface
+                        .insert(*id, Deserialize::deserialize(record)?);
+                }
+                "CURVE_BOUNDED_SURFACE" => {
+                    self.curve_bounded_surface
+                        .insert(*id, Deserialize::deserialize(record)?);
+                }
 
                 "VERTEX_POINT" => {
                     self.vertex_point
@@ -292,312 +402,267 @@ impl Table {
                         }
                     }
                 }
-                "DEFINITIONAL_REPRESENTATION" => {
+                "COORDINATES_LIST" => {
+                    self.coordinates_list
+                        .insert(*id, Deserialize::deserialize(record)?);
+                }
+                "TRIANGULATED_FACE" => {
                     if let Parameter::List(params) = &record.parameter {
-                        if params.len() == 3 {
-                            self.definitional_representation.insert(
+                        if params.len() == 7 {
+                            self.triangulated_face.insert(
                                 *id,
-                                DefinitionalRepresentationHolder {
+                                TriangulatedFaceHolder {
                                     label: Deserialize::deserialize(&params[0])?,
-                                    representation_item: Deserialize::deserialize(&params[1])?,
-                                    contex_of_items: match &params[2] {
-                                        Parameter::Ref(x) => PlaceHolder::Ref(x.clone()),
-                                        _ => PlaceHolder::Owned(DummyHolder {
-                                            record: format!("{:?}", params[2]),
-                                            is_simple: true,
-                                        }),
-                                    },
+                                    coordinates: Deserialize::deserialize(&params[1])?,
+                                    pnmax: Deserialize::deserialize(&params[2])?,
+                                    normals: Deserialize::deserialize(&params[3])?,
+                                    pnindex: Deserialize::deserialize(&params[5])?,
+                                    triangles: Deserialize::deserialize(&params[6])?,
                                 },
                             );
                         }
                     }
                 }
-                _ => {
-                    self.dummy.insert(
-                        *id,
-                        DummyHolder {
-                            record: format!("{record:?}"),
-                            is_simple: true,
-                        },
-                    );
-                }
-            },
-            EntityInstance::Complex {
-                id,
-                subsuper: SubSuperRecord(records),
-            } => {
-                use NonRationalBSplineCurveHolder as NRBC;
-                use NonRationalBSplineSurfaceHolder as NRBS;
-                if records.len() == 7 {
-                    match (
-                        records[0].name.as_str(),
-                        &records[0].parameter,
-                        records[1].name.as_str(),
-                        &records[1].parameter,
-                        records[2].name.as_str(),
-                        &records[2].parameter,
-                        records[3].name.as_str(),
-                        &records[3].parameter,
-                        records[4].name.as_str(),
-                        &records[4].parameter,
-                        records[5].name.as_str(),
-                        &records[5].parameter,
-                        records[6].name.as_str(),
-                        &records[6].parameter,
-                    ) {
-                        (
-                            "BOUNDED_CURVE",
-                            _,
-                            "B_SPLINE_CURVE",
-                            Parameter::List(bsp_params),
-                            "B_SPLINE_CURVE_WITH_KNOTS",
-                            Parameter::List(knots_params),
-                            "CURVE",
-                            _,
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_CURVE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            params.extend(knots_params.clone());
-                            self.rational_b_spline_curve.insert(
+                "COMPLEX_TRIANGULATED_FACE" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 8 {
+                            self.complex_triangulated_face.insert(
                                 *id,
-                                RationalBSplineCurveHolder {
-                                    non_rational_b_spline_curve: PlaceHolder::Owned(
-                                        NRBC::BSplineCurveWithKnots(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                ComplexTriangulatedFaceHolder {
+                                    label: Deserialize::deserialize(&params[0])?,
+                                    coordinates: Deserialize::deserialize(&params[1])?,
+                                    pnmax: Deserialize::deserialize(&params[2])?,
+                                    normals: Deserialize::deserialize(&params[3])?,
+                                    pnindex: Deserialize::deserialize(&params[5])?,
+                                    triangle_strips: Deserialize::deserialize(&params[6])?,
+                                    triangle_fans: Deserialize::deserialize(&params[7])?,
                                 },
                             );
                         }
-                        (
-                            "BEZIER_CURVE",
-                            _,
-                            "BOUNDED_CURVE",
-                            _,
-                            "B_SPLINE_CURVE",
-                            Parameter::List(bsp_params),
-                            "CURVE",
-                            _,
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_CURVE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            self.rational_b_spline_curve.insert(
+                    }
+                }
+                "TESSELLATED_SHELL" | "TESSELLATED_SOLID" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 3 {
+                            self.tessellated_shell.insert(
                                 *id,
-                                RationalBSplineCurveHolder {
-                                    non_rational_b_spline_curve: PlaceHolder::Owned(
-                                        NRBC::BezierCurve(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                TessellatedShellHolder {
+                                    label: Deserialize::deserialize(&params[0])?,
+                                    items: Deserialize::deserialize(&params[1])?,
                                 },
                             );
                         }
-                        (
-                            "BOUNDED_CURVE",
-                            _,
-                            "B_SPLINE_CURVE",
-                            Parameter::List(bsp_params),
-                            "CURVE",
-                            _,
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "QUASI_UNIFORM_CURVE",
-                            _,
-                            "RATIONAL_B_SPLINE_CURVE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                        ) => {
-                            let mut params = vec![label[0].clone()];
-                            params.extend(bsp_params.iter().cloned());
-                            self.rational_b_spline_curve.insert(
+                    }
+                }
+                "PRODUCT" => {
+                    self.product
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "PRODUCT_DEFINITION_FORMATION" => {
+                    self.product_definition_formation
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 4 {
+                            self.product_definition_formation.insert(
                                 *id,
-                                RationalBSplineCurveHolder {
-                                    non_rational_b_spline_curve: PlaceHolder::Owned(
-                                        NRBC::QuasiUniformCurve(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                ProductDefinitionFormationHolder {
+                                    id: Deserialize::deserialize(&params[0])?,
+                                    description: Deserialize::deserialize(&params[1])?,
+                                    of_product: Deserialize::deserialize(&params[2])?,
                                 },
                             );
                         }
-                        (
-                            "BOUNDED_CURVE",
-                            _,
-                            "B_SPLINE_CURVE",
-                            Parameter::List(bsp_params),
-                            "CURVE",
-                            _,
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_CURVE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                            "UNIFORM_CURVE",
-                            _,
-                        ) => {
-                            let mut params = vec![label[0].clone()];
-                            params.extend(bsp_params.iter().cloned());
-                            self.rational_b_spline_curve.insert(
+                    }
+                }
+                "PRODUCT_DEFINITION" => {
+                    self.product_definition
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 5 {
+                            self.product_definition.insert(
                                 *id,
-                                RationalBSplineCurveHolder {
-                                    non_rational_b_spline_curve: PlaceHolder::Owned(
-                                        NRBC::UniformCurve(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                ProductDefinitionHolder {
+                                    id: Deserialize::deserialize(&params[0])?,
+                                    description: Deserialize::deserialize(&params[1])?,
+                                    formation: Deserialize::deserialize(&params[2])?,
+                                    frame_of_reference: Deserialize::deserialize(&params[3])?,
                                 },
                             );
                         }
-                        (
-                            "BOUNDED_SURFACE",
-                            _,
-                            "B_SPLINE_SURFACE",
-                            Parameter::List(bsp_params),
-                            "B_SPLINE_SURFACE_WITH_KNOTS",
-                            Parameter::List(knots_params),
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_SURFACE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                            "SURFACE",
-                            _,
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            params.extend(knots_params.clone());
-                            self.rational_b_spline_surface.insert(
+                    }
+                }
+                "PRODUCT_DEFINITION_SHAPE" => {
+                    self.product_definition_shape
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "NEXT_ASSEMBLY_USAGE_OCCURRENCE" => {
+                    self.next_assembly_usage_occurrence
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SHAPE_DEFINITION_REPRESENTATION" => {
+                    self.shape_definition_representation
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SHAPE_REPRESENTATION"
+                | "ADVANCED_BREP_SHAPE_REPRESENTATION"
+                | "MANIFOLD_SURFACE_SHAPE_REPRESENTATION"
+                | "FACETED_BREP_SHAPE_REPRESENTATION"
+                | "GEOMETRICALLY_BOUNDED_SURFACE_SHAPE_REPRESENTATION"
+                | "GEOMETRICALLY_BOUNDED_WIREFRAME_SHAPE_REPRESENTATION"
+                | "EDGE_BASED_WIREFRAME_SHAPE_REPRESENTATION"
+                | "TESSELLATED_SHAPE_REPRESENTATION" => {
+                    self.shape_representation
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SHAPE_REPRESENTATION_RELATIONSHIP" => {
+                    self.shape_representation_relationship
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "MANIFOLD_SOLID_BREP" => {
+                    self.manifold_solid_brep
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "BREP_WITH_VOIDS" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 3 {
+                            self.manifold_solid_brep.insert(
                                 *id,
-                                RationalBSplineSurfaceHolder {
-                                    non_rational_b_spline_surface: PlaceHolder::Owned(
-                                        NRBS::BSplineSurfaceWithKnots(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                ManifoldSolidBrepHolder {
+                                    label: Deserialize::deserialize(&params[0])?,
+                                    outer: Deserialize::deserialize(&params[1])?,
                                 },
                             );
                         }
-                        (
-                            "BEZIER_SURFACE",
-                            _,
-                            "BOUNDED_SURFACE",
-                            _,
-                            "B_SPLINE_SURFACE",
-                            Parameter::List(bsp_params),
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_SURFACE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                            "SURFACE",
-                            _,
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            self.rational_b_spline_surface.insert(
+                    }
+                }
+                "SHELL_BASED_SURFACE_MODEL" => {
+                    self.shell_based_surface_model
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "ITEM_DEFINED_TRANSFORMATION" => {
+                    self.item_defined_transformation
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "CONTEXT_DEPENDENT_SHAPE_REPRESENTATION" => {
+                    self.context_dependent_shape_representation
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "STYLED_ITEM" => {
+                    self.styled_item
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "OVER_RIDING_STYLED_ITEM" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 4 {
+                            self.styled_item.insert(
                                 *id,
-                                RationalBSplineSurfaceHolder {
-                                    non_rational_b_spline_surface: PlaceHolder::Owned(
-                                        NRBS::BezierSurface(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                StyledItemHolder {
+                                    label: Deserialize::deserialize(&params[0])?,
+                                    styles: Deserialize::deserialize(&params[1])?,
+                                    item: Deserialize::deserialize(&params[2])?,
                                 },
                             );
                         }
-                        (
-                            "BOUNDED_SURFACE",
-                            _,
-                            "B_SPLINE_SURFACE",
-                            Parameter::List(bsp_params),
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "QUASI_UNIFORM_SURFACE",
-                            _,
-                            "RATIONAL_B_SPLINE_SURFACE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                            "SURFACE",
-                            _,
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            self.rational_b_spline_surface.insert(
+                    }
+                }
+                "PRESENTATION_STYLE_ASSIGNMENT" => {
+                    self.presentation_style_assignment
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SURFACE_STYLE_USAGE" => {
+                    // the side, `.BOTH.`, `.POSITIVE.` or `.NEGATIVE.`, is not read.
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 2 {
+                            self.surface_style_usage.insert(
                                 *id,
-                                RationalBSplineSurfaceHolder {
-                                    non_rational_b_spline_surface: PlaceHolder::Owned(
-                                        NRBS::QuasiUniformSurface(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                SurfaceStyleUsageHolder {
+                                    style: Deserialize::deserialize(&params[1])?,
                                 },
                             );
                         }
-                        (
-                            "BOUNDED_SURFACE",
-                            _,
-                            "B_SPLINE_SURFACE",
-                            Parameter::List(bsp_params),
-                            "GEOMETRIC_REPRESENTATION_ITEM",
-                            _,
-                            "RATIONAL_B_SPLINE_SURFACE",
-                            Parameter::List(weights),
-                            "REPRESENTATION_ITEM",
-                            Parameter::List(label),
-                            "SURFACE",
-                            _,
-                            "UNIFORM_SURFACE",
-                            _,
-                        ) => {
-                            let mut params = label.clone();
-                            params.extend(bsp_params.clone());
-                            self.rational_b_spline_surface.insert(
+                    }
+                }
+                "SURFACE_SIDE_STYLE" => {
+                    self.surface_side_style
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SURFACE_STYLE_FILL_AREA" => {
+                    self.surface_style_fill_area
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "FILL_AREA_STYLE" => {
+                    self.fill_area_style
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "FILL_AREA_STYLE_COLOUR" => {
+                    self.fill_area_style_colour
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "COLOUR_RGB" => {
+                    self.colour_rgb
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "SURFACE_STYLE_RENDERING_WITH_PROPERTIES" => {
+                    // the rendering method is not read.
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 3 {
+                            self.surface_style_rendering_with_properties.insert(
                                 *id,
-                                RationalBSplineSurfaceHolder {
-                                    non_rational_b_spline_surface: PlaceHolder::Owned(
-                                        NRBS::UniformSurface(Deserialize::deserialize(
-                                            &Parameter::List(params),
-                                        )?),
-                                    ),
-                                    weights_data: Deserialize::deserialize(&weights[0])?,
+                                SurfaceStyleRenderingWithPropertiesHolder {
+                                    surface_colour: Deserialize::deserialize(&params[1])?,
+                                    properties: Deserialize::deserialize(&params[2])?,
                                 },
                             );
                         }
-                        _ => {
-                            self.dummy.insert(
+                    }
+                }
+                "SURFACE_STYLE_TRANSPARENT" => {
+                    self.surface_style_transparent
+                        .insert(*id, Deserialize::deserialize(&record.parameter)?);
+                }
+                "DEFINITIONAL_REPRESENTATION" => {
+                    if let Parameter::List(params) = &record.parameter {
+                        if params.len() == 3 {
+                            self.definitional_representation.insert(
                                 *id,
-                                DummyHolder {
-                                    record: format!("{records:?}"),
-                                    is_simple: false,
+                                DefinitionalRepresentationHolder {
+                                    label: Deserialize::deserialize(&params[0])?,
+                                    representation_item: Deserialize::deserialize(&params[1])?,
+                                    contex_of_items: match &params[2] {
+                                        Parameter::Ref(x) => PlaceHolder::Ref(x.clone()),
+                                        _ => PlaceHolder::Owned(DummyHolder {
+                                            record: format!("{:?}", params[2]),
+                                            is_simple: true,
+                                        }),
+                                    },
                                 },
                             );
                         }
                     }
-                } else {
+                }
+                _ => {
+                    if !self.push_generated_instance(*id, record)? {
+                        self.dummy.insert(
+                            *id,
+                            DummyHolder {
+                                record: format!("{record:?}"),
+                                is_simple: true,
+                            },
+                        );
+                    }
+                }
+            },
+            EntityInstance::Complex {
+                id,
+                subsuper: SubSuperRecord(records),
+            } => {
+                if !self.push_complex_b_spline(*id, records)?
+                    && !self.push_complex_representation_relationship(*id, records)?
+                {
                     self.dummy.insert(
                         *id,
                         DummyHolder {
@@ -610,6 +675,234 @@ impl Table {
         }
         Ok(())
     }
+    /// Reads a complex instance of `representation_relationship_with_transformation`,
+    /// which places a component in an assembly.
+    ///
+    /// Returns `false` if the records are of the other types.
+    fn push_complex_representation_relationship(
+        &mut self,
+        id: u64,
+        records: &[Record],
+    ) -> ruststep::error::Result<bool> {
+        const RECORDS: [&str; 3] = [
+            "REPRESENTATION_RELATIONSHIP",
+            "REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION",
+            "SHAPE_REPRESENTATION_RELATIONSHIP",
+        ];
+        if !records
+            .iter()
+            .all(|record| RECORDS.contains(&record.name.as_str()))
+        {
+            return Ok(false);
+        }
+        let list = |name: &str| {
+            let record = records.iter().find(|record| record.name == name)?;
+            match &record.parameter {
+                Parameter::List(params) => Some(params.clone()),
+                _ => None,
+            }
+        };
+        let (Some(relationship), Some(transformation)) = (
+            list("REPRESENTATION_RELATIONSHIP"),
+            list("REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION"),
+        ) else {
+            return Ok(false);
+        };
+        if relationship.len() != 4 || transformation.len() != 1 {
+            return Ok(false);
+        }
+        self.representation_relationship_with_transformation.insert(
+            id,
+            RepresentationRelationshipWithTransformationHolder {
+                name: Deserialize::deserialize(&relationship[0])?,
+                description: Deserialize::deserialize(&relationship[1])?,
+                rep_1: Deserialize::deserialize(&relationship[2])?,
+                rep_2: Deserialize::deserialize(&relationship[3])?,
+                transformation_operator: Deserialize::deserialize(&transformation[0])?,
+            },
+        );
+        Ok(true)
+    }
+    /// Assembles a complex instance of a (rational) B-spline curve or surface.
+    ///
+    /// The partial records may appear in any order and any combination of supertypes
+    /// is accepted as long as exactly one of the knot specifications, i.e. `*_WITH_KNOTS`,
+    /// `BEZIER_*`, `QUASI_UNIFORM_*` or `UNIFORM_*`, is given.
+    /// Returns `false` if the records do not compose a B-spline entity.
+    fn push_complex_b_spline(
+        &mut self,
+        id: u64,
+        records: &[Record],
+    ) -> ruststep::error::Result<bool> {
+        use NonRationalBSplineCurveHolder as NRBC;
+        use NonRationalBSplineSurfaceHolder as NRBS;
+        const CURVE_RECORDS: [&str; 10] = [
+            "BEZIER_CURVE",
+            "BOUNDED_CURVE",
+            "B_SPLINE_CURVE",
+            "B_SPLINE_CURVE_WITH_KNOTS",
+            "CURVE",
+            "GEOMETRIC_REPRESENTATION_ITEM",
+            "QUASI_UNIFORM_CURVE",
+            "RATIONAL_B_SPLINE_CURVE",
+            "REPRESENTATION_ITEM",
+            "UNIFORM_CURVE",
+        ];
+        const SURFACE_RECORDS: [&str; 10] = [
+            "BEZIER_SURFACE",
+            "BOUNDED_SURFACE",
+            "B_SPLINE_SURFACE",
+            "B_SPLINE_SURFACE_WITH_KNOTS",
+            "GEOMETRIC_REPRESENTATION_ITEM",
+            "QUASI_UNIFORM_SURFACE",
+            "RATIONAL_B_SPLINE_SURFACE",
+            "REPRESENTATION_ITEM",
+            "SURFACE",
+            "UNIFORM_SURFACE",
+        ];
+        let find = |name: &str| records.iter().find(|record| record.name == name);
+        let list = |name: &str| match find(name) {
+            Some(record) => match &record.parameter {
+                Parameter::List(params) => Some(params.clone()),
+                _ => None,
+            },
+            None => None,
+        };
+        let (is_curve, allowed) = if find("B_SPLINE_CURVE").is_some() {
+            (true, CURVE_RECORDS)
+        } else if find("B_SPLINE_SURFACE").is_some() {
+            (false, SURFACE_RECORDS)
+        } else {
+            return Ok(false);
+        };
+        if !records
+            .iter()
+            .all(|record| allowed.contains(&record.name.as_str()))
+        {
+            return Ok(false);
+        }
+        let label = list("REPRESENTATION_ITEM")
+            .and_then(|label| label.into_iter().next())
+            .unwrap_or(Parameter::NotProvided);
+        let (bsp, knots, bezier, quasi_uniform, uniform, rational) = match is_curve {
+            true => (
+                "B_SPLINE_CURVE",
+                "B_SPLINE_CURVE_WITH_KNOTS",
+                "BEZIER_CURVE",
+                "QUASI_UNIFORM_CURVE",
+                "UNIFORM_CURVE",
+                "RATIONAL_B_SPLINE_CURVE",
+            ),
+            false => (
+                "B_SPLINE_SURFACE",
+                "B_SPLINE_SURFACE_WITH_KNOTS",
+                "BEZIER_SURFACE",
+                "QUASI_UNIFORM_SURFACE",
+                "UNIFORM_SURFACE",
+                "RATIONAL_B_SPLINE_SURFACE",
+            ),
+        };
+        let bsp_params = match list(bsp) {
+            Some(bsp_params) => bsp_params,
+            None => return Ok(false),
+        };
+        let mut params = vec![label];
+        params.extend(bsp_params);
+        let knots_params = list(knots);
+        let specifications = [
+            knots_params.is_some(),
+            find(bezier).is_some(),
+            find(quasi_uniform).is_some(),
+            find(uniform).is_some(),
+        ];
+        if specifications.into_iter().filter(|x| *x).count() != 1 {
+            return Ok(false);
+        }
+        if let Some(knots_params) = knots_params {
+            params.extend(knots_params);
+        }
+        let params = Parameter::List(params);
+        let weights_data = match find(rational) {
+            Some(_) => match list(rational) {
+                Some(weights) if weights.len() == 1 => Some(weights[0].clone()),
+                _ => return Ok(false),
+            },
+            None => None,
+        };
+        match (is_curve, weights_data) {
+            (true, Some(weights)) => {
+                let non_rational_b_spline_curve = if find(knots).is_some() {
+                    NRBC::BSplineCurveWithKnots(Deserialize::deserialize(&params)?)
+                } else if find(bezier).is_some() {
+                    NRBC::BezierCurve(Deserialize::deserialize(&params)?)
+                } else if find(quasi_uniform).is_some() {
+                    NRBC::QuasiUniformCurve(Deserialize::deserialize(&params)?)
+                } else {
+                    NRBC::UniformCurve(Deserialize::deserialize(&params)?)
+                };
+                self.rational_b_spline_curve.insert(
+                    id,
+                    RationalBSplineCurveHolder {
+                        non_rational_b_spline_curve: PlaceHolder::Owned(
+                            non_rational_b_spline_curve,
+                        ),
+                        weights_data: Deserialize::deserialize(&weights)?,
+                    },
+                );
+            }
+            (true, None) => {
+                if find(knots).is_some() {
+                    self.b_spline_curve_with_knots
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else if find(bezier).is_some() {
+                    self.bezier_curve
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else if find(quasi_uniform).is_some() {
+                    self.quasi_uniform_curve
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else {
+                    self.uniform_curve
+                        .insert(id, Deserialize::deserialize(&params)?);
+                }
+            }
+            (false, Some(weights)) => {
+                let non_rational_b_spline_surface = if find(knots).is_some() {
+                    NRBS::BSplineSurfaceWithKnots(Deserialize::deserialize(&params)?)
+                } else if find(bezier).is_some() {
+                    NRBS::BezierSurface(Deserialize::deserialize(&params)?)
+                } else if find(quasi_uniform).is_some() {
+                    NRBS::QuasiUniformSurface(Deserialize::deserialize(&params)?)
+                } else {
+                    NRBS::UniformSurface(Deserialize::deserialize(&params)?)
+                };
+                self.rational_b_spline_surface.insert(
+                    id,
+                    RationalBSplineSurfaceHolder {
+                        non_rational_b_spline_surface: PlaceHolder::Owned(
+                            non_rational_b_spline_surface,
+                        ),
+                        weights_data: Deserialize::deserialize(&weights)?,
+                    },
+                );
+            }
+            (false, None) => {
+                if find(knots).is_some() {
+                    self.b_spline_surface_with_knots
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else if find(bezier).is_some() {
+                    self.bezier_surface
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else if find(quasi_uniform).is_some() {
+                    self.quasi_uniform_surface
+                        .insert(id, Deserialize::deserialize(&params)?);
+                } else {
+                    self.uniform_surface
+                        .insert(id, Deserialize::deserialize(&params)?);
+                }
+            }
+        }
+        Ok(true)
+    }
     #[inline(always)]
     pub fn from_data_section(data_section: &DataSection) -> Table {
         Table::from_iter(&data_section.entities)
@@ -619,68 +912,479 @@ impl Table {
         let exchange = ruststep::parser::parse(step_str).ok()?;
         Some(Table::from_data_section(&exchange.data[0]))
     }
-}
+    /// Reads the STEP file and reports the problems found on the way instead of printing them.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::*;
+    /// let step_str = "ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION((''), '2;1');
+    /// FILE_NAME('', '', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+    /// #2 = VERTEX_POINT('', #3);
+    /// #4 = PRESENTATION_LAYER_ASSIGNMENT('', '', (#2));
+    /// ENDSEC;
+    /// END-ISO-10303-21;";
+    /// let (table, report) = Table::from_step_with_report(step_str).unwrap();
+    /// assert_eq!(table.cartesian_point.len(), 1);
+    /// assert_eq!(report.dangling_references, vec![(2, 3)]);
+    /// assert_eq!(report.unsupported_entities["PRESENTATION_LAYER_ASSIGNMENT"], 1);
+    /// ```
+    pub fn from_step_with_report(
+        step_str: &str,
+    ) -> ruststep::error::Result<(Table, StepReadReport)> {
+        let exchange = ruststep::parser::parse(step_str)?;
+        Ok(match exchange.data.first() {
+            Some(data_section) => Table::from_data_section_with_report(data_section),
+            None => Default::default(),
+        })
+    }
+    /// Reads the data section and reports the problems found on the way.
+    pub fn from_data_section_with_report(data_section: &DataSection) -> (Table, StepReadReport) {
+        let mut table = Table::default();
+        let mut report = StepReadReport::default();
+        let entities = &data_section.entities;
+        entities.iter().for_each(|instance| {
+            if let Err(e) = table.push_instance(instance) {
+                report
+                    .failed_entities
+                    .push((instance_id(instance), e.to_string()));
+            }
+        });
 
-impl<'a> FromIterator<&'a EntityInstance> for Table {
-    fn from_iter<I: IntoIterator<Item = &'a EntityInstance>>(iter: I) -> Table {
-        let mut res = Table::default();
-        iter.into_iter().for_each(|instance| {
-            res.push_instance(instance)
-                .unwrap_or_else(|e| eprintln!("{e}"))
+        let ids: HashSet<u64> = entities.iter().map(instance_id).collect();
+        entities.iter().for_each(|instance| {
+            let id = instance_id(instance);
+            if table.dummy.contains_key(&id) {
+                *report
+                    .unsupported_entities
+                    .entry(instance_type_name(instance))
+                    .or_default() += 1;
+            }
+            let mut refs = Vec::new();
+            match instance {
+                EntityInstance::Simple { record, .. } => {
+                    collect_references(&record.parameter, &mut refs)
+                }
+                EntityInstance::Complex {
+                    subsuper: SubSuperRecord(records),
+                    ..
+                } => records
+                    .iter()
+                    .for_each(|record| collect_references(&record.parameter, &mut refs)),
+            }
+            refs.into_iter()
+                .filter(|to| !ids.contains(to))
+                .for_each(|to| report.dangling_references.push((id, to)));
         });
-        res
+
+        let mut shells: Vec<_> = table.shell.iter().collect();
+        shells.sort_by_key(|(idx, _)| **idx);
+        shells
+            .into_iter()
+            .for_each(|(idx, shell)| match table.to_compressed_shell(shell) {
+                Ok(cshell) if cshell.faces.len() < shell.cfs_faces.len() => {
+                    let reason = format!(
+                        "{} of {} faces could not be converted",
+                        shell.cfs_faces.len() - cshell.faces.len(),
+                        shell.cfs_faces.len(),
+                    );
+                    report.failed_shells.push((*idx, reason));
+                }
+                Ok(_) => {}
+                Err(e) => report.failed_shells.push((*idx, e.to_string())),
+            });
+        (table, report)
     }
 }
 
-/// Undefined structures are parsed into this.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
-#[holder(table = Table)]
-#[holder(field = dummy)]
-#[holder(generate_deserialize)]
-pub struct Dummy {
-    pub record: String,
-    pub is_simple: bool,
+fn instance_id(instance: &EntityInstance) -> u64 {
+    match instance {
+        EntityInstance::Simple { id, .. } => *id,
+        EntityInstance::Complex { id, .. } => *id,
+    }
 }
 
-/// `cartesian_point`
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Holder)]
-#[holder(table = Table)]
-#[holder(field = cartesian_point)]
-#[holder(generate_deserialize)]
-pub struct CartesianPoint {
-    pub label: Option<String>,
-    pub coordinates: Vec<f64>,
-}
-impl From<&CartesianPoint> for Point2 {
-    #[inline(always)]
-    fn from(pt: &CartesianPoint) -> Self {
-        let pt = &pt.coordinates;
-        match pt.len() {
-            0 => Point2::origin(),
-            1 => Point2::new(pt[0], 0.0),
-            _ => Point2::new(pt[0], pt[1]),
+/// the keyword of a simple entity, or the keywords of the partial records in parentheses.
+fn instance_type_name(instance: &EntityInstance) -> String {
+    match instance {
+        EntityInstance::Simple { record, .. } => record.name.clone(),
+        EntityInstance::Complex {
+            subsuper: SubSuperRecord(records),
+            ..
+        } => {
+            let names: Vec<&str> = records.iter().map(|record| record.name.as_str()).collect();
+            format!("({})", names.join(" "))
         }
     }
 }
-impl From<&CartesianPoint> for Point3 {
-    #[inline(always)]
-    fn from(pt: &CartesianPoint) -> Self {
-        let pt = &pt.coordinates;
-        match pt.len() {
-            0 => Point3::origin(),
-            1 => Point3::new(pt[0], 0.0, 0.0),
-            2 => Point3::new(pt[0], pt[1], 0.0),
-            _ => Point3::new(pt[0], pt[1], pt[2]),
-        }
+
+fn collect_references(parameter: &Parameter, refs: &mut Vec<u64>) {
+    match parameter {
+        Parameter::Ref(Name::Entity(idx)) => refs.push(*idx),
+        Parameter::List(params) => params
+            .iter()
+            .for_each(|param| collect_references(param, refs)),
+        Parameter::Typed { parameter, .. } => collect_references(parameter, refs),
+        _ => {}
     }
 }
 
-/// `direction`
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Holder)]
-#[holder(table = Table)]
-#[holder(field = direction)]
-#[holder(generate_deserialize)]
-pub struct Direction {
+/// Problems found by [`Table::from_step_with_report`].
+///
+/// An empty report means that every entity has been read and every shell has been converted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepReadReport {
+    /// ids of the entities which failed to be deserialized, with the reasons
+    pub failed_entities: Vec<(u64, String)>,
+    /// type names of the entities not supported by this crate, with the numbers of instances
+    pub unsupported_entities: BTreeMap<String, usize>,
+    /// pairs of the referring entity id and the missing entity id
+    pub dangling_references: Vec<(u64, u64)>,
+    /// ids of the shells which failed to be converted to `CompressedShell`, with the reasons
+    pub failed_shells: Vec<(u64, String)>,
+}
+
+impl StepReadReport {
+    /// Returns `true` if no problem is reported.
+    ///
+    /// Unsupported entities are not problems by themselves: most of them are not geometry.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.failed_entities.is_empty()
+            && self.dangling_references.is_empty()
+            && self.failed_shells.is_empty()
+    }
+}
+
+impl std::fmt::Display for StepReadReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, reason) in &self.failed_entities {
+            writeln!(f, "failed to read #{idx}: {reason}")?;
+        }
+        for (from, to) in &self.dangling_references {
+            writeln!(f, "#{from} refers to missing #{to}")?;
+        }
+        for (idx, reason) in &self.failed_shells {
+            writeln!(f, "failed to convert shell #{idx}: {reason}")?;
+        }
+        for (name, count) in &self.unsupported_entities {
+            writeln!(f, "unsupported entity {name}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A repair done in the lenient reading mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepRepair {
+    /// The record `#id` could not be deserialized as written and has been read after the fixes.
+    RecordRepaired { id: u64, fixes: String },
+    /// The record `#id` could not be repaired and has been skipped.
+    RecordSkipped { id: u64, reason: String },
+    /// The vertex `#id` has no point, so the edges and faces on it have been skipped.
+    VertexSkipped { id: u64, reason: String },
+    /// The pcurve of the surface curve of the edge `#id` is broken, so the 3D curve is used.
+    EdgeCurveReplacedByCurve3D { id: u64, reason: String },
+    /// The geometry of the edge `#id` is broken, so the line between the vertices is used.
+    EdgeCurveReplacedByLine { id: u64, reason: String },
+    /// The edge `#id` could not be repaired and has been skipped.
+    EdgeSkipped { id: u64, reason: String },
+    /// The face `#id` could not be repaired and has been skipped. `id` is 0 for faces written inline.
+    FaceSkipped { id: u64, reason: String },
+}
+
+impl std::fmt::Display for StepRepair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RecordRepaired { id, fixes } => write!(f, "#{id} is read with fixes: {fixes}"),
+            Self::RecordSkipped { id, reason } => write!(f, "#{id} is skipped: {reason}"),
+            Self::VertexSkipped { id, reason } => write!(f, "vertex #{id} is skipped: {reason}"),
+            Self::EdgeCurveReplacedByCurve3D { id, reason } => {
+                write!(f, "the 3D curve is used for edge #{id}: {reason}")
+            }
+            Self::EdgeCurveReplacedByLine { id, reason } => {
+                write!(f, "a line is used for edge #{id}: {reason}")
+            }
+            Self::EdgeSkipped { id, reason } => write!(f, "edge #{id} is skipped: {reason}"),
+            Self::FaceSkipped { id, reason } => write!(f, "face #{id} is skipped: {reason}"),
+        }
+    }
+}
+
+/// enumeration values used in the entities read by this crate
+const KNOWN_ENUMERATIONS: [&str; 25] = [
+    "T",
+    "F",
+    "U",
+    "POLYLINE_FORM",
+    "CIRCULAR_ARC",
+    "ELLIPTIC_ARC",
+    "PARABOLIC_ARC",
+    "HYPERBOLIC_ARC",
+    "UNSPECIFIED",
+    "UNIFORM_KNOTS",
+    "QUASI_UNIFORM_KNOTS",
+    "PIECEWISE_BEZIER_KNOTS",
+    "PLANE_SURF",
+    "CYLINDRICAL_SURF",
+    "CONICAL_SURF",
+    "SPHERICAL_SURF",
+    "TOROIDAL_SURF",
+    "SURF_OF_REVOLUTION",
+    "RULED_SURF",
+    "GENERALISED_CONE",
+    "QUADRIC_SURF",
+    "SURF_OF_LINEAR_EXTRUSION",
+    "CURVE_3D",
+    "PCURVE_S1",
+    "PCURVE_S2",
+];
+
+/// Normalizes the case and the spelling of the enumeration values.
+///
+/// The unknown values are read as unspecified only if `unspecified`, i.e. in the slots of
+/// [`UNSPECIFIED_SLOTS`], since the other enumerations, e.g. `.CONTINUOUS.`, are valid.
+fn normalize_enumeration(parameter: &Parameter, unspecified: bool) -> Parameter {
+    match parameter {
+        Parameter::Enumeration(value) => {
+            let value = value.trim_matches('.').to_uppercase();
+            let value = match value.as_str() {
+                "TRUE" => "T",
+                "FALSE" => "F",
+                "UNKNOWN" => "U",
+                _ if KNOWN_ENUMERATIONS.contains(&value.as_str()) => value.as_str(),
+                // misspelled forms and knot types
+                _ if unspecified => "UNSPECIFIED",
+                _ => value.as_str(),
+            };
+            Parameter::Enumeration(value.to_string())
+        }
+        Parameter::List(params) => Parameter::List(
+            params
+                .iter()
+                .map(|param| normalize_enumeration(param, unspecified))
+                .collect(),
+        ),
+        _ => parameter.clone(),
+    }
+}
+
+/// the slots of the forms and the knot types of the B-spline curves and surfaces,
+/// the enumeration values which may be read as unspecified
+const UNSPECIFIED_SLOTS: [(&str, &[usize]); 8] = [
+    ("B_SPLINE_CURVE_WITH_KNOTS", &[3, 8]),
+    ("BEZIER_CURVE", &[3]),
+    ("QUASI_UNIFORM_CURVE", &[3]),
+    ("UNIFORM_CURVE", &[3]),
+    ("B_SPLINE_SURFACE_WITH_KNOTS", &[4, 12]),
+    ("BEZIER_SURFACE", &[4]),
+    ("QUASI_UNIFORM_SURFACE", &[4]),
+    ("UNIFORM_SURFACE", &[4]),
+];
+
+/// the slots of the closedness and the self-intersection of the B-spline curves and surfaces,
+/// the logical values which may be read as unknown
+const LOGICAL_SLOTS: [(&str, &[usize]); 8] = [
+    ("B_SPLINE_CURVE_WITH_KNOTS", &[4, 5]),
+    ("BEZIER_CURVE", &[4, 5]),
+    ("QUASI_UNIFORM_CURVE", &[4, 5]),
+    ("UNIFORM_CURVE", &[4, 5]),
+    ("B_SPLINE_SURFACE_WITH_KNOTS", &[5, 6, 7]),
+    ("BEZIER_SURFACE", &[5, 6, 7]),
+    ("QUASI_UNIFORM_SURFACE", &[5, 6, 7]),
+    ("UNIFORM_SURFACE", &[5, 6, 7]),
+];
+
+/// the slots of `record_name` in `slots`, e.g. [`LOGICAL_SLOTS`]
+fn record_slots(slots: &[(&str, &'static [usize])], record_name: &str) -> &'static [usize] {
+    slots
+        .iter()
+        .find(|(name, _)| record_name.eq_ignore_ascii_case(name))
+        .map_or(&[][..], |(_, slots)| *slots)
+}
+
+/// Candidates of the repaired record, with the descriptions of the fixes, in the order to be tried.
+///
+/// The missing and extra trailing parameters are tried on the original parameters first,
+/// and then on the ones with the other fixes.
+fn repaired_records(record: &Record) -> Vec<(Record, String)> {
+    let Parameter::List(params) = &record.parameter else {
+        return Vec::new();
+    };
+    let to_record = |params: Vec<Parameter>| Record {
+        name: record.name.clone(),
+        parameter: Parameter::List(params),
+    };
+    let mut candidates = Vec::new();
+    let mut base = params.clone();
+    let mut fixes = Vec::new();
+    let unspecified_slots = record_slots(&UNSPECIFIED_SLOTS, &record.name);
+    let normalized: Vec<Parameter> = params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| normalize_enumeration(param, unspecified_slots.contains(&i)))
+        .collect();
+    if normalized != *params {
+        base = normalized;
+        fixes.push("normalized enumeration values");
+        candidates.push((to_record(base.clone()), fixes.join(", ")));
+    }
+    // An unset logical value is unknown. The other unset values, e.g. orientations,
+    // are not guessed: the records are skipped and so are the faces on them.
+    let logical_slots = record_slots(&LOGICAL_SLOTS, &record.name);
+    let filled: Vec<Parameter> = base
+        .iter()
+        .enumerate()
+        .map(|(i, param)| match param {
+            Parameter::NotProvided if logical_slots.contains(&i) => {
+                Parameter::Enumeration("U".to_string())
+            }
+            _ => param.clone(),
+        })
+        .collect();
+    if filled != base {
+        base = filled;
+        fixes.push("read unset logical values as .U.");
+        candidates.push((to_record(base.clone()), fixes.join(", ")));
+    }
+    let mut resized = |base: &[Parameter], fixes: &[&str]| {
+        (1..=2).for_each(|n| {
+            if base.len() > n {
+                let mut fixes = fixes.to_vec();
+                fixes.push("removed extra trailing parameters");
+                candidates.push((to_record(base[..base.len() - n].to_vec()), fixes.join(", ")));
+            }
+            let mut padded = base.to_vec();
+            padded.extend(std::iter::repeat(Parameter::NotProvided).take(n));
+            let mut fixes = fixes.to_vec();
+            fixes.push("added missing trailing parameters as $");
+            candidates.push((to_record(padded), fixes.join(", ")));
+        })
+    };
+    resized(&params[..], &[]);
+    if base != *params {
+        resized(&base[..], &fixes[..]);
+    }
+    candidates
+}
+
+impl Table {
+    /// Pushes the instance, repairing the record if it cannot be read as written.
+    ///
+    /// Returns the repair if one was needed.
+    /// The error of the original record is returned if no repair works.
+    pub fn push_instance_lenient(
+        &mut self,
+        instance: &EntityInstance,
+    ) -> ruststep::error::Result<Option<StepRepair>> {
+        let error = match self.push_instance(instance) {
+            Ok(()) => return Ok(None),
+            Err(error) => error,
+        };
+        let EntityInstance::Simple { id, record } = instance else {
+            return Err(error);
+        };
+        for (record, fixes) in repaired_records(record) {
+            let instance = EntityInstance::Simple { id: *id, record };
+            if self.push_instance(&instance).is_ok() {
+                return Ok(Some(StepRepair::RecordRepaired { id: *id, fixes }));
+            }
+        }
+        Err(error)
+    }
+    /// Reads the data section in the lenient mode.
+    ///
+    /// The records which cannot be repaired are skipped and reported as
+    /// [`StepRepair::RecordSkipped`].
+    pub fn from_data_section_lenient(data_section: &DataSection) -> (Table, Vec<StepRepair>) {
+        let mut table = Table::default();
+        let repairs = data_section
+            .entities
+            .iter()
+            .filter_map(|instance| {
+                table.push_instance_lenient(instance).unwrap_or_else(|e| {
+                    Some(StepRepair::RecordSkipped {
+                        id: instance_id(instance),
+                        reason: e.to_string(),
+                    })
+                })
+            })
+            .collect();
+        (table, repairs)
+    }
+    /// Reads the STEP file in the lenient mode.
+    #[inline(always)]
+    pub fn from_step_lenient(step_str: &str) -> Option<(Table, Vec<StepRepair>)> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        Some(Table::from_data_section_lenient(exchange.data.first()?))
+    }
+}
+
+/// The instances which cannot be read are skipped.
+/// [`Table::from_data_section_with_report`] reports them.
+impl<'a> FromIterator<&'a EntityInstance> for Table {
+    fn from_iter<I: IntoIterator<Item = &'a EntityInstance>>(iter: I) -> Table {
+        let mut res = Table::default();
+        iter.into_iter().for_each(|instance| {
+            let _ = res.push_instance(instance);
+        });
+        res
+    }
+}
+
+/// Undefined structures are parsed into this.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = dummy)]
+#[holder(generate_deserialize)]
+pub struct Dummy {
+    pub record: String,
+    pub is_simple: bool,
+}
+
+/// `cartesian_point`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = cartesian_point)]
+#[holder(generate_deserialize)]
+pub struct CartesianPoint {
+    pub label: Option<String>,
+    pub coordinates: Vec<f64>,
+}
+impl From<&CartesianPoint> for Point2 {
+    #[inline(always)]
+    fn from(pt: &CartesianPoint) -> Self {
+        let pt = &pt.coordinates;
+        match pt.len() {
+            0 => Point2::origin(),
+            1 => Point2::new(pt[0], 0.0),
+            _ => Point2::new(pt[0], pt[1]),
+        }
+    }
+}
+impl From<&CartesianPoint> for Point3 {
+    #[inline(always)]
+    fn from(pt: &CartesianPoint) -> Self {
+        let pt = &pt.coordinates;
+        match pt.len() {
+            0 => Point3::origin(),
+            1 => Point3::new(pt[0], 0.0, 0.0),
+            2 => Point3::new(pt[0], pt[1], 0.0),
+            _ => Point3::new(pt[0], pt[1], pt[2]),
+        }
+    }
+}
+
+/// `direction`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = direction)]
+#[holder(generate_deserialize)]
+pub struct Direction {
     pub label: Option<String>,
     pub direction_ratios: Vec<f64>,
 }
@@ -1541,6 +2245,12 @@ pub enum SurfaceAny {
     BSplineSurface(Box<BSplineSurfaceAny>),
     #[holder(use_place_holder)]
     SweptSurface(Box<SweptSurfaceAny>),
+    #[holder(use_place_holder)]
+    OffsetSurface(Box<OffsetSurface>),
+    #[holder(use_place_holder)]
+    RectangularTrimmedSurface(Box<RectangularTrimmedSurface>),
+    #[holder(use_place_holder)]
+    CurveBoundedSurface(Box<CurveBoundedSurface>),
 }
 
 impl TryFrom<&SurfaceAny> for Surface {
@@ -1552,6 +2262,9 @@ impl TryFrom<&SurfaceAny> for Surface {
             ElementarySurface(x) => Self::ElementarySurface(Box::new(x.as_ref().try_into()?)),
             BSplineSurface(x) => x.as_ref().try_into()?,
             SweptSurface(x) => Self::SweptCurve(Box::new(x.as_ref().try_into()?)),
+            OffsetSurface(x) => Self::OffsetSurface(Box::new(x.as_ref().try_into()?)),
+            RectangularTrimmedSurface(x) => x.as_ref().try_into()?,
+            CurveBoundedSurface(x) => x.as_ref().try_into()?,
         })
     }
 }
@@ -1571,6 +2284,8 @@ pub enum ElementarySurfaceAny {
     ToroidalSurface(Box<ToroidalSurface>),
     #[holder(use_place_holder)]
     ConicalSurface(Box<ConicalSurface>),
+    #[holder(use_place_holder)]
+    DegenerateToroidalSurface(Box<DegenerateToroidalSurface>),
 }
 
 impl TryFrom<&ElementarySurfaceAny> for ElementarySurface {
@@ -1584,6 +2299,7 @@ impl TryFrom<&ElementarySurfaceAny> for ElementarySurface {
             CylindricalSurface(x) => Self::CylindricalSurface(x.as_ref().into()),
             ToroidalSurface(x) => Self::ToroidalSurface(x.as_ref().try_into()?),
             ConicalSurface(x) => Self::ConicalSurface(x.as_ref().into()),
+            DegenerateToroidalSurface(x) => Self::ToroidalSurface(x.as_ref().try_into()?),
         })
     }
 }
@@ -1691,6 +2407,44 @@ impl TryFrom<&ToroidalSurface> for alias::ToroidalSurface {
     }
 }
 
+/// `degenerate_toroidal_surface`
+///
+/// The minor radius is larger than the major one, so the torus intersects itself.
+/// `select_outer` only tells which half of the surface is used by the faces,
+/// which are trimmed by their boundaries anyway, so the whole torus is returned.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = degenerate_toroidal_surface)]
+#[holder(generate_deserialize)]
+pub struct DegenerateToroidalSurface {
+    label: Option<String>,
+    #[holder(use_place_holder)]
+    position: Axis2Placement3d,
+    major_radius: f64,
+    minor_radius: f64,
+    select_outer: bool,
+}
+
+impl TryFrom<&DegenerateToroidalSurface> for alias::ToroidalSurface {
+    type Error = StepConvertingError;
+    #[inline(always)]
+    fn try_from(
+        DegenerateToroidalSurface {
+            position,
+            major_radius,
+            minor_radius,
+            ..
+        }: &DegenerateToroidalSurface,
+    ) -> Result<Self, StepConvertingError> {
+        if *major_radius <= TOLERANCE || *minor_radius <= TOLERANCE {
+            return Err("DegenerateToroidalSurface radius too small".into());
+        }
+        let mat = Matrix4::from(position);
+        let torus = Torus::new(Point3::origin(), *major_radius, *minor_radius);
+        Ok(Processor::new(torus).transformed(mat))
+    }
+}
+
 /// `conical_surface`
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
 #[holder(table = Table)]
@@ -2051,6 +2805,84 @@ impl TryFrom<&SurfaceOfRevolution> for StepRevolutedCurve {
     }
 }
 
+/// `offset_surface`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = offset_surface)]
+#[holder(generate_deserialize)]
+pub struct OffsetSurface {
+    label: Option<String>,
+    #[holder(use_place_holder)]
+    basis_surface: SurfaceAny,
+    distance: f64,
+    self_intersect: Logical,
+}
+
+impl TryFrom<&OffsetSurface> for alias::OffsetSurface<Surface> {
+    type Error = StepConvertingError;
+    #[inline(always)]
+    fn try_from(value: &OffsetSurface) -> Result<Self, Self::Error> {
+        let surface = Surface::try_from(&value.basis_surface)?;
+        Ok(alias::OffsetSurface::new(surface, value.distance))
+    }
+}
+
+/// `rectangular_trimmed_surface`
+///
+/// Faces are trimmed by their boundaries, so only the basis surface is returned.
+/// If exactly one of the senses is reversed, the orientation of the basis surface is reversed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = rectangular_trimmed_surface)]
+#[holder(generate_deserialize)]
+pub struct RectangularTrimmedSurface {
+    label: Option<String>,
+    #[holder(use_place_holder)]
+    basis_surface: SurfaceAny,
+    u1: f64,
+    u2: f64,
+    v1: f64,
+    v2: f64,
+    usense: bool,
+    vsense: bool,
+}
+
+impl TryFrom<&RectangularTrimmedSurface> for Surface {
+    type Error = StepConvertingError;
+    #[inline(always)]
+    fn try_from(value: &RectangularTrimmedSurface) -> Result<Self, Self::Error> {
+        let mut surface = Surface::try_from(&value.basis_surface)?;
+        if value.usense != value.vsense {
+            surface.invert();
+        }
+        Ok(surface)
+    }
+}
+
+/// `curve_bounded_surface`
+///
+/// Faces are trimmed by their boundaries, so only the basis surface is returned.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = curve_bounded_surface)]
+#[holder(generate_deserialize)]
+pub struct CurveBoundedSurface {
+    label: Option<String>,
+    #[holder(use_place_holder)]
+    basis_surface: SurfaceAny,
+    #[holder(use_place_holder)]
+    boundaries: Vec<Dummy>,
+    implicit_outer: bool,
+}
+
+impl TryFrom<&CurveBoundedSurface> for Surface {
+    type Error = StepConvertingError;
+    #[inline(always)]
+    fn try_from(value: &CurveBoundedSurface) -> Result<Self, Self::Error> {
+        Surface::try_from(&value.basis_surface)
+    }
+}
+
 /// `vertex_point`
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
 #[holder(table = Table)]
@@ -2574,44 +3406,572 @@ pub struct OrientedShell {
     pub orientation: bool,
 }
 
-impl Table {
-    fn place_holder_edge_any_to_index_and_edge_curve(
-        &self,
-        edge: &PlaceHolder<EdgeAnyHolder>,
-    ) -> Option<(u64, EdgeCurveHolder)> {
-        use PlaceHolder::Ref;
-        let Ref(Name::Entity(ref idx)) = edge else {
-            return None;
-        };
-        self.oriented_edge
-            .get(idx)
-            .and_then(|oriented_edge| {
-                Some((
-                    oriented_edge.edge_element_idx()?,
-                    oriented_edge.edge_element_holder(self)?,
-                ))
-            })
-            .or_else(|| {
-                self.edge_curve
-                    .get(idx)
-                    .map(|edge_curve| (*idx, edge_curve.clone()))
-            })
+/// `coordinates_list`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = coordinates_list)]
+#[holder(generate_deserialize)]
+pub struct CoordinatesList {
+    pub label: Option<String>,
+    pub npoints: i64,
+    pub position_coords: Vec<Vec<f64>>,
+}
+
+/// `triangulated_face`
+///
+/// The optional `geometric_link` to the B-rep face is not read.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = triangulated_face)]
+#[holder(generate_deserialize)]
+pub struct TriangulatedFace {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub coordinates: CoordinatesList,
+    pub pnmax: i64,
+    pub normals: Vec<Vec<f64>>,
+    pub pnindex: Vec<i64>,
+    pub triangles: Vec<Vec<i64>>,
+}
+
+impl TryFrom<&TriangulatedFace> for PolygonMesh {
+    type Error = StepConvertingError;
+    fn try_from(face: &TriangulatedFace) -> Result<Self, Self::Error> {
+        tessellated_face_to_polygon_mesh(
+            &face.coordinates.position_coords,
+            &face.normals,
+            &face.pnindex,
+            triangles_to_array(&face.triangles)?,
+        )
     }
-    fn face_any_to_orientation_and_face(
-        &self,
-        face: Option<FaceAnyHolder>,
-    ) -> Option<(bool, FaceSurfaceHolder)> {
-        match face? {
-            FaceAnyHolder::FaceSurface(face) => Some((true, face)),
-            FaceAnyHolder::OrientedFace(oriented_face) => {
-                let face_element = oriented_face.face_element_holder(self)?;
-                Some((oriented_face.orientation, face_element))
-            }
-        }
+}
+
+/// `complex_triangulated_face`
+///
+/// The optional `geometric_link` to the B-rep face is not read.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = complex_triangulated_face)]
+#[holder(generate_deserialize)]
+pub struct ComplexTriangulatedFace {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub coordinates: CoordinatesList,
+    pub pnmax: i64,
+    pub normals: Vec<Vec<f64>>,
+    pub pnindex: Vec<i64>,
+    pub triangle_strips: Vec<Vec<i64>>,
+    pub triangle_fans: Vec<Vec<i64>>,
+}
+
+impl TryFrom<&ComplexTriangulatedFace> for PolygonMesh {
+    type Error = StepConvertingError;
+    fn try_from(face: &ComplexTriangulatedFace) -> Result<Self, Self::Error> {
+        tessellated_face_to_polygon_mesh(
+            &face.coordinates.position_coords,
+            &face.normals,
+            &face.pnindex,
+            strips_and_fans_to_triangles(&face.triangle_strips, &face.triangle_fans),
+        )
     }
+}
 
-    fn shell_vertices(&self, shell: &ShellHolder) -> (Vec<Point3>, HashMap<u64, usize>) {
-        use PlaceHolder::Ref;
+fn triangles_to_array(triangles: &[Vec<i64>]) -> Result<Vec<[i64; 3]>, StepConvertingError> {
+    triangles
+        .iter()
+        .map(|tri| match tri.as_slice() {
+            [a, b, c] => Ok([*a, *b, *c]),
+            _ => Err("a triangle must have three indices.".into()),
+        })
+        .collect()
+}
+
+fn strips_and_fans_to_triangles(strips: &[Vec<i64>], fans: &[Vec<i64>]) -> Vec<[i64; 3]> {
+    // the orientation of the triangles in a strip alternates.
+    let strips = strips.iter().flat_map(|strip| {
+        strip.windows(3).enumerate().map(|(i, w)| match i % 2 {
+            0 => [w[0], w[1], w[2]],
+            _ => [w[1], w[0], w[2]],
+        })
+    });
+    let fans = fans
+        .iter()
+        .filter(|fan| !fan.is_empty())
+        .flat_map(|fan| fan[1..].windows(2).map(|w| [fan[0], w[0], w[1]]));
+    strips
+        .chain(fans)
+        // strips are often joined by degenerate triangles
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .collect()
+}
+
+/// Creates a mesh of a tessellated face.
+///
+/// `triangles` are one-based indices of `pnindex`, or of `position_coords` if `pnindex` is empty.
+/// `normals` are given for each index of `triangles`, or once for the whole face.
+/// Only the referred coordinates are included in the mesh.
+fn tessellated_face_to_polygon_mesh(
+    position_coords: &[Vec<f64>],
+    normals: &[Vec<f64>],
+    pnindex: &[i64],
+    triangles: Vec<[i64; 3]>,
+) -> Result<PolygonMesh, StepConvertingError> {
+    fn index(idx: i64) -> Result<usize, StepConvertingError> {
+        match idx > 0 {
+            true => Ok(idx as usize - 1),
+            false => Err(format!("index {idx} is out of range.").into()),
+        }
+    }
+    fn vector(coords: Option<&Vec<f64>>) -> Result<Vector3, StepConvertingError> {
+        match coords.map(Vec::as_slice) {
+            Some([x, y, z]) => Ok(Vector3::new(*x, *y, *z)),
+            _ => Err("index of tessellated face is out of range.".into()),
+        }
+    }
+    let mut attrs = StandardAttributes::default();
+    let (mut pos_map, mut nor_map) = (HashMap::new(), HashMap::new());
+    let mut vertex = |idx: i64| -> Result<StandardVertex, StepConvertingError> {
+        let idx = index(idx)?;
+        let coord_idx = match pnindex.is_empty() {
+            true => idx,
+            false => index(
+                *pnindex
+                    .get(idx)
+                    .ok_or("index of pnindex is out of range.")?,
+            )?,
+        };
+        let pos = match pos_map.get(&coord_idx) {
+            Some(pos) => *pos,
+            None => {
+                let p = Point3::from_vec(vector(position_coords.get(coord_idx))?);
+                attrs.positions.push(p);
+                pos_map.insert(coord_idx, attrs.positions.len() - 1);
+                attrs.positions.len() - 1
+            }
+        };
+        let nor_idx = match normals.len() {
+            0 => None,
+            1 => Some(0),
+            _ => Some(idx),
+        };
+        let nor = match nor_idx {
+            Some(nor_idx) => match nor_map.get(&nor_idx) {
+                Some(nor) => Some(*nor),
+                None => {
+                    attrs.normals.push(vector(normals.get(nor_idx))?);
+                    nor_map.insert(nor_idx, attrs.normals.len() - 1);
+                    Some(attrs.normals.len() - 1)
+                }
+            },
+            None => None,
+        };
+        Ok(StandardVertex { pos, uv: None, nor })
+    };
+    let tri_faces = triangles
+        .into_iter()
+        .map(|[a, b, c]| Ok([vertex(a)?, vertex(b)?, vertex(c)?]))
+        .collect::<Result<Vec<_>, StepConvertingError>>()?;
+    let faces = Faces::from_tri_and_quad_faces(tri_faces, Vec::new());
+    Ok(PolygonMesh::new(attrs, faces))
+}
+
+/// `tessellated_face`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(generate_deserialize)]
+pub enum TessellatedFaceAny {
+    #[holder(use_place_holder)]
+    TriangulatedFace(TriangulatedFace),
+    #[holder(use_place_holder)]
+    ComplexTriangulatedFace(ComplexTriangulatedFace),
+}
+
+impl TryFrom<&TessellatedFaceAny> for PolygonMesh {
+    type Error = StepConvertingError;
+    fn try_from(face: &TessellatedFaceAny) -> Result<Self, Self::Error> {
+        match face {
+            TessellatedFaceAny::TriangulatedFace(face) => face.try_into(),
+            TessellatedFaceAny::ComplexTriangulatedFace(face) => face.try_into(),
+        }
+    }
+}
+
+/// `tessellated_shell`
+///
+/// `tessellated_solid` is also parsed to this struct.
+/// The items of the shell are read only if they are tessellated faces.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = tessellated_shell)]
+#[holder(generate_deserialize)]
+pub struct TessellatedShell {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub items: Vec<TessellatedFaceAny>,
+}
+
+/// `product`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = product)]
+#[holder(generate_deserialize)]
+pub struct Product {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub frame_of_reference: Vec<Dummy>,
+}
+
+/// `product_definition_formation`
+///
+/// `product_definition_formation_with_specified_source` is also parsed to this struct.
+/// The `id` is usually the revision of the product.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = product_definition_formation)]
+#[holder(generate_deserialize)]
+pub struct ProductDefinitionFormation {
+    pub id: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub of_product: Product,
+}
+
+/// `product_definition`
+///
+/// `product_definition_with_associated_documents` is also parsed to this struct.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = product_definition)]
+#[holder(generate_deserialize)]
+pub struct ProductDefinition {
+    pub id: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub formation: ProductDefinitionFormation,
+    #[holder(use_place_holder)]
+    pub frame_of_reference: Dummy,
+}
+
+/// `product_definition_shape`
+///
+/// The `definition` may also be a relationship between product definitions,
+/// e.g. `next_assembly_usage_occurrence`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = product_definition_shape)]
+#[holder(generate_deserialize)]
+pub struct ProductDefinitionShape {
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub definition: ProductDefinition,
+}
+
+/// `next_assembly_usage_occurrence`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = next_assembly_usage_occurrence)]
+#[holder(generate_deserialize)]
+pub struct NextAssemblyUsageOccurrence {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub relating_product_definition: ProductDefinition,
+    #[holder(use_place_holder)]
+    pub related_product_definition: ProductDefinition,
+    pub reference_designator: Option<String>,
+}
+
+/// `shape_definition_representation`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = shape_definition_representation)]
+#[holder(generate_deserialize)]
+pub struct ShapeDefinitionRepresentation {
+    #[holder(use_place_holder)]
+    pub definition: ProductDefinitionShape,
+    #[holder(use_place_holder)]
+    pub used_representation: ShapeRepresentation,
+}
+
+/// `shape_representation`
+///
+/// The subtypes, e.g. `advanced_brep_shape_representation`, are also parsed to this struct.
+/// The items are kept as references since they may be of any type.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = shape_representation)]
+#[holder(generate_deserialize)]
+pub struct ShapeRepresentation {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub items: Vec<Dummy>,
+    #[holder(use_place_holder)]
+    pub context_of_items: Dummy,
+}
+
+/// `shape_representation_relationship`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = shape_representation_relationship)]
+#[holder(generate_deserialize)]
+pub struct ShapeRepresentationRelationship {
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub rep_1: ShapeRepresentation,
+    #[holder(use_place_holder)]
+    pub rep_2: ShapeRepresentation,
+}
+
+/// `manifold_solid_brep`
+///
+/// `brep_with_voids` is also parsed to this struct without the voids.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = manifold_solid_brep)]
+#[holder(generate_deserialize)]
+pub struct ManifoldSolidBrep {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub outer: Shell,
+}
+
+/// `shell_based_surface_model`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = shell_based_surface_model)]
+#[holder(generate_deserialize)]
+pub struct ShellBasedSurfaceModel {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub sbsm_boundary: Vec<Shell>,
+}
+
+/// `item_defined_transformation`
+///
+/// Only the transformation between `axis2_placement_3d`s is read.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = item_defined_transformation)]
+#[holder(generate_deserialize)]
+pub struct ItemDefinedTransformation {
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub transform_item_1: Axis2Placement3d,
+    #[holder(use_place_holder)]
+    pub transform_item_2: Axis2Placement3d,
+}
+
+impl From<&ItemDefinedTransformation> for Matrix4 {
+    /// The transformation mapping `transform_item_1` to `transform_item_2`.
+    #[inline(always)]
+    fn from(transformation: &ItemDefinedTransformation) -> Matrix4 {
+        let item_1 = Matrix4::from(&transformation.transform_item_1);
+        let item_2 = Matrix4::from(&transformation.transform_item_2);
+        item_2 * SquareMatrix::invert(&item_1).unwrap_or_else(Matrix4::identity)
+    }
+}
+
+/// `representation_relationship_with_transformation`
+///
+/// Read from the complex instance with `representation_relationship`
+/// and `shape_representation_relationship`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = representation_relationship_with_transformation)]
+#[holder(generate_deserialize)]
+pub struct RepresentationRelationshipWithTransformation {
+    pub name: String,
+    pub description: Option<String>,
+    #[holder(use_place_holder)]
+    pub rep_1: ShapeRepresentation,
+    #[holder(use_place_holder)]
+    pub rep_2: ShapeRepresentation,
+    #[holder(use_place_holder)]
+    pub transformation_operator: ItemDefinedTransformation,
+}
+
+/// `context_dependent_shape_representation`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = context_dependent_shape_representation)]
+#[holder(generate_deserialize)]
+pub struct ContextDependentShapeRepresentation {
+    #[holder(use_place_holder)]
+    pub representation_relation: RepresentationRelationshipWithTransformation,
+    #[holder(use_place_holder)]
+    pub represented_product_relation: ProductDefinitionShape,
+}
+
+/// `styled_item`
+///
+/// `over_riding_styled_item` is also read as `styled_item`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = styled_item)]
+#[holder(generate_deserialize)]
+pub struct StyledItem {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub styles: Vec<Dummy>,
+    #[holder(use_place_holder)]
+    pub item: Dummy,
+}
+
+/// `presentation_style_assignment`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = presentation_style_assignment)]
+#[holder(generate_deserialize)]
+pub struct PresentationStyleAssignment {
+    #[holder(use_place_holder)]
+    pub styles: Vec<Dummy>,
+}
+
+/// `surface_style_usage`, without the side
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = surface_style_usage)]
+#[holder(generate_deserialize)]
+pub struct SurfaceStyleUsage {
+    #[holder(use_place_holder)]
+    pub style: Dummy,
+}
+
+/// `surface_side_style`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = surface_side_style)]
+#[holder(generate_deserialize)]
+pub struct SurfaceSideStyle {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub styles: Vec<Dummy>,
+}
+
+/// `surface_style_fill_area`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = surface_style_fill_area)]
+#[holder(generate_deserialize)]
+pub struct SurfaceStyleFillArea {
+    #[holder(use_place_holder)]
+    pub fill_area: Dummy,
+}
+
+/// `fill_area_style`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = fill_area_style)]
+#[holder(generate_deserialize)]
+pub struct FillAreaStyle {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub fill_styles: Vec<Dummy>,
+}
+
+/// `fill_area_style_colour`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = fill_area_style_colour)]
+#[holder(generate_deserialize)]
+pub struct FillAreaStyleColour {
+    pub label: Option<String>,
+    #[holder(use_place_holder)]
+    pub fill_colour: Dummy,
+}
+
+/// `colour_rgb`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = colour_rgb)]
+#[holder(generate_deserialize)]
+pub struct ColourRgb {
+    pub label: Option<String>,
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+}
+
+/// `surface_style_rendering_with_properties`, without the rendering method
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = surface_style_rendering_with_properties)]
+#[holder(generate_deserialize)]
+pub struct SurfaceStyleRenderingWithProperties {
+    #[holder(use_place_holder)]
+    pub surface_colour: Dummy,
+    #[holder(use_place_holder)]
+    pub properties: Vec<Dummy>,
+}
+
+/// `surface_style_transparent`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Holder)]
+#[holder(table = Table)]
+#[holder(field = surface_style_transparent)]
+#[holder(generate_deserialize)]
+pub struct SurfaceStyleTransparent {
+    pub transparency: f64,
+}
+
+/// the entity id of the reference
+fn place_holder_idx<T>(place_holder: &PlaceHolder<T>) -> Option<u64> {
+    match place_holder {
+        PlaceHolder::Ref(Name::Entity(idx)) => Some(*idx),
+        _ => None,
+    }
+}
+
+/// the entries sorted by the entity ids
+fn sorted<T>(map: &HashMap<u64, T>) -> Vec<(u64, &T)> {
+    let mut vec: Vec<_> = map.iter().map(|(idx, x)| (*idx, x)).collect();
+    vec.sort_by_key(|(idx, _)| *idx);
+    vec
+}
+
+impl Table {
+    fn place_holder_edge_any_to_index_and_edge_curve(
+        &self,
+        edge: &PlaceHolder<EdgeAnyHolder>,
+    ) -> Option<(u64, EdgeCurveHolder)> {
+        use PlaceHolder::Ref;
+        let Ref(Name::Entity(ref idx)) = edge else {
+            return None;
+        };
+        self.oriented_edge
+            .get(idx)
+            .and_then(|oriented_edge| {
+                Some((
+                    oriented_edge.edge_element_idx()?,
+                    oriented_edge.edge_element_holder(self)?,
+                ))
+            })
+            .or_else(|| {
+                self.edge_curve
+                    .get(idx)
+                    .map(|edge_curve| (*idx, edge_curve.clone()))
+            })
+    }
+    fn face_any_to_orientation_and_face(
+        &self,
+        face: Option<FaceAnyHolder>,
+    ) -> Option<(bool, FaceSurfaceHolder)> {
+        match face? {
+            FaceAnyHolder::FaceSurface(face) => Some((true, face)),
+            FaceAnyHolder::OrientedFace(oriented_face) => {
+                let face_element = oriented_face.face_element_holder(self)?;
+                Some((oriented_face.orientation, face_element))
+            }
+        }
+    }
+
+    fn shell_vertices(&self, shell: &ShellHolder) -> (Vec<Point3>, HashMap<u64, usize>) {
+        use PlaceHolder::Ref;
         let mut vidx_map = HashMap::<u64, usize>::new();
         let vertex_to_point = |v: PlaceHolder<VertexPointHolder>| {
             if let Ref(Name::Entity(ref idx)) = v {
@@ -2718,14 +4078,22 @@ impl Table {
         Some(edges)
     }
 
+    /// The faces are converted concurrently with the `parallel` feature.
+    /// The order of the faces is the same as `cfs_faces` in either case.
     fn shell_faces(
         &self,
         shell: &ShellHolder,
         eidx_map: &HashMap<u64, usize>,
     ) -> Vec<CompressedFace<Surface>> {
-        shell
+        let faces: Vec<_> = shell
             .cfs_faces_holder(self)
             .filter_map(|face| self.face_any_to_orientation_and_face(face))
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let faces = faces.into_iter();
+        #[cfg(feature = "parallel")]
+        let faces = faces.into_par_iter();
+        faces
             .filter_map(|(orientation, face)| {
                 let step_surface: SurfaceAny = face
                     .face_geometry
@@ -2786,4 +4154,334 @@ impl Table {
             faces: self.shell_faces(shell, &eidx_map),
         })
     }
+
+    /// construct `CompressedShell`s from all `Shell`s in STEP file
+    ///
+    /// The shells are converted concurrently with the `parallel` feature.
+    /// The results are sorted by the entity ids, so the output does not depend on the thread scheduling.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::*;
+    /// let step_string = include_str!(concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/../resources/step/occt-cube.step",
+    /// ));
+    /// let table = Table::from_step(step_string).unwrap();
+    /// let shells = table.to_compressed_shells();
+    /// assert_eq!(shells.len(), 1);
+    /// let (_, cshell) = &shells[0];
+    /// assert_eq!(cshell.as_ref().unwrap().faces.len(), 6);
+    /// ```
+    pub fn to_compressed_shells(
+        &self,
+    ) -> Vec<(
+        u64,
+        Result<CompressedShell<Point3, Curve3D, Surface>, StepConvertingError>,
+    )> {
+        let mut shells: Vec<_> = self.shell.iter().collect();
+        shells.sort_by_key(|(idx, _)| **idx);
+        #[cfg(not(feature = "parallel"))]
+        let shells = shells.into_iter();
+        #[cfg(feature = "parallel")]
+        let shells = shells.into_par_iter();
+        shells
+            .map(|(idx, shell)| (*idx, self.to_compressed_shell(shell)))
+            .collect()
+    }
+
+    fn coordinates_list_holder<'a>(
+        &'a self,
+        coordinates: &'a PlaceHolder<CoordinatesListHolder>,
+    ) -> Result<&'a CoordinatesListHolder, StepConvertingError> {
+        match coordinates {
+            PlaceHolder::Owned(coordinates) => Ok(coordinates),
+            PlaceHolder::Ref(Name::Entity(ref idx)) => self
+                .coordinates_list
+                .get(idx)
+                .ok_or_else(|| format!("coordinates_list #{idx} is not found.").into()),
+            _ => Err("coordinates_list must be an entity.".into()),
+        }
+    }
+
+    fn triangulated_face_holder_to_polygon_mesh(
+        &self,
+        face: &TriangulatedFaceHolder,
+    ) -> Result<PolygonMesh, StepConvertingError> {
+        tessellated_face_to_polygon_mesh(
+            &self
+                .coordinates_list_holder(&face.coordinates)?
+                .position_coords,
+            &face.normals,
+            &face.pnindex,
+            triangles_to_array(&face.triangles)?,
+        )
+    }
+
+    fn complex_triangulated_face_holder_to_polygon_mesh(
+        &self,
+        face: &ComplexTriangulatedFaceHolder,
+    ) -> Result<PolygonMesh, StepConvertingError> {
+        tessellated_face_to_polygon_mesh(
+            &self
+                .coordinates_list_holder(&face.coordinates)?
+                .position_coords,
+            &face.normals,
+            &face.pnindex,
+            strips_and_fans_to_triangles(&face.triangle_strips, &face.triangle_fans),
+        )
+    }
+
+    /// construct `PolygonMesh` from `TessellatedShell` in STEP file
+    ///
+    /// Items other than triangulated faces, e.g. `tessellated_edge`, are skipped.
+    /// The faces and the coordinates lists are read by reference, without being copied.
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use truck_stepio::r#in::*;
+    /// let step_str = "DATA;
+    /// #1 = TESSELLATED_SHELL('', (#2), $);
+    /// #2 = TRIANGULATED_FACE('', #3, 0, (), $, (), ((1, 2, 3), (1, 3, 4)));
+    /// #3 = COORDINATES_LIST('', 4, ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (1.0, 1.0, 0.0), (0.0, 1.0, 0.0)));
+    /// ENDSEC;";
+    /// let data_section = ruststep::ast::DataSection::from_str(step_str).unwrap();
+    /// let table = Table::from_data_section(&data_section);
+    /// let mesh = table.to_polygon_mesh(&table.tessellated_shell[&1]).unwrap();
+    /// assert_eq!(mesh.positions().len(), 4);
+    /// assert_eq!(mesh.faces().len(), 2);
+    /// ```
+    pub fn to_polygon_mesh(
+        &self,
+        shell: &TessellatedShellHolder,
+    ) -> Result<PolygonMesh, StepConvertingError> {
+        let mut mesh = PolygonMesh::default();
+        for item in &shell.items {
+            let face_mesh = match item {
+                PlaceHolder::Owned(TessellatedFaceAnyHolder::TriangulatedFace(face)) => {
+                    self.triangulated_face_holder_to_polygon_mesh(face)
+                }
+                PlaceHolder::Owned(TessellatedFaceAnyHolder::ComplexTriangulatedFace(face)) => {
+                    self.complex_triangulated_face_holder_to_polygon_mesh(face)
+                }
+                PlaceHolder::Ref(Name::Entity(ref idx)) => {
+                    if let Some(face) = self.triangulated_face.get(idx) {
+                        self.triangulated_face_holder_to_polygon_mesh(face)
+                    } else if let Some(face) = self.complex_triangulated_face.get(idx) {
+                        self.complex_triangulated_face_holder_to_polygon_mesh(face)
+                    } else {
+                        continue;
+                    }
+                }
+                _ => continue,
+            };
+            mesh.merge(face_mesh?);
+        }
+        Ok(mesh)
+    }
+
+    /// construct `CompressedShell` of `truck` from `Shell` in STEP file, repairing broken edges and faces
+    ///
+    /// Compared with [`Table::to_compressed_shell`],
+    /// - an edge whose pcurve is broken is read from the 3D curve of the surface curve,
+    /// - an edge whose geometry is broken is replaced by the line between its vertices,
+    /// - a face whose surface or boundary is broken is skipped instead of being returned with lacked boundaries.
+    ///
+    /// All the repairs are returned with the shell.
+    pub fn to_compressed_shell_lenient(
+        &self,
+        shell: &ShellHolder,
+    ) -> (CompressedShell<Point3, Curve3D, Surface>, Vec<StepRepair>) {
+        let mut repairs = Vec::new();
+        let (vertices, vidx_map) = self.shell_vertices_lenient(shell, &mut repairs);
+        let (edges, eidx_map) = self.shell_edges_lenient(shell, &vertices, &vidx_map, &mut repairs);
+        let faces = self.shell_faces_lenient(shell, &eidx_map, &mut repairs);
+        let shell = CompressedShell {
+            vertices,
+            edges,
+            faces,
+        };
+        (shell, repairs)
+    }
+
+    fn shell_edge_curves(&self, shell: &ShellHolder) -> Vec<(u64, EdgeCurveHolder)> {
+        let mut edges: Vec<(u64, EdgeCurveHolder)> = shell
+            .cfs_faces_holder(self)
+            .filter_map(|face| self.face_any_to_orientation_and_face(face))
+            .flat_map(|(_, face)| face.bounds_holder(self))
+            .filter_map(|bound| bound?.bound_holder(self))
+            .flat_map(|bound| bound.edge_list)
+            .filter_map(|edge| self.place_holder_edge_any_to_index_and_edge_curve(&edge))
+            .collect();
+        let mut visited = HashSet::new();
+        edges.retain(|(idx, _)| visited.insert(*idx));
+        edges
+    }
+
+    fn shell_vertices_lenient(
+        &self,
+        shell: &ShellHolder,
+        repairs: &mut Vec<StepRepair>,
+    ) -> (Vec<Point3>, HashMap<u64, usize>) {
+        let mut vertices = Vec::new();
+        let mut vidx_map = HashMap::<u64, usize>::new();
+        let mut skipped = HashSet::new();
+        self.shell_edge_curves(shell)
+            .into_iter()
+            .flat_map(|(_, edge)| [edge.edge_start, edge.edge_end])
+            .for_each(|vertex| {
+                let PlaceHolder::Ref(Name::Entity(idx)) = vertex else {
+                    return;
+                };
+                if vidx_map.contains_key(&idx) || skipped.contains(&idx) {
+                    return;
+                }
+                match EntityTable::<VertexPointHolder>::get_owned(self, idx) {
+                    Ok(p) => {
+                        vidx_map.insert(idx, vertices.len());
+                        vertices.push(Point3::from(&p.vertex_geometry));
+                    }
+                    Err(e) => {
+                        skipped.insert(idx);
+                        let reason = e.to_string();
+                        repairs.push(StepRepair::VertexSkipped { id: idx, reason });
+                    }
+                }
+            });
+        (vertices, vidx_map)
+    }
+
+    fn shell_edges_lenient(
+        &self,
+        shell: &ShellHolder,
+        vertices: &[Point3],
+        vidx_map: &HashMap<u64, usize>,
+        repairs: &mut Vec<StepRepair>,
+    ) -> (Vec<CompressedEdge<Curve3D>>, HashMap<u64, usize>) {
+        let vertex_index = |v: &PlaceHolder<VertexPointHolder>| match v {
+            PlaceHolder::Ref(Name::Entity(idx)) => vidx_map.get(idx).copied(),
+            _ => None,
+        };
+        let mut edges = Vec::new();
+        let mut eidx_map = HashMap::<u64, usize>::new();
+        for (idx, edge) in self.shell_edge_curves(shell) {
+            let (Some(front), Some(back)) =
+                (vertex_index(&edge.edge_start), vertex_index(&edge.edge_end))
+            else {
+                let reason = "the vertex is missing".to_string();
+                repairs.push(StepRepair::EdgeSkipped { id: idx, reason });
+                continue;
+            };
+            let parsed = edge
+                .clone()
+                .into_owned(self)
+                .map_err(|e| e.to_string())
+                .and_then(|edge| edge.parse_curve3d().map_err(|e| e.to_string()));
+            let curve = match parsed {
+                Ok(curve) => curve,
+                Err(reason) => {
+                    let (p, q) = (vertices[front], vertices[back]);
+                    if let Some(curve) = self.surface_curve_3d(&edge, p, q) {
+                        repairs.push(StepRepair::EdgeCurveReplacedByCurve3D { id: idx, reason });
+                        curve
+                    } else if !p.near(&q) {
+                        repairs.push(StepRepair::EdgeCurveReplacedByLine { id: idx, reason });
+                        Curve3D::Line(Line(p, q))
+                    } else {
+                        repairs.push(StepRepair::EdgeSkipped { id: idx, reason });
+                        continue;
+                    }
+                }
+            };
+            eidx_map.insert(idx, edges.len());
+            edges.push(CompressedEdge {
+                vertices: (front, back),
+                curve,
+            });
+        }
+        (edges, eidx_map)
+    }
+
+    /// the 3D curve of the edge geometry if it is a surface curve
+    fn surface_curve_3d(&self, edge: &EdgeCurveHolder, p: Point3, q: Point3) -> Option<Curve3D> {
+        let PlaceHolder::Ref(Name::Entity(idx)) = &edge.edge_geometry else {
+            return None;
+        };
+        let curve_3d = self
+            .surface_curve
+            .get(idx)?
+            .curve_3d
+            .clone()
+            .into_owned(self)
+            .ok()?;
+        let (p, q) = match edge.same_sense {
+            true => (p, q),
+            false => (q, p),
+        };
+        EdgeCurve::sub_parse_curve3d(&curve_3d, p, q, edge.same_sense).ok()
+    }
+
+    fn shell_faces_lenient(
+        &self,
+        shell: &ShellHolder,
+        eidx_map: &HashMap<u64, usize>,
+        repairs: &mut Vec<StepRepair>,
+    ) -> Vec<CompressedFace<Surface>> {
+        let face_ids = shell.cfs_faces.iter().map(|face| match face {
+            PlaceHolder::Ref(Name::Entity(idx)) => *idx,
+            _ => 0,
+        });
+        face_ids
+            .zip(shell.cfs_faces_holder(self))
+            .filter_map(|(id, face)| match self.face_lenient(face, eidx_map) {
+                Ok(face) => Some(face),
+                Err(reason) => {
+                    repairs.push(StepRepair::FaceSkipped { id, reason });
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn face_lenient(
+        &self,
+        face: Option<FaceAnyHolder>,
+        eidx_map: &HashMap<u64, usize>,
+    ) -> Result<CompressedFace<Surface>, String> {
+        let (orientation, face) = self
+            .face_any_to_orientation_and_face(face)
+            .ok_or("the face is missing")?;
+        let step_surface: SurfaceAny = face
+            .face_geometry
+            .clone()
+            .into_owned(self)
+            .map_err(|e| e.to_string())?;
+        let mut surface = Surface::try_from(&step_surface).map_err(|e| e.to_string())?;
+        if !face.same_sense {
+            surface.invert()
+        }
+        let boundaries = face
+            .bounds_holder(self)
+            .into_iter()
+            .map(|bound| -> Result<Vec<CompressedEdgeIndex>, String> {
+                let bound = bound.ok_or("the face bound is missing")?;
+                let len = bound
+                    .bound_holder(self)
+                    .ok_or("the edge loop is missing")?
+                    .edge_list
+                    .len();
+                let edges = self
+                    .face_bound_to_edges(bound, eidx_map)
+                    .ok_or("the edge loop is missing")?;
+                match edges.len() == len {
+                    true => Ok(edges),
+                    false => Err(format!("{} of {len} edges are skipped", len - edges.len())),
+                }
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(CompressedFace {
+            surface,
+            boundaries,
+            orientation,
+        })
+    }
 }
@@ -0,0 +1,575 @@
+//! Reads the product manufacturing information, PMI, of AP242 files.
+//!
+//! The graphical PMI is drawn by the annotation occurrences, i.e. `annotation_curve_occurrence`
+//! and `tessellated_annotation_occurrence`, grouped by `draughting_callout` and placed on
+//! `annotation_plane`. Their geometry is read from `polyline`, `tessellated_curve_set` and
+//! the triangulated surface sets, through `geometric_curve_set` and `tessellated_geometric_set`.
+//! The other curves, e.g. the circles of the arrow heads, are not read.
+//! The geometry is in the coordinates of the model, as AP242 recommends.
+//!
+//! The semantic PMI is listed without the geometry: the geometric tolerances, the dimensions
+//! with their plus-minus tolerances, and the datums.
+//! Since the tolerances are often complex instances, all are read from [`DataSection`] directly.
+
+use super::units::{parameter_to_real, parameter_to_refs, parameters, Instances};
+use super::*;
+
+/// the depth of the nested sets at which the geometry is cut, in case of cyclic references
+const MAX_DEPTH: usize = 16;
+
+/// the records of the annotation occurrences, whose parameters are the ones of `styled_item`
+const ANNOTATION_OCCURRENCES: [&str; 5] = [
+    "ANNOTATION_CURVE_OCCURRENCE",
+    "ANNOTATION_FILL_AREA_OCCURRENCE",
+    "ANNOTATION_OCCURRENCE",
+    "DRAUGHTING_ANNOTATION_OCCURRENCE",
+    "TESSELLATED_ANNOTATION_OCCURRENCE",
+];
+
+/// Annotation plane, on which the annotations are drawn
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepAnnotationPlane {
+    /// the entity id of `annotation_plane`
+    pub entity_id: u64,
+    /// the name of `annotation_plane`
+    pub name: String,
+    /// the origin of the plane
+    pub origin: [f64; 3],
+    /// the unit normal of the plane
+    pub normal: [f64; 3],
+}
+
+/// Graphical annotation, e.g. the lines and the characters of a dimension
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepAnnotation {
+    /// the entity id of the annotation occurrence
+    pub entity_id: u64,
+    /// the name of the annotation occurrence
+    pub name: String,
+    /// the entity id of `draughting_callout` containing the annotation
+    pub callout: Option<u64>,
+    /// the name of `draughting_callout` containing the annotation, e.g. `Linear Size.1`
+    pub callout_name: Option<String>,
+    /// the plane of the annotation
+    pub plane: Option<StepAnnotationPlane>,
+    /// the polylines
+    pub polylines: Vec<Vec<[f64; 3]>>,
+    /// the triangles, e.g. of the filled characters
+    pub triangles: Vec<[[f64; 3]; 3]>,
+}
+
+/// Geometric tolerance, e.g. `flatness_tolerance`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepGeometricTolerance {
+    /// the entity id of the tolerance
+    pub entity_id: u64,
+    /// the kind, the lower-cased name of the subtype, e.g. `position_tolerance`
+    pub kind: String,
+    /// the name of the tolerance
+    pub name: String,
+    /// the width of the tolerance zone
+    pub magnitude: Option<f64>,
+    /// the name of the unit of `magnitude`, e.g. `millimetre`
+    pub unit: Option<String>,
+}
+
+/// Dimension, `dimensional_size` or `dimensional_location` and their subtypes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepDimension {
+    /// the entity id of the dimension
+    pub entity_id: u64,
+    /// the kind, the lower-cased name of the entity, e.g. `dimensional_size`
+    pub kind: String,
+    /// the name of the dimension, e.g. `diameter`
+    pub name: String,
+    /// the nominal value from `dimensional_characteristic_representation`
+    pub value: Option<f64>,
+    /// the name of the unit of the values
+    pub unit: Option<String>,
+    /// the lower deviation from `plus_minus_tolerance`
+    pub lower: Option<f64>,
+    /// the upper deviation from `plus_minus_tolerance`
+    pub upper: Option<f64>,
+}
+
+/// Datum, e.g. `A`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepDatum {
+    /// the entity id of `datum`
+    pub entity_id: u64,
+    /// the name of `datum`
+    pub name: String,
+    /// the identification, e.g. `A`
+    pub identification: String,
+}
+
+/// PMI of the STEP file, each list sorted by the entity ids
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepPmi {
+    /// the graphical annotations
+    pub annotations: Vec<StepAnnotation>,
+    /// the geometric tolerances
+    pub tolerances: Vec<StepGeometricTolerance>,
+    /// the dimensions
+    pub dimensions: Vec<StepDimension>,
+    /// the datums
+    pub datums: Vec<StepDatum>,
+}
+
+fn parameter_to_string(parameter: Option<&Parameter>) -> String {
+    match parameter {
+        Some(Parameter::String(string)) => string.clone(),
+        _ => String::new(),
+    }
+}
+
+fn parameter_to_reals(parameter: Option<&Parameter>) -> Vec<f64> {
+    match parameter {
+        Some(Parameter::List(list)) => list
+            .iter()
+            .filter_map(|parameter| parameter_to_real(Some(parameter)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// the positive integers, e.g. the indices of the points
+fn parameter_to_indices(parameter: Option<&Parameter>) -> Vec<usize> {
+    match parameter {
+        Some(Parameter::List(list)) => list
+            .iter()
+            .filter_map(|x| match x {
+                Parameter::Integer(x) if *x > 0 => Some(*x as usize),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// the lists of the indices, e.g. of the line strips
+fn parameter_to_index_lists(parameter: Option<&Parameter>) -> Vec<Vec<usize>> {
+    match parameter {
+        Some(Parameter::List(lists)) => lists
+            .iter()
+            .map(|list| parameter_to_indices(Some(list)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl Instances<'_> {
+    /// the records of the instance `idx`
+    fn records(&self, idx: u64) -> &[&Record] {
+        self.0.get(&idx).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// the first record of the instance `idx` whose name satisfies `predicate`
+    fn find_record(&self, idx: u64, predicate: impl Fn(&str) -> bool) -> Option<&Record> {
+        self.records(idx)
+            .iter()
+            .copied()
+            .find(|record| predicate(&record.name))
+    }
+
+    fn point(&self, idx: u64) -> Option<[f64; 3]> {
+        let record = self.record(idx, "CARTESIAN_POINT")?;
+        let coordinates = parameter_to_reals(parameters(record).get(1));
+        Some([
+            *coordinates.first()?,
+            *coordinates.get(1)?,
+            coordinates.get(2).copied().unwrap_or(0.0),
+        ])
+    }
+
+    fn direction(&self, idx: u64) -> Option<[f64; 3]> {
+        let record = self.record(idx, "DIRECTION")?;
+        let ratios = parameter_to_reals(parameters(record).get(1));
+        let [x, y, z] = [0, 1, 2].map(|i| ratios.get(i).copied().unwrap_or(0.0));
+        let norm = f64::sqrt(x * x + y * y + z * z);
+        match norm > 0.0 {
+            true => Some([x / norm, y / norm, z / norm]),
+            false => None,
+        }
+    }
+
+    /// the positions of `coordinates_list`
+    fn coordinates(&self, idx: u64) -> Vec<[f64; 3]> {
+        let Some(record) = self.record(idx, "COORDINATES_LIST") else {
+            return Vec::new();
+        };
+        match parameters(record).get(2) {
+            Some(Parameter::List(points)) => points
+                .iter()
+                .map(|point| {
+                    let coordinates = parameter_to_reals(Some(point));
+                    [0, 1, 2].map(|i| coordinates.get(i).copied().unwrap_or(0.0))
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Adds the polylines and the triangles of the geometry `idx` to `annotation`.
+    fn add_geometry(&self, idx: u64, annotation: &mut StepAnnotation, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let Some(record) = self.records(idx).first() else {
+            return;
+        };
+        let params = parameters(record);
+        match record.name.as_str() {
+            "POLYLINE" => {
+                let points = parameter_to_refs(params.get(1));
+                let polyline: Vec<[f64; 3]> = points
+                    .into_iter()
+                    .filter_map(|idx| self.point(idx))
+                    .collect();
+                if polyline.len() > 1 {
+                    annotation.polylines.push(polyline);
+                }
+            }
+            "GEOMETRIC_CURVE_SET" | "GEOMETRIC_SET" | "TESSELLATED_GEOMETRIC_SET" => {
+                parameter_to_refs(params.get(1))
+                    .into_iter()
+                    .for_each(|idx| self.add_geometry(idx, annotation, depth + 1));
+            }
+            "TESSELLATED_CURVE_SET" => {
+                let coordinates = parameter_to_refs(params.get(1));
+                let positions = self.coordinates(*coordinates.first().unwrap_or(&0));
+                parameter_to_index_lists(params.get(2))
+                    .into_iter()
+                    .for_each(|strip| {
+                        let polyline: Vec<[f64; 3]> = strip
+                            .into_iter()
+                            .filter_map(|i| i.checked_sub(1).and_then(|i| positions.get(i)))
+                            .copied()
+                            .collect();
+                        if polyline.len() > 1 {
+                            annotation.polylines.push(polyline);
+                        }
+                    });
+            }
+            "TRIANGULATED_SURFACE_SET" | "COMPLEX_TRIANGULATED_SURFACE_SET" => {
+                let coordinates = parameter_to_refs(params.get(1));
+                let positions = self.coordinates(*coordinates.first().unwrap_or(&0));
+                // the indices go through `pnindex` if it is given.
+                let pnindex = parameter_to_indices(params.get(4));
+                let position = |i: usize| match pnindex.is_empty() {
+                    true => positions.get(i.checked_sub(1)?).copied(),
+                    false => positions
+                        .get(pnindex.get(i.checked_sub(1)?)?.checked_sub(1)?)
+                        .copied(),
+                };
+                let lists = |i: usize| -> Vec<Vec<i64>> {
+                    parameter_to_index_lists(params.get(i))
+                        .into_iter()
+                        .map(|list| list.into_iter().map(|i| i as i64).collect())
+                        .collect()
+                };
+                // the strips and the fans are split as the ones of the tessellated faces.
+                let triangles: Vec<[i64; 3]> = match record.name == "TRIANGULATED_SURFACE_SET" {
+                    true => lists(5)
+                        .into_iter()
+                        .filter_map(|tri| <[i64; 3]>::try_from(tri).ok())
+                        .collect(),
+                    false => strips_and_fans_to_triangles(&lists(5), &lists(6)),
+                };
+                triangles
+                    .into_iter()
+                    .filter_map(|tri| {
+                        let [a, b, c] = tri.map(|i| position(i as usize));
+                        Some([a?, b?, c?])
+                    })
+                    .for_each(|tri| annotation.triangles.push(tri));
+            }
+            _ => {}
+        }
+    }
+
+    /// the value and the name of the unit of `measure_with_unit`, or of the typed measure
+    fn measure(&self, parameter: Option<&Parameter>) -> Option<(f64, Option<String>)> {
+        let idx = match parameter? {
+            Parameter::Ref(Name::Entity(idx)) => *idx,
+            parameter => return Some((parameter_to_real(Some(parameter))?, None)),
+        };
+        // the records of the subtypes are empty in the complex instances.
+        let params = self.records(idx).iter().find_map(|record| {
+            let params = parameters(record);
+            (record.name.ends_with("MEASURE_WITH_UNIT") && !params.is_empty()).then_some(params)
+        })?;
+        let value = parameter_to_real(params.first())?;
+        let unit = parameter_to_refs(params.get(1))
+            .first()
+            .and_then(|idx| self.unit(*idx, 0))
+            .map(|(_, _, name)| name);
+        Some((value, unit))
+    }
+
+    /// the value of `measure_representation_item`, simple or complex
+    fn measure_item(&self, idx: u64) -> Option<(String, f64, Option<String>)> {
+        if let Some(record) = self.record(idx, "MEASURE_REPRESENTATION_ITEM") {
+            let params = parameters(record);
+            if params.len() == 3 {
+                let value = parameter_to_real(params.get(1))?;
+                let unit = parameter_to_refs(params.get(2))
+                    .first()
+                    .and_then(|idx| self.unit(*idx, 0))
+                    .map(|(_, _, name)| name);
+                return Some((parameter_to_string(params.first()), value, unit));
+            }
+        }
+        let name = self
+            .record(idx, "REPRESENTATION_ITEM")
+            .map(|record| parameter_to_string(parameters(record).first()))
+            .unwrap_or_default();
+        let (value, unit) = self.measure(Some(&Parameter::Ref(Name::Entity(idx))))?;
+        Some((name, value, unit))
+    }
+
+    fn annotation_plane(&self, idx: u64) -> Option<StepAnnotationPlane> {
+        let record = self.record(idx, "ANNOTATION_PLANE")?;
+        let params = parameters(record);
+        let plane = self.record(*parameter_to_refs(params.get(2)).first()?, "PLANE")?;
+        let placement_idx = *parameter_to_refs(parameters(plane).get(1)).first()?;
+        let placement = parameters(self.record(placement_idx, "AXIS2_PLACEMENT_3D")?);
+        let origin = self.point(*parameter_to_refs(placement.get(1)).first()?)?;
+        let normal = parameter_to_refs(placement.get(2))
+            .first()
+            .and_then(|idx| self.direction(*idx))
+            .unwrap_or([0.0, 0.0, 1.0]);
+        Some(StepAnnotationPlane {
+            entity_id: idx,
+            name: parameter_to_string(params.first()),
+            origin,
+            normal,
+        })
+    }
+}
+
+impl StepPmi {
+    /// Reads the PMI of the data section.
+    pub fn from_data_section(data_section: &DataSection) -> Self {
+        let instances = Instances::new(data_section);
+        let mut ids: Vec<u64> = instances.0.keys().copied().collect();
+        ids.sort();
+
+        // the callouts and the planes containing the annotations
+        let mut callouts = HashMap::<u64, (u64, String)>::new();
+        let mut planes = HashMap::<u64, StepAnnotationPlane>::new();
+        ids.iter().for_each(|idx| {
+            let callout = instances.find_record(*idx, |name| name.ends_with("_CALLOUT"));
+            if let Some(record) = callout {
+                let params = parameters(record);
+                let name = parameter_to_string(params.first());
+                parameter_to_refs(params.get(1))
+                    .into_iter()
+                    .for_each(|content| {
+                        callouts.insert(content, (*idx, name.clone()));
+                    });
+            }
+            if let Some(plane) = instances.annotation_plane(*idx) {
+                let record = instances.record(*idx, "ANNOTATION_PLANE").unwrap();
+                parameter_to_refs(parameters(record).get(3))
+                    .into_iter()
+                    .for_each(|element| {
+                        planes.insert(element, plane.clone());
+                    });
+            }
+        });
+
+        let mut pmi = StepPmi::default();
+        ids.iter().for_each(|idx| {
+            let records = instances.records(*idx);
+            let is_annotation = records
+                .iter()
+                .any(|record| ANNOTATION_OCCURRENCES.contains(&record.name.as_str()));
+            if is_annotation {
+                // the parameters are in `styled_item` if the occurrence is a complex instance.
+                let record = instances
+                    .record(*idx, "STYLED_ITEM")
+                    .or_else(|| records.iter().copied().find(|r| parameters(r).len() == 3));
+                if let Some(record) = record {
+                    let params = parameters(record);
+                    let callout = callouts.get(idx);
+                    let mut annotation = StepAnnotation {
+                        entity_id: *idx,
+                        name: parameter_to_string(params.first()),
+                        callout: callout.map(|(callout, _)| *callout),
+                        callout_name: callout.map(|(_, name)| name.clone()),
+                        plane: planes.get(idx).or_else(|| planes.get(&callout?.0)).cloned(),
+                        polylines: Vec::new(),
+                        triangles: Vec::new(),
+                    };
+                    parameter_to_refs(params.get(2))
+                        .into_iter()
+                        .for_each(|item| instances.add_geometry(item, &mut annotation, 0));
+                    pmi.annotations.push(annotation);
+                }
+            }
+
+            // `geometric_tolerance` is the partial record of the complex instances.
+            let tolerance = instances.record(*idx, "GEOMETRIC_TOLERANCE").or_else(|| {
+                instances.find_record(*idx, |name| {
+                    name.ends_with("_TOLERANCE") && name != "PLUS_MINUS_TOLERANCE"
+                })
+            });
+            if let Some(record) = tolerance {
+                // the modifiers, e.g. `modified_geometric_tolerance`, are not the kinds.
+                let kind = records
+                    .iter()
+                    .map(|record| record.name.as_str())
+                    .find(|name| {
+                        name.ends_with("_TOLERANCE")
+                            && !name.ends_with("GEOMETRIC_TOLERANCE")
+                            && *name != "PLUS_MINUS_TOLERANCE"
+                    })
+                    .unwrap_or(record.name.as_str());
+                let params = parameters(record);
+                let measure = instances.measure(params.get(2));
+                pmi.tolerances.push(StepGeometricTolerance {
+                    entity_id: *idx,
+                    kind: kind.to_lowercase(),
+                    name: parameter_to_string(params.first()),
+                    magnitude: measure.as_ref().map(|(value, _)| *value),
+                    unit: measure.and_then(|(_, unit)| unit),
+                });
+            }
+
+            let dimension = instances.find_record(*idx, |name| {
+                name.starts_with("DIMENSIONAL_SIZE")
+                    || name.starts_with("DIMENSIONAL_LOCATION")
+                    || name == "ANGULAR_SIZE"
+                    || name == "ANGULAR_LOCATION"
+            });
+            if let Some(record) = dimension {
+                let params = parameters(record);
+                // the size is `(applies_to, name)`, the location is `(name, description, ..)`.
+                let name = match record.name.contains("SIZE") {
+                    true => parameter_to_string(params.get(1)),
+                    false => parameter_to_string(params.first()),
+                };
+                pmi.dimensions.push(StepDimension {
+                    entity_id: *idx,
+                    kind: record.name.to_lowercase(),
+                    name,
+                    value: None,
+                    unit: None,
+                    lower: None,
+                    upper: None,
+                });
+            }
+
+            if let Some(record) = instances.record(*idx, "DATUM") {
+                let params = parameters(record);
+                pmi.datums.push(StepDatum {
+                    entity_id: *idx,
+                    name: parameter_to_string(params.first()),
+                    identification: parameter_to_string(params.get(4)),
+                });
+            }
+        });
+
+        // the values and the deviations refer to the dimensions.
+        let dimensions: HashMap<u64, usize> = pmi
+            .dimensions
+            .iter()
+            .enumerate()
+            .map(|(i, dimension)| (dimension.entity_id, i))
+            .collect();
+        ids.iter().for_each(|idx| {
+            if let Some(record) =
+                instances.record(*idx, "DIMENSIONAL_CHARACTERISTIC_REPRESENTATION")
+            {
+                let params = parameters(record);
+                let Some(&i) = parameter_to_refs(params.first())
+                    .first()
+                    .and_then(|idx| dimensions.get(idx))
+                else {
+                    return;
+                };
+                let Some(representation) = parameter_to_refs(params.get(1))
+                    .first()
+                    .and_then(|idx| instances.record(*idx, "SHAPE_DIMENSION_REPRESENTATION"))
+                else {
+                    return;
+                };
+                let items: Vec<_> = parameter_to_refs(parameters(representation).get(1))
+                    .into_iter()
+                    .filter_map(|idx| instances.measure_item(idx))
+                    .collect();
+                let nominal = items
+                    .iter()
+                    .find(|(name, _, _)| name == "nominal value")
+                    .or_else(|| items.first());
+                if let Some((_, value, unit)) = nominal {
+                    pmi.dimensions[i].value = Some(*value);
+                    pmi.dimensions[i].unit = unit.clone();
+                }
+            }
+            if let Some(record) = instances.record(*idx, "PLUS_MINUS_TOLERANCE") {
+                let params = parameters(record);
+                let Some(&i) = parameter_to_refs(params.get(1))
+                    .first()
+                    .and_then(|idx| dimensions.get(idx))
+                else {
+                    return;
+                };
+                let Some(range) = parameter_to_refs(params.first())
+                    .first()
+                    .and_then(|idx| instances.record(*idx, "TOLERANCE_VALUE"))
+                else {
+                    return;
+                };
+                let range = parameters(range);
+                pmi.dimensions[i].lower = instances.measure(range.first()).map(|(x, _)| x);
+                pmi.dimensions[i].upper = instances.measure(range.get(1)).map(|(x, _)| x);
+            }
+        });
+        pmi
+    }
+
+    /// Reads the PMI of the STEP file.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::pmi::*;
+    /// let step_str = "ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION((''), '2;1');
+    /// FILE_NAME('', '', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+    /// #2 = CARTESIAN_POINT('', (10.0, 0.0, 0.0));
+    /// #3 = POLYLINE('', (#1, #2));
+    /// #4 = GEOMETRIC_CURVE_SET('', (#3));
+    /// #5 = ANNOTATION_CURVE_OCCURRENCE('leader', (), #4);
+    /// #6 = DRAUGHTING_CALLOUT('Flatness.1', (#5));
+    /// #7 = FLATNESS_TOLERANCE('Flatness.1', '', #8, #20);
+    /// #8 = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(0.05), #9);
+    /// #9 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );
+    /// ENDSEC;
+    /// END-ISO-10303-21;";
+    /// let pmi = StepPmi::from_step(step_str).unwrap();
+    /// assert_eq!(pmi.annotations.len(), 1);
+    /// assert_eq!(pmi.annotations[0].callout_name.as_deref(), Some("Flatness.1"));
+    /// assert_eq!(pmi.annotations[0].polylines, vec![vec![[0.0; 3], [10.0, 0.0, 0.0]]]);
+    /// assert_eq!(pmi.tolerances[0].kind, "flatness_tolerance");
+    /// assert_eq!(pmi.tolerances[0].magnitude, Some(0.05));
+    /// assert_eq!(pmi.tolerances[0].unit.as_deref(), Some("millimetre"));
+    /// ```
+    pub fn from_step(step_str: &str) -> Option<Self> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        Some(StepPmi::from_data_section(exchange.data.first()?))
+    }
+
+    /// Returns `true` if the file has no PMI.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+            && self.tolerances.is_empty()
+            && self.dimensions.is_empty()
+            && self.datums.is_empty()
+    }
+}
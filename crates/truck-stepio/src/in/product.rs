@@ -0,0 +1,434 @@
+//! Reads which product, i.e. part or assembly, each shell belongs to.
+//!
+//! The shells are reached from the products through
+//! `product` ← `product_definition_formation` ← `product_definition` ← `product_definition_shape`
+//! ← `shape_definition_representation` → `shape_representation`,
+//! and one `shape_representation_relationship` from the representation of the product.
+//!
+//! The components of assemblies are `next_assembly_usage_occurrence`s,
+//! placed by `context_dependent_shape_representation`s with `item_defined_transformation`s.
+
+use super::*;
+
+/// the deepest assembly placed by [`product_instances`], against the cyclic product structures
+const MAX_ASSEMBLY_DEPTH: usize = 64;
+
+/// Product in STEP file with the shells owned by it
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepProduct {
+    /// the entity id of `product`
+    pub entity_id: u64,
+    /// the product id, e.g. the part number
+    pub id: String,
+    /// the product name
+    pub name: String,
+    /// the product description
+    pub description: String,
+    /// the `id` of `product_definition_formation`, which is usually the revision
+    pub revision: Option<String>,
+    /// the entity ids of the shells in [`Table::shell`]
+    pub shells: Vec<u64>,
+    /// the entity ids of the tessellated shells in [`Table::tessellated_shell`]
+    pub tessellated_shells: Vec<u64>,
+    /// `false` if the product is a component of an assembly
+    pub top_level: bool,
+}
+
+impl StepProduct {
+    /// The name with the revision, e.g. `bracket rev. B`.
+    ///
+    /// The id is used if the name is empty.
+    pub fn caption(&self) -> String {
+        let name = match self.name.is_empty() {
+            true => &self.id,
+            false => &self.name,
+        };
+        match &self.revision {
+            Some(revision) => format!("{name} rev. {revision}"),
+            None => name.clone(),
+        }
+    }
+}
+
+/// Occurrence of a product as a component of an assembly
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepProductOccurrence {
+    /// the entity id of `next_assembly_usage_occurrence`
+    pub entity_id: u64,
+    /// the occurrence id
+    pub id: String,
+    /// the occurrence name
+    pub name: String,
+    /// the entity id of the product of the assembly
+    pub parent: u64,
+    /// the entity id of the product of the component
+    pub child: u64,
+    /// the placement of the component in the coordinate of the assembly
+    pub transform: Matrix4,
+}
+
+/// Places the products in the coordinates of the top-level products.
+///
+/// Returns the entity ids of the products with their transforms, one for each placement reached
+/// from the top-level products through the occurrences, so a part used twice appears twice.
+/// The top-level products are placed by the identity.
+pub fn product_instances(
+    products: &[StepProduct],
+    occurrences: &[StepProductOccurrence],
+) -> Vec<(u64, Matrix4)> {
+    fn place(
+        components: &HashMap<u64, Vec<&StepProductOccurrence>>,
+        product: u64,
+        transform: Matrix4,
+        depth: usize,
+        instances: &mut Vec<(u64, Matrix4)>,
+    ) {
+        instances.push((product, transform));
+        if depth >= MAX_ASSEMBLY_DEPTH {
+            return;
+        }
+        components
+            .get(&product)
+            .into_iter()
+            .flatten()
+            .for_each(|occurrence| {
+                let transform = transform * occurrence.transform;
+                place(
+                    components,
+                    occurrence.child,
+                    transform,
+                    depth + 1,
+                    instances,
+                )
+            });
+    }
+    let mut components = HashMap::<u64, Vec<&StepProductOccurrence>>::new();
+    occurrences.iter().for_each(|occurrence| {
+        components
+            .entry(occurrence.parent)
+            .or_default()
+            .push(occurrence)
+    });
+    let mut instances = Vec::new();
+    products
+        .iter()
+        .filter(|product| product.top_level)
+        .for_each(|product| {
+            place(
+                &components,
+                product.entity_id,
+                Matrix4::identity(),
+                0,
+                &mut instances,
+            )
+        });
+    instances
+}
+
+/// the relations of the product structure indexed by the entity ids, built once per query
+struct ProductIndex<'a> {
+    table: &'a Table,
+    /// the shape representations of each product definition
+    definition_representations: HashMap<u64, Vec<u64>>,
+    /// the representations related from each representation by `shape_representation_relationship`
+    related_representations: HashMap<u64, Vec<u64>>,
+    /// the placements of the components of each occurrence
+    occurrence_placements:
+        HashMap<u64, Vec<&'a RepresentationRelationshipWithTransformationHolder>>,
+}
+
+impl<'a> ProductIndex<'a> {
+    fn new(table: &'a Table) -> Self {
+        let definition_of_shape = |pds_idx: Option<u64>| {
+            table
+                .product_definition_shape
+                .get(&pds_idx?)
+                .and_then(|pds| place_holder_idx(&pds.definition))
+        };
+        let mut definition_representations = HashMap::<u64, Vec<u64>>::new();
+        sorted(&table.shape_definition_representation)
+            .into_iter()
+            .for_each(|(_, sdr)| {
+                let pd_idx = definition_of_shape(place_holder_idx(&sdr.definition));
+                if let (Some(pd_idx), Some(rep_idx)) =
+                    (pd_idx, place_holder_idx(&sdr.used_representation))
+                {
+                    definition_representations
+                        .entry(pd_idx)
+                        .or_default()
+                        .push(rep_idx);
+                }
+            });
+        let own_representations: HashSet<u64> = definition_representations
+            .values()
+            .flatten()
+            .copied()
+            .collect();
+        let mut related_representations = HashMap::<u64, Vec<u64>>::new();
+        sorted(&table.shape_representation_relationship)
+            .into_iter()
+            .for_each(|(_, srr)| {
+                if let (Some(rep_1), Some(rep_2)) =
+                    (place_holder_idx(&srr.rep_1), place_holder_idx(&srr.rep_2))
+                {
+                    // `rep_1` should be the one of the product,
+                    // but some systems write the relationship inversely.
+                    let inverse = !own_representations.contains(&rep_1)
+                        && own_representations.contains(&rep_2);
+                    let (rep_1, rep_2) = match inverse {
+                        true => (rep_2, rep_1),
+                        false => (rep_1, rep_2),
+                    };
+                    related_representations
+                        .entry(rep_1)
+                        .or_default()
+                        .push(rep_2);
+                }
+            });
+        let mut occurrence_placements = HashMap::<u64, Vec<_>>::new();
+        sorted(&table.context_dependent_shape_representation)
+            .into_iter()
+            .for_each(|(_, cdsr)| {
+                let nauo_idx =
+                    definition_of_shape(place_holder_idx(&cdsr.represented_product_relation));
+                let rrwt = place_holder_idx(&cdsr.representation_relation).and_then(|idx| {
+                    table
+                        .representation_relationship_with_transformation
+                        .get(&idx)
+                });
+                if let (Some(nauo_idx), Some(rrwt)) = (nauo_idx, rrwt) {
+                    occurrence_placements
+                        .entry(nauo_idx)
+                        .or_default()
+                        .push(rrwt);
+                }
+            });
+        Self {
+            table,
+            definition_representations,
+            related_representations,
+            occurrence_placements,
+        }
+    }
+
+    /// the entity id of the product of the product definition `pd_idx`
+    fn product_of_definition(&self, pd_idx: u64) -> Option<u64> {
+        let pd = self.table.product_definition.get(&pd_idx)?;
+        let pdf = self
+            .table
+            .product_definition_formation
+            .get(&place_holder_idx(&pd.formation)?)?;
+        place_holder_idx(&pdf.of_product)
+    }
+
+    /// the shape representations of the product definition `pd_idx`
+    fn definition_representations(&self, pd_idx: u64) -> &[u64] {
+        self.definition_representations
+            .get(&pd_idx)
+            .map_or(&[][..], Vec::as_slice)
+    }
+
+    /// the placement of the component given by the occurrence `nauo_idx`
+    fn occurrence_transform(&self, nauo_idx: u64, child_pd_idx: u64) -> Option<Matrix4> {
+        let child_reps = self.definition_representations(child_pd_idx);
+        let rrwt = self.occurrence_placements.get(&nauo_idx)?.first()?;
+        let idt_idx = place_holder_idx(&rrwt.transformation_operator)?;
+        let idt =
+            EntityTable::<ItemDefinedTransformationHolder>::get_owned(self.table, idt_idx).ok()?;
+        let transform = Matrix4::from(&idt);
+        // `rep_1` should be the component, but some systems write the relationship inversely.
+        let inverse = place_holder_idx(&rrwt.rep_1).is_some_and(|idx| !child_reps.contains(&idx))
+            && place_holder_idx(&rrwt.rep_2).is_some_and(|idx| child_reps.contains(&idx));
+        match inverse {
+            true => SquareMatrix::invert(&transform),
+            false => Some(transform),
+        }
+    }
+
+    /// the shells and the tessellated shells in the representation
+    /// and in the ones related from it, not following the relationships further
+    fn representation_shells(&self, rep_idx: u64) -> (Vec<u64>, Vec<u64>) {
+        let table = self.table;
+        let (mut shells, mut tessellated_shells) = (Vec::new(), Vec::new());
+        let related = self
+            .related_representations
+            .get(&rep_idx)
+            .map_or(&[][..], Vec::as_slice);
+        std::iter::once(&rep_idx)
+            .chain(related)
+            .for_each(|rep_idx| {
+                if let Some(rep) = table.shape_representation.get(rep_idx) {
+                    rep.items
+                        .iter()
+                        .filter_map(place_holder_idx)
+                        .for_each(|idx| {
+                            if table.shell.contains_key(&idx) {
+                                shells.push(idx);
+                            } else if let Some(brep) = table.manifold_solid_brep.get(&idx) {
+                                shells.extend(place_holder_idx(&brep.outer));
+                            } else if let Some(model) = table.shell_based_surface_model.get(&idx) {
+                                shells.extend(
+                                    model.sbsm_boundary.iter().filter_map(place_holder_idx),
+                                );
+                            } else if table.tessellated_shell.contains_key(&idx) {
+                                tessellated_shells.push(idx);
+                            }
+                        });
+                }
+            });
+        (shells, tessellated_shells)
+    }
+}
+
+impl Table {
+    /// Reads the products with their shells, sorted by the entity ids.
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use truck_stepio::r#in::*;
+    /// let step_str = "DATA;
+    /// #1 = PRODUCT('P-001', 'bracket', '', (#2));
+    /// #2 = PRODUCT_CONTEXT('', #3, 'mechanical');
+    /// #3 = APPLICATION_CONTEXT('automotive design');
+    /// #4 = PRODUCT_DEFINITION_FORMATION('B', '', #1);
+    /// #5 = PRODUCT_DEFINITION('design', '', #4, #6);
+    /// #6 = PRODUCT_DEFINITION_CONTEXT('part definition', #3, 'design');
+    /// #7 = PRODUCT_DEFINITION_SHAPE('', '', #5);
+    /// #8 = SHAPE_DEFINITION_REPRESENTATION(#7, #9);
+    /// #9 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#10), #12);
+    /// #10 = MANIFOLD_SOLID_BREP('', #11);
+    /// #11 = CLOSED_SHELL('', ());
+    /// #12 = GEOMETRIC_REPRESENTATION_CONTEXT(3);
+    /// ENDSEC;";
+    /// let data_section = ruststep::ast::DataSection::from_str(step_str).unwrap();
+    /// let table = Table::from_data_section(&data_section);
+    /// let products = table.products();
+    /// assert_eq!(products.len(), 1);
+    /// assert_eq!(products[0].caption(), "bracket rev. B");
+    /// assert_eq!(products[0].shells, vec![11]);
+    /// ```
+    pub fn products(&self) -> Vec<StepProduct> {
+        let index = ProductIndex::new(self);
+        // the first formation of each product
+        let mut revisions = HashMap::<u64, &str>::new();
+        sorted(&self.product_definition_formation)
+            .into_iter()
+            .for_each(|(_, pdf)| {
+                if let Some(idx) = place_holder_idx(&pdf.of_product) {
+                    revisions.entry(idx).or_insert(&pdf.id);
+                }
+            });
+        let mut products: Vec<StepProduct> = sorted(&self.product)
+            .into_iter()
+            .map(|(idx, product)| StepProduct {
+                entity_id: idx,
+                id: product.id.clone(),
+                name: product.name.clone(),
+                description: product.description.clone().unwrap_or_default(),
+                revision: revisions
+                    .get(&idx)
+                    .filter(|revision| !revision.is_empty())
+                    .map(|revision| revision.to_string()),
+                top_level: true,
+                ..Default::default()
+            })
+            .collect();
+        let position: HashMap<u64, usize> = products
+            .iter()
+            .enumerate()
+            .map(|(i, product)| (product.entity_id, i))
+            .collect();
+        index
+            .definition_representations
+            .iter()
+            .for_each(|(pd_idx, reps)| {
+                let product = index
+                    .product_of_definition(*pd_idx)
+                    .and_then(|idx| position.get(&idx));
+                let Some(&i) = product else {
+                    return;
+                };
+                reps.iter().for_each(|rep_idx| {
+                    let (shells, tessellated_shells) = index.representation_shells(*rep_idx);
+                    products[i].shells.extend(shells);
+                    products[i].tessellated_shells.extend(tessellated_shells);
+                });
+            });
+        self.next_assembly_usage_occurrence
+            .values()
+            .filter_map(|nauo| place_holder_idx(&nauo.related_product_definition))
+            .filter_map(|pd_idx| index.product_of_definition(pd_idx))
+            .filter_map(|idx| position.get(&idx))
+            .for_each(|&i| products[i].top_level = false);
+        products.iter_mut().for_each(|product| {
+            product.shells.sort();
+            product.shells.dedup();
+            product.tessellated_shells.sort();
+            product.tessellated_shells.dedup();
+        });
+        products
+    }
+
+    /// Reads the components of the assemblies, sorted by the entity ids.
+    ///
+    /// The transform is the identity if the placement of the component is not found.
+    pub fn product_occurrences(&self) -> Vec<StepProductOccurrence> {
+        let index = ProductIndex::new(self);
+        sorted(&self.next_assembly_usage_occurrence)
+            .into_iter()
+            .filter_map(|(idx, nauo)| {
+                let parent_pd = place_holder_idx(&nauo.relating_product_definition)?;
+                let child_pd = place_holder_idx(&nauo.related_product_definition)?;
+                Some(StepProductOccurrence {
+                    entity_id: idx,
+                    id: nauo.id.clone(),
+                    name: nauo.name.clone(),
+                    parent: index.product_of_definition(parent_pd)?,
+                    child: index.product_of_definition(child_pd)?,
+                    transform: index
+                        .occurrence_transform(idx, child_pd)
+                        .unwrap_or_else(Matrix4::identity),
+                })
+            })
+            .collect()
+    }
+
+    /// The shells which need not be meshed since their products have the tessellated shells,
+    /// sorted by the entity ids.
+    ///
+    /// AP242 files may ship the tessellated shapes alongside the B-rep ones.
+    /// The shells and the tessellated shells owned by no product are regarded as one product.
+    pub fn shells_with_tessellation(&self) -> Vec<u64> {
+        let products = self.products();
+        let owned_shells: HashSet<u64> = products
+            .iter()
+            .flat_map(|product| product.shells.iter().copied())
+            .collect();
+        let owned_tessellated_shells: HashSet<u64> = products
+            .iter()
+            .flat_map(|product| product.tessellated_shells.iter().copied())
+            .collect();
+        let mut shells: Vec<u64> = products
+            .iter()
+            .filter(|product| !product.tessellated_shells.is_empty())
+            .flat_map(|product| product.shells.iter().copied())
+            .collect();
+        let unowned_tessellation = self
+            .tessellated_shell
+            .keys()
+            .any(|idx| !owned_tessellated_shells.contains(idx));
+        if unowned_tessellation {
+            shells.extend(self.shell.keys().filter(|idx| !owned_shells.contains(idx)));
+        }
+        shells.sort();
+        shells.dedup();
+        shells
+    }
+
+    /// The first top-level product, i.e. the root of the assembly or the single part.
+    pub fn top_level_product(&self) -> Option<StepProduct> {
+        self.products()
+            .into_iter()
+            .find(|product| product.top_level)
+    }
+}
@@ -0,0 +1,551 @@
+//! Reads the DATA section incrementally from [`std::io::Read`].
+//!
+//! [`Table::from_step`] needs the whole file as `&str` and the whole syntax tree at once.
+//! The readers in this module hold only one entity instance at a time.
+//! [`Table::from_reader`] builds the whole [`Table`], while [`CompactTable`] keeps the records
+//! sorted by the entity ids and builds the tables only of the entities needed at a time.
+
+use super::*;
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+/// Entity types read for a coarse preview of the tessellated shapes.
+pub const TESSELLATED_PREVIEW_TYPES: [&str; 5] = [
+    "COORDINATES_LIST",
+    "TRIANGULATED_FACE",
+    "COMPLEX_TRIANGULATED_FACE",
+    "TESSELLATED_SHELL",
+    "TESSELLATED_SOLID",
+];
+
+/// Entity types read for the product structure, see [`Table::products`].
+pub const PRODUCT_STRUCTURE_TYPES: [&str; 24] = [
+    "PRODUCT",
+    "PRODUCT_DEFINITION_FORMATION",
+    "PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE",
+    "PRODUCT_DEFINITION",
+    "PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS",
+    "PRODUCT_DEFINITION_SHAPE",
+    "NEXT_ASSEMBLY_USAGE_OCCURRENCE",
+    "SHAPE_DEFINITION_REPRESENTATION",
+    "SHAPE_REPRESENTATION",
+    "ADVANCED_BREP_SHAPE_REPRESENTATION",
+    "MANIFOLD_SURFACE_SHAPE_REPRESENTATION",
+    "FACETED_BREP_SHAPE_REPRESENTATION",
+    "GEOMETRICALLY_BOUNDED_SURFACE_SHAPE_REPRESENTATION",
+    "GEOMETRICALLY_BOUNDED_WIREFRAME_SHAPE_REPRESENTATION",
+    "EDGE_BASED_WIREFRAME_SHAPE_REPRESENTATION",
+    "TESSELLATED_SHAPE_REPRESENTATION",
+    "SHAPE_REPRESENTATION_RELATIONSHIP",
+    "MANIFOLD_SOLID_BREP",
+    "BREP_WITH_VOIDS",
+    "SHELL_BASED_SURFACE_MODEL",
+    "OPEN_SHELL",
+    "CLOSED_SHELL",
+    "TESSELLATED_SHELL",
+    "TESSELLATED_SOLID",
+];
+
+/// Options of [`Table::from_reader_with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// If `Some`, only the simple entities of these types are read.
+    /// Complex entities are read if one of their partial records is of these types.
+    pub entity_types: Option<HashSet<String>>,
+    /// If `Some(n)`, stops reading when `n` entities have been read.
+    pub max_entities: Option<usize>,
+    /// If `false`, unsupported entities are registered to [`Table::dummy`]
+    /// with only their type names instead of the whole records.
+    pub keep_unsupported_records: bool,
+}
+
+impl StreamOptions {
+    /// Options for a coarse preview: only [`TESSELLATED_PREVIEW_TYPES`] are read.
+    pub fn tessellated_preview() -> Self {
+        Self {
+            entity_types: Some(
+                TESSELLATED_PREVIEW_TYPES
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Options for the product names: only [`PRODUCT_STRUCTURE_TYPES`] are read.
+    pub fn product_structure() -> Self {
+        Self {
+            entity_types: Some(
+                PRODUCT_STRUCTURE_TYPES
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the entities of `entity_types` too, e.g. the products with the tessellated preview.
+    ///
+    /// Does nothing if all the types are already read.
+    pub fn with_entity_types(mut self, entity_types: &[&str]) -> Self {
+        if let Some(types) = &mut self.entity_types {
+            types.extend(entity_types.iter().map(|s| s.to_string()));
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LexState {
+    Normal,
+    String,
+    Comment,
+}
+
+/// Iterator over the statements, i.e. the texts separated by `;`, of a STEP file.
+///
+/// Comments are removed. Semicolons in strings are not separators.
+#[derive(Debug)]
+pub struct StatementReader<R> {
+    reader: BufReader<R>,
+    state: LexState,
+    previous: u8,
+    statement: Vec<u8>,
+}
+
+impl<R: Read> StatementReader<R> {
+    /// constructor
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            state: LexState::Normal,
+            previous: 0,
+            statement: Vec::new(),
+        }
+    }
+
+    fn take_statement(&mut self) -> String {
+        let statement = String::from_utf8_lossy(&self.statement).trim().to_string();
+        self.statement.clear();
+        statement
+    }
+}
+
+impl<R: Read> Iterator for StatementReader<R> {
+    type Item = io::Result<String>;
+    fn next(&mut self) -> Option<io::Result<String>> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            if buf.is_empty() {
+                let statement = self.take_statement();
+                return match statement.is_empty() {
+                    true => None,
+                    false => Some(Ok(statement)),
+                };
+            }
+            let mut consumed = 0;
+            let mut end_of_statement = false;
+            for &c in buf {
+                consumed += 1;
+                match (self.state, c) {
+                    (LexState::Normal, b'\'') => {
+                        self.state = LexState::String;
+                        self.statement.push(c);
+                    }
+                    (LexState::Normal, b'*') if self.previous == b'/' => {
+                        // `/` has already been pushed as the beginning of the comment.
+                        self.statement.pop();
+                        self.state = LexState::Comment;
+                        // `/*/` must not close the comment.
+                        self.previous = 0;
+                        continue;
+                    }
+                    (LexState::Normal, b';') => end_of_statement = true,
+                    (LexState::String, b'\'') => {
+                        // `''` in a string is read as leaving and entering the string again.
+                        self.state = LexState::Normal;
+                        self.statement.push(c);
+                    }
+                    (LexState::Comment, b'/') if self.previous == b'*' => {
+                        self.state = LexState::Normal;
+                        self.previous = 0;
+                        continue;
+                    }
+                    (LexState::Comment, _) => {}
+                    _ => self.statement.push(c),
+                }
+                self.previous = c;
+                if end_of_statement {
+                    break;
+                }
+            }
+            self.reader.consume(consumed);
+            if end_of_statement {
+                return Some(Ok(self.take_statement()));
+            }
+        }
+    }
+}
+
+/// Returns `true` if `statement` begins the DATA section.
+///
+/// `DATA` may have parameters since the second edition of ISO 10303-21.
+pub(super) fn is_data_keyword(statement: &str) -> bool {
+    statement
+        .strip_prefix("DATA")
+        .is_some_and(|rest| rest.is_empty() || rest.trim_start().starts_with('('))
+}
+
+/// The statements in the DATA sections.
+fn data_statements<R: Read>(reader: R) -> impl Iterator<Item = io::Result<String>> {
+    let mut in_data = false;
+    StatementReader::new(reader).filter_map(move |statement| {
+        let statement = match statement {
+            Ok(statement) => statement,
+            Err(e) => return Some(Err(e)),
+        };
+        match (in_data, statement.as_str()) {
+            (false, keyword) => {
+                in_data = is_data_keyword(keyword);
+                None
+            }
+            (true, "ENDSEC") => {
+                in_data = false;
+                None
+            }
+            (true, _) => Some(Ok(statement)),
+        }
+    })
+}
+
+fn invalid_statement(statement: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("not an entity instance: {statement}"),
+    )
+}
+
+/// Splits the entity instance statement `#id = record` into the id and the record.
+fn split_statement(statement: &str) -> io::Result<(u64, &str)> {
+    let (lhs, rhs) = statement
+        .split_once('=')
+        .ok_or_else(|| invalid_statement(statement))?;
+    let id = lhs
+        .trim()
+        .strip_prefix('#')
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| invalid_statement(statement))?;
+    Ok((id, rhs.trim()))
+}
+
+/// The type names of the record, i.e. of the partial records of a complex entity.
+fn type_names(record: &str) -> Vec<&str> {
+    let Some(partials) = record.strip_prefix('(') else {
+        return record
+            .split('(')
+            .next()
+            .map(str::trim)
+            .into_iter()
+            .collect();
+    };
+    let mut names = Vec::new();
+    let (mut depth, mut in_string, mut start) = (0, false, None);
+    for (i, c) in partials.char_indices() {
+        match c {
+            '\'' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => {
+                if let Some(start) = start.take() {
+                    names.push(partials[start..i].trim());
+                }
+                depth += 1;
+            }
+            ')' => depth -= 1,
+            _ if depth == 0 && start.is_none() && (c.is_ascii_alphabetic() || c == '_') => {
+                start = Some(i)
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn is_wanted(record: &str, options: &StreamOptions) -> bool {
+    match &options.entity_types {
+        Some(entity_types) => type_names(record)
+            .iter()
+            .any(|name| entity_types.contains(*name)),
+        None => true,
+    }
+}
+
+fn parse_entity_instance(statement: &str) -> io::Result<EntityInstance> {
+    EntityInstance::from_str(&format!("{statement};"))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e}: {statement}")))
+}
+
+impl Table {
+    /// Reads the STEP file from `reader` without loading the whole file.
+    ///
+    /// Same as [`Table::from_reader_with_options`] with the default options.
+    #[inline(always)]
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Table> {
+        Table::from_reader_with_options(reader, &StreamOptions::default())
+    }
+
+    /// Reads the STEP file from `reader` without loading the whole file.
+    ///
+    /// Syntax errors and I/O errors are returned as `io::Error`.
+    /// Entities which cannot be deserialized are skipped,
+    /// and reported by [`Table::from_reader_with_report`].
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::{stream::*, *};
+    /// let step_str = "ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION(('a comment; with a semicolon'), '2;1');
+    /// FILE_NAME('', '', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0)); /* comment; */
+    /// #2 = COORDINATES_LIST('', 3, ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)));
+    /// #3 = TRIANGULATED_FACE('', #2, 0, (), $, (), ((1, 2, 3)));
+    /// #4 = TESSELLATED_SHELL('', (#3), $);
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// ";
+    /// let table = Table::from_reader(step_str.as_bytes()).unwrap();
+    /// assert_eq!(table.cartesian_point.len(), 1);
+    ///
+    /// let options = StreamOptions::tessellated_preview();
+    /// let table = Table::from_reader_with_options(step_str.as_bytes(), &options).unwrap();
+    /// assert!(table.cartesian_point.is_empty());
+    /// assert_eq!(table.tessellated_shell.len(), 1);
+    /// ```
+    #[inline(always)]
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &StreamOptions,
+    ) -> io::Result<Table> {
+        Table::from_reader_with_report(reader, options).map(|(table, _)| table)
+    }
+
+    /// Reads the STEP file from `reader`, reporting the entities which cannot be deserialized
+    /// in [`StepReadReport::failed_entities`].
+    pub fn from_reader_with_report<R: Read>(
+        reader: R,
+        options: &StreamOptions,
+    ) -> io::Result<(Table, StepReadReport)> {
+        let mut table = Table::default();
+        let mut report = StepReadReport::default();
+        let mut count = 0;
+        for statement in data_statements(reader) {
+            let statement = statement?;
+            let (id, record) = split_statement(&statement)?;
+            if !is_wanted(record, options) {
+                continue;
+            }
+            let instance = parse_entity_instance(&statement)?;
+            if let Err(e) = table.push_instance(&instance) {
+                report.failed_entities.push((id, e.to_string()));
+            }
+            if !options.keep_unsupported_records {
+                compact_dummy(&mut table, &instance);
+            }
+            count += 1;
+            if options.max_entities.is_some_and(|max| count >= max) {
+                break;
+            }
+        }
+        Ok((table, report))
+    }
+}
+
+fn compact_dummy(table: &mut Table, instance: &EntityInstance) {
+    if let Some(dummy) = table.dummy.get_mut(&instance_id(instance)) {
+        dummy.record = instance_type_name(instance);
+    }
+}
+
+/// Removes the spaces outside the strings.
+fn compact_record(record: &str) -> Box<str> {
+    let mut in_string = false;
+    record
+        .chars()
+        .filter(|c| {
+            if *c == '\'' {
+                in_string = !in_string;
+            }
+            in_string || !c.is_whitespace()
+        })
+        .collect()
+}
+
+/// Entity instances of a DATA section stored compactly, as the texts sorted by the entity ids.
+///
+/// A [`Table`] holds every entity in its hash maps at once, which does not fit in memory
+/// for very large assemblies. This store keeps only the records, without the spaces,
+/// and builds the tables of the entities reachable from the given roots on demand,
+/// e.g. one shell at a time.
+/// # Example
+/// ```
+/// use truck_stepio::r#in::{stream::*, *};
+/// let step_str = include_str!(concat!(
+///     env!("CARGO_MANIFEST_DIR"),
+///     "/../resources/step/occt-cube.step",
+/// ));
+/// let store = CompactTable::from_reader(step_str.as_bytes()).unwrap();
+/// let shells = store.ids_of_types(&["CLOSED_SHELL", "OPEN_SHELL"]);
+/// assert_eq!(shells.len(), 1);
+///
+/// // only the entities reachable from the shell are deserialized.
+/// let (table, report) = store.table_of(shells.iter().copied());
+/// assert!(report.is_clean());
+/// let shell = table.to_compressed_shell(&table.shell[&shells[0]]).unwrap();
+/// assert_eq!(shell.faces.len(), 6);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactTable {
+    records: Vec<(u64, Box<str>)>,
+    keep_unsupported_records: bool,
+}
+
+impl CompactTable {
+    /// Reads the DATA section from `reader` with the default options.
+    #[inline(always)]
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Self> {
+        Self::from_reader_with_options(reader, &StreamOptions::default())
+    }
+
+    /// Reads the DATA section from `reader`, keeping only the records chosen by `options`.
+    ///
+    /// The records are not parsed here: syntax errors are reported when the tables are built.
+    pub fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: &StreamOptions,
+    ) -> io::Result<Self> {
+        let mut records = Vec::new();
+        for statement in data_statements(reader) {
+            let statement = statement?;
+            let (id, record) = split_statement(&statement)?;
+            if !is_wanted(record, options) {
+                continue;
+            }
+            records.push((id, compact_record(record)));
+            if options.max_entities.is_some_and(|max| records.len() >= max) {
+                break;
+            }
+        }
+        records.sort_by_key(|(id, _)| *id);
+        records.shrink_to_fit();
+        Ok(Self {
+            records,
+            keep_unsupported_records: options.keep_unsupported_records,
+        })
+    }
+
+    /// the number of the records
+    #[inline(always)]
+    pub fn len(&self) -> usize { self.records.len() }
+    /// Returns `true` if no record is stored.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool { self.records.is_empty() }
+
+    /// The record of the entity `#id` without the spaces, e.g. `CARTESIAN_POINT('',(0.0,0.0))`.
+    pub fn get(&self, id: u64) -> Option<&str> {
+        let idx = self.records.binary_search_by_key(&id, |(id, _)| *id).ok()?;
+        Some(&self.records[idx].1)
+    }
+
+    /// The ids of the entities of `types`, or whose partial records are of `types`, in order.
+    pub fn ids_of_types(&self, types: &[&str]) -> Vec<u64> {
+        self.records
+            .iter()
+            .filter(|(_, record)| type_names(record).iter().any(|name| types.contains(name)))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Builds the table of all the records.
+    pub fn to_table(&self) -> (Table, StepReadReport) {
+        let mut table = Table::default();
+        let mut report = StepReadReport::default();
+        self.records.iter().for_each(|(id, record)| {
+            self.push_record(*id, record, &mut table, &mut report);
+        });
+        (table, report)
+    }
+
+    /// Builds the table of the entities reachable from `roots`.
+    ///
+    /// The references to the entities not stored, e.g. filtered out by the options,
+    /// are reported in [`StepReadReport::dangling_references`].
+    pub fn table_of(&self, roots: impl IntoIterator<Item = u64>) -> (Table, StepReadReport) {
+        let mut table = Table::default();
+        let mut report = StepReadReport::default();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<u64> = roots.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Some(record) = self.get(id) else {
+                continue;
+            };
+            let Some(instance) = self.push_record(id, record, &mut table, &mut report) else {
+                continue;
+            };
+            let mut refs = Vec::new();
+            match &instance {
+                EntityInstance::Simple { record, .. } => {
+                    collect_references(&record.parameter, &mut refs)
+                }
+                EntityInstance::Complex {
+                    subsuper: SubSuperRecord(records),
+                    ..
+                } => records
+                    .iter()
+                    .for_each(|record| collect_references(&record.parameter, &mut refs)),
+            }
+            refs.into_iter().for_each(|to| match self.get(to) {
+                Some(_) => stack.push(to),
+                None => report.dangling_references.push((id, to)),
+            });
+        }
+        report.failed_entities.sort();
+        report.dangling_references.sort();
+        (table, report)
+    }
+
+    /// Parses the record and pushes it to `table`, returning the parsed instance.
+    fn push_record(
+        &self,
+        id: u64,
+        record: &str,
+        table: &mut Table,
+        report: &mut StepReadReport,
+    ) -> Option<EntityInstance> {
+        let instance = match parse_entity_instance(&format!("#{id}={record}")) {
+            Ok(instance) => instance,
+            Err(e) => {
+                report.failed_entities.push((id, e.to_string()));
+                return None;
+            }
+        };
+        if let Err(e) = table.push_instance(&instance) {
+            report.failed_entities.push((id, e.to_string()));
+        }
+        if !self.keep_unsupported_records {
+            compact_dummy(table, &instance);
+        }
+        Some(instance)
+    }
+}
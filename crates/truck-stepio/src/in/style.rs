@@ -0,0 +1,164 @@
+//! Reads the colours and the transparencies of the shapes.
+//!
+//! The colours are reached from the shapes through
+//! `styled_item` → `presentation_style_assignment` → `surface_style_usage` → `surface_side_style`
+//! → `surface_style_fill_area` → `fill_area_style` → `fill_area_style_colour` → `colour_rgb`.
+//! The transparencies are in `surface_style_rendering_with_properties` of `surface_side_style`.
+//!
+//! Only the colours given by `colour_rgb` are read.
+
+use super::*;
+
+/// Colour and transparency given to a shape, e.g. a solid, a shell or a face
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepStyledItem {
+    /// the entity id of `styled_item`
+    pub entity_id: u64,
+    /// the name of `styled_item`
+    pub name: String,
+    /// the entity id of the styled shape
+    pub item: u64,
+    /// the RGB colour, each component is in `[0, 1]`
+    pub colour: Option<[f64; 3]>,
+    /// the transparency in `[0, 1]`, `0.0` is opaque
+    pub transparency: Option<f64>,
+}
+
+impl Table {
+    fn colour(&self, idx: u64) -> Option<[f64; 3]> {
+        let colour = self.colour_rgb.get(&idx)?;
+        Some([colour.red, colour.green, colour.blue])
+    }
+
+    fn fill_area_colour(&self, idx: u64) -> Option<[f64; 3]> {
+        let fill_area = self.surface_style_fill_area.get(&idx)?;
+        let fill_area_style = self
+            .fill_area_style
+            .get(&place_holder_idx(&fill_area.fill_area)?)?;
+        fill_area_style
+            .fill_styles
+            .iter()
+            .filter_map(place_holder_idx)
+            .filter_map(|idx| self.fill_area_style_colour.get(&idx))
+            .find_map(|colour| self.colour(place_holder_idx(&colour.fill_colour)?))
+    }
+
+    /// the `surface_side_style`s of the styled item
+    fn surface_side_styles<'a>(
+        &'a self,
+        styled_item: &'a StyledItemHolder,
+    ) -> impl Iterator<Item = &'a SurfaceSideStyleHolder> {
+        styled_item
+            .styles
+            .iter()
+            .filter_map(place_holder_idx)
+            .filter_map(|idx| self.presentation_style_assignment.get(&idx))
+            .flat_map(|assignment| assignment.styles.iter().filter_map(place_holder_idx))
+            // some systems omit `surface_style_usage`.
+            .filter_map(|idx| match self.surface_style_usage.get(&idx) {
+                Some(usage) => place_holder_idx(&usage.style),
+                None => Some(idx),
+            })
+            .filter_map(|idx| self.surface_side_style.get(&idx))
+    }
+
+    /// Reads the colours and the transparencies of the shapes, sorted by the entity ids.
+    ///
+    /// The styled items without both the colour and the transparency are omitted.
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use truck_stepio::r#in::*;
+    /// let step_str = "DATA;
+    /// #1 = STYLED_ITEM('', (#2), #10);
+    /// #2 = PRESENTATION_STYLE_ASSIGNMENT((#3));
+    /// #3 = SURFACE_STYLE_USAGE(.BOTH., #4);
+    /// #4 = SURFACE_SIDE_STYLE('', (#5));
+    /// #5 = SURFACE_STYLE_FILL_AREA(#6);
+    /// #6 = FILL_AREA_STYLE('', (#7));
+    /// #7 = FILL_AREA_STYLE_COLOUR('', #8);
+    /// #8 = COLOUR_RGB('', 1.0, 0.5, 0.0);
+    /// ENDSEC;";
+    /// let data_section = ruststep::ast::DataSection::from_str(step_str).unwrap();
+    /// let table = Table::from_data_section(&data_section);
+    /// let styled_items = table.styled_items();
+    /// assert_eq!(styled_items.len(), 1);
+    /// assert_eq!(styled_items[0].item, 10);
+    /// assert_eq!(styled_items[0].colour, Some([1.0, 0.5, 0.0]));
+    /// assert_eq!(styled_items[0].transparency, None);
+    /// ```
+    pub fn styled_items(&self) -> Vec<StepStyledItem> {
+        sorted(&self.styled_item)
+            .into_iter()
+            .filter_map(|(idx, styled_item)| {
+                let (mut colour, mut transparency) = (None, None);
+                self.surface_side_styles(styled_item)
+                    .flat_map(|side_style| side_style.styles.iter().filter_map(place_holder_idx))
+                    .for_each(|idx| {
+                        if let Some(fill_colour) = self.fill_area_colour(idx) {
+                            colour = colour.or(Some(fill_colour));
+                        } else if let Some(rendering) =
+                            self.surface_style_rendering_with_properties.get(&idx)
+                        {
+                            let surface_colour = place_holder_idx(&rendering.surface_colour)
+                                .and_then(|idx| self.colour(idx));
+                            colour = colour.or(surface_colour);
+                            transparency = transparency.or_else(|| {
+                                rendering
+                                    .properties
+                                    .iter()
+                                    .filter_map(place_holder_idx)
+                                    .find_map(|idx| self.surface_style_transparent.get(&idx))
+                                    .map(|transparent| transparent.transparency)
+                            });
+                        }
+                    });
+                if colour.is_none() && transparency.is_none() {
+                    return None;
+                }
+                Some(StepStyledItem {
+                    entity_id: idx,
+                    name: styled_item.label.clone().unwrap_or_default(),
+                    item: place_holder_idx(&styled_item.item)?,
+                    colour,
+                    transparency,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the styles of the shells and the tessellated shells, by their entity ids.
+    ///
+    /// The style given to the shell itself takes precedence over the ones given to
+    /// the solid or the surface model made of the shell. The styles of the faces are not read.
+    pub fn shell_styles(&self) -> HashMap<u64, StepStyledItem> {
+        let styled_items = self.styled_items();
+        let mut styles = HashMap::new();
+        styled_items.iter().for_each(|style| {
+            let shells: Vec<u64> = if let Some(brep) = self.manifold_solid_brep.get(&style.item) {
+                place_holder_idx(&brep.outer).into_iter().collect()
+            } else if let Some(model) = self.shell_based_surface_model.get(&style.item) {
+                model
+                    .sbsm_boundary
+                    .iter()
+                    .filter_map(place_holder_idx)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            shells.into_iter().for_each(|idx| {
+                styles.entry(idx).or_insert_with(|| style.clone());
+            });
+        });
+        styled_items
+            .into_iter()
+            .filter(|style| {
+                self.shell.contains_key(&style.item)
+                    || self.tessellated_shell.contains_key(&style.item)
+            })
+            .for_each(|style| {
+                styles.insert(style.item, style);
+            });
+        styles
+    }
+}
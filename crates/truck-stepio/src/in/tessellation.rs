@@ -0,0 +1,254 @@
+//! Converts and triangulates the shells of [`Table`] at once.
+//!
+//! With the `parallel` feature, the shells are processed concurrently,
+//! and the faces of each shell are also converted and triangulated concurrently.
+//! The results are always sorted by the entity ids of the shells.
+//!
+//! [`Table::triangulate_shells_for_view`] derives the tolerance from the image size and the camera,
+//! so that the meshes are no finer than the pixels they are drawn on.
+
+use super::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::borrow::Borrow;
+use truck_meshalgo::tessellation::RobustMeshableShape;
+
+/// The shell read from STEP file, realized in `truck`
+pub type StepCompressedShell = CompressedShell<Point3, Curve3D, Surface>;
+/// The triangulated shell
+pub type MeshedShell = CompressedShell<Point3, PolylineCurve<Point3>, Option<PolygonMesh>>;
+
+impl Table {
+    /// Converts all `Shell`s in STEP file and triangulates them.
+    ///
+    /// `tolerance` gives the tolerance of the triangulation for each converted shell.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::{tessellation::*, *};
+    /// let step_string = include_str!(concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/../resources/step/occt-cube.step",
+    /// ));
+    /// let table = Table::from_step(step_string).unwrap();
+    /// let meshed = table.triangulate_shells(|_| 0.01);
+    /// assert_eq!(meshed.len(), 1);
+    /// let (_, shell) = &meshed[0];
+    /// assert_eq!(shell.as_ref().unwrap().faces.len(), 6);
+    /// ```
+    pub fn triangulate_shells<F>(
+        &self,
+        tolerance: F,
+    ) -> Vec<(u64, Result<MeshedShell, StepConvertingError>)>
+    where
+        F: Fn(&StepCompressedShell) -> f64 + Send + Sync,
+    {
+        let mut shells: Vec<_> = self.shell.iter().collect();
+        shells.sort_by_key(|(idx, _)| **idx);
+        #[cfg(not(feature = "parallel"))]
+        let shells = shells.into_iter();
+        #[cfg(feature = "parallel")]
+        let shells = shells.into_par_iter();
+        shells
+            .map(|(idx, shell)| {
+                let meshed = self.to_compressed_shell(shell).map(|cshell| {
+                    let tol = tolerance(&cshell);
+                    cshell.robust_triangulation(tol)
+                });
+                (*idx, meshed)
+            })
+            .collect()
+    }
+}
+
+/// Camera of the image for which the shells are triangulated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViewCamera {
+    /// The camera showing the whole model, as thumbnails do.
+    Fit,
+    /// The perspective camera at `position` with the vertical field of view `fov` in radians.
+    Perspective {
+        /// the position of the camera
+        position: Point3,
+        /// the vertical field of view in radians
+        fov: f64,
+    },
+    /// The parallel camera whose image height is `screen_size` in the model units.
+    Parallel {
+        /// the height of the image in the model units
+        screen_size: f64,
+    },
+}
+
+/// Tolerance of the triangulation derived from the image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTolerance {
+    /// the width and the height of the image in pixels
+    pub image_size: (u32, u32),
+    /// the camera
+    pub camera: ViewCamera,
+    /// the allowed chord deviation in pixels, `0.5` by default
+    pub pixels: f64,
+    /// the allowed angle between adjacent facets in radians, 15 degrees by default
+    ///
+    /// The angle is measured on the circle whose diameter is the one of the part.
+    pub angle: f64,
+}
+
+impl ViewTolerance {
+    /// constructor with the default deviations
+    pub fn new(image_size: (u32, u32), camera: ViewCamera) -> Self {
+        Self {
+            image_size,
+            camera,
+            pixels: 0.5,
+            angle: 15.0_f64.to_radians(),
+        }
+    }
+
+    /// The length of one pixel in the model units at the nearest point of `part`.
+    ///
+    /// `model` is the bounding box of the whole model, used by [`ViewCamera::Fit`].
+    pub fn pixel_size(&self, model: &BoundingBox<Point3>, part: &BoundingBox<Point3>) -> f64 {
+        let (width, height) = (self.image_size.0.max(1), self.image_size.1.max(1));
+        match self.camera {
+            ViewCamera::Fit => model.diameter() / u32::min(width, height) as f64,
+            ViewCamera::Perspective { position, fov } => {
+                let radius = part.diameter() / 2.0;
+                // the part never touches the camera in the images to be drawn.
+                let distance = f64::max(position.distance(part.center()) - radius, radius * 1.0e-3);
+                2.0 * distance * f64::tan(fov / 2.0) / height as f64
+            }
+            ViewCamera::Parallel { screen_size } => screen_size / height as f64,
+        }
+    }
+
+    /// The tolerance of the triangulation of `part`.
+    ///
+    /// `model` is the bounding box of the whole model, used by [`ViewCamera::Fit`].
+    pub fn chord_tolerance(&self, model: &BoundingBox<Point3>, part: &BoundingBox<Point3>) -> f64 {
+        if part.is_empty() {
+            return 0.01;
+        }
+        let chord = self.pixel_size(model, part) * self.pixels;
+        let sag = part.diameter() / 2.0 * (1.0 - f64::cos(self.angle / 2.0));
+        f64::max(f64::min(chord, sag), 10.0 * TOLERANCE)
+    }
+}
+
+fn push_curve_points(bdd: &mut BoundingBox<Point3>, curve: &Curve3D) {
+    match curve {
+        Curve3D::Line(line) => {
+            bdd.push(line.0);
+            bdd.push(line.1);
+        }
+        Curve3D::Polyline(polyline) => polyline.0.iter().for_each(|p| bdd.push(*p)),
+        Curve3D::BSplineCurve(bsp) => bsp.control_points().iter().for_each(|p| bdd.push(*p)),
+        Curve3D::NurbsCurve(nurbs) => nurbs
+            .non_rationalized()
+            .control_points()
+            .iter()
+            .for_each(|v| bdd.push(v.to_point())),
+        // conics and pcurves have no control points in 3D, a few samples are enough.
+        Curve3D::Conic(_) | Curve3D::PCurve(_) => {
+            let (t0, t1) = curve.range_tuple();
+            (0..=8).for_each(|i| bdd.push(curve.subs(t0 + (t1 - t0) * i as f64 / 8.0)));
+        }
+    }
+}
+
+fn push_surface_points(bdd: &mut BoundingBox<Point3>, surface: &Surface) {
+    match surface {
+        Surface::BSplineSurface(bsp) => bsp
+            .control_points()
+            .iter()
+            .flatten()
+            .for_each(|p| bdd.push(*p)),
+        Surface::NurbsSurface(nurbs) => nurbs
+            .non_rationalized()
+            .control_points()
+            .iter()
+            .flatten()
+            .for_each(|v| bdd.push(v.to_point())),
+        // other faces are bounded by their edges except for the bulges.
+        _ => {}
+    }
+}
+
+/// The bounding box of `shell` computed from the vertices and the control points.
+///
+/// This is much cheaper than a tessellation and contains the shell
+/// except for the bulges of the faces on elementary and swept surfaces, e.g. the domes of spheres.
+pub fn control_point_bounding_box(shell: &StepCompressedShell) -> BoundingBox<Point3> {
+    let mut bdd: BoundingBox<Point3> = shell.vertices.iter().collect();
+    shell
+        .edges
+        .iter()
+        .for_each(|edge| push_curve_points(&mut bdd, &edge.curve));
+    shell
+        .faces
+        .iter()
+        .for_each(|face| push_surface_points(&mut bdd, &face.surface));
+    bdd
+}
+
+impl Table {
+    /// Converts all `Shell`s in STEP file and triangulates them for the image given by `view`.
+    ///
+    /// The bounding boxes are computed by [`control_point_bounding_box`],
+    /// so the shells are triangulated only once.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::{tessellation::*, *};
+    /// let step_string = include_str!(concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/../resources/step/occt-cube.step",
+    /// ));
+    /// let table = Table::from_step(step_string).unwrap();
+    /// let view = ViewTolerance::new((256, 256), ViewCamera::Fit);
+    /// let meshed = table.triangulate_shells_for_view(&view);
+    /// assert_eq!(meshed[0].1.as_ref().unwrap().faces.len(), 6);
+    /// ```
+    pub fn triangulate_shells_for_view(
+        &self,
+        view: &ViewTolerance,
+    ) -> Vec<(u64, Result<MeshedShell, StepConvertingError>)> {
+        triangulate_converted_shells(self.to_compressed_shells(), view)
+    }
+}
+
+/// Triangulates the converted shells for the image given by `view`,
+/// as [`Table::triangulate_shells_for_view`] does, e.g. the shells reloaded from
+/// [`StepCache`](super::cache::StepCache) or the ones of IGES files keyed by the pointers.
+pub fn triangulate_converted_shells<K, S, E>(
+    shells: Vec<(K, Result<S, E>)>,
+    view: &ViewTolerance,
+) -> Vec<(K, Result<MeshedShell, E>)>
+where
+    K: Send,
+    S: Borrow<StepCompressedShell> + Send,
+    E: Send,
+{
+    let bdds: Vec<BoundingBox<Point3>> = shells
+        .iter()
+        .map(|(_, shell)| match shell {
+            Ok(shell) => control_point_bounding_box(Borrow::borrow(shell)),
+            Err(_) => BoundingBox::new(),
+        })
+        .collect();
+    let model = bdds.iter().fold(BoundingBox::new(), |mut model, bdd| {
+        model += bdd;
+        model
+    });
+    #[cfg(not(feature = "parallel"))]
+    let shells = shells.into_iter().zip(bdds);
+    #[cfg(feature = "parallel")]
+    let shells = shells.into_par_iter().zip(bdds);
+    shells
+        .map(|((idx, shell), bdd)| {
+            let tol = view.chord_tolerance(&model, &bdd);
+            let triangulate =
+                |shell: S| Borrow::<StepCompressedShell>::borrow(&shell).robust_triangulation(tol);
+            (idx, shell.map(triangulate))
+        })
+        .collect()
+}
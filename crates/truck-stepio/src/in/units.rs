@@ -0,0 +1,243 @@
+//! Reads the units and the uncertainty of the geometry.
+//!
+//! The units are given by `global_unit_assigned_context` and the uncertainty by
+//! `global_uncertainty_assigned_context`, both of which are the partial records of
+//! the complex instance of the representation context.
+//! Since the units are also complex instances, they are read from [`DataSection`] directly.
+
+use super::*;
+
+/// Units and uncertainty of the geometric representation context
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepUnitContext {
+    /// the length unit in metres, e.g. `0.001` for millimetre
+    pub length: f64,
+    /// the name of the length unit, e.g. `millimetre` or `inch`
+    pub length_name: String,
+    /// the plane angle unit in radians
+    pub angle: f64,
+    /// the name of the plane angle unit, e.g. `radian` or `degree`
+    pub angle_name: String,
+    /// the uncertainty in the length unit
+    pub uncertainty: Option<f64>,
+}
+
+impl Default for StepUnitContext {
+    fn default() -> Self {
+        Self {
+            length: 0.001,
+            length_name: "millimetre".to_string(),
+            angle: 1.0,
+            angle_name: "radian".to_string(),
+            uncertainty: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum UnitKind {
+    Length,
+    PlaneAngle,
+    Other,
+}
+
+pub(super) fn parameters(record: &Record) -> &[Parameter] {
+    match &record.parameter {
+        Parameter::List(params) => params,
+        _ => &[],
+    }
+}
+
+pub(super) fn parameter_to_real(parameter: Option<&Parameter>) -> Option<f64> {
+    match parameter? {
+        Parameter::Real(x) => Some(*x),
+        Parameter::Integer(x) => Some(*x as f64),
+        Parameter::Typed { parameter, .. } => parameter_to_real(Some(parameter)),
+        _ => None,
+    }
+}
+
+pub(super) fn parameter_to_refs(parameter: Option<&Parameter>) -> Vec<u64> {
+    match parameter {
+        Some(Parameter::Ref(Name::Entity(idx))) => vec![*idx],
+        Some(Parameter::List(list)) => list
+            .iter()
+            .flat_map(|parameter| parameter_to_refs(Some(parameter)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn si_prefix(prefix: &str) -> Option<f64> {
+    let factor = match prefix {
+        "EXA" => 1.0e18,
+        "PETA" => 1.0e15,
+        "TERA" => 1.0e12,
+        "GIGA" => 1.0e9,
+        "MEGA" => 1.0e6,
+        "KILO" => 1.0e3,
+        "HECTO" => 1.0e2,
+        "DECA" => 1.0e1,
+        "DECI" => 1.0e-1,
+        "CENTI" => 1.0e-2,
+        "MILLI" => 1.0e-3,
+        "MICRO" => 1.0e-6,
+        "NANO" => 1.0e-9,
+        "PICO" => 1.0e-12,
+        "FEMTO" => 1.0e-15,
+        "ATTO" => 1.0e-18,
+        _ => return None,
+    };
+    Some(factor)
+}
+
+/// the records of the simple and the complex instances
+pub(super) struct Instances<'a>(pub(super) HashMap<u64, Vec<&'a Record>>);
+
+impl<'a> Instances<'a> {
+    pub(super) fn new(data_section: &'a DataSection) -> Self {
+        let map = data_section
+            .entities
+            .iter()
+            .map(|instance| match instance {
+                EntityInstance::Simple { id, record } => (*id, vec![record]),
+                EntityInstance::Complex {
+                    id,
+                    subsuper: SubSuperRecord(records),
+                } => (*id, records.iter().collect()),
+            })
+            .collect();
+        Self(map)
+    }
+
+    pub(super) fn record(&self, idx: u64, name: &str) -> Option<&'a Record> {
+        self.0
+            .get(&idx)?
+            .iter()
+            .copied()
+            .find(|record| record.name == name)
+    }
+
+    /// the kind, the factor to the SI unit and the name of the unit `idx`
+    pub(super) fn unit(&self, idx: u64, depth: usize) -> Option<(UnitKind, f64, String)> {
+        let records = self.0.get(&idx)?;
+        let kind = records
+            .iter()
+            .find_map(|record| match record.name.as_str() {
+                "LENGTH_UNIT" => Some(UnitKind::Length),
+                "PLANE_ANGLE_UNIT" => Some(UnitKind::PlaneAngle),
+                _ => None,
+            })
+            .unwrap_or(UnitKind::Other);
+        if let Some(si_unit) = self.record(idx, "SI_UNIT") {
+            let params = parameters(si_unit);
+            let enumeration = |parameter: Option<&Parameter>| match parameter {
+                Some(Parameter::Enumeration(value)) => Some(value.trim_matches('.').to_uppercase()),
+                _ => None,
+            };
+            let name = enumeration(params.get(1))?;
+            let prefix = enumeration(params.first());
+            let factor = match &prefix {
+                Some(prefix) => si_prefix(prefix)?,
+                None => 1.0,
+            };
+            let name = prefix.unwrap_or_default() + &name;
+            return Some((kind, factor, name.to_lowercase()));
+        }
+        // conversion based units are defined by the other units, e.g. inch by millimetre.
+        let conversion = self.record(idx, "CONVERSION_BASED_UNIT")?;
+        if depth > 8 {
+            return None;
+        }
+        let params = parameters(conversion);
+        let name = match params.first() {
+            Some(Parameter::String(name)) => name.to_lowercase(),
+            _ => String::new(),
+        };
+        let measure_idx = *parameter_to_refs(params.get(1)).first()?;
+        let measure = self.0.get(&measure_idx)?.first()?;
+        let measure_params = parameters(measure);
+        let value = parameter_to_real(measure_params.first())?;
+        let base_idx = *parameter_to_refs(measure_params.get(1)).first()?;
+        let (_, base_factor, _) = self.unit(base_idx, depth + 1)?;
+        Some((kind, value * base_factor, name))
+    }
+}
+
+impl StepUnitContext {
+    /// Reads the units of the first representation context with `global_unit_assigned_context`.
+    ///
+    /// The lacked units are millimetre and radian.
+    /// Returns `None` if no context has the units.
+    pub fn from_data_section(data_section: &DataSection) -> Option<Self> {
+        let instances = Instances::new(data_section);
+        let mut ids: Vec<u64> = instances.0.keys().copied().collect();
+        ids.sort();
+        let (context_idx, unit_context) = ids
+            .into_iter()
+            .find_map(|idx| Some((idx, instances.record(idx, "GLOBAL_UNIT_ASSIGNED_CONTEXT")?)))?;
+        let mut res = StepUnitContext::default();
+        parameter_to_refs(parameters(unit_context).first())
+            .into_iter()
+            .filter_map(|idx| instances.unit(idx, 0))
+            .for_each(|(kind, factor, name)| match kind {
+                UnitKind::Length => {
+                    res.length = factor;
+                    res.length_name = name;
+                }
+                UnitKind::PlaneAngle => {
+                    res.angle = factor;
+                    res.angle_name = name;
+                }
+                UnitKind::Other => {}
+            });
+        res.uncertainty = instances
+            .record(context_idx, "GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT")
+            .and_then(|record| {
+                parameter_to_refs(parameters(record).first())
+                    .into_iter()
+                    .find_map(|idx| {
+                        let measure = instances.record(idx, "UNCERTAINTY_MEASURE_WITH_UNIT")?;
+                        let params = parameters(measure);
+                        let value = parameter_to_real(params.first())?;
+                        let unit_idx = *parameter_to_refs(params.get(1)).first()?;
+                        let (_, factor, _) = instances.unit(unit_idx, 0)?;
+                        Some(value * factor / res.length)
+                    })
+            });
+        Some(res)
+    }
+
+    /// Reads the units of the STEP file.
+    /// # Example
+    /// ```
+    /// use truck_stepio::r#in::units::*;
+    /// let step_str = "ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION((''), '2;1');
+    /// FILE_NAME('', '', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = (
+    ///     GEOMETRIC_REPRESENTATION_CONTEXT(3)
+    ///     GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#5))
+    ///     GLOBAL_UNIT_ASSIGNED_CONTEXT((#2, #3, #4))
+    ///     REPRESENTATION_CONTEXT('', '')
+    /// );
+    /// #2 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT($,.METRE.) );
+    /// #3 = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );
+    /// #4 = ( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() );
+    /// #5 = UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE(1.0E-7), #2, '', '');
+    /// ENDSEC;
+    /// END-ISO-10303-21;";
+    /// let units = StepUnitContext::from_step(step_str).unwrap();
+    /// assert_eq!(units.length, 1.0);
+    /// assert_eq!(units.length_name, "metre");
+    /// assert_eq!(units.uncertainty, Some(1.0e-7));
+    /// ```
+    pub fn from_step(step_str: &str) -> Option<Self> {
+        let exchange = ruststep::parser::parse(step_str).ok()?;
+        StepUnitContext::from_data_section(exchange.data.first()?)
+    }
+}
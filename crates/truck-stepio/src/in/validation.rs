@@ -0,0 +1,382 @@
+//! Validates the topology and the geometry of the converted shells.
+//!
+//! Holes in the thumbnails come either from the file or from the tessellation.
+//! [`ShellValidation`] tells which by checking the shell before the tessellation:
+//! - the edge-use counts, which give the closedness and the manifoldness,
+//! - the connections of the boundary loops and the directions of the edges in the adjacent faces,
+//! - the distances from the ends of the edge curves to the vertices,
+//! - the distances from the edge curves to the surfaces of the faces.
+
+use super::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// the number of the divisions of the edges on checking the distances to the surfaces
+const CURVE_DIVISION: usize = 8;
+/// the number of the trials of searching the parameters on the surfaces
+const TRIALS: usize = 100;
+
+/// A problem of an edge
+#[derive(Clone, Debug, PartialEq)]
+pub enum EdgeIssue {
+    /// The edge is used by no face, e.g. its face has failed to be converted.
+    Unused,
+    /// The edge is used by more than two faces.
+    NonManifold,
+    /// The faces use the edge in the same direction, so their orientations are inconsistent.
+    InconsistentOrientation,
+    /// The vertex of the edge is not in the shell.
+    MissingVertex {
+        /// the index of the vertex
+        vertex: usize,
+    },
+    /// The end of the curve is farther than the tolerance from the vertex.
+    VertexOffCurve {
+        /// the index of the vertex
+        vertex: usize,
+        /// the distance between the end and the vertex
+        distance: f64,
+    },
+}
+
+/// A problem of a face
+#[derive(Clone, Debug, PartialEq)]
+pub enum FaceIssue {
+    /// The boundary refers to an edge which is not in the shell.
+    MissingEdge {
+        /// the index of the boundary in the face
+        boundary: usize,
+        /// the index of the edge
+        edge: usize,
+    },
+    /// The edge at `position` of the boundary does not end at the start of the next edge.
+    OpenLoop {
+        /// the index of the boundary in the face
+        boundary: usize,
+        /// the position of the edge in the boundary
+        position: usize,
+    },
+    /// The curve of the edge is farther than the tolerance from the surface.
+    CurveOffSurface {
+        /// the index of the edge
+        edge: usize,
+        /// the maximum distance between the sampled points of the curve and the surface
+        distance: f64,
+    },
+    /// The parameter of a point of the edge is not found on the surface.
+    ParameterNotFound {
+        /// the index of the edge
+        edge: usize,
+    },
+}
+
+/// Validation of an edge
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EdgeValidation {
+    /// the number of the uses by the face boundaries
+    pub uses: usize,
+    /// the maximum distance between the ends of the curve and the vertices
+    pub vertex_distance: f64,
+    /// the problems
+    pub issues: Vec<EdgeIssue>,
+}
+
+/// Validation of a face
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FaceValidation {
+    /// the maximum distance between the edge curves and the surface
+    pub surface_distance: f64,
+    /// the problems
+    pub issues: Vec<FaceIssue>,
+}
+
+/// Validation report of a shell, in the orders of the edges and the faces of the shell
+/// # Example
+/// ```
+/// use truck_stepio::r#in::{validation::*, *};
+/// let step_string = include_str!(concat!(
+///     env!("CARGO_MANIFEST_DIR"),
+///     "/../resources/step/occt-cube.step",
+/// ));
+/// let table = Table::from_step(step_string).unwrap();
+/// let (_, shell) = &table.to_compressed_shells()[0];
+/// let validation = ShellValidation::new(shell.as_ref().unwrap(), 1.0e-6);
+/// assert!(validation.is_valid());
+/// assert!(validation.is_closed());
+/// assert_eq!(validation.edges.len(), 12);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShellValidation {
+    /// the tolerance of the distances
+    pub tolerance: f64,
+    /// the validations of the edges
+    pub edges: Vec<EdgeValidation>,
+    /// the validations of the faces
+    pub faces: Vec<FaceValidation>,
+}
+
+/// the maximum distance from the points on `curve` to `surface`, `None` if a parameter is not found
+fn curve_surface_distance<C, S>(curve: &C, surface: &S) -> Option<f64>
+where
+    C: ParametricCurve3D + BoundedCurve,
+    S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>, {
+    let (t0, t1) = curve.range_tuple();
+    let mut hint = None;
+    (0..=CURVE_DIVISION).try_fold(0.0, |distance: f64, i| {
+        let point = curve.subs(t0 + (t1 - t0) * i as f64 / CURVE_DIVISION as f64);
+        let (u, v) = hint
+            .and_then(|(u, v)| {
+                surface.search_nearest_parameter(point, SPHint2D::Parameter(u, v), TRIALS)
+            })
+            .or_else(|| surface.search_nearest_parameter(point, SPHint2D::None, TRIALS))?;
+        hint = Some((u, v));
+        Some(distance.max(surface.subs(u, v).distance(point)))
+    })
+}
+
+/// the validation of `face`, pushing the directions of the uses of the edges to `uses`
+fn validate_face<C, S>(
+    shell: &CompressedShell<Point3, C, S>,
+    face: &CompressedFace<S>,
+    tolerance: f64,
+    uses: &mut [Vec<bool>],
+) -> FaceValidation
+where
+    C: ParametricCurve3D + BoundedCurve,
+    S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>,
+{
+    let mut validation = FaceValidation::default();
+    let mut checked = HashSet::new();
+    for (boundary_idx, boundary) in face.boundaries.iter().enumerate() {
+        let mut ends = Vec::with_capacity(boundary.len());
+        for edge_idx in boundary {
+            let Some(edge) = shell.edges.get(edge_idx.index) else {
+                validation.issues.push(FaceIssue::MissingEdge {
+                    boundary: boundary_idx,
+                    edge: edge_idx.index,
+                });
+                continue;
+            };
+            uses[edge_idx.index].push(edge_idx.orientation == face.orientation);
+            let (v0, v1) = edge.vertices;
+            ends.push(match edge_idx.orientation {
+                true => (v0, v1),
+                false => (v1, v0),
+            });
+            // the seam edges are used twice by a face.
+            if !checked.insert(edge_idx.index) {
+                continue;
+            }
+            match curve_surface_distance(&edge.curve, &face.surface) {
+                Some(distance) => {
+                    validation.surface_distance = validation.surface_distance.max(distance);
+                    if distance > tolerance {
+                        validation.issues.push(FaceIssue::CurveOffSurface {
+                            edge: edge_idx.index,
+                            distance,
+                        });
+                    }
+                }
+                None => validation.issues.push(FaceIssue::ParameterNotFound {
+                    edge: edge_idx.index,
+                }),
+            }
+        }
+        if ends.len() != boundary.len() {
+            continue;
+        }
+        for (i, end) in ends.iter().enumerate() {
+            if end.1 != ends[(i + 1) % ends.len()].0 {
+                validation.issues.push(FaceIssue::OpenLoop {
+                    boundary: boundary_idx,
+                    position: i,
+                });
+            }
+        }
+    }
+    validation
+}
+
+impl ShellValidation {
+    /// Validates `shell` with the tolerance of the distances.
+    pub fn new<C, S>(shell: &CompressedShell<Point3, C, S>, tolerance: f64) -> Self
+    where
+        C: ParametricCurve3D + BoundedCurve,
+        S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>, {
+        let mut edges: Vec<EdgeValidation> = shell
+            .edges
+            .iter()
+            .map(|edge| {
+                let mut validation = EdgeValidation::default();
+                let (v0, v1) = edge.vertices;
+                [(v0, edge.curve.front()), (v1, edge.curve.back())]
+                    .into_iter()
+                    .for_each(|(vertex, point)| match shell.vertices.get(vertex) {
+                        Some(p) => {
+                            let distance = p.distance(point);
+                            validation.vertex_distance = validation.vertex_distance.max(distance);
+                            if distance > tolerance {
+                                let issue = EdgeIssue::VertexOffCurve { vertex, distance };
+                                validation.issues.push(issue);
+                            }
+                        }
+                        None => validation.issues.push(EdgeIssue::MissingVertex { vertex }),
+                    });
+                validation
+            })
+            .collect();
+        // the directions of the uses of the edges relative to the shell
+        let mut uses = vec![Vec::new(); shell.edges.len()];
+        let faces = shell
+            .faces
+            .iter()
+            .map(|face| validate_face(shell, face, tolerance, &mut uses))
+            .collect();
+        edges.iter_mut().zip(uses).for_each(|(validation, uses)| {
+            validation.uses = uses.len();
+            match uses.len() {
+                0 => validation.issues.push(EdgeIssue::Unused),
+                1 => {}
+                2 if uses[0] == uses[1] => {
+                    validation.issues.push(EdgeIssue::InconsistentOrientation)
+                }
+                2 => {}
+                _ => validation.issues.push(EdgeIssue::NonManifold),
+            }
+        });
+        Self {
+            tolerance,
+            edges,
+            faces,
+        }
+    }
+
+    /// Returns `true` if every edge is used by two faces and every boundary is a closed loop.
+    pub fn is_closed(&self) -> bool {
+        self.edges.iter().all(|edge| edge.uses == 2)
+            && self.faces.iter().all(|face| {
+                face.issues
+                    .iter()
+                    .all(|issue| !matches!(issue, FaceIssue::OpenLoop { .. }))
+            })
+    }
+
+    /// Returns `true` if no edge is used by more than two faces.
+    pub fn is_manifold(&self) -> bool { self.edges.iter().all(|edge| edge.uses <= 2) }
+
+    /// Returns `true` if the adjacent faces use the edges in the opposite directions.
+    pub fn is_oriented(&self) -> bool {
+        self.edges
+            .iter()
+            .flat_map(|edge| &edge.issues)
+            .all(|issue| *issue != EdgeIssue::InconsistentOrientation)
+    }
+
+    /// Returns `true` if no problem is found.
+    ///
+    /// Open shells may be valid: the edges used by only one face are not problems by themselves.
+    pub fn is_valid(&self) -> bool {
+        self.edges.iter().all(|edge| edge.issues.is_empty())
+            && self.faces.iter().all(|face| face.issues.is_empty())
+    }
+
+    /// Validates the boundary shells of `solid`.
+    pub fn of_solid<C, S>(solid: &CompressedSolid<Point3, C, S>, tolerance: f64) -> Vec<Self>
+    where
+        C: ParametricCurve3D + BoundedCurve,
+        S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>, {
+        solid
+            .boundaries
+            .iter()
+            .map(|shell| Self::new(shell, tolerance))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for EdgeIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeIssue::Unused => f.write_str("used by no face"),
+            EdgeIssue::NonManifold => f.write_str("used by more than two faces"),
+            EdgeIssue::InconsistentOrientation => {
+                f.write_str("used in the same direction by the adjacent faces")
+            }
+            EdgeIssue::MissingVertex { vertex } => write!(f, "vertex {vertex} is missing"),
+            EdgeIssue::VertexOffCurve { vertex, distance } => {
+                write!(f, "vertex {vertex} is {distance:e} off the curve")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FaceIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaceIssue::MissingEdge { boundary, edge } => {
+                write!(f, "boundary {boundary} refers to missing edge {edge}")
+            }
+            FaceIssue::OpenLoop { boundary, position } => {
+                write!(
+                    f,
+                    "boundary {boundary} is open after the edge at {position}"
+                )
+            }
+            FaceIssue::CurveOffSurface { edge, distance } => {
+                write!(f, "edge {edge} is {distance:e} off the surface")
+            }
+            FaceIssue::ParameterNotFound { edge } => {
+                write!(f, "edge {edge} is not projected onto the surface")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ShellValidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "closed: {}, manifold: {}, oriented: {}, tolerance: {:e}",
+            self.is_closed(),
+            self.is_manifold(),
+            self.is_oriented(),
+            self.tolerance,
+        )?;
+        for (i, edge) in self.edges.iter().enumerate() {
+            if edge.uses == 1 {
+                writeln!(f, "edge {i}: used by only one face")?;
+            }
+            for issue in &edge.issues {
+                writeln!(f, "edge {i}: {issue}")?;
+            }
+        }
+        for (i, face) in self.faces.iter().enumerate() {
+            for issue in &face.issues {
+                writeln!(f, "face {i}: {issue}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Table {
+    /// Converts all `Shell`s in STEP file and validates them with the tolerance of the distances.
+    ///
+    /// The tolerance is usually the uncertainty of the file, read by [`units::StepUnitContext`].
+    /// The results are sorted by the entity ids of the shells.
+    pub fn validate_shells(
+        &self,
+        tolerance: f64,
+    ) -> Vec<(u64, Result<ShellValidation, StepConvertingError>)> {
+        let shells = self.to_compressed_shells();
+        #[cfg(not(feature = "parallel"))]
+        let shells = shells.into_iter();
+        #[cfg(feature = "parallel")]
+        let shells = shells.into_par_iter();
+        shells
+            .map(|(idx, shell)| {
+                let validation = shell.map(|shell| ShellValidation::new(&shell, tolerance));
+                (idx, validation)
+            })
+            .collect()
+    }
+}
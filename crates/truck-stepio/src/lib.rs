@@ -3,8 +3,15 @@
 //! # Current Status
 //!
 //! It is possible to output data modeled by truck-modeling.
-//! Shapes created by set operations cannot be output yet.
-//! Input will come further down the road.
+//! The intersection curves of shapes created by set operations are output as
+//! B-spline approximations with the pcurves on both surfaces.
+//!
+//! STEP files are read by [`in`](crate::r#in) into the shells of truck, the tessellated
+//! shapes, the products, the styles and the PMI, and the shells are validated.
+//! Large files can be streamed. With the feature `tessellation`, the shells are triangulated
+//! and two revisions are compared by `in::diff`, and the converted shells are cached by
+//! `in::cache` with the feature `cache`.
+//! IGES files are also read by [`iges`].
 
 #![cfg_attr(not(debug_assertions), deny(warnings))]
 #![deny(clippy::all, rust_2018_idioms)]
@@ -19,6 +26,8 @@
     unused_qualifications
 )]
 
+#[cfg(feature = "in")]
+pub mod iges;
 /// STEP input module
 /// # Example
 /// ```
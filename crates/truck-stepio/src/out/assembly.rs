@@ -0,0 +1,270 @@
+use super::{
+    units::{ContextDisplay, ConversionUnitsDisplay},
+    Result, *,
+};
+use truck_geometry::prelude::*;
+
+#[derive(Clone, Debug)]
+enum AssemblyModel<'a, P, C, S> {
+    Shell(&'a CompressedShell<P, C, S>),
+    Solid(&'a CompressedSolid<P, C, S>),
+}
+
+#[derive(Clone, Debug)]
+struct AssemblyProduct<'a, P, C, S> {
+    id: String,
+    name: String,
+    models: Vec<AssemblyModel<'a, P, C, S>>,
+}
+
+#[derive(Clone, Debug)]
+struct AssemblyInstance {
+    parent: usize,
+    child: usize,
+    name: String,
+    transform: Matrix4,
+}
+
+/// Display struct for outputting assemblies, i.e. products placed in the other products.
+///
+/// Each product is written with its own `product_definition` and `shape_representation`,
+/// and each instance is written as `next_assembly_usage_occurrence`
+/// with `item_defined_transformation`.
+/// # Example
+/// ```
+/// use truck_modeling::*;
+/// use truck_stepio::out::*;
+/// let v = builder::vertex(Point3::origin());
+/// let e = builder::tsweep(&v, Vector3::unit_x());
+/// let f = builder::tsweep(&e, Vector3::unit_y());
+/// let cube = builder::tsweep(&f, Vector3::unit_z()).compress();
+///
+/// let mut assembly = StepAssembly::default();
+/// let root = assembly.push_product("ASM-1", "two cubes");
+/// let part = assembly.push_product("P-1", "cube");
+/// assembly.push_solid(part, &cube);
+/// assembly.push_instance(root, part, "left", Matrix4::identity());
+/// assembly.push_instance(root, part, "right", Matrix4::from_translation(Vector3::unit_x() * 2.0));
+/// let step_string = CompleteStepDisplay::new(assembly, Default::default()).to_string();
+/// ruststep::parser::parse(&step_string).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct StepAssembly<'a, P, C, S> {
+    products: Vec<AssemblyProduct<'a, P, C, S>>,
+    instances: Vec<AssemblyInstance>,
+    units: StepUnits,
+}
+
+impl<'a, P, C, S> Default for StepAssembly<'a, P, C, S> {
+    fn default() -> Self {
+        Self {
+            products: Vec::new(),
+            instances: Vec::new(),
+            units: Default::default(),
+        }
+    }
+}
+
+impl<'a, P, C, S> StepAssembly<'a, P, C, S> {
+    /// push a product, i.e. a part or a sub-assembly, and return its index
+    pub fn push_product(&mut self, id: &str, name: &str) -> usize {
+        self.products.push(AssemblyProduct {
+            id: escape_string(id),
+            name: escape_string(name),
+            models: Vec::new(),
+        });
+        self.products.len() - 1
+    }
+    /// push a shell to the product `product`
+    pub fn push_shell(&mut self, product: usize, shell: &'a CompressedShell<P, C, S>) {
+        self.products[product]
+            .models
+            .push(AssemblyModel::Shell(shell))
+    }
+    /// push a solid to the product `product`
+    pub fn push_solid(&mut self, product: usize, solid: &'a CompressedSolid<P, C, S>) {
+        self.products[product]
+            .models
+            .push(AssemblyModel::Solid(solid))
+    }
+    /// place the product `child` in the product `parent` by the rigid transformation `transform`
+    ///
+    /// # Panics
+    /// Panics if `parent` or `child` is not an index of the products, or `parent == child`.
+    /// The cycles of the instances are not checked.
+    pub fn push_instance(&mut self, parent: usize, child: usize, name: &str, transform: Matrix4) {
+        assert!(parent < self.products.len() && child < self.products.len());
+        assert_ne!(parent, child, "a product cannot be a component of itself");
+        self.instances.push(AssemblyInstance {
+            parent,
+            child,
+            name: escape_string(name),
+            transform,
+        })
+    }
+    /// Sets the units and the uncertainty, millimetre and radian by default.
+    #[inline]
+    pub fn set_units(&mut self, units: StepUnits) { self.units = units }
+}
+
+/// `axis2_placement_3d` of the rigid transformation, 4 lines
+fn fmt_placement(transform: &Matrix4, idx: usize, f: &mut Formatter<'_>) -> Result {
+    let location = StepDisplay::new(transform[3].to_point(), idx + 1);
+    let axis = StepDisplay::new(
+        VectorAsDirection(transform[2].truncate().normalize()),
+        idx + 2,
+    );
+    let ref_direction = StepDisplay::new(
+        VectorAsDirection(transform[0].truncate().normalize()),
+        idx + 3,
+    );
+    f.write_fmt(format_args!(
+        "#{idx} = AXIS2_PLACEMENT_3D('', #{location_idx}, #{axis_idx}, #{ref_direction_idx});
+{location}{axis}{ref_direction}",
+        location_idx = idx + 1,
+        axis_idx = idx + 2,
+        ref_direction_idx = idx + 3,
+    ))
+}
+
+const PRODUCT_LENGTH: usize = 10;
+const INSTANCE_LENGTH: usize = 9;
+
+impl<'a, P, C, S> Display for StepAssembly<'a, P, C, S>
+where
+    P: DisplayByStep + Copy,
+    C: DisplayByStep + StepLength + StepCurve,
+    S: DisplayByStep + StepLength + StepSurface,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.pad(
+"#1 = APPLICATION_PROTOCOL_DEFINITION('international standard', 'automotive_design', 2000, #2);
+#2 = APPLICATION_CONTEXT('core data for automotive mechanical design processes');
+#3 = PRODUCT_CONTEXT('', #2, 'mechanical');
+#4 = PRODUCT_DEFINITION_CONTEXT('part definition', #2, 'design');\n"
+        )?;
+        let mut cursor = 10;
+        let layouts = self
+            .products
+            .iter()
+            .map(|product| {
+                let idx = cursor;
+                cursor += PRODUCT_LENGTH;
+                if !product.models.is_empty() {
+                    cursor += 2;
+                }
+                let models = product
+                    .models
+                    .iter()
+                    .map(|model| {
+                        let model = match model {
+                            AssemblyModel::Shell(shell) => PreStepModel::from_shell(shell, cursor),
+                            AssemblyModel::Solid(solid) => PreStepModel::from_solid(solid, cursor),
+                        };
+                        let res = (cursor, model);
+                        cursor += res.1.step_length();
+                        res
+                    })
+                    .collect::<Vec<_>>();
+                (idx, models)
+            })
+            .collect::<Vec<_>>();
+        let instance_indices = self
+            .instances
+            .iter()
+            .map(|_| {
+                let idx = cursor;
+                cursor += INSTANCE_LENGTH;
+                idx
+            })
+            .collect::<Vec<_>>();
+        let context = ContextDisplay {
+            units: &self.units,
+            idx: 5,
+            conversion_idx: cursor,
+        };
+        Display::fmt(&context, f)?;
+
+        layouts
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, (idx, models))| {
+                let product = &self.products[i];
+                let placements = self
+                    .instances
+                    .iter()
+                    .zip(&instance_indices)
+                    .filter(|(instance, _)| instance.parent == i)
+                    .map(|(_, instance_idx)| instance_idx + 5);
+                f.write_fmt(format_args!(
+                    "#{idx} = PRODUCT('{id}', '{name}', '', (#3));
+#{pdf} = PRODUCT_DEFINITION_FORMATION('', '', #{idx});
+#{pd} = PRODUCT_DEFINITION('design', '', #{pdf}, #4);
+#{pds} = PRODUCT_DEFINITION_SHAPE('', '', #{pd});
+#{sdr} = SHAPE_DEFINITION_REPRESENTATION(#{pds}, #{sr});
+#{sr} = SHAPE_REPRESENTATION('', {items}, #5);\n",
+                    id = product.id,
+                    name = product.name,
+                    pdf = idx + 1,
+                    pd = idx + 2,
+                    pds = idx + 3,
+                    sdr = idx + 4,
+                    sr = idx + 5,
+                    items = IndexSliceDisplay(std::iter::once(idx + 6).chain(placements)),
+                ))?;
+                fmt_placement(&Matrix4::identity(), idx + 6, f)?;
+                if models.is_empty() {
+                    return Ok(());
+                }
+                f.write_fmt(format_args!(
+                    "#{absr} = ADVANCED_BREP_SHAPE_REPRESENTATION('', {models_slice}, #5);
+#{srr} = SHAPE_REPRESENTATION_RELATIONSHIP('', '', #{sr}, #{absr});\n",
+                    absr = idx + PRODUCT_LENGTH,
+                    srr = idx + PRODUCT_LENGTH + 1,
+                    sr = idx + 5,
+                    models_slice =
+                        IndexSliceDisplay(models.iter().map(|(model_idx, _)| *model_idx)),
+                ))?;
+                models
+                    .iter()
+                    .try_for_each(|(_, model)| Display::fmt(model, f))
+            })?;
+
+        self.instances
+            .iter()
+            .zip(instance_indices)
+            .enumerate()
+            .try_for_each(|(i, (instance, idx))| {
+                let (parent_idx, child_idx) = (layouts[instance.parent].0, layouts[instance.child].0);
+                f.write_fmt(format_args!(
+                    "#{idx} = NEXT_ASSEMBLY_USAGE_OCCURRENCE('{id}', '{name}', '', #{parent_pd}, #{child_pd}, $);
+#{pds} = PRODUCT_DEFINITION_SHAPE('', '', #{idx});
+#{cdsr} = CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(#{rrwt}, #{pds});
+#{rrwt} = (
+    REPRESENTATION_RELATIONSHIP('', '', #{child_sr}, #{parent_sr})
+    REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION(#{idt})
+    SHAPE_REPRESENTATION_RELATIONSHIP()
+);
+#{idt} = ITEM_DEFINED_TRANSFORMATION('', '', #{child_origin}, #{placement});\n",
+                    id = i + 1,
+                    name = instance.name,
+                    parent_pd = parent_idx + 2,
+                    child_pd = child_idx + 2,
+                    pds = idx + 1,
+                    cdsr = idx + 2,
+                    rrwt = idx + 3,
+                    child_sr = child_idx + 5,
+                    parent_sr = parent_idx + 5,
+                    idt = idx + 4,
+                    child_origin = child_idx + 6,
+                    placement = idx + 5,
+                ))?;
+                fmt_placement(&instance.transform, idx + 5, f)
+            })?;
+        let conversions = ConversionUnitsDisplay {
+            units: &self.units,
+            idx: context.conversion_idx,
+        };
+        Display::fmt(&conversions, f)
+    }
+}
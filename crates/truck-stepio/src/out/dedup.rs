@@ -0,0 +1,158 @@
+//! Shares the identical geometry of the models when the ids are assigned.
+//!
+//! The points of the vertices, the curves of the edges and the surfaces of the faces are keyed
+//! on their own entities written at the id `#0`, with the real numbers snapped to the grid of
+//! the tolerance, so the numbers closer than the tolerance may still fall into the neighbouring
+//! cells. The geometry found in the registry is referred to by the id assigned at first and is
+//! not written again. The topology, the products and the styles are never shared.
+
+use super::{DisplayByStep, FnDisplay, Formatter, Result, StepCurveSubstitute};
+use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::Write;
+
+/// the geometry of a shell which may be shared with the identical one
+#[derive(Clone, Copy)]
+pub(super) enum Geometry<'b, P, C, S> {
+    /// the point of a vertex
+    Point(&'b P),
+    /// the curve of an edge
+    Curve(&'b C),
+    /// the curve output in place of the one of an edge
+    Substitute(&'b dyn StepCurveSubstitute),
+    /// the surface of a face
+    Surface(&'b S),
+}
+
+impl<'b, P, C, S> DisplayByStep for Geometry<'b, P, C, S>
+where
+    P: DisplayByStep,
+    C: DisplayByStep,
+    S: DisplayByStep,
+{
+    fn fmt(&self, idx: usize, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Point(x) => DisplayByStep::fmt(*x, idx, f),
+            Self::Curve(x) => DisplayByStep::fmt(*x, idx, f),
+            Self::Substitute(x) => DisplayByStep::fmt(*x, idx, f),
+            Self::Surface(x) => DisplayByStep::fmt(*x, idx, f),
+        }
+    }
+}
+
+/// registry of the geometry of the models, keyed on the values
+#[derive(Clone, Debug)]
+pub(super) struct SharedGeometry<P, C, S> {
+    tolerance: f64,
+    /// the ids of the geometry registered at first
+    ids: HashMap<String, usize>,
+    /// writes the geometry at `#0`, which needs the bounds of the display
+    display: fn(&Geometry<'_, P, C, S>, &mut Formatter<'_>) -> Result,
+}
+
+impl<P, C, S> SharedGeometry<P, C, S>
+where
+    P: DisplayByStep,
+    C: DisplayByStep,
+    S: DisplayByStep,
+{
+    pub(super) fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            ids: HashMap::new(),
+            display: |geometry, f| DisplayByStep::fmt(geometry, 0, f),
+        }
+    }
+}
+
+impl<P, C, S> SharedGeometry<P, C, S> {
+    /// Returns the id of the identical geometry registered before,
+    /// or registers `geometry` at `idx` and returns `None`.
+    ///
+    /// The geometry failing to be written is never shared, and fails again when it is written.
+    pub(super) fn share(&mut self, geometry: Geometry<'_, P, C, S>, idx: usize) -> Option<usize> {
+        let mut text = String::new();
+        let display = FnDisplay(|f: &mut Formatter<'_>| (self.display)(&geometry, f));
+        write!(&mut text, "{display}").ok()?;
+        match self.ids.entry(snap_reals(&text, self.tolerance)) {
+            Entry::Occupied(entry) => Some(*entry.get()),
+            Entry::Vacant(entry) => {
+                entry.insert(idx);
+                None
+            }
+        }
+    }
+}
+
+/// Replaces the real numbers of `text` outside the strings by their cells of the grid.
+fn snap_reals(text: &str, tolerance: f64) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    let mut quoted = false;
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        let signed = matches!(c, '-' | '+') && next.is_some_and(|c| c.is_ascii_digit());
+        if c == '\'' {
+            quoted = !quoted;
+        }
+        if quoted || !(c.is_ascii_digit() || signed) {
+            res.push(c);
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        let mut exponent = false;
+        while let Some((j, c)) = chars.next_if(|(_, c)| {
+            c.is_ascii_digit()
+                || matches!(c, '.' | 'E' | 'e')
+                || (exponent && matches!(c, '-' | '+'))
+        }) {
+            exponent = matches!(c, 'E' | 'e');
+            end = j + c.len_utf8();
+        }
+        let number = &text[i..end];
+        match number.contains(['.', 'E', 'e']) {
+            true => match number.parse::<f64>() {
+                Ok(x) => write!(&mut res, "~{}", (x / tolerance).round() as i64).unwrap(),
+                Err(_) => res.push_str(number),
+            },
+            false => res.push_str(number),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_reals_within_tolerance() {
+        let key = |text: &str| snap_reals(text, 1.0e-6);
+        assert_eq!(
+            key("#0 = CARTESIAN_POINT('', (0.0, 1.0000000000E-9, -1.0));\n"),
+            "#0 = CARTESIAN_POINT('', (~0, ~0, ~-1000000));\n",
+        );
+        assert_eq!(
+            key("#0 = CARTESIAN_POINT('', (0.0, 0.0, -1.0));\n"),
+            key("#0 = CARTESIAN_POINT('', (0.0, -1.0000000000E-9, -0.9999999999));\n"),
+        );
+        assert_ne!(
+            key("#0 = DIRECTION('', (1.0, 0.0));\n"),
+            key("#0 = DIRECTION('', (1.0, 1.0000000000E-3));\n"),
+        );
+    }
+
+    #[test]
+    fn strings_and_identifiers() {
+        assert_eq!(
+            snap_reals("#0 = AXIS2_PLACEMENT_3D('it''s 2.0', #1, #2, #3);", 0.5),
+            "#0 = AXIS2_PLACEMENT_3D('it''s 2.0', #1, #2, #3);",
+        );
+        assert_eq!(
+            snap_reals(
+                "#0 = B_SPLINE_CURVE_WITH_KNOTS('', 3, (#1), .UNSPECIFIED., .F., .F., (4), (0.0));",
+                0.5
+            ),
+            "#0 = B_SPLINE_CURVE_WITH_KNOTS('', 3, (#1), .UNSPECIFIED., .F., .F., (4), (~0));",
+        );
+    }
+}
@@ -364,7 +364,7 @@ where
         ))?;
         self.leader().fmt(curve_idx, f)?;
         self.surface0().fmt(surface0_idx, f)?;
-        self.surface1().fmt(surface0_idx, f)
+        self.surface1().fmt(surface1_idx, f)
     }
 }
 
@@ -458,7 +458,10 @@ impl DisplayByStep for ModelingCurve {
             ModelingCurve::Line(x) => DisplayByStep::fmt(x, idx, f),
             ModelingCurve::BSplineCurve(x) => DisplayByStep::fmt(x, idx, f),
             ModelingCurve::NurbsCurve(x) => DisplayByStep::fmt(x, idx, f),
-            ModelingCurve::IntersectionCurve(x) => DisplayByStep::fmt(x, idx, f),
+            ModelingCurve::IntersectionCurve(x) => match self.step_substitute() {
+                Some(approx) => DisplayByStep::fmt(approx.as_ref(), idx, f),
+                None => DisplayByStep::fmt(x, idx, f),
+            },
         }
     }
 }
@@ -469,12 +472,27 @@ impl StepLength for ModelingCurve {
             ModelingCurve::Line(_) => Line::<Point3>::LENGTH,
             ModelingCurve::BSplineCurve(x) => x.step_length(),
             ModelingCurve::NurbsCurve(x) => x.step_length(),
-            ModelingCurve::IntersectionCurve(x) => x.step_length(),
+            ModelingCurve::IntersectionCurve(x) => match self.step_substitute() {
+                Some(approx) => approx.step_length(),
+                None => x.step_length(),
+            },
         }
     }
 }
 
-impl StepCurve for ModelingCurve {}
+/// The intersection curves are output as their [`IntersectionCurveApproximation`]s.
+impl StepCurve for ModelingCurve {
+    fn step_substitute(&self) -> Option<Arc<dyn StepCurveSubstitute>> {
+        match self {
+            ModelingCurve::IntersectionCurve(x) => {
+                let approx =
+                    IntersectionCurveApproximation::try_new(x, INTERSECTION_CURVE_TOLERANCE)?;
+                Some(Arc::new(approx))
+            }
+            _ => None,
+        }
+    }
+}
 
 impl DisplayByStep for Plane {
     fn fmt(&self, idx: usize, f: &mut Formatter<'_>) -> Result {
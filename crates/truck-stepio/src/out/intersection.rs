@@ -0,0 +1,282 @@
+use super::{Result, *};
+use truck_geometry::prelude::*;
+use truck_modeling::Surface as ModelingSurface;
+
+/// the number of the segments before the bisections
+const INITIAL_DIVISION: usize = 8;
+/// the maximum number of the bisections of a segment
+const MAX_DEPTH: usize = 16;
+/// the number of the trials of searching the parameters on the surfaces
+const TRIALS: usize = 100;
+
+/// Tolerance of the approximations of the intersection curves in [`truck_modeling::Curve`].
+pub const INTERSECTION_CURVE_TOLERANCE: f64 = 1.0e-5;
+
+/// The parameters of the surfaces in STEP, in which the pcurves are output.
+pub trait StepParameters {
+    /// The affine map from the parameters in truck to the ones in STEP as a homogeneous matrix,
+    /// `None` if the parameters are not related by an affine map.
+    fn step_parameters(&self) -> Option<Matrix3>;
+}
+
+impl StepParameters for BSplineSurface<Point3> {
+    #[inline(always)]
+    fn step_parameters(&self) -> Option<Matrix3> { Some(Matrix3::identity()) }
+}
+
+impl StepParameters for NurbsSurface<Vector4> {
+    #[inline(always)]
+    fn step_parameters(&self) -> Option<Matrix3> { Some(Matrix3::identity()) }
+}
+
+/// The axes in STEP are orthonormal: the x-axis is along `u_axis` and the y-axis is
+/// `normal` times the x-axis.
+impl StepParameters for Plane {
+    fn step_parameters(&self) -> Option<Matrix3> {
+        let (a, b) = (self.u_axis(), self.v_axis());
+        let x = a.normalize();
+        let y = self.normal().cross(x);
+        Some(Matrix3::from_cols(
+            Vector3::new(a.dot(x), a.dot(y), 0.0),
+            Vector3::new(b.dot(x), b.dot(y), 0.0),
+            Vector3::unit_z(),
+        ))
+    }
+}
+
+/// The surfaces of revolution have no pcurves: the parameters in STEP are swapped and
+/// the angle depends on the units.
+impl StepParameters for ModelingSurface {
+    fn step_parameters(&self) -> Option<Matrix3> {
+        match self {
+            ModelingSurface::Plane(x) => x.step_parameters(),
+            ModelingSurface::BSplineSurface(x) => x.step_parameters(),
+            ModelingSurface::NurbsSurface(x) => x.step_parameters(),
+            ModelingSurface::RevolutedCurve(_) => None,
+        }
+    }
+}
+
+impl<T: StepParameters> StepParameters for Box<T> {
+    #[inline(always)]
+    fn step_parameters(&self) -> Option<Matrix3> { self.as_ref().step_parameters() }
+}
+
+/// Cubic B-spline approximation of an intersection curve with the pcurves on both surfaces.
+///
+/// Output as `intersection_curve` whose 3D curve is the B-spline curve and whose associated
+/// geometries are the `pcurve`s on the two surfaces, mapped into the parameters in STEP by
+/// [`StepParameters`]. If either surface has no such map, only the B-spline curve is output.
+#[derive(Clone, Debug)]
+pub struct IntersectionCurveApproximation<S> {
+    curve: BSplineCurve<Point3>,
+    pcurves: [PCurve<BSplineCurve<Point2>, S>; 2],
+    /// the pcurves in the parameters in STEP
+    step_pcurves: Option<[PCurve<BSplineCurve<Point2>, S>; 2]>,
+}
+
+/// a point on the intersection curve with its parameters on the surfaces
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    t: f64,
+    point: (Point3, Vector3),
+    uv: [(Point2, Vector2); 2],
+}
+
+/// the parameter on the surface and its derivation along the curve
+fn surface_parameter<S>(
+    surface: &S,
+    (point, der): (Point3, Vector3),
+    hint: Option<Point2>,
+    tol: f64,
+) -> Option<(Point2, Vector2)>
+where
+    S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>,
+{
+    let hint = match hint {
+        Some(uv) => SPHint2D::Parameter(uv.x, uv.y),
+        None => SPHint2D::None,
+    };
+    let (u, v) = surface.search_nearest_parameter(point, hint, TRIALS)?;
+    if surface.subs(u, v).distance(point) > tol {
+        return None;
+    }
+    // the least squares solution of `uder * du + vder * dv = der`
+    let (uder, vder) = (surface.uder(u, v), surface.vder(u, v));
+    let gram = Matrix2::new(
+        uder.dot(uder),
+        uder.dot(vder),
+        uder.dot(vder),
+        vder.dot(vder),
+    );
+    let uvder = gram.invert()? * Vector2::new(uder.dot(der), vder.dot(der));
+    Some((Point2::new(u, v), uvder))
+}
+
+/// the point at `s` in `[0, 1]` on the cubic Hermite curve between the parameters `h` apart
+fn hermite<P: EuclideanSpace<Scalar = f64>>(
+    (p0, d0): (P, P::Diff),
+    (p1, d1): (P, P::Diff),
+    h: f64,
+    s: f64,
+) -> P {
+    let (c1, c2, c3) = (d0 * (h / 3.0), (p1 - p0) - d1 * (h / 3.0), p1 - p0);
+    let r = 1.0 - s;
+    p0 + c1 * (3.0 * s * r * r) + c2 * (3.0 * s * s * r) + c3 * (s * s * s)
+}
+
+/// the cubic B-spline curve joining the Hermite segments between the samples
+fn hermite_spline<P: EuclideanSpace<Scalar = f64>>(
+    samples: &[Sample],
+    point: impl Fn(&Sample) -> (P, P::Diff),
+) -> BSplineCurve<P> {
+    let mut knots = vec![samples[0].t; 4];
+    let mut control_points = Vec::with_capacity(3 * samples.len() - 2);
+    samples.windows(2).for_each(|w| {
+        let ((p0, d0), (p1, d1)) = (point(&w[0]), point(&w[1]));
+        let h = w[1].t - w[0].t;
+        control_points.extend([p0, p0 + d0 * (h / 3.0), p1 - d1 * (h / 3.0)]);
+        knots.extend([w[1].t; 3]);
+    });
+    let last = samples[samples.len() - 1];
+    control_points.push(point(&last).0);
+    knots.push(last.t);
+    BSplineCurve::new(KnotVec::from(knots), control_points)
+}
+
+struct Approximator<'a, C, S> {
+    curve: &'a IntersectionCurve<C, S>,
+    tol: f64,
+}
+
+impl<'a, C, S> Approximator<'a, C, S>
+where
+    IntersectionCurve<C, S>: ParametricCurve3D,
+    S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3>,
+{
+    fn sample(&self, t: f64, hint: Option<&Sample>) -> Option<Sample> {
+        let point = (self.curve.subs(t), self.curve.der(t));
+        let uv_hint = |i: usize| hint.map(|sample: &Sample| sample.uv[i].0);
+        let uv0 = surface_parameter(self.curve.surface0(), point, uv_hint(0), self.tol)?;
+        let uv1 = surface_parameter(self.curve.surface1(), point, uv_hint(1), self.tol)?;
+        Some(Sample {
+            t,
+            point,
+            uv: [uv0, uv1],
+        })
+    }
+
+    /// Pushes the samples after `start` until `end` so that the segments are within the tolerance.
+    fn bisect(
+        &self,
+        start: Sample,
+        end: Sample,
+        depth: usize,
+        samples: &mut Vec<Sample>,
+    ) -> Option<()> {
+        let h = end.t - start.t;
+        let surfaces = [self.curve.surface0(), self.curve.surface1()];
+        let is_near = |s: f64| {
+            let Some(sample) = self.sample(start.t + h * s, Some(&start)) else {
+                return false;
+            };
+            let p = sample.point.0;
+            hermite(start.point, end.point, h, s).distance(p) < self.tol
+                && surfaces.iter().enumerate().all(|(i, surface)| {
+                    let uv = hermite(start.uv[i], end.uv[i], h, s);
+                    surface.subs(uv.x, uv.y).distance(p) < self.tol
+                })
+        };
+        if [0.25, 0.5, 0.75].into_iter().all(is_near) {
+            samples.push(end);
+            return Some(());
+        }
+        if depth == MAX_DEPTH {
+            return None;
+        }
+        let mid = self.sample(start.t + h / 2.0, Some(&start))?;
+        self.bisect(start, mid, depth + 1, samples)?;
+        self.bisect(mid, end, depth + 1, samples)
+    }
+}
+
+impl<S: Clone> IntersectionCurveApproximation<S> {
+    /// Approximates `curve` so that the B-spline curve and the pcurves are within `tol` from it.
+    ///
+    /// Returns `None` if the parameters on the surfaces are not found, e.g. the curve crosses
+    /// the seam of a periodic surface.
+    pub fn try_new<C>(curve: &IntersectionCurve<C, S>, tol: f64) -> Option<Self>
+    where
+        IntersectionCurve<C, S>: ParametricCurve3D + BoundedCurve,
+        S: ParametricSurface3D + SearchNearestParameter<D2, Point = Point3> + StepParameters, {
+        let approximator = Approximator { curve, tol };
+        let (t0, t1) = curve.range_tuple();
+        let mut samples = vec![approximator.sample(t0, None)?];
+        (1..=INITIAL_DIVISION).try_for_each(|i| {
+            let t = t0 + (t1 - t0) * i as f64 / INITIAL_DIVISION as f64;
+            let start = *samples.last()?;
+            let end = approximator.sample(t, Some(&start))?;
+            approximator.bisect(start, end, 0, &mut samples)
+        })?;
+        let surfaces = [curve.surface0(), curve.surface1()];
+        let pcurve = |i: usize, map: Matrix3| {
+            let uv = |sample: &Sample| {
+                let (uv, der) = sample.uv[i];
+                (map.transform_point(uv), map.transform_vector(der))
+            };
+            PCurve::new(hermite_spline(&samples, uv), surfaces[i].clone())
+        };
+        let step_pcurves = match (surfaces[0].step_parameters(), surfaces[1].step_parameters()) {
+            (Some(map0), Some(map1)) => Some([pcurve(0, map0), pcurve(1, map1)]),
+            _ => None,
+        };
+        Some(Self {
+            curve: hermite_spline(&samples, |sample| sample.point),
+            pcurves: [
+                pcurve(0, Matrix3::identity()),
+                pcurve(1, Matrix3::identity()),
+            ],
+            step_pcurves,
+        })
+    }
+
+    /// Returns the B-spline curve in 3D space.
+    #[inline]
+    pub const fn curve(&self) -> &BSplineCurve<Point3> { &self.curve }
+
+    /// Returns the pcurves on the surfaces, in the parameters in truck.
+    #[inline]
+    pub const fn pcurves(&self) -> &[PCurve<BSplineCurve<Point2>, S>; 2] { &self.pcurves }
+}
+
+impl<S> DisplayByStep for IntersectionCurveApproximation<S>
+where S: DisplayByStep + StepLength
+{
+    fn fmt(&self, idx: usize, f: &mut Formatter<'_>) -> Result {
+        let Some(pcurves) = &self.step_pcurves else {
+            return DisplayByStep::fmt(&self.curve, idx, f);
+        };
+        let curve_idx = idx + 1;
+        let pcurve0_idx = curve_idx + self.curve.step_length();
+        let pcurve1_idx = pcurve0_idx + pcurves[0].step_length();
+        f.write_fmt(format_args!(
+            "#{idx} = INTERSECTION_CURVE('', #{curve_idx}, (#{pcurve0_idx}, #{pcurve1_idx}), .CURVE_3D.);\n"
+        ))?;
+        DisplayByStep::fmt(&self.curve, curve_idx, f)?;
+        DisplayByStep::fmt(&pcurves[0], pcurve0_idx, f)?;
+        DisplayByStep::fmt(&pcurves[1], pcurve1_idx, f)
+    }
+}
+
+impl<S: StepLength> StepLength for IntersectionCurveApproximation<S> {
+    fn step_length(&self) -> usize {
+        match &self.step_pcurves {
+            Some([pcurve0, pcurve1]) => {
+                1 + self.curve.step_length() + pcurve0.step_length() + pcurve1.step_length()
+            }
+            None => self.curve.step_length(),
+        }
+    }
+}
+
+impl<S> StepCurve for IntersectionCurveApproximation<S> {}
@@ -0,0 +1,255 @@
+use super::{
+    units::{ContextDisplay, ConversionUnitsDisplay},
+    Result, *,
+};
+use truck_geometry::prelude::*;
+use truck_polymesh::{PolygonMesh, StandardVertex};
+
+/// Forms of the polygon meshes in STEP file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepMeshForm {
+    /// `tessellated_shell` of `triangulated_face` with `coordinates_list`, defined in AP242.
+    #[default]
+    Tessellated,
+    /// `faceted_brep` bounded by `poly_loop`s, readable by AP203 and AP214 systems.
+    ///
+    /// The meshes are written as closed shells, so they should be closed.
+    FacetedBrep,
+}
+
+/// Display struct for outputting polygon meshes.
+/// # Example
+/// ```
+/// use truck_polymesh::*;
+/// use truck_stepio::out::*;
+/// let positions = vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+///     Point3::new(0.0, 0.0, 1.0),
+/// ];
+/// let v = |pos| StandardVertex { pos, uv: None, nor: None };
+/// let triangles = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let faces = Faces::from_tri_and_quad_faces(triangles.map(|tri| tri.map(v)).to_vec(), Vec::new());
+/// let tetrahedron = PolygonMesh::new(StandardAttributes { positions, ..Default::default() }, faces);
+///
+/// let meshes = StepMeshes::from_iter([&tetrahedron]);
+/// let step_string = CompleteStepDisplay::new(meshes, Default::default()).to_string();
+/// assert!(step_string.contains("TRIANGULATED_FACE"));
+///
+/// let meshes = StepMeshes::new(StepMeshForm::FacetedBrep).with_meshes([&tetrahedron]);
+/// let step_string = CompleteStepDisplay::new(meshes, Default::default()).to_string();
+/// assert!(step_string.contains("POLY_LOOP"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StepMeshes<'a> {
+    meshes: Vec<&'a PolygonMesh>,
+    form: StepMeshForm,
+    units: StepUnits,
+}
+
+impl<'a> StepMeshes<'a> {
+    /// constructor
+    #[inline]
+    pub fn new(form: StepMeshForm) -> Self {
+        Self {
+            meshes: Vec::new(),
+            form,
+            units: Default::default(),
+        }
+    }
+    /// push a mesh
+    #[inline]
+    pub fn push_mesh(&mut self, mesh: &'a PolygonMesh) { self.meshes.push(mesh) }
+    /// push meshes and return `self`
+    #[inline]
+    pub fn with_meshes<I: IntoIterator<Item = &'a PolygonMesh>>(mut self, meshes: I) -> Self {
+        self.meshes.extend(meshes);
+        self
+    }
+    /// Sets the units and the uncertainty, millimetre and radian by default.
+    #[inline]
+    pub fn set_units(&mut self, units: StepUnits) { self.units = units }
+}
+
+impl<'a> FromIterator<&'a PolygonMesh> for StepMeshes<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a PolygonMesh>>(iter: I) -> Self {
+        Self::default().with_meshes(iter)
+    }
+}
+
+fn point_display(p: &Point3) -> SliceDisplay<'_, f64> { SliceDisplay(AsRef::<[f64; 3]>::as_ref(p)) }
+
+fn vector_display(v: &Vector3) -> SliceDisplay<'_, f64> {
+    SliceDisplay(AsRef::<[f64; 3]>::as_ref(v))
+}
+
+/// the polygons which have at least three distinct positions
+fn polygons(mesh: &PolygonMesh) -> impl Iterator<Item = Vec<usize>> + '_ {
+    mesh.faces().face_iter().filter_map(|face| {
+        let mut polygon: Vec<usize> = face.iter().map(|v| v.pos).collect();
+        polygon.dedup();
+        if polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+        match polygon.len() >= 3 {
+            true => Some(polygon),
+            false => None,
+        }
+    })
+}
+
+/// `tessellated_shell`, 3 lines
+fn fmt_tessellated(mesh: &PolygonMesh, idx: usize, f: &mut Formatter<'_>) -> Result {
+    let positions = mesh.positions();
+    let normals = mesh.normals();
+    let triangles = mesh
+        .faces()
+        .triangle_iter()
+        .filter(|[a, b, c]| a.pos != b.pos && b.pos != c.pos && c.pos != a.pos)
+        .collect::<Vec<[StandardVertex; 3]>>();
+    let with_normals = !triangles.is_empty()
+        && triangles
+            .iter()
+            .flatten()
+            .all(|v| v.nor.is_some_and(|nor| nor < normals.len()));
+    // `pnindex` is required only if the normals are given for the vertices.
+    let (pnindex, tri_indices) = match with_normals {
+        true => {
+            let mut map = HashMap::<(usize, usize), usize>::new();
+            let mut pnindex = Vec::new();
+            let tri_indices = triangles
+                .iter()
+                .map(|tri| {
+                    tri.map(|v| {
+                        let key = (v.pos, v.nor.unwrap_or_default());
+                        *map.entry(key).or_insert_with(|| {
+                            pnindex.push(key);
+                            pnindex.len()
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+            (pnindex, tri_indices)
+        }
+        false => {
+            let tri_indices = triangles
+                .iter()
+                .map(|tri| tri.map(|v| v.pos + 1))
+                .collect::<Vec<_>>();
+            (Vec::new(), tri_indices)
+        }
+    };
+    let coordinates = positions.iter().map(point_display).collect::<Vec<_>>();
+    let normal_list = pnindex
+        .iter()
+        .map(|(_, nor)| vector_display(&normals[*nor]))
+        .collect::<Vec<_>>();
+    let pnindex_list = pnindex.iter().map(|(pos, _)| pos + 1).collect::<Vec<_>>();
+    let triangle_list = tri_indices
+        .iter()
+        .map(|tri| SliceDisplay(tri.as_slice()))
+        .collect::<Vec<_>>();
+    f.write_fmt(format_args!(
+        "#{idx} = TESSELLATED_SHELL('', (#{face_idx}), $);
+#{face_idx} = TRIANGULATED_FACE('', #{coordinates_idx}, {pnmax}, {normals}, $, {pnindex}, {triangles});
+#{coordinates_idx} = COORDINATES_LIST('', {npoints}, {coordinates});\n",
+        face_idx = idx + 1,
+        coordinates_idx = idx + 2,
+        // the points of the face are indexed by `pnindex` if it is given
+        pnmax = match pnindex.is_empty() {
+            true => positions.len(),
+            false => pnindex.len(),
+        },
+        normals = SliceDisplay(&normal_list),
+        pnindex = SliceDisplay(&pnindex_list),
+        triangles = SliceDisplay(&triangle_list),
+        npoints = positions.len(),
+        coordinates = SliceDisplay(&coordinates),
+    ))
+}
+
+fn faceted_brep_length(mesh: &PolygonMesh) -> usize {
+    2 + 3 * polygons(mesh).count() + mesh.positions().len()
+}
+
+/// `faceted_brep`, [`faceted_brep_length`] lines
+fn fmt_faceted_brep(mesh: &PolygonMesh, idx: usize, f: &mut Formatter<'_>) -> Result {
+    let polygons = polygons(mesh).collect::<Vec<_>>();
+    let ep_points = idx + 2 + 3 * polygons.len();
+    f.write_fmt(format_args!(
+        "#{idx} = FACETED_BREP('', #{shell_idx});
+#{shell_idx} = CLOSED_SHELL('', {faces});\n",
+        shell_idx = idx + 1,
+        faces = IndexSliceDisplay((0..polygons.len()).map(|i| idx + 2 + 3 * i)),
+    ))?;
+    polygons.iter().enumerate().try_for_each(|(i, polygon)| {
+        let face_idx = idx + 2 + 3 * i;
+        f.write_fmt(format_args!(
+            "#{face_idx} = FACE('', (#{bound_idx}));
+#{bound_idx} = FACE_OUTER_BOUND('', #{loop_idx}, .T.);
+#{loop_idx} = POLY_LOOP('', {points});\n",
+            bound_idx = face_idx + 1,
+            loop_idx = face_idx + 2,
+            points = IndexSliceDisplay(polygon.iter().map(|pos| ep_points + pos)),
+        ))
+    })?;
+    mesh.positions()
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, p)| Display::fmt(&StepDisplay::new(*p, ep_points + i), f))
+}
+
+impl<'a> Display for StepMeshes<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.pad(
+"#1 = APPLICATION_PROTOCOL_DEFINITION('international standard', 'automotive_design', 2000, #2);
+#2 = APPLICATION_CONTEXT('core data for automotive mechanical design processes');
+#3 = SHAPE_DEFINITION_REPRESENTATION(#4, #10);
+#4 = PRODUCT_DEFINITION_SHAPE('','', #5);
+#5 = PRODUCT_DEFINITION('design','', #6, #9);
+#6 = PRODUCT_DEFINITION_FORMATION('','', #7);
+#7 = PRODUCT('','','', (#8));
+#8 = PRODUCT_CONTEXT('', #2, 'mechanical');
+#9 = PRODUCT_DEFINITION_CONTEXT('part definition', #2, 'design');\n")?;
+        let mut cursor = 16;
+        let indices = self
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let idx = cursor;
+                cursor += match self.form {
+                    StepMeshForm::Tessellated => 3,
+                    StepMeshForm::FacetedBrep => faceted_brep_length(mesh),
+                };
+                idx
+            })
+            .collect::<Vec<_>>();
+        let representation = match self.form {
+            StepMeshForm::Tessellated => "TESSELLATED_SHAPE_REPRESENTATION",
+            StepMeshForm::FacetedBrep => "FACETED_BREP_SHAPE_REPRESENTATION",
+        };
+        f.write_fmt(format_args!(
+            "#10 = {representation}('', {items}, #11);\n",
+            items = IndexSliceDisplay(indices.iter().copied()),
+        ))?;
+        let context = ContextDisplay {
+            units: &self.units,
+            idx: 11,
+            conversion_idx: cursor,
+        };
+        Display::fmt(&context, f)?;
+        self.meshes
+            .iter()
+            .zip(&indices)
+            .try_for_each(|(mesh, idx)| match self.form {
+                StepMeshForm::Tessellated => fmt_tessellated(mesh, *idx, f),
+                StepMeshForm::FacetedBrep => fmt_faceted_brep(mesh, *idx, f),
+            })?;
+        let conversions = ConversionUnitsDisplay {
+            units: &self.units,
+            idx: cursor,
+        };
+        Display::fmt(&conversions, f)
+    }
+}
@@ -1,4 +1,7 @@
-use std::fmt::{Debug, Display, Formatter, Result};
+use std::{
+    fmt::{Debug, Display, Formatter, Result},
+    sync::Arc,
+};
 
 use truck_topology::compress::*;
 
@@ -9,6 +12,9 @@ const ERR: Result = Err(std::fmt::Error);
 #[cfg(feature = "derive")]
 pub use truck_derivers::{DisplayByStep, StepLength};
 
+/// escape the quotations in the strings of step file
+fn escape_string(string: &str) -> String { string.replace('\'', "''") }
+
 /// display boolean number to step file
 #[derive(Clone, Copy, Debug)]
 pub struct BooleanDisplay(pub bool);
@@ -101,6 +107,19 @@ impl<'a> Display for SliceDisplay<'a, SliceDisplay<'a, f64>> {
     }
 }
 
+impl<'a> Display for SliceDisplay<'a, SliceDisplay<'a, usize>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str("(")?;
+        self.0.iter().enumerate().try_for_each(|(i, x)| {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            Display::fmt(x, f)
+        })?;
+        f.write_str(")")
+    }
+}
+
 /// display index slice
 /// # Examples
 /// ```
@@ -144,6 +163,13 @@ impl<'a, I: Clone + IntoIterator<Item = usize>> Display for SliceDisplay<'a, Ind
     }
 }
 
+/// display by a closure
+struct FnDisplay<F>(F);
+
+impl<F: Fn(&mut Formatter<'_>) -> Result> Display for FnDisplay<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result { (self.0)(f) }
+}
+
 /// trait for outputting by STEP file format.
 pub trait DisplayByStep {
     ///  formatter
@@ -234,21 +260,37 @@ macro_rules! impl_const_step_length {
     };
 }
 
+/// Curve output in place of another curve, e.g. the approximation of an intersection curve.
+pub trait StepCurveSubstitute: DisplayByStep + StepLength + Debug + Send + Sync {}
+
+impl<T: DisplayByStep + StepLength + Debug + Send + Sync> StepCurveSubstitute for T {}
+
 /// Additional information for output to `edge_curve`.
 pub trait StepCurve {
     /// the parameter `same_sense`.
     #[inline(always)]
     fn same_sense(&self) -> bool { true }
+    /// The curve output in place of this one, `None` if this curve is output as it is.
+    ///
+    /// The substitute is computed once per edge when the ids are assigned.
+    #[inline(always)]
+    fn step_substitute(&self) -> Option<Arc<dyn StepCurveSubstitute>> { None }
 }
 
 impl<T: StepCurve> StepCurve for &T {
     #[inline(always)]
     fn same_sense(&self) -> bool { (*self).same_sense() }
+    #[inline(always)]
+    fn step_substitute(&self) -> Option<Arc<dyn StepCurveSubstitute>> { (*self).step_substitute() }
 }
 
 impl<T: StepCurve> StepCurve for Box<T> {
     #[inline(always)]
     fn same_sense(&self) -> bool { self.as_ref().same_sense() }
+    #[inline(always)]
+    fn step_substitute(&self) -> Option<Arc<dyn StepCurveSubstitute>> {
+        self.as_ref().step_substitute()
+    }
 }
 
 /// Additional information for output to `face_surface`.
@@ -339,13 +381,21 @@ ENDSEC;\n",
 
 /// Display model with configuations
 #[derive(Clone, Debug)]
-pub struct StepModel<'a, P, C, S>(PreStepModel<'a, P, C, S>);
+pub struct StepModel<'a, P, C, S> {
+    model: PreStepModel<'a, P, C, S>,
+    styles: Vec<(usize, StepStyle)>,
+    units: StepUnits,
+}
 
 /// Display models with configuations
 #[derive(Clone, Debug)]
 pub struct StepModels<'a, P, C, S> {
     models: Vec<PreStepModel<'a, P, C, S>>,
     next_idx: usize,
+    styles: Vec<(usize, StepStyle)>,
+    units: StepUnits,
+    /// the registry of the geometry if it is shared
+    shared: Option<dedup::SharedGeometry<P, C, S>>,
 }
 
 /// Display struct for outputting STEP file format with header.
@@ -364,25 +414,46 @@ impl<T: Display> Display for CompleteStepDisplay<T> {
     }
 }
 
+impl From<StepHeaderDescriptor> for StepHeader {
+    fn from(header: StepHeaderDescriptor) -> Self {
+        StepHeader {
+            file_name: header.file_name,
+            time_stamp: header.time_stamp,
+            authors: header.authors,
+            organization: header.organization,
+            origination_system: header.organization_system,
+            authorization: header.authorization,
+            schema: "ISO-10303-042".to_string(),
+        }
+    }
+}
+
 impl<T> CompleteStepDisplay<T> {
     /// constructor
     #[inline]
     pub fn new(display: T, header: StepHeaderDescriptor) -> Self {
         CompleteStepDisplay {
             display,
-            header: StepHeader {
-                file_name: header.file_name,
-                time_stamp: header.time_stamp,
-                authors: header.authors,
-                organization: header.organization,
-                origination_system: header.organization_system,
-                authorization: header.authorization,
-                schema: "ISO-10303-042".to_string(),
-            },
+            header: header.into(),
         }
     }
 }
 
+mod assembly;
+mod dedup;
 mod geometry;
+mod intersection;
+mod mesh;
+mod style;
 mod topology;
+mod units;
+mod writer;
+pub use assembly::StepAssembly;
 pub use geometry::VectorAsDirection;
+pub use intersection::{
+    IntersectionCurveApproximation, StepParameters, INTERSECTION_CURVE_TOLERANCE,
+};
+pub use mesh::{StepMeshForm, StepMeshes};
+pub use style::StepStyle;
+pub use units::{StepAngleUnit, StepLengthUnit, StepUnits};
+pub use writer::StepWriter;
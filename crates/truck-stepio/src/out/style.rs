@@ -0,0 +1,124 @@
+use super::{Result, *};
+
+/// Name, colour and transparency of a solid, a shell or a face.
+///
+/// The name is written to the entity itself,
+/// and the colour and the transparency are written as `styled_item`
+/// collected in `mechanical_design_geometric_presentation_representation`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepStyle {
+    /// the name of the entity, not written if empty
+    pub name: String,
+    /// the RGB colour, each component is in `[0, 1]`
+    pub colour: Option<[f64; 3]>,
+    /// the transparency in `[0, 1]`, `0.0` is opaque
+    ///
+    /// The colour is white if only the transparency is given.
+    pub transparency: Option<f64>,
+}
+
+impl StepStyle {
+    #[inline]
+    pub(super) fn is_styled(&self) -> bool { self.colour.is_some() || self.transparency.is_some() }
+
+    fn styled_item_length(&self) -> usize {
+        match self.transparency {
+            Some(_) => 10,
+            None => 8,
+        }
+    }
+}
+
+/// `styled_item`s of the styles and `mechanical_design_geometric_presentation_representation`
+#[derive(Clone, Debug)]
+pub(super) struct StyledItemsDisplay<'a> {
+    /// pairs of the index of the styled entity and its style
+    pub styles: &'a [(usize, StepStyle)],
+    /// the index of `mechanical_design_geometric_presentation_representation`
+    pub idx: usize,
+    /// the index of the representation context
+    pub context_idx: usize,
+}
+
+impl<'a> StepLength for StyledItemsDisplay<'a> {
+    fn step_length(&self) -> usize {
+        match self.styles.is_empty() {
+            true => 0,
+            false => {
+                1 + self
+                    .styles
+                    .iter()
+                    .map(|(_, style)| style.styled_item_length())
+                    .sum::<usize>()
+            }
+        }
+    }
+}
+
+impl<'a> Display for StyledItemsDisplay<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let StyledItemsDisplay {
+            styles,
+            idx,
+            context_idx,
+        } = *self;
+        if styles.is_empty() {
+            return Ok(());
+        }
+        let mut cursor = idx + 1;
+        let item_indices = styles
+            .iter()
+            .map(|(_, style)| {
+                let res = cursor;
+                cursor += style.styled_item_length();
+                res
+            })
+            .collect::<Vec<_>>();
+        f.write_fmt(format_args!(
+            "#{idx} = MECHANICAL_DESIGN_GEOMETRIC_PRESENTATION_REPRESENTATION('', {items}, #{context_idx});\n",
+            items = IndexSliceDisplay(item_indices.iter().copied()),
+        ))?;
+        styles
+            .iter()
+            .zip(item_indices)
+            .try_for_each(|((target, style), idx)| {
+                let [r, g, b] = style.colour.unwrap_or([1.0, 1.0, 1.0]);
+                let side_styles = match style.transparency {
+                    Some(_) => vec![idx + 4, idx + 8],
+                    None => vec![idx + 4],
+                };
+                f.write_fmt(format_args!(
+                    "#{idx} = STYLED_ITEM('', (#{psa}), #{target});
+#{psa} = PRESENTATION_STYLE_ASSIGNMENT((#{ssu}));
+#{ssu} = SURFACE_STYLE_USAGE(.BOTH., #{sss});
+#{sss} = SURFACE_SIDE_STYLE('', {side_styles});
+#{ssfa} = SURFACE_STYLE_FILL_AREA(#{fas});
+#{fas} = FILL_AREA_STYLE('', (#{fasc}));
+#{fasc} = FILL_AREA_STYLE_COLOUR('', #{colour});
+#{colour} = COLOUR_RGB('', {r}, {g}, {b});\n",
+                    psa = idx + 1,
+                    ssu = idx + 2,
+                    sss = idx + 3,
+                    ssfa = idx + 4,
+                    fas = idx + 5,
+                    fasc = idx + 6,
+                    colour = idx + 7,
+                    side_styles = IndexSliceDisplay(side_styles),
+                    r = FloatDisplay(r),
+                    g = FloatDisplay(g),
+                    b = FloatDisplay(b),
+                ))?;
+                match style.transparency {
+                    Some(transparency) => f.write_fmt(format_args!(
+                        "#{ssr} = SURFACE_STYLE_RENDERING_WITH_PROPERTIES(.NORMAL_SHADING., #{colour}, (#{sst}));
+#{sst} = SURFACE_STYLE_TRANSPARENT({transparency});\n",
+                        ssr = idx + 8,
+                        colour = idx + 7,
+                        sst = idx + 9,
+                        transparency = FloatDisplay(transparency),
+                    )),
+                    None => Ok(()),
+                }
+            })
+    }
+}
@@ -1,4 +1,29 @@
-use super::{Result, *};
+use super::{
+    dedup::{Geometry, SharedGeometry},
+    style::StyledItemsDisplay,
+    units::{ContextDisplay, ConversionUnitsDisplay},
+    Result, *,
+};
+use std::collections::HashMap;
+
+/// the id of a geometry, `shared` if it is written by another entity
+#[derive(Clone, Copy, Debug)]
+struct GeometryIdx {
+    idx: usize,
+    shared: bool,
+}
+
+/// Assigns `len` ids from `cursor` to a geometry, unless it is `shared` by another entity.
+fn geometry_idx(cursor: &mut usize, len: usize, shared: Option<usize>) -> GeometryIdx {
+    match shared {
+        Some(idx) => GeometryIdx { idx, shared: true },
+        None => {
+            let idx = *cursor;
+            *cursor += len;
+            GeometryIdx { idx, shared: false }
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub(super) struct StepShell<'a, P, C, S> {
@@ -7,19 +32,35 @@ pub(super) struct StepShell<'a, P, C, S> {
     face_indices: Vec<usize>,
     ep_edges: usize,
     ep_vertices: usize,
-    surface_indices: Vec<usize>,
-    curve_indices: Vec<usize>,
-    ep_points: usize,
+    surface_indices: Vec<GeometryIdx>,
+    curve_indices: Vec<GeometryIdx>,
+    /// the curves output in place of the ones of the edges
+    curve_substitutes: Vec<Option<Arc<dyn StepCurveSubstitute>>>,
+    point_indices: Vec<GeometryIdx>,
+    /// the id next to the last entity
+    end: usize,
     is_open: bool,
+    name: String,
+    face_names: Vec<String>,
 }
 
 impl<'a, P, C, S> StepShell<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn new(shell: &'a CompressedShell<P, C, S>, idx: usize, is_open: bool) -> Self {
+        Self::with_sharing(shell, idx, is_open, |_, _| None)
+    }
+    /// Lays out the entities from `idx`, referring to the geometry given by `share`
+    /// in place of writing it. `share` is called with each geometry and its id if written.
+    fn with_sharing(
+        shell: &'a CompressedShell<P, C, S>,
+        idx: usize,
+        is_open: bool,
+        mut share: impl FnMut(Geometry<'_, P, C, S>, usize) -> Option<usize>,
+    ) -> Self {
         let faces = &shell.faces;
         let edges = &shell.edges;
         let vertices = &shell.vertices;
@@ -41,20 +82,35 @@ where
         let surface_indices = faces
             .iter()
             .map(|f| {
-                let res = cursor;
-                cursor += f.surface.step_length();
-                res
+                let shared = share(Geometry::Surface(&f.surface), cursor);
+                geometry_idx(&mut cursor, f.surface.step_length(), shared)
             })
             .collect::<Vec<_>>();
+        let curve_substitutes = edges
+            .iter()
+            .map(|e| e.curve.step_substitute())
+            .collect::<Vec<_>>();
         let curve_indices = edges
             .iter()
-            .map(|e| {
-                let res = cursor;
-                cursor += e.curve.step_length();
-                res
+            .zip(&curve_substitutes)
+            .map(|(e, substitute)| match substitute {
+                Some(substitute) => {
+                    let shared = share(Geometry::Substitute(substitute.as_ref()), cursor);
+                    geometry_idx(&mut cursor, substitute.step_length(), shared)
+                }
+                None => {
+                    let shared = share(Geometry::Curve(&e.curve), cursor);
+                    geometry_idx(&mut cursor, e.curve.step_length(), shared)
+                }
+            })
+            .collect::<Vec<_>>();
+        let point_indices = vertices
+            .iter()
+            .map(|v| {
+                let shared = share(Geometry::Point(v), cursor);
+                geometry_idx(&mut cursor, 1, shared)
             })
             .collect::<Vec<_>>();
-        let ep_points = cursor;
         StepShell {
             entity: shell,
             idx,
@@ -63,8 +119,12 @@ where
             ep_vertices,
             surface_indices,
             curve_indices,
-            ep_points,
+            curve_substitutes,
+            point_indices,
+            end: cursor,
             is_open,
+            name: String::new(),
+            face_names: vec![String::new(); faces.len()],
         }
     }
 }
@@ -84,8 +144,11 @@ where
             ep_vertices,
             surface_indices,
             curve_indices,
-            ep_points,
+            curve_substitutes,
+            point_indices,
             is_open,
+            face_names,
+            ..
         } = self;
         let faces = &entity.faces;
         let edges = &entity.edges;
@@ -101,7 +164,7 @@ where
         faces.iter().enumerate().try_for_each(|(i, f)| {
             let idx = face_indices[i];
             let mut cursor = idx + 1;
-            let face_geometry = surface_indices[i];
+            let face_geometry = surface_indices[i].idx;
             let face_bounds = match f.boundaries.is_empty() {
                 true => vec![cursor],
                 false => {
@@ -114,7 +177,8 @@ where
                 }
             };
             formatter.write_fmt(format_args!(
-                "#{idx} = FACE_SURFACE('', {face_bound}, #{face_geometry}, {same_sense});\n",
+                "#{idx} = FACE_SURFACE('{name}', {face_bound}, #{face_geometry}, {same_sense});\n",
+                name = face_names[i],
                 same_sense = BooleanDisplay(f.orientation == f.surface.same_sense()),
                 face_bound = IndexSliceDisplay(face_bounds.clone()),
             ))?;
@@ -160,60 +224,87 @@ where
                 idx = ep_edges + i,
                 edge_start = ep_vertices + e.vertices.0,
                 edge_end = ep_vertices + e.vertices.1,
-                edge_geometry = curve_indices[i],
+                edge_geometry = curve_indices[i].idx,
             ))
         })?;
-        (0..vertices.len()).try_for_each(|i| {
-            formatter.write_fmt(format_args!(
-                "#{idx} = VERTEX_POINT('', #{vertex_geometry});\n",
-                idx = ep_vertices + i,
-                vertex_geometry = ep_points + i,
-            ))
-        })?;
-        faces.iter().zip(surface_indices).try_for_each(|(f, idx)| {
-            Display::fmt(&StepDisplay::new(&f.surface, *idx), formatter)
-        })?;
+        point_indices
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, point)| {
+                formatter.write_fmt(format_args!(
+                    "#{idx} = VERTEX_POINT('', #{vertex_geometry});\n",
+                    idx = ep_vertices + i,
+                    vertex_geometry = point.idx,
+                ))
+            })?;
+        faces
+            .iter()
+            .zip(surface_indices)
+            .filter(|(_, surface)| !surface.shared)
+            .try_for_each(|(f, surface)| {
+                Display::fmt(&StepDisplay::new(&f.surface, surface.idx), formatter)
+            })?;
         edges
             .iter()
+            .zip(curve_substitutes)
             .zip(curve_indices)
-            .try_for_each(|(e, idx)| Display::fmt(&StepDisplay::new(&e.curve, *idx), formatter))?;
+            .filter(|(_, curve)| !curve.shared)
+            .try_for_each(|((e, substitute), curve)| match substitute {
+                Some(substitute) => DisplayByStep::fmt(substitute.as_ref(), curve.idx, formatter),
+                None => Display::fmt(&StepDisplay::new(&e.curve, curve.idx), formatter),
+            })?;
         vertices
             .iter()
-            .enumerate()
-            .try_for_each(|(i, v)| Display::fmt(&StepDisplay::new(*v, ep_points + i), formatter))
+            .zip(point_indices)
+            .filter(|(_, point)| !point.shared)
+            .try_for_each(|(v, point)| Display::fmt(&StepDisplay::new(*v, point.idx), formatter))
     }
 }
 
 impl<'a, P, C, S> StepLength for StepShell<'a, P, C, S> {
-    fn step_length(&self) -> usize {
-        1 + self.ep_points + self.entity.vertices.len() - self.face_indices[0]
-    }
+    fn step_length(&self) -> usize { self.end - self.idx }
 }
 
 #[derive(Clone, Debug)]
 pub(super) struct StepSolid<'a, P, C, S> {
+    entity: &'a CompressedSolid<P, C, S>,
     idx: usize,
     boundaries: Vec<StepShell<'a, P, C, S>>,
+    name: String,
 }
 
 impl<'a, P, C, S> StepSolid<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn new(solid: &'a CompressedSolid<P, C, S>, idx: usize) -> Self {
+        Self::with_sharing(solid, idx, |_, _| None)
+    }
+    /// Lays out the entities from `idx`, referring to the geometry given by `share`
+    /// as [`StepShell::with_sharing`] does.
+    fn with_sharing(
+        solid: &'a CompressedSolid<P, C, S>,
+        idx: usize,
+        mut share: impl FnMut(Geometry<'_, P, C, S>, usize) -> Option<usize>,
+    ) -> Self {
         let mut cursor = idx + 1;
         let boundaries = solid
             .boundaries
             .iter()
             .map(|shell| {
-                let res = StepShell::new(shell, cursor, false);
+                let res = StepShell::with_sharing(shell, cursor, false, &mut share);
                 cursor += 1 + res.step_length();
                 res
             })
             .collect::<Vec<_>>();
-        StepSolid { idx, boundaries }
+        StepSolid {
+            entity: solid,
+            idx,
+            boundaries,
+            name: String::new(),
+        }
     }
 }
 
@@ -224,7 +315,12 @@ where
     S: DisplayByStep + StepLength + StepSurface,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let StepSolid { idx, boundaries } = self;
+        let StepSolid {
+            idx,
+            boundaries,
+            name,
+            ..
+        } = self;
         match boundaries.len() {
             0 => {
                 f.pad("empty solid!")?;
@@ -234,14 +330,14 @@ where
                 let shell_idx = idx + 1;
                 let step_shell = &boundaries[0];
                 f.write_fmt(format_args!(
-                    "#{idx} = MANIFOLD_SOLID_BREP('', #{shell_idx});\n"
+                    "#{idx} = MANIFOLD_SOLID_BREP('{name}', #{shell_idx});\n"
                 ))?;
                 Display::fmt(step_shell, f)
             }
             _ => {
                 let first_shell_idx = boundaries[0].face_indices[0] - 1;
                 f.write_fmt(format_args!(
-                    "#{idx} = BREP_WITH_VOIDS('', #{first_shell_idx}, {other_shells});\n",
+                    "#{idx} = BREP_WITH_VOIDS('{name}', #{first_shell_idx}, {other_shells});\n",
                     other_shells = IndexSliceDisplay(
                         boundaries[1..]
                             .iter()
@@ -281,10 +377,100 @@ pub(super) enum PreStepModel<'a, P, C, S> {
     Solid(StepSolid<'a, P, C, S>),
 }
 
+impl<'a, P, C, S> PreStepModel<'a, P, C, S>
+where
+    P: Copy,
+    C: StepLength + StepCurve,
+    S: StepLength,
+{
+    /// shell based surface model whose index is `idx`
+    pub(super) fn from_shell(shell: &'a CompressedShell<P, C, S>, idx: usize) -> Self {
+        Self::Shell(StepShell::new(shell, idx + 1, true))
+    }
+    /// solid model whose index is `idx`
+    pub(super) fn from_solid(solid: &'a CompressedSolid<P, C, S>, idx: usize) -> Self {
+        Self::Solid(StepSolid::new(solid, idx))
+    }
+    /// Lays out the model again from the index `idx` with the names kept,
+    /// referring to the geometry given by `share`.
+    fn relayout(
+        &self,
+        idx: usize,
+        share: impl FnMut(Geometry<'_, P, C, S>, usize) -> Option<usize>,
+    ) -> Self {
+        match self {
+            Self::Shell(x) => Self::Shell(StepShell {
+                name: x.name.clone(),
+                face_names: x.face_names.clone(),
+                ..StepShell::with_sharing(x.entity, idx + 1, x.is_open, share)
+            }),
+            Self::Solid(x) => {
+                let mut solid = StepSolid::with_sharing(x.entity, idx, share);
+                solid.name = x.name.clone();
+                solid
+                    .boundaries
+                    .iter_mut()
+                    .zip(&x.boundaries)
+                    .for_each(|(shell, old)| shell.face_names = old.face_names.clone());
+                Self::Solid(solid)
+            }
+        }
+    }
+}
+
+impl<'a, P, C, S> PreStepModel<'a, P, C, S> {
+    /// the index of the root entity, `shell_based_surface_model` or `manifold_solid_brep`
+    pub(super) fn root_idx(&self) -> usize {
+        match self {
+            Self::Shell(x) => x.idx - 1,
+            Self::Solid(x) => x.idx,
+        }
+    }
+    /// set the name of the root entity
+    pub(super) fn set_name(&mut self, name: &str) {
+        match self {
+            Self::Shell(x) => x.name = escape_string(name),
+            Self::Solid(x) => x.name = escape_string(name),
+        }
+    }
+    /// the indices of the entities which may be styled, the root entity and the faces
+    fn style_targets(&self) -> Vec<usize> {
+        let shells = match self {
+            Self::Shell(x) => std::slice::from_ref(x),
+            Self::Solid(x) => x.boundaries.as_slice(),
+        };
+        let faces = shells
+            .iter()
+            .flat_map(|shell| shell.face_indices.iter().copied());
+        std::iter::once(self.root_idx()).chain(faces).collect()
+    }
+    /// Sets the name of the `face`-th face and returns the index of the face.
+    ///
+    /// The faces of solids are numbered through the boundaries.
+    pub(super) fn set_face_name(&mut self, mut face: usize, name: &str) -> Option<usize> {
+        let shells = match self {
+            Self::Shell(x) => std::slice::from_mut(x),
+            Self::Solid(x) => x.boundaries.as_mut_slice(),
+        };
+        let (shell, i) = shells.iter_mut().find_map(|shell| {
+            let len = shell.face_indices.len();
+            match face < len {
+                true => Some((shell, face)),
+                false => {
+                    face -= len;
+                    None
+                }
+            }
+        })?;
+        shell.face_names[i] = escape_string(name);
+        Some(shell.face_indices[i])
+    }
+}
+
 impl<'a, P, C, S> From<&'a CompressedShell<P, C, S>> for PreStepModel<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn from(shell: &'a CompressedShell<P, C, S>) -> Self {
@@ -295,7 +481,7 @@ where
 impl<'a, P, C, S> From<&'a CompressedSolid<P, C, S>> for PreStepModel<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn from(solid: &'a CompressedSolid<P, C, S>) -> Self { Self::Solid(StepSolid::new(solid, 16)) }
@@ -311,9 +497,10 @@ where
         match self {
             Self::Shell(x) => {
                 f.write_fmt(format_args!(
-                    "#{idx} = SHELL_BASED_SURFACE_MODEL('', (#{shell_idx}));\n",
+                    "#{idx} = SHELL_BASED_SURFACE_MODEL('{name}', (#{shell_idx}));\n",
                     idx = x.idx - 1,
-                    shell_idx = x.idx
+                    shell_idx = x.idx,
+                    name = x.name,
                 ))?;
                 Display::fmt(&x, f)
             }
@@ -334,19 +521,31 @@ impl<'a, P, C, S> StepLength for PreStepModel<'a, P, C, S> {
 impl<'a, P, C, S> From<&'a CompressedShell<P, C, S>> for StepModel<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
-    fn from(shell: &'a CompressedShell<P, C, S>) -> Self { Self(shell.into()) }
+    fn from(shell: &'a CompressedShell<P, C, S>) -> Self {
+        Self {
+            model: shell.into(),
+            styles: Vec::new(),
+            units: Default::default(),
+        }
+    }
 }
 
 impl<'a, P, C, S> From<&'a CompressedSolid<P, C, S>> for StepModel<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
-    fn from(solid: &'a CompressedSolid<P, C, S>) -> Self { Self(solid.into()) }
+    fn from(solid: &'a CompressedSolid<P, C, S>) -> Self {
+        Self {
+            model: solid.into(),
+            styles: Vec::new(),
+            units: Default::default(),
+        }
+    }
 }
 
 impl<'a, P, C, S> Display for StepModel<'a, P, C, S>
@@ -366,20 +565,76 @@ where
 #7 = PRODUCT('','','', (#8));
 #8 = PRODUCT_CONTEXT('', #2, 'mechanical');
 #9 = PRODUCT_DEFINITION_CONTEXT('part definition', #2, 'design');
-#10 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#16), #11);
-#11 = (
-    GEOMETRIC_REPRESENTATION_CONTEXT(3) 
-    GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#15))
-    GLOBAL_UNIT_ASSIGNED_CONTEXT((#12, #13, #14))
-    REPRESENTATION_CONTEXT('Context #1', '3D Context with UNIT and UNCERTAINTY')
-);
-#12 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );
-#13 = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );
-#14 = ( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() );
-#15 = UNCERTAINTY_MEASURE_WITH_UNIT(1.0E-6, #12, 'distance_accuracy_value','confusion accuracy');\n"
+#10 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#16), #11);\n"
         )?;
-        Display::fmt(&self.0, f)
+        let styles = StyledItemsDisplay {
+            styles: &self.styles,
+            idx: 16 + self.model.step_length(),
+            context_idx: 11,
+        };
+        let context = ContextDisplay {
+            units: &self.units,
+            idx: 11,
+            conversion_idx: styles.idx + styles.step_length(),
+        };
+        Display::fmt(&context, f)?;
+        Display::fmt(&self.model, f)?;
+        Display::fmt(&styles, f)?;
+        let conversions = ConversionUnitsDisplay {
+            units: &self.units,
+            idx: context.conversion_idx,
+        };
+        Display::fmt(&conversions, f)
+    }
+}
+
+fn push_style(styles: &mut Vec<(usize, StepStyle)>, target: usize, style: StepStyle) {
+    styles.retain(|(idx, _)| *idx != target);
+    if style.is_styled() {
+        styles.push((target, style));
+    }
+}
+
+impl<'a, P, C, S> StepModel<'a, P, C, S> {
+    /// Sets the name, the colour and the transparency of the model.
+    /// # Example
+    /// ```
+    /// use truck_modeling::*;
+    /// use truck_stepio::out::*;
+    /// let v = builder::vertex(Point3::origin());
+    /// let e = builder::tsweep(&v, Vector3::unit_x());
+    /// let f = builder::tsweep(&e, Vector3::unit_y());
+    /// let cube = builder::tsweep(&f, Vector3::unit_z()).compress();
+    ///
+    /// let mut model = StepModel::from(&cube);
+    /// model.set_style(StepStyle {
+    ///     name: "cube".to_string(),
+    ///     colour: Some([1.0, 0.0, 0.0]),
+    ///     transparency: None,
+    /// });
+    /// let step_string = CompleteStepDisplay::new(model, Default::default()).to_string();
+    /// assert!(step_string.contains("MANIFOLD_SOLID_BREP('cube'"));
+    /// assert!(step_string.contains("COLOUR_RGB('', 1.0, 0.0, 0.0)"));
+    /// ```
+    pub fn set_style(&mut self, style: StepStyle) {
+        self.model.set_name(&style.name);
+        push_style(&mut self.styles, self.model.root_idx(), style);
+    }
+    /// Sets the name, the colour and the transparency of the `face`-th face.
+    ///
+    /// The faces of solids are numbered through the boundaries.
+    /// # Panics
+    /// Panics if the model does not have the `face`-th face.
+    pub fn set_face_style(&mut self, face: usize, style: StepStyle) {
+        let face_idx = self
+            .model
+            .set_face_name(face, &style.name)
+            .unwrap_or_else(|| panic!("the model does not have the {face}-th face"));
+        push_style(&mut self.styles, face_idx, style);
     }
+    /// Sets the units and the uncertainty, millimetre and radian by default.
+    #[inline]
+    pub fn set_units(&mut self, units: StepUnits) { self.units = units }
 }
 
 impl<'a, P, C, S> Default for StepModels<'a, P, C, S> {
@@ -387,67 +642,169 @@ impl<'a, P, C, S> Default for StepModels<'a, P, C, S> {
         Self {
             models: Vec::new(),
             next_idx: 16,
+            styles: Vec::new(),
+            units: Default::default(),
+            shared: None,
         }
     }
 }
 
+/// the closure sharing the geometry registered in `shared`, never sharing if `None`
+fn share_by<'s, P, C, S>(
+    shared: &'s mut Option<SharedGeometry<P, C, S>>,
+) -> impl FnMut(Geometry<'_, P, C, S>, usize) -> Option<usize> + 's {
+    move |geometry, idx| shared.as_mut()?.share(geometry, idx)
+}
+
 impl<'a, P, C, S> StepModels<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     /// push a shell to step models
     pub fn push_shell(&mut self, shell: &'a CompressedShell<P, C, S>) {
-        let model = PreStepModel::Shell(StepShell::new(shell, self.next_idx + 1, true));
+        let share = share_by(&mut self.shared);
+        let model = PreStepModel::Shell(StepShell::with_sharing(
+            shell,
+            self.next_idx + 1,
+            true,
+            share,
+        ));
         self.next_idx += model.step_length();
         self.models.push(model)
     }
     /// push a solid to step models
     pub fn push_solid(&mut self, solid: &'a CompressedSolid<P, C, S>) {
-        let model = PreStepModel::Solid(StepSolid::new(solid, self.next_idx));
+        let share = share_by(&mut self.shared);
+        let model = PreStepModel::Solid(StepSolid::with_sharing(solid, self.next_idx, share));
         self.next_idx += model.step_length();
         self.models.push(model)
     }
 }
 
+impl<'a, P, C, S> StepModels<'a, P, C, S> {
+    /// Sets the name, the colour and the transparency of the `model`-th pushed model.
+    /// # Panics
+    /// Panics if `model` is not less than the number of the pushed models.
+    pub fn set_style(&mut self, model: usize, style: StepStyle) {
+        let model = &mut self.models[model];
+        model.set_name(&style.name);
+        push_style(&mut self.styles, model.root_idx(), style);
+    }
+    /// Sets the name, the colour and the transparency of the `face`-th face of the `model`-th model.
+    ///
+    /// The faces of solids are numbered through the boundaries.
+    /// # Panics
+    /// Panics if the model or the face does not exist.
+    pub fn set_face_style(&mut self, model: usize, face: usize, style: StepStyle) {
+        let face_idx = self.models[model]
+            .set_face_name(face, &style.name)
+            .unwrap_or_else(|| panic!("the {model}-th model does not have the {face}-th face"));
+        push_style(&mut self.styles, face_idx, style);
+    }
+    /// Sets the units and the uncertainty, millimetre and radian by default.
+    /// # Example
+    /// ```
+    /// use truck_modeling::*;
+    /// use truck_stepio::out::*;
+    /// let v = builder::vertex(Point3::origin());
+    /// let e = builder::tsweep(&v, Vector3::unit_x());
+    /// let f = builder::tsweep(&e, Vector3::unit_y());
+    /// let cube = builder::tsweep(&f, Vector3::unit_z()).compress();
+    ///
+    /// let mut models = StepModels::default();
+    /// models.push_solid(&cube);
+    /// models.set_units(StepUnits {
+    ///     length: StepLengthUnit::Inch,
+    ///     angle: StepAngleUnit::Degree,
+    ///     uncertainty: 1.0e-5,
+    /// });
+    /// let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    /// assert!(step_string.contains("CONVERSION_BASED_UNIT('INCH'"));
+    /// ruststep::parser::parse(&step_string).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_units(&mut self, units: StepUnits) { self.units = units }
+}
+
+impl<'a, P, C, S> StepModels<'a, P, C, S>
+where
+    P: DisplayByStep + Copy,
+    C: DisplayByStep + StepLength + StepCurve,
+    S: DisplayByStep + StepLength,
+{
+    /// Shares the identical points of the vertices, curves of the edges and surfaces of the faces
+    /// among all the models, comparing the real numbers by `tolerance`.
+    /// `None`, the default, writes every entity.
+    ///
+    /// The models pushed before are laid out again with their names and styles kept,
+    /// and the models pushed after share the geometry as they are pushed.
+    /// # Example
+    /// ```
+    /// use truck_modeling::*;
+    /// use truck_stepio::out::*;
+    /// let v = builder::vertex(Point3::origin());
+    /// let e = builder::tsweep(&v, Vector3::unit_x());
+    /// let f = builder::tsweep(&e, Vector3::unit_y());
+    /// let cube = builder::tsweep(&f, Vector3::unit_z()).compress();
+    /// let plate = Shell::from(vec![f]).compress();
+    ///
+    /// let mut models = StepModels::default();
+    /// models.push_solid(&cube);
+    /// models.push_shell(&plate);
+    /// let verbatim = CompleteStepDisplay::new(models.clone(), Default::default()).to_string();
+    /// models.set_deduplication(Some(1.0e-6));
+    /// let shared = CompleteStepDisplay::new(models, Default::default()).to_string();
+    /// // the plate shares its plane with the bottom of the cube.
+    /// assert!(shared.matches("PLANE(").count() < verbatim.matches("PLANE(").count());
+    /// ruststep::parser::parse(&shared).unwrap();
+    /// ```
+    pub fn set_deduplication(&mut self, tolerance: Option<f64>) {
+        self.shared = tolerance.map(SharedGeometry::new);
+        let mut next_idx = 16;
+        let mut targets = HashMap::new();
+        for model in &mut self.models {
+            let relaid = model.relayout(next_idx, share_by(&mut self.shared));
+            next_idx += relaid.step_length();
+            targets.extend(
+                model
+                    .style_targets()
+                    .into_iter()
+                    .zip(relaid.style_targets()),
+            );
+            *model = relaid;
+        }
+        self.next_idx = next_idx;
+        self.styles
+            .iter_mut()
+            .for_each(|(target, _)| *target = targets[&*target]);
+    }
+}
+
 impl<'a, P, C, S> FromIterator<&'a CompressedShell<P, C, S>> for StepModels<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn from_iter<T: IntoIterator<Item = &'a CompressedShell<P, C, S>>>(iter: T) -> Self {
-        let mut next_idx = 16;
-        let models = iter
-            .into_iter()
-            .map(|shell| {
-                let model = PreStepModel::Shell(StepShell::new(shell, next_idx + 1, true));
-                next_idx += model.step_length();
-                model
-            })
-            .collect();
-        Self { models, next_idx }
+        let mut models = Self::default();
+        iter.into_iter().for_each(|shell| models.push_shell(shell));
+        models
     }
 }
 
 impl<'a, P, C, S> FromIterator<&'a CompressedSolid<P, C, S>> for StepModels<'a, P, C, S>
 where
     P: Copy,
-    C: StepLength,
+    C: StepLength + StepCurve,
     S: StepLength,
 {
     fn from_iter<T: IntoIterator<Item = &'a CompressedSolid<P, C, S>>>(iter: T) -> Self {
-        let mut next_idx = 16;
-        let models = iter
-            .into_iter()
-            .map(|solid| {
-                let model = PreStepModel::Solid(StepSolid::new(solid, next_idx));
-                next_idx += model.step_length();
-                model
-            })
-            .collect();
-        Self { models, next_idx }
+        let mut models = Self::default();
+        iter.into_iter().for_each(|solid| models.push_solid(solid));
+        models
     }
 }
 
@@ -458,6 +815,32 @@ where
     S: DisplayByStep + StepLength + StepSurface,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.fmt_head(f)?;
+        self.models
+            .iter()
+            .try_for_each(|model| Display::fmt(model, f))?;
+        self.fmt_tail(f)
+    }
+}
+
+impl<'a, P, C, S> StepModels<'a, P, C, S> {
+    fn styles_display(&self) -> StyledItemsDisplay<'_> {
+        StyledItemsDisplay {
+            styles: &self.styles,
+            idx: self.next_idx,
+            context_idx: 11,
+        }
+    }
+    fn context_display(&self) -> ContextDisplay<'_> {
+        let styles = self.styles_display();
+        ContextDisplay {
+            units: &self.units,
+            idx: 11,
+            conversion_idx: styles.idx + styles.step_length(),
+        }
+    }
+    /// Writes the product, the shape representation and the context, before the models.
+    pub(super) fn fmt_head(&self, f: &mut Formatter<'_>) -> Result {
         f.pad(
 "#1 = APPLICATION_PROTOCOL_DEFINITION('international standard', 'automotive_design', 2000, #2);
 #2 = APPLICATION_CONTEXT('core data for automotive mechanical design processes');
@@ -468,26 +851,19 @@ where
 #7 = PRODUCT('','','', (#8));
 #8 = PRODUCT_CONTEXT('', #2, 'mechanical');
 #9 = PRODUCT_DEFINITION_CONTEXT('part definition', #2, 'design');\n")?;
-        let models_slice = IndexSliceDisplay(self.models.iter().map(|model| match model {
-            PreStepModel::Shell(x) => x.idx - 1,
-            PreStepModel::Solid(x) => x.idx,
-        }));
+        let models_slice = IndexSliceDisplay(self.models.iter().map(PreStepModel::root_idx));
         f.write_fmt(format_args!(
             "#10 = ADVANCED_BREP_SHAPE_REPRESENTATION('', {models_slice}, #11);\n"
         ))?;
-        f.pad("#11 = (
-    GEOMETRIC_REPRESENTATION_CONTEXT(3) 
-    GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#15))
-    GLOBAL_UNIT_ASSIGNED_CONTEXT((#12, #13, #14))
-    REPRESENTATION_CONTEXT('Context #1', '3D Context with UNIT and UNCERTAINTY')
-);
-#12 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );
-#13 = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );
-#14 = ( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() );
-#15 = UNCERTAINTY_MEASURE_WITH_UNIT(1.0E-6, #12, 'distance_accuracy_value','confusion accuracy');\n"
-        )?;
-        self.models
-            .iter()
-            .try_for_each(|model| Display::fmt(model, f))
+        Display::fmt(&self.context_display(), f)
+    }
+    /// Writes the styles and the units, after the models.
+    pub(super) fn fmt_tail(&self, f: &mut Formatter<'_>) -> Result {
+        Display::fmt(&self.styles_display(), f)?;
+        let conversions = ConversionUnitsDisplay {
+            units: &self.units,
+            idx: self.context_display().conversion_idx,
+        };
+        Display::fmt(&conversions, f)
     }
 }
@@ -0,0 +1,147 @@
+use super::{Result, *};
+
+/// Length units of STEP file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepLengthUnit {
+    /// millimetre, `SI_UNIT(.MILLI., .METRE.)`
+    #[default]
+    Millimetre,
+    /// metre, `SI_UNIT($, .METRE.)`
+    Metre,
+    /// inch, converted from millimetre
+    Inch,
+}
+
+/// Plane angle units of STEP file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepAngleUnit {
+    /// radian, `SI_UNIT($, .RADIAN.)`
+    #[default]
+    Radian,
+    /// degree, converted from radian
+    Degree,
+}
+
+/// Units and uncertainty of the geometry in STEP file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepUnits {
+    /// the length unit, millimetre by default
+    pub length: StepLengthUnit,
+    /// the plane angle unit, radian by default
+    pub angle: StepAngleUnit,
+    /// the distance regarded as zero in the length unit, `1.0e-6` by default
+    pub uncertainty: f64,
+}
+
+impl Default for StepUnits {
+    fn default() -> Self {
+        Self {
+            length: StepLengthUnit::Millimetre,
+            angle: StepAngleUnit::Radian,
+            uncertainty: 1.0e-6,
+        }
+    }
+}
+
+impl StepUnits {
+    /// the entity index of the angle conversion, relative to the one of the length conversion
+    fn angle_conversion_offset(&self) -> usize {
+        match self.length {
+            StepLengthUnit::Inch => 3,
+            _ => 0,
+        }
+    }
+}
+
+/// The representation context and the units, 5 lines from `idx`.
+///
+/// The records of the conversion based units are written by [`ConversionUnitsDisplay`].
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ContextDisplay<'a> {
+    pub units: &'a StepUnits,
+    /// the index of the representation context
+    pub idx: usize,
+    /// the index of the first record written by [`ConversionUnitsDisplay`]
+    pub conversion_idx: usize,
+}
+
+impl<'a> Display for ContextDisplay<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let ContextDisplay {
+            units,
+            idx,
+            conversion_idx,
+        } = *self;
+        let (length_idx, angle_idx, solid_angle_idx, uncertainty_idx) =
+            (idx + 1, idx + 2, idx + 3, idx + 4);
+        f.write_fmt(format_args!(
+            "#{idx} = (
+    GEOMETRIC_REPRESENTATION_CONTEXT(3)
+    GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#{uncertainty_idx}))
+    GLOBAL_UNIT_ASSIGNED_CONTEXT((#{length_idx}, #{angle_idx}, #{solid_angle_idx}))
+    REPRESENTATION_CONTEXT('Context #1', '3D Context with UNIT and UNCERTAINTY')
+);\n"
+        ))?;
+        match units.length {
+            StepLengthUnit::Millimetre => f.write_fmt(format_args!(
+                "#{length_idx} = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );\n"
+            )),
+            StepLengthUnit::Metre => f.write_fmt(format_args!(
+                "#{length_idx} = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT($,.METRE.) );\n"
+            )),
+            StepLengthUnit::Inch => f.write_fmt(format_args!(
+                "#{length_idx} = ( CONVERSION_BASED_UNIT('INCH', #{conversion_idx}) LENGTH_UNIT() NAMED_UNIT(#{exponents_idx}) );\n",
+                exponents_idx = conversion_idx + 1,
+            )),
+        }?;
+        match units.angle {
+            StepAngleUnit::Radian => f.write_fmt(format_args!(
+                "#{angle_idx} = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );\n"
+            )),
+            StepAngleUnit::Degree => f.write_fmt(format_args!(
+                "#{angle_idx} = ( CONVERSION_BASED_UNIT('DEGREE', #{measure_idx}) NAMED_UNIT(#{exponents_idx}) PLANE_ANGLE_UNIT() );\n",
+                measure_idx = conversion_idx + units.angle_conversion_offset(),
+                exponents_idx = conversion_idx + units.angle_conversion_offset() + 1,
+            )),
+        }?;
+        f.write_fmt(format_args!(
+            "#{solid_angle_idx} = ( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() );
+#{uncertainty_idx} = UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE({uncertainty}), #{length_idx}, 'distance_accuracy_value', 'confusion accuracy');\n",
+            uncertainty = FloatDisplay(units.uncertainty),
+        ))
+    }
+}
+
+/// The records of the conversion based units, e.g. inch and degree.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ConversionUnitsDisplay<'a> {
+    pub units: &'a StepUnits,
+    pub idx: usize,
+}
+
+impl<'a> Display for ConversionUnitsDisplay<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let ConversionUnitsDisplay { units, idx } = *self;
+        if units.length == StepLengthUnit::Inch {
+            f.write_fmt(format_args!(
+                "#{idx} = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4), #{mm_idx});
+#{exponents_idx} = DIMENSIONAL_EXPONENTS(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+#{mm_idx} = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );\n",
+                exponents_idx = idx + 1,
+                mm_idx = idx + 2,
+            ))?;
+        }
+        if units.angle == StepAngleUnit::Degree {
+            let idx = idx + units.angle_conversion_offset();
+            f.write_fmt(format_args!(
+                "#{idx} = PLANE_ANGLE_MEASURE_WITH_UNIT(PLANE_ANGLE_MEASURE({radian}), #{rad_idx});
+#{exponents_idx} = DIMENSIONAL_EXPONENTS(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+#{rad_idx} = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );\n",
+                radian = FloatDisplay(std::f64::consts::PI / 180.0),
+                exponents_idx = idx + 1,
+                rad_idx = idx + 2,
+            ))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Streams STEP files to [`io::Write`] without formatting them into a `String`.
+
+use super::{
+    CompleteStepDisplay, Display, DisplayByStep, FnDisplay, StepCurve, StepHeader,
+    StepHeaderDescriptor, StepLength, StepModels, StepSurface,
+};
+use std::fmt::{self, Write as _};
+use std::io::{self, BufWriter, Write};
+
+/// forwards the formatted strings to the writer, keeping the I/O error
+struct IoAdapter<'w, W> {
+    writer: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<'w, W: Write> fmt::Write for IoAdapter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Writes `display` to `writer`, with the I/O errors as they are
+/// and the errors of the formatting as [`io::ErrorKind::InvalidData`].
+fn write_formatted<W: Write>(writer: &mut W, display: impl Display) -> io::Result<()> {
+    let mut adapter = IoAdapter {
+        writer,
+        error: None,
+    };
+    write!(adapter, "{display}").map_err(|_| match adapter.error {
+        Some(e) => e,
+        None => io::Error::new(
+            io::ErrorKind::InvalidData,
+            "failed to output the entities to STEP file",
+        ),
+    })
+}
+
+/// Writer of STEP files to [`io::Write`] through a buffer.
+///
+/// The models are output one by one, so the file is never held in memory as a whole.
+/// # Example
+/// ```
+/// use truck_modeling::*;
+/// use truck_stepio::out::*;
+/// let v = builder::vertex(Point3::origin());
+/// let e = builder::tsweep(&v, Vector3::unit_x());
+/// let f = builder::tsweep(&e, Vector3::unit_y());
+/// let cube = builder::tsweep(&f, Vector3::unit_z()).compress();
+///
+/// let mut models = StepModels::default();
+/// models.push_solid(&cube);
+/// models.push_solid(&cube);
+///
+/// let mut progress = Vec::new();
+/// let mut writer = StepWriter::new(Vec::new(), Default::default());
+/// writer.set_progress(|written, total| progress.push((written, total)));
+/// writer.write_models(&models).unwrap();
+/// let step_bytes = writer.into_inner().unwrap();
+///
+/// assert_eq!(progress, vec![(1, 2), (2, 2)]);
+/// let step_string = String::from_utf8(step_bytes).unwrap();
+/// ruststep::parser::parse(&step_string).unwrap();
+/// ```
+pub struct StepWriter<'a, W: Write> {
+    writer: BufWriter<W>,
+    header: StepHeader,
+    progress: Option<Box<dyn FnMut(usize, usize) + 'a>>,
+}
+
+impl<'a, W: Write> fmt::Debug for StepWriter<'a, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StepWriter")
+            .field("header", &self.header)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl<'a, W: Write> StepWriter<'a, W> {
+    /// constructor
+    #[inline]
+    pub fn new(writer: W, header: StepHeaderDescriptor) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            header: header.into(),
+            progress: None,
+        }
+    }
+    /// Sets the callback called with the numbers of the written models and of all the models
+    /// each time a model has been written.
+    #[inline]
+    pub fn set_progress(&mut self, progress: impl FnMut(usize, usize) + 'a) {
+        self.progress = Some(Box::new(progress));
+    }
+    fn report(&mut self, written: usize, total: usize) {
+        if let Some(progress) = &mut self.progress {
+            progress(written, total)
+        }
+    }
+    /// Writes the file of `models`, reporting the progress model by model.
+    pub fn write_models<P, C, S>(&mut self, models: &StepModels<'_, P, C, S>) -> io::Result<()>
+    where
+        P: DisplayByStep + Copy,
+        C: DisplayByStep + StepLength + StepCurve,
+        S: DisplayByStep + StepLength + StepSurface, {
+        let total = models.models.len();
+        write_formatted(
+            &mut self.writer,
+            format_args!("ISO-10303-21;\n{}DATA;\n", self.header),
+        )?;
+        write_formatted(
+            &mut self.writer,
+            FnDisplay(|f: &mut fmt::Formatter<'_>| models.fmt_head(f)),
+        )?;
+        for (i, model) in models.models.iter().enumerate() {
+            write_formatted(&mut self.writer, model)?;
+            self.report(i + 1, total);
+        }
+        write_formatted(
+            &mut self.writer,
+            FnDisplay(|f: &mut fmt::Formatter<'_>| models.fmt_tail(f)),
+        )?;
+        self.writer.write_all(b"ENDSEC;\nEND-ISO-10303-21;\n")
+    }
+    /// Writes the file of `display`, e.g. [`StepModel`](super::StepModel) or
+    /// [`StepAssembly`](super::StepAssembly), as [`CompleteStepDisplay`] does.
+    pub fn write_display(&mut self, display: impl Display) -> io::Result<()> {
+        let complete = CompleteStepDisplay {
+            display,
+            header: self.header.clone(),
+        };
+        write_formatted(&mut self.writer, complete)
+    }
+    /// Flushes the buffer and returns the underlying writer.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
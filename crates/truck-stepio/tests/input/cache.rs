@@ -0,0 +1,101 @@
+use std::io;
+use truck_stepio::r#in::{cache::*, tessellation::*, *};
+
+const STEP_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../resources/step/");
+
+const STEP_FILES: &[&str] = &[
+    "occt-cone.step",
+    "occt-cube.step",
+    "occt-cylinder.step",
+    "occt-sphere.step",
+    "occt-torus.step",
+    "abc-0000.step",
+];
+
+fn written(cache: &StepCache) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    cache.write(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn cache_roundtrip() {
+    STEP_FILES.iter().for_each(|file_name| {
+        let step_string = std::fs::read_to_string([STEP_DIRECTORY, file_name].concat()).unwrap();
+        let cache = StepCache::from_step(&step_string).unwrap();
+        assert!(
+            cache.shells.iter().all(|(_, shell)| shell.is_ok()),
+            "{file_name}"
+        );
+
+        let bytes = written(&cache);
+        assert!(bytes.len() < step_string.len(), "{file_name}");
+        let reloaded = StepCache::read(bytes.as_slice()).unwrap();
+        assert_eq!(written(&reloaded), bytes, "{file_name}");
+
+        let view = ViewTolerance::new((256, 256), ViewCamera::Fit);
+        let table = Table::from_step(&step_string).unwrap();
+        let expected = table.triangulate_shells_for_view(&view);
+        let meshed = reloaded.triangulate_shells_for_view(&view);
+        assert_eq!(meshed.len(), expected.len());
+        meshed
+            .iter()
+            .zip(&expected)
+            .for_each(|((idx0, shell0), (idx1, shell1))| {
+                assert_eq!(idx0, idx1);
+                let (shell0, shell1) = (shell0.as_ref().unwrap(), shell1.as_ref().unwrap());
+                assert_eq!(shell0.faces.len(), shell1.faces.len(), "{file_name}");
+                assert_eq!(shell0.vertices, shell1.vertices, "{file_name}");
+            });
+    });
+}
+
+#[test]
+fn invalid_caches() {
+    let error = StepCache::read(b"ISO-10303-21;\nHEADER;".as_slice()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    let mut bytes = written(&StepCache::default());
+    bytes[8] += 1;
+    let error = StepCache::read(bytes.as_slice()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    bytes[8] -= 1;
+    bytes.truncate(bytes.len() - 1);
+    assert!(StepCache::read(bytes.as_slice()).is_err());
+}
+
+#[test]
+fn load_or_create() {
+    let directory = std::env::temp_dir().join(format!("truck-stepio-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let (step_path, cache_path) = (directory.join("model.step"), directory.join("model.cache"));
+    let cube = std::fs::read_to_string([STEP_DIRECTORY, "occt-cube.step"].concat()).unwrap();
+    let cylinder =
+        std::fs::read_to_string([STEP_DIRECTORY, "occt-cylinder.step"].concat()).unwrap();
+
+    std::fs::write(&step_path, &cube).unwrap();
+    let created = StepCache::load_or_create(&step_path, &cache_path).unwrap();
+    assert!(created.is_cache_of(cube.as_bytes()));
+    let saved = std::fs::read(&cache_path).unwrap();
+    let reloaded = StepCache::load_or_create(&step_path, &cache_path).unwrap();
+    assert_eq!(written(&reloaded), saved);
+    assert_eq!(std::fs::read(&cache_path).unwrap(), saved);
+
+    // the outdated and the broken caches are rebuilt.
+    std::fs::write(&step_path, &cylinder).unwrap();
+    let rebuilt = StepCache::load_or_create(&step_path, &cache_path).unwrap();
+    assert!(rebuilt.is_cache_of(cylinder.as_bytes()));
+    std::fs::write(&cache_path, b"broken").unwrap();
+    let rebuilt = StepCache::load_or_create(&step_path, &cache_path).unwrap();
+    assert!(rebuilt.is_cache_of(cylinder.as_bytes()));
+    assert_eq!(std::fs::read(&cache_path).unwrap(), written(&rebuilt));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn stable_hash() {
+    assert_eq!(source_hash(b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(source_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+}
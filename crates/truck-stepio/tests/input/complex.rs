@@ -0,0 +1,165 @@
+use ruststep::{ast::DataSection, tables::EntityTable};
+use std::str::FromStr;
+use truck_geometry::prelude::*;
+use truck_stepio::r#in::*;
+
+const POINTS: &str = "#2 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#3 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#4 = CARTESIAN_POINT('', (1.0, 1.0, 0.0));
+#5 = CARTESIAN_POINT('', (0.0, 1.0, 1.0));
+";
+
+fn table(entity: &str) -> Table {
+    let step = format!("DATA;\n#1 = {entity};\n{POINTS}ENDSEC;");
+    Table::from_data_section(&DataSection::from_str(&step).unwrap())
+}
+
+/// pairs of a complex instance and the simple instance equivalent to it
+const NON_RATIONAL_CURVES: [(&str, &str); 5] = [
+    (
+        "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+        B_SPLINE_CURVE_WITH_KNOTS((3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.) CURVE()
+        GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM('curve'))",
+        "B_SPLINE_CURVE_WITH_KNOTS('curve', 2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.,
+        (3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.)",
+    ),
+    // without the optional supertypes
+    (
+        "(B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+        B_SPLINE_CURVE_WITH_KNOTS((3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.))",
+        "B_SPLINE_CURVE_WITH_KNOTS($, 2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.,
+        (3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.)",
+    ),
+    (
+        "(BEZIER_CURVE() BOUNDED_CURVE() B_SPLINE_CURVE(3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+        CURVE() GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM(''))",
+        "BEZIER_CURVE('', 3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)",
+    ),
+    (
+        "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) CURVE()
+        GEOMETRIC_REPRESENTATION_ITEM() QUASI_UNIFORM_CURVE() REPRESENTATION_ITEM(''))",
+        "QUASI_UNIFORM_CURVE('', 2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)",
+    ),
+    (
+        "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) CURVE()
+        GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM('') UNIFORM_CURVE())",
+        "UNIFORM_CURVE('', 2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)",
+    ),
+];
+
+#[test]
+fn non_rational_curves() {
+    NON_RATIONAL_CURVES.iter().for_each(|(complex, simple)| {
+        let (table0, table1) = (table(complex), table(simple));
+        assert!(table0.dummy.is_empty(), "{complex}");
+        assert_eq!(table0, table1);
+    });
+}
+
+const RATIONAL_CURVES: [&str; 5] = [
+    "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+    B_SPLINE_CURVE_WITH_KNOTS((3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.) CURVE()
+    GEOMETRIC_REPRESENTATION_ITEM() RATIONAL_B_SPLINE_CURVE((1.0, 2.0, 3.0, 4.0))
+    REPRESENTATION_ITEM(''))",
+    // without the optional supertypes
+    "(B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+    B_SPLINE_CURVE_WITH_KNOTS((3, 1, 3), (0.0, 0.5, 1.0), .UNSPECIFIED.)
+    RATIONAL_B_SPLINE_CURVE((1.0, 2.0, 3.0, 4.0)))",
+    "(BEZIER_CURVE() BOUNDED_CURVE() B_SPLINE_CURVE(3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.)
+    CURVE() GEOMETRIC_REPRESENTATION_ITEM() RATIONAL_B_SPLINE_CURVE((1.0, 2.0, 3.0, 4.0))
+    REPRESENTATION_ITEM(''))",
+    "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) CURVE()
+    GEOMETRIC_REPRESENTATION_ITEM() QUASI_UNIFORM_CURVE() RATIONAL_B_SPLINE_CURVE((1.0, 2.0, 3.0, 4.0))
+    REPRESENTATION_ITEM(''))",
+    "(BOUNDED_CURVE() B_SPLINE_CURVE(2, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) CURVE()
+    GEOMETRIC_REPRESENTATION_ITEM() RATIONAL_B_SPLINE_CURVE((1.0, 2.0, 3.0, 4.0))
+    REPRESENTATION_ITEM('') UNIFORM_CURVE())",
+];
+
+#[test]
+fn rational_curves() {
+    RATIONAL_CURVES.iter().for_each(|complex| {
+        let table = table(complex);
+        assert!(table.dummy.is_empty(), "{complex}");
+        let curve = EntityTable::<RationalBSplineCurveHolder>::get_owned(&table, 1).unwrap();
+        let nurbs = NurbsCurve::<Vector4>::try_from(&curve).unwrap();
+        assert_eq!(nurbs.control_points().len(), 4);
+        assert_eq!(nurbs.control_point(3)[3], 4.0);
+    });
+}
+
+const SURFACE_CONTROL_POINTS: &str = "((#2, #3), (#4, #5))";
+
+#[test]
+fn surfaces() {
+    let corpus = [
+        (
+            format!(
+                "(BOUNDED_SURFACE() B_SPLINE_SURFACE(1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)
+                B_SPLINE_SURFACE_WITH_KNOTS((2, 2), (2, 2), (0.0, 1.0), (0.0, 1.0), .UNSPECIFIED.)
+                GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM('') SURFACE())"
+            ),
+            format!(
+                "B_SPLINE_SURFACE_WITH_KNOTS('', 1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.,
+                (2, 2), (2, 2), (0.0, 1.0), (0.0, 1.0), .UNSPECIFIED.)"
+            ),
+        ),
+        (
+            format!(
+                "(BEZIER_SURFACE() BOUNDED_SURFACE() B_SPLINE_SURFACE(1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)
+                GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM('') SURFACE())"
+            ),
+            format!("BEZIER_SURFACE('', 1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)"),
+        ),
+        (
+            format!(
+                "(BOUNDED_SURFACE() B_SPLINE_SURFACE(1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)
+                GEOMETRIC_REPRESENTATION_ITEM() QUASI_UNIFORM_SURFACE() REPRESENTATION_ITEM('') SURFACE())"
+            ),
+            format!("QUASI_UNIFORM_SURFACE('', 1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)"),
+        ),
+        (
+            format!(
+                "(BOUNDED_SURFACE() B_SPLINE_SURFACE(1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)
+                GEOMETRIC_REPRESENTATION_ITEM() REPRESENTATION_ITEM('') SURFACE() UNIFORM_SURFACE())"
+            ),
+            format!("UNIFORM_SURFACE('', 1, 1, {SURFACE_CONTROL_POINTS}, .UNSPECIFIED., .U., .U., .U.)"),
+        ),
+    ];
+    corpus.iter().for_each(|(complex, simple)| {
+        let (table0, table1) = (table(complex), table(simple));
+        assert!(table0.dummy.is_empty(), "{complex}");
+        assert_eq!(table0, table1);
+
+        // add rational part
+        let rational = complex.replace(
+            "REPRESENTATION_ITEM('')",
+            "RATIONAL_B_SPLINE_SURFACE(((1.0, 2.0), (3.0, 4.0))) REPRESENTATION_ITEM('')",
+        );
+        let table = table(&rational);
+        assert!(table.dummy.is_empty(), "{rational}");
+        let surface = EntityTable::<RationalBSplineSurfaceHolder>::get_owned(&table, 1).unwrap();
+        let nurbs = NurbsSurface::<Vector4>::try_from(&surface).unwrap();
+        assert_eq!(nurbs.control_point(1, 1)[3], 4.0);
+    });
+}
+
+#[test]
+fn not_b_spline() {
+    let corpus = [
+        // unit context is not a geometry
+        "(GEOMETRIC_REPRESENTATION_CONTEXT(3) GLOBAL_UNIT_ASSIGNED_CONTEXT((#2)) REPRESENTATION_CONTEXT('', ''))",
+        // the knot specification is ambiguous
+        "(BEZIER_CURVE() B_SPLINE_CURVE(3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) UNIFORM_CURVE())",
+        // the knot specification is missing
+        "(BOUNDED_CURVE() B_SPLINE_CURVE(3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) CURVE())",
+        // an unknown subtype
+        "(B_SPLINE_CURVE(3, (#2, #3, #4, #5), .UNSPECIFIED., .F., .U.) BEZIER_CURVE() UNKNOWN_CURVE())",
+    ];
+    corpus.iter().for_each(|complex| {
+        let table = table(complex);
+        assert!(table.dummy.contains_key(&1), "{complex}");
+        assert!(table.rational_b_spline_curve.is_empty());
+        assert!(table.bezier_curve.is_empty());
+    });
+}
@@ -0,0 +1,70 @@
+use ruststep::{ast::DataSection, tables::EntityTable};
+use std::str::FromStr;
+use truck_stepio::r#in::*;
+
+#[allow(dead_code)]
+#[path = "../../examples/express-codegen.rs"]
+mod express_codegen;
+
+#[test]
+fn generated_holders_are_up_to_date() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    for (path, content) in express_codegen::generate_files(dir).unwrap() {
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            current == content,
+            "{} is outdated; run `cargo run --example express-codegen`",
+            path.display(),
+        );
+    }
+}
+
+#[test]
+fn read_generated_entities() {
+    let data_section = DataSection::from_str(
+        "DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = CARTESIAN_POINT('', (1.0, 1.0, 0.0));
+#4 = DIRECTION('', (1.0, 0.0, 0.0));
+#5 = VECTOR('', #4, 1.0);
+#6 = LINE('', #1, #5);
+#7 = POLYLINE('', (#1, #2, #3));
+#8 = COMPOSITE_CURVE_SEGMENT(.CONTINUOUS., .T., #7);
+#9 = COMPOSITE_CURVE_SEGMENT(.DISCONTINUOUS., .F., #6);
+#10 = COMPOSITE_CURVE('CompositeCurve', (#8, #9), .F.);
+#11 = POINT_ON_CURVE('PointOnCurve', #6, 0.5);
+#12 = POLY_LOOP('PolyLoop', (#1, #2, #3));
+#13 = VERTEX_POINT('', #1);
+#14 = VERTEX_LOOP('VertexLoop', #13);
+ENDSEC;
+",
+    )
+    .unwrap();
+    let table = Table::from_data_section(&data_section);
+    assert!(table.dummy.is_empty());
+    assert_eq!(table.composite_curve_segment.len(), 2);
+
+    let curve = EntityTable::<CompositeCurveHolder>::get_owned(&table, 10).unwrap();
+    assert_eq!(curve.label.as_deref(), Some("CompositeCurve"));
+    assert_eq!(curve.self_intersect, ruststep::primitive::Logical::False);
+    assert_eq!(curve.segments.len(), 2);
+    assert_eq!(curve.segments[0].transition, TransitionCode::Continuous);
+    assert!(curve.segments[0].same_sense);
+    assert!(matches!(
+        curve.segments[0].parent_curve,
+        CurveAny::BoundedCurve(_)
+    ));
+    assert_eq!(curve.segments[1].transition, TransitionCode::Discontinuous);
+    assert!(matches!(curve.segments[1].parent_curve, CurveAny::Line(_)));
+
+    let point = EntityTable::<PointOnCurveHolder>::get_owned(&table, 11).unwrap();
+    assert_eq!(point.point_parameter, 0.5);
+
+    let poly_loop = EntityTable::<PolyLoopHolder>::get_owned(&table, 12).unwrap();
+    let polygon: Vec<_> = poly_loop.polygon.iter().map(|p| p.coordinates[1]).collect();
+    assert_eq!(polygon, vec![0.0, 0.0, 1.0]);
+
+    // `vertex` has no `*Any` enum, so the vertex is kept as the reference to `Dummy`.
+    assert!(table.vertex_loop.contains_key(&14));
+}
@@ -935,6 +935,90 @@ proptest! {
     }
 }
 
+fn exec_offset_surface(
+    org_coord: [f64; 3],
+    dir_array: [f64; 2],
+    ref_dir_array: [f64; 2],
+    distance: f64,
+) {
+    let origin = Point3::from(org_coord);
+    let z = dir_from_array(dir_array);
+    let ref_dir = dir_from_array(ref_dir_array);
+    let v = z.cross(ref_dir);
+    let y = match v.so_small() {
+        true => return,
+        false => v.normalize(),
+    };
+    let x = y.cross(z).normalize();
+    let step_str = format!(
+        "DATA;
+#1 = OFFSET_SURFACE('', #2, {}, .F.);
+#2 = PLANE('', #3);
+#3 = AXIS2_PLACEMENT_3D('', #4, #5, #6);
+{}{}{}ENDSEC;",
+        float_to_str(distance),
+        StepDisplay::new(origin, 4),
+        StepDisplay::new(VectorAsDirection(z), 5),
+        StepDisplay::new(VectorAsDirection(ref_dir.normalize()), 6),
+    );
+    let step_offset = step_to_entity::<OffsetSurfaceHolder>(&step_str);
+    let offset = alias::OffsetSurface::<alias::Surface>::try_from(&step_offset).unwrap();
+    (0..=10)
+        .flat_map(move |i| (0..=10).map(move |j| (i, j)))
+        .for_each(|(i, j)| {
+            let (u, v) = (i as f64 - 5.0, j as f64 - 5.0);
+            let res = offset.subs(u, v);
+            let ans = origin + u * x + v * y + distance * z;
+            assert_near!(res, ans, "u:{u} v:{v} res:{res:?} ans:{ans:?}");
+            assert_near!(offset.uder(u, v), x);
+            assert_near!(offset.vder(u, v), y);
+        })
+}
+
+proptest! {
+    #[test]
+    fn offset_surface(
+        org_coord in array::uniform3(-100.0f64..100.0f64),
+        dir_array in array::uniform2(0.0f64..1.0f64),
+        ref_dir_array in array::uniform2(0.0f64..1.0f64),
+        distance in -100.0f64..100.0f64,
+    ) {
+        exec_offset_surface(org_coord, dir_array, ref_dir_array, distance)
+    }
+}
+
+#[test]
+fn bounded_plane() {
+    let plane = truck::Plane::new(
+        Point3::new(1.0, 2.0, 3.0),
+        Point3::new(2.0, 2.0, 3.0),
+        Point3::new(1.0, 3.0, 3.0),
+    );
+    let ans = alias::Surface::ElementarySurface(Box::new(alias::ElementarySurface::Plane(plane)));
+    let basis = format!(
+        "#2 = PLANE('', #3);
+#3 = AXIS2_PLACEMENT_3D('', #4, #5, #6);
+{}{}{}",
+        StepDisplay::new(Point3::new(1.0, 2.0, 3.0), 4),
+        StepDisplay::new(VectorAsDirection(Vector3::unit_z()), 5),
+        StepDisplay::new(VectorAsDirection(Vector3::unit_x()), 6),
+    );
+
+    let step_str = format!(
+        "DATA;\n#1 = RECTANGULAR_TRIMMED_SURFACE('', #2, 0.0, 1.0, 0.0, 2.0, .T., .T.);\n{basis}ENDSEC;"
+    );
+    let step_surface = step_to_entity::<SurfaceAnyHolder>(&step_str);
+    let surface = alias::Surface::try_from(&step_surface).unwrap();
+    assert_eq!(surface, ans);
+
+    let step_str = format!(
+        "DATA;\n#1 = CURVE_BOUNDED_SURFACE('', #2, (#7), .F.);\n{basis}#7 = BOUNDARY_CURVE('', (), .T.);\nENDSEC;"
+    );
+    let step_surface = step_to_entity::<SurfaceAnyHolder>(&step_str);
+    let surface = alias::Surface::try_from(&step_surface).unwrap();
+    assert_eq!(surface, ans);
+}
+
 fn exec_spherical_surface(
     org_coord: [f64; 3],
     dir_array: [f64; 2],
@@ -1111,6 +1195,60 @@ proptest! {
     }
 }
 
+fn exec_degenerate_toroidal_surface(org_coord: [f64; 3], dir_array: [f64; 2], radii: [f64; 2]) {
+    let p = Point3::from(org_coord);
+    let z = dir_from_array(dir_array);
+    let x = match z.cross(Vector3::unit_x()).so_small() {
+        true => Vector3::unit_y(),
+        false => Vector3::unit_x(),
+    };
+    let y = z.cross(x).normalize();
+    let x = y.cross(z).normalize();
+    let major_radius = f64::min(radii[0], radii[1]) / 2.0;
+    let minor_radius = f64::max(radii[0], radii[1]);
+    let step_str = format!(
+        "DATA;
+#1 = DEGENERATE_TOROIDAL_SURFACE('', #2, {major_radius}, {minor_radius}, .T.);
+#2 = AXIS2_PLACEMENT_3D('', #3, #4, #5);
+{}{}{}ENDSEC;",
+        StepDisplay::new(p, 3),
+        StepDisplay::new(VectorAsDirection(z), 4),
+        StepDisplay::new(VectorAsDirection(x), 5),
+    );
+    let step_toroidal = step_to_entity::<ElementarySurfaceAnyHolder>(&step_str);
+    let toroidal = alias::ElementarySurface::try_from(&step_toroidal).unwrap();
+    let mat = Matrix4::from_cols(
+        x.extend(0.0),
+        y.extend(0.0),
+        z.extend(0.0),
+        p.to_vec().extend(1.0),
+    );
+    (0..=10)
+        .flat_map(move |i| (0..=10).map(move |j| (i, j)))
+        .for_each(|(i, j)| {
+            let u = 2.0 * PI * i as f64 / 10.0;
+            let v = 2.0 * PI * j as f64 / 10.0;
+            let res = toroidal.subs(u, v);
+            let ans = mat.transform_point(Point3::new(
+                (major_radius + minor_radius * f64::cos(v)) * f64::cos(u),
+                (major_radius + minor_radius * f64::cos(v)) * f64::sin(u),
+                minor_radius * f64::sin(v),
+            ));
+            assert_near!(res, ans, "u:{u} v:{v} res:{res:?} ans:{ans:?}");
+        })
+}
+
+proptest! {
+    #[test]
+    fn degenerate_toroidal_surface(
+        org_coord in array::uniform3(-100.0f64..100.0f64),
+        dir_array in array::uniform2(0.0f64..1.0f64),
+        radii in array::uniform2(1.0e-2f64..100.0f64),
+    ) {
+        exec_degenerate_toroidal_surface(org_coord, dir_array, radii)
+    }
+}
+
 fn exec_conical_surface(
     org_coord: [f64; 3],
     dir_array: [f64; 2],
@@ -0,0 +1,107 @@
+use truck_stepio::r#in::{header::*, *};
+
+const AP242: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('a part', 'with a comment; and a semicolon'), '2;1');
+FILE_NAME('part.stp', '2023-05-06T07:08:09+09:00', ('alice', 'bob'), ('truck'), 'ST-DEVELOPER v18', 'SOME CAD 2023', 'approved');
+FILE_SCHEMA(('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF { 1 0 10303 442 1 1 4 }'));
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+ENDSEC;
+END-ISO-10303-21;
+";
+
+fn assert_ap242(header: &StepHeader) {
+    assert_eq!(
+        header.description,
+        vec!["a part", "with a comment; and a semicolon"]
+    );
+    assert_eq!(header.implementation_level, "2;1");
+    assert_eq!(header.file_name, "part.stp");
+    assert_eq!(header.time_stamp, "2023-05-06T07:08:09+09:00");
+    assert_eq!(header.authors, vec!["alice", "bob"]);
+    assert_eq!(header.organization, vec!["truck"]);
+    assert_eq!(header.preprocessor_version, "ST-DEVELOPER v18");
+    assert_eq!(header.originating_system, "SOME CAD 2023");
+    assert_eq!(header.authorization, "approved");
+    assert_eq!(header.schema(), Some(StepSchema::Ap242));
+}
+
+#[test]
+fn read_header() {
+    let header = StepHeader::from_step(AP242).unwrap();
+    assert_ap242(&header);
+
+    let (table, header) = Table::from_step_with_header(AP242).unwrap();
+    assert_eq!(table.cartesian_point.len(), 1);
+    assert_ap242(&header);
+}
+
+#[test]
+fn read_header_from_reader() {
+    let header = StepHeader::from_reader(AP242.as_bytes()).unwrap();
+    assert_ap242(&header);
+
+    // the reader stops at the end of the header, so the broken data section is not read.
+    let broken = AP242.replace("#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));", "#1 = (");
+    let header = StepHeader::from_reader(broken.as_bytes()).unwrap();
+    assert_ap242(&header);
+
+    // the data sections of AP242 files may be named.
+    let named = AP242.replace(
+        "DATA;",
+        "DATA('shapes', ('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF'));",
+    );
+    let header = StepHeader::from_reader(named.as_bytes()).unwrap();
+    assert_ap242(&header);
+    let table = Table::from_reader(named.as_bytes()).unwrap();
+    assert_eq!(table.cartesian_point.len(), 1);
+}
+
+#[test]
+fn empty_names_are_skipped() {
+    let step = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+ENDSEC;
+DATA;
+ENDSEC;
+END-ISO-10303-21;
+";
+    let header = StepHeader::from_step(step).unwrap();
+    assert!(header.description.is_empty());
+    assert!(header.authors.is_empty());
+    assert!(header.organization.is_empty());
+    assert_eq!(header.schema(), Some(StepSchema::Ap203));
+}
+
+#[test]
+fn schema_identifiers() {
+    let cases = [
+        ("CONFIG_CONTROL_DESIGN", StepSchema::Ap203),
+        ("config_control_design", StepSchema::Ap203),
+        (
+            "AUTOMOTIVE_DESIGN_CC2 { 1 2 10303 214 -1 1 5 4 }",
+            StepSchema::Ap214,
+        ),
+        ("AP214_AUTOMOTIVE_DESIGN", StepSchema::Ap214),
+        (
+            "AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF",
+            StepSchema::Ap242,
+        ),
+        (
+            "ISO-10303-042",
+            StepSchema::Other("ISO-10303-042".to_string()),
+        ),
+    ];
+    cases.into_iter().for_each(|(identifier, schema)| {
+        assert_eq!(
+            StepSchema::from_identifier(identifier),
+            schema,
+            "{identifier}"
+        );
+    });
+}
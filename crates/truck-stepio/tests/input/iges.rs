@@ -0,0 +1,322 @@
+use truck_stepio::iges::*;
+
+/// the status number of the entities referred by the others
+const DEPENDENT: &str = "00010000";
+const INDEPENDENT: &str = "00000000";
+
+/// Writes IGES file of the entities `(type, transformation, status, parameters)`.
+///
+/// The directory entry of the `i`-th entity is at `2 * i + 1`.
+fn iges_string(global: &str, entities: &[(u32, usize, &str, &str)]) -> String {
+    let mut directory = Vec::new();
+    let mut parameter = Vec::new();
+    for (i, (entity_type, transformation, status, params)) in entities.iter().enumerate() {
+        let sequence = 2 * i + 1;
+        let start = parameter.len() + 1;
+        let mut push_line = |line: &str| {
+            let len = parameter.len() + 1;
+            parameter.push(format!("{line:64}{sequence:>8}P{len:>7}"));
+        };
+        let mut line = String::new();
+        for token in params.split_inclusive(',') {
+            if line.len() + token.len() > 64 {
+                push_line(&line);
+                line.clear();
+            }
+            line.push_str(token);
+        }
+        push_line(&line);
+        let count = parameter.len() + 1 - start;
+        let fields0 = [entity_type.to_string(), start.to_string()]
+            .into_iter()
+            .chain(["0"; 4].map(String::from))
+            .chain([transformation.to_string(), "0".to_string()])
+            .map(|field| format!("{field:>8}"))
+            .collect::<String>();
+        let fields1 = [
+            entity_type.to_string(),
+            "0".into(),
+            "0".into(),
+            count.to_string(),
+            "0".into(),
+        ]
+        .into_iter()
+        .chain([""; 4].map(String::from))
+        .map(|field| format!("{field:>8}"))
+        .collect::<String>();
+        directory.push(format!("{fields0}{status:>8}D{sequence:>7}"));
+        directory.push(format!("{fields1}D{:>7}", sequence + 1));
+    }
+    let terminate = format!(
+        "S{:>7}G{:>7}D{:>7}P{:>7}{:40}T{:>7}",
+        1,
+        1,
+        directory.len(),
+        parameter.len(),
+        "",
+        1,
+    );
+    [
+        format!("{:72}S{:>7}", "truck-stepio test", 1),
+        format!("{global:72}G{:>7}", 1),
+    ]
+    .into_iter()
+    .chain(directory)
+    .chain(parameter)
+    .chain([terminate])
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+const MM: &str = "1H,,1H;,4Htest,8Htest.igs,,,32,38,6,308,15,,1.,2,2HMM,1,1.,,0.001,10.,,;";
+
+fn parse(entities: &[(u32, usize, &str, &str)]) -> IgesFile {
+    iges_string(MM, entities).parse().unwrap()
+}
+
+fn assert_near(p: Point3, q: Point3) { assert!(p.distance(q) < 1.0e-8, "{p:?} != {q:?}") }
+
+/// the bilinear surface on the unit square in xy-plane
+const UNIT_SQUARE: &str = "128,1,1,1,1,0,0,1,0,0,0.,0.,1.,1.,0.,0.,1.,1.,1.,1.,1.,1.,\
+0.,0.,0.,1.,0.,0.,0.,1.,0.,1.,1.,0.,0.,1.,0.,1.;";
+
+#[test]
+fn global_section() {
+    let iges = parse(&[(110, 0, INDEPENDENT, "110,0.,0.,0.,1.,0.,0.;")]);
+    assert_eq!(iges.start, "truck-stepio test");
+    assert_eq!(iges.global.file_name, "test.igs");
+    assert_eq!(iges.global.units_flag, 2);
+    assert_eq!(iges.global.units_name, "MM");
+    assert_eq!(iges.global.unit_in_metres(), 0.001);
+    assert_eq!(iges.global.resolution, 0.001);
+    assert_eq!(iges.entities.len(), 1);
+    assert_eq!(iges.pointers(110).collect::<Vec<_>>(), vec![1]);
+
+    let entity = iges.entity(1).unwrap();
+    assert_eq!(entity.directory.entity_type, 110);
+    assert_eq!(entity.parameters.len(), 6);
+    assert_eq!(entity.real(3).unwrap(), 1.0);
+    assert!(iges.entity(3).is_err());
+}
+
+#[test]
+fn transformed_line() {
+    // the rotation by 90 degrees around the z-axis and the translation along it
+    let iges = parse(&[
+        (
+            124,
+            0,
+            DEPENDENT,
+            "124,0.,-1.,0.,0.,1.,0.,0.,0.,0.,0.,1.,5.;",
+        ),
+        (110, 1, INDEPENDENT, "110,0.,0.,0.,1.D0,0.,0.;"),
+    ]);
+    let curve = iges.curve(3).unwrap();
+    assert_near(curve.front(), Point3::new(0.0, 0.0, 5.0));
+    assert_near(curve.back(), Point3::new(0.0, 1.0, 5.0));
+}
+
+#[test]
+fn circular_arcs() {
+    let iges = parse(&[
+        (100, 0, INDEPENDENT, "100,1.,0.,0.,1.,0.,0.,1.;"),
+        (100, 0, INDEPENDENT, "100,0.,1.,1.,3.,1.,3.,1.;"),
+    ]);
+    let arc = iges.curve(1).unwrap();
+    assert_near(arc.front(), Point3::new(1.0, 0.0, 1.0));
+    assert_near(arc.back(), Point3::new(0.0, 1.0, 1.0));
+    let (t0, t1) = arc.range_tuple();
+    let middle = arc.subs((t0 + t1) / 2.0);
+    assert_near(middle, Point3::new(f64::sqrt(0.5), f64::sqrt(0.5), 1.0));
+
+    // the arc whose ends coincide is the whole circle.
+    let circle = iges.curve(3).unwrap();
+    assert_near(circle.front(), Point3::new(3.0, 1.0, 0.0));
+    assert_near(circle.back(), Point3::new(3.0, 1.0, 0.0));
+    let (t0, t1) = circle.range_tuple();
+    assert!(f64::abs(t1 - t0 - 2.0 * std::f64::consts::PI) < 1.0e-8);
+}
+
+#[test]
+fn bspline_curves() {
+    let iges = parse(&[
+        (
+            126,
+            0,
+            INDEPENDENT,
+            "126,2,2,0,0,1,0,0.,0.,0.,1.,1.,1.,1.,1.,1.,0.,0.,0.,1.,1.,0.,2.,0.,0.,0.,1.,0.,0.,1.;",
+        ),
+        (
+            126,
+            0,
+            INDEPENDENT,
+            "126,2,2,0,0,0,0,0.,0.,0.,1.,1.,1.,1.,0.7071067811865476,1.,\
+1.,0.,0.,1.,1.,0.,0.,1.,0.,0.,1.,0.,0.,1.;",
+        ),
+    ]);
+    let parabola = iges.curve(1).unwrap();
+    assert!(matches!(parabola, Curve3D::BSplineCurve(_)));
+    assert_near(parabola.subs(0.5), Point3::new(1.0, 0.5, 0.0));
+
+    // the quarter of the unit circle
+    let arc = iges.curve(3).unwrap();
+    assert!(matches!(arc, Curve3D::NurbsCurve(_)));
+    (0..=8).for_each(|i| {
+        let p = arc.subs(i as f64 / 8.0);
+        assert!(f64::abs(p.to_vec().magnitude() - 1.0) < 1.0e-8, "{p:?}");
+    });
+}
+
+#[test]
+fn untrimmed_face() {
+    let iges = parse(&[
+        (128, 0, DEPENDENT, UNIT_SQUARE),
+        (144, 0, INDEPENDENT, "144,1,0,0,0;"),
+    ]);
+    let surface = iges.surface(1).unwrap();
+    assert_near(surface.subs(0.5, 0.25), Point3::new(0.5, 0.25, 0.0));
+
+    let shells = iges.to_compressed_shells();
+    assert_eq!(shells.len(), 1);
+    let (pointer, shell) = &shells[0];
+    assert_eq!(*pointer, 3);
+    let shell = shell.as_ref().unwrap();
+    assert_eq!(shell.faces.len(), 1);
+    let boundaries = &shell.faces[0].boundaries;
+    assert_eq!(boundaries.len(), 1);
+    assert_eq!(
+        boundaries[0]
+            .iter()
+            .map(|edge| (edge.index, edge.orientation))
+            .collect::<Vec<_>>(),
+        vec![(0, true), (1, true), (2, true), (3, true)],
+    );
+    [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+        .into_iter()
+        .zip(&shell.vertices)
+        .for_each(|((x, y), p)| assert_near(*p, Point3::new(x, y, 0.0)));
+}
+
+#[test]
+fn trimmed_face() {
+    // the triangle in the parameter space given by the composite curve of the lines
+    let iges = parse(&[
+        (128, 0, DEPENDENT, UNIT_SQUARE),
+        (110, 0, DEPENDENT, "110,0.,0.,0.,1.,0.,0.;"),
+        (110, 0, DEPENDENT, "110,1.,0.,0.,0.,1.,0.;"),
+        (110, 0, DEPENDENT, "110,0.,1.,0.,0.,0.,0.;"),
+        (102, 0, DEPENDENT, "102,3,3,5,7;"),
+        (142, 0, DEPENDENT, "142,1,1,9,0,1;"),
+        (144, 0, INDEPENDENT, "144,1,1,0,11;"),
+    ]);
+    let shells = iges.to_compressed_shells();
+    assert_eq!(shells.len(), 1);
+    let (pointer, shell) = &shells[0];
+    assert_eq!(*pointer, 13);
+    let shell = shell.as_ref().unwrap();
+    assert_eq!(shell.edges.len(), 3);
+    assert_eq!(shell.faces[0].boundaries[0].len(), 3);
+    assert!(shell
+        .edges
+        .iter()
+        .all(|edge| matches!(edge.curve, Curve3D::PCurve(_))));
+    [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+        .into_iter()
+        .zip(&shell.vertices)
+        .for_each(|((x, y), p)| assert_near(*p, Point3::new(x, y, 0.0)));
+
+    let mesh = iges.to_polygon_mesh_for_view(&ViewTolerance::new((64, 64), ViewCamera::Fit));
+    assert!(!mesh.faces().is_empty());
+    mesh.positions()
+        .iter()
+        .for_each(|p| assert!(p.x + p.y < 1.0 + 1.0e-6 && p.z.abs() < 1.0e-6, "{p:?}"));
+}
+
+#[test]
+fn skipped_faces() {
+    // the face #1 is a surface and the face #5 does not exist.
+    let iges = parse(&[
+        (128, 0, DEPENDENT, UNIT_SQUARE),
+        (514, 0, INDEPENDENT, "514,2,1,1,5,1;"),
+    ]);
+    let (shells, skipped) = iges.to_compressed_shells_with_skipped_faces();
+    assert_eq!(shells.len(), 1);
+    assert_eq!(shells[0].0, 3);
+    assert!(shells[0].1.as_ref().unwrap().faces.is_empty());
+    let pointers =
+        |skipped: &[(usize, IgesError)]| skipped.iter().map(|(p, _)| *p).collect::<Vec<_>>();
+    assert_eq!(pointers(&skipped), vec![1, 5]);
+
+    let view = ViewTolerance::new((64, 64), ViewCamera::Fit);
+    let (mesh, skipped) = iges.to_polygon_mesh_for_view_with_skipped(&view);
+    assert!(mesh.faces().is_empty());
+    assert_eq!(pointers(&skipped), vec![1, 5]);
+}
+
+#[test]
+fn brep_solid() {
+    let iges = parse(&[
+        (128, 0, DEPENDENT, UNIT_SQUARE),
+        (
+            502,
+            0,
+            DEPENDENT,
+            "502,4,0.,0.,0.,1.,0.,0.,1.,1.,0.,0.,1.,0.;",
+        ),
+        (110, 0, DEPENDENT, "110,0.,0.,0.,1.,0.,0.;"),
+        (110, 0, DEPENDENT, "110,1.,0.,0.,1.,1.,0.;"),
+        (110, 0, DEPENDENT, "110,1.,1.,0.,0.,1.,0.;"),
+        // the last edge is reversed in the loop.
+        (110, 0, DEPENDENT, "110,0.,0.,0.,0.,1.,0.;"),
+        (
+            504,
+            0,
+            DEPENDENT,
+            "504,4,5,3,1,3,2,7,3,2,3,3,9,3,3,3,4,11,3,1,3,4;",
+        ),
+        (
+            508,
+            0,
+            DEPENDENT,
+            "508,4,0,13,1,1,0,0,13,2,1,0,0,13,3,1,0,0,13,4,0,0;",
+        ),
+        (510, 0, DEPENDENT, "510,1,1,1,15;"),
+        (514, 0, DEPENDENT, "514,1,17,1;"),
+        (186, 0, INDEPENDENT, "186,19,1,0;"),
+    ]);
+    let shells = iges.to_compressed_shells();
+    assert_eq!(shells.len(), 1);
+    let (pointer, shell) = &shells[0];
+    assert_eq!(*pointer, 19);
+    let shell = shell.as_ref().unwrap();
+    assert_eq!(shell.vertices.len(), 4);
+    assert_eq!(shell.edges.len(), 4);
+    assert_eq!(shell.faces.len(), 1);
+    let boundary = &shell.faces[0].boundaries[0];
+    assert_eq!(
+        boundary
+            .iter()
+            .map(|edge| edge.orientation)
+            .collect::<Vec<_>>(),
+        vec![true, true, true, false],
+    );
+    // the loop is connected.
+    let ends = boundary
+        .iter()
+        .map(|edge| {
+            let (front, back) = shell.edges[edge.index].vertices;
+            match edge.orientation {
+                true => (front, back),
+                false => (back, front),
+            }
+        })
+        .collect::<Vec<_>>();
+    (0..4).for_each(|i| assert_eq!(ends[i].1, ends[(i + 1) % 4].0));
+
+    let meshed = iges.triangulate_shells_for_view(&ViewTolerance::new((64, 64), ViewCamera::Fit));
+    let mesh = meshed[0].1.as_ref().unwrap().faces[0]
+        .surface
+        .as_ref()
+        .unwrap();
+    assert!(!mesh.faces().is_empty());
+}
@@ -0,0 +1,154 @@
+use ruststep::ast::DataSection;
+use std::str::FromStr;
+use truck_stepio::r#in::{alias::*, *};
+
+#[test]
+fn repair_records() {
+    let step = "DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = DIRECTION('', (1.0, 0.0, 0.0));
+#4 = VECTOR('', #3, 1.0, 2.0);
+#5 = B_SPLINE_CURVE_WITH_KNOTS('', 1, (#1, #2), .UNSPECIFED., .FALSE., .F., (2, 2), (0.0, 1.0), .UNSPECIFIED.);
+#6 = VERTEX_POINT('', #1);
+#7 = VERTEX_POINT('', #2);
+#8 = EDGE_CURVE('', #6, #7, #5, .T.);
+#9 = ORIENTED_EDGE('', *, *, #8, $);
+#10 = B_SPLINE_CURVE_WITH_KNOTS('', 1, (#1, #2), .UNSPECIFIED., $, $, (2, 2), (0.0, 1.0), .UNSPECIFIED.);
+ENDSEC;";
+    let data_section = DataSection::from_str(step).unwrap();
+    let strict = Table::from_data_section(&data_section);
+    assert!(strict.vector.is_empty());
+    assert!(strict.b_spline_curve_with_knots.is_empty());
+    assert!(strict.oriented_edge.is_empty());
+
+    let (table, repairs) = Table::from_data_section_lenient(&data_section);
+    assert_eq!(table.vector.len(), 1);
+    assert_eq!(table.b_spline_curve_with_knots.len(), 2);
+    // the unset orientation is not guessed
+    assert!(table.oriented_edge.is_empty());
+    let ids: Vec<u64> = repairs
+        .iter()
+        .filter_map(|repair| match repair {
+            StepRepair::RecordRepaired { id, .. } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(ids, vec![4, 5, 10]);
+    assert_eq!(repairs.len(), 4);
+    assert!(matches!(
+        repairs[2],
+        StepRepair::RecordSkipped { id: 9, .. }
+    ));
+}
+
+#[test]
+fn keep_valid_enumerations() {
+    let step = "DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = B_SPLINE_CURVE_WITH_KNOTS('', 1, (#1, #2), .UNSPECIFIED., .F., .F., (2, 2), (0.0, 1.0), .UNSPECIFIED.);
+#4 = COMPOSITE_CURVE_SEGMENT(.CONTINUOUS., .T., #3, $);
+#5 = COMPOSITE_CURVE_SEGMENT(.CONTINUOUS., .TRUE., #3, $);
+ENDSEC;";
+    let data_section = DataSection::from_str(step).unwrap();
+    let (table, repairs) = Table::from_data_section_lenient(&data_section);
+    // `.CONTINUOUS.` is not read as `.UNSPECIFIED.` out of the B-spline forms and knot types
+    assert_eq!(table.composite_curve_segment.len(), 2);
+    assert_eq!(repairs.len(), 2);
+    assert!(matches!(
+        &repairs[0],
+        StepRepair::RecordRepaired { id: 4, fixes } if fixes == "removed extra trailing parameters"
+    ));
+}
+
+const SQUARE: &str = "DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = CARTESIAN_POINT('', (1.0, 1.0, 0.0));
+#4 = CARTESIAN_POINT('', (0.0, 1.0, 0.0));
+#5 = DIRECTION('', (1.0, 0.0, 0.0));
+#6 = VECTOR('', #5, 1.0);
+#7 = DIRECTION('', (0.0, 1.0, 0.0));
+#8 = VECTOR('', #7, 1.0);
+#9 = LINE('', #1, #6);
+#10 = LINE('', #2, #8);
+#11 = LINE('', #4, #6);
+#12 = VERTEX_POINT('', #1);
+#13 = VERTEX_POINT('', #2);
+#14 = VERTEX_POINT('', #3);
+#15 = VERTEX_POINT('', #4);
+#16 = EDGE_CURVE('', #12, #13, #9, .T.);
+#17 = EDGE_CURVE('', #13, #14, #10, .T.);
+#18 = EDGE_CURVE('', #15, #14, #11, .T.);
+#19 = EDGE_CURVE('', #15, #12, #99, .T.);
+#20 = ORIENTED_EDGE('', *, *, #16, .T.);
+#21 = ORIENTED_EDGE('', *, *, #17, .T.);
+#22 = ORIENTED_EDGE('', *, *, #18, .F.);
+#23 = ORIENTED_EDGE('', *, *, #19, .T.);
+#24 = EDGE_LOOP('', (#20, #21, #22, #23));
+#25 = FACE_OUTER_BOUND('', #24, .T.);
+#26 = DIRECTION('', (0.0, 0.0, 1.0));
+#27 = AXIS2_PLACEMENT_3D('', #1, #26, #5);
+#28 = PLANE('', #27);
+#29 = ADVANCED_FACE('', (#25), #28, .T.);
+#30 = ADVANCED_FACE('', (#25), #98, .F.);
+#31 = CLOSED_SHELL('', (#29, #30));
+ENDSEC;";
+
+#[test]
+fn repair_shell() {
+    let table = Table::from_data_section(&DataSection::from_str(SQUARE).unwrap());
+    let shell = &table.shell[&31];
+
+    // the strict mode drops the broken edge and returns the face referring to it.
+    let strict = table.to_compressed_shell(shell).unwrap();
+    assert_eq!(strict.edges.len(), 3);
+    assert_eq!(strict.faces.len(), 1);
+    assert!(strict.faces[0].boundaries[0]
+        .iter()
+        .any(|edge| edge.index >= strict.edges.len()));
+
+    let (cshell, repairs) = table.to_compressed_shell_lenient(shell);
+    assert_eq!(cshell.vertices.len(), 4);
+    assert_eq!(cshell.edges.len(), 4);
+    assert_eq!(cshell.faces.len(), 1);
+    assert_eq!(cshell.faces[0].boundaries[0].len(), 4);
+    assert_eq!(repairs.len(), 2);
+    assert!(matches!(
+        repairs[0],
+        StepRepair::EdgeCurveReplacedByLine { id: 19, .. }
+    ));
+    assert!(matches!(repairs[1], StepRepair::FaceSkipped { id: 30, .. }));
+
+    let edge = &cshell.edges[3];
+    let (p, q) = (
+        cshell.vertices[edge.vertices.0],
+        cshell.vertices[edge.vertices.1],
+    );
+    assert_eq!(p, Point3::new(0.0, 1.0, 0.0));
+    assert_eq!(q, Point3::new(0.0, 0.0, 0.0));
+    assert_eq!(edge.curve.front(), p);
+    assert_eq!(edge.curve.back(), q);
+}
+
+#[test]
+fn skip_face_on_missing_vertex() {
+    let step = SQUARE.replace(
+        "#15 = VERTEX_POINT('', #4);",
+        "#15 = VERTEX_POINT('', #97);",
+    );
+    let table = Table::from_data_section(&DataSection::from_str(&step).unwrap());
+    let (cshell, repairs) = table.to_compressed_shell_lenient(&table.shell[&31]);
+    assert!(cshell.faces.is_empty());
+    assert!(matches!(
+        repairs[0],
+        StepRepair::VertexSkipped { id: 15, .. }
+    ));
+    assert!(repairs
+        .iter()
+        .any(|repair| matches!(repair, StepRepair::EdgeSkipped { id: 18, .. })));
+    assert!(repairs
+        .iter()
+        .any(|repair| matches!(repair, StepRepair::FaceSkipped { id: 29, .. })));
+}
@@ -1,3 +1,16 @@
+mod cache;
+mod complex;
+mod generated;
 mod geometry;
+mod header;
+mod iges;
+mod lenient;
+mod parallel;
+mod pmi;
+mod product;
+mod report;
+mod stream;
 mod table;
 mod tessellate_shape;
+mod tessellation;
+mod validation;
@@ -0,0 +1,146 @@
+use ruststep::ast::DataSection;
+use std::str::FromStr;
+use truck_stepio::r#in::{alias::*, tessellation::*, *};
+
+const SQUARE: &str = "#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = CARTESIAN_POINT('', (1.0, 1.0, 0.0));
+#4 = CARTESIAN_POINT('', (0.0, 1.0, 0.0));
+#5 = DIRECTION('', (1.0, 0.0, 0.0));
+#6 = VECTOR('', #5, 1.0);
+#7 = DIRECTION('', (0.0, 1.0, 0.0));
+#8 = VECTOR('', #7, 1.0);
+#9 = LINE('', #1, #6);
+#10 = LINE('', #2, #8);
+#11 = LINE('', #4, #6);
+#12 = VERTEX_POINT('', #1);
+#13 = VERTEX_POINT('', #2);
+#14 = VERTEX_POINT('', #3);
+#15 = VERTEX_POINT('', #4);
+#16 = EDGE_CURVE('', #12, #13, #9, .T.);
+#17 = EDGE_CURVE('', #13, #14, #10, .T.);
+#18 = EDGE_CURVE('', #15, #14, #11, .T.);
+#19 = EDGE_CURVE('', #12, #15, #32, .T.);
+#20 = ORIENTED_EDGE('', *, *, #16, .T.);
+#21 = ORIENTED_EDGE('', *, *, #17, .T.);
+#22 = ORIENTED_EDGE('', *, *, #18, .F.);
+#23 = ORIENTED_EDGE('', *, *, #19, .F.);
+#24 = EDGE_LOOP('', (#20, #21, #22, #23));
+#25 = FACE_OUTER_BOUND('', #24, .T.);
+#26 = DIRECTION('', (0.0, 0.0, 1.0));
+#27 = AXIS2_PLACEMENT_3D('', #1, #26, #5);
+#28 = PLANE('', #27);
+#29 = ADVANCED_FACE('', (#25), #28, .T.);
+#30 = ADVANCED_FACE('', (#25), #28, .F.);
+#32 = LINE('', #1, #8);
+";
+
+fn many_shells(n: u64) -> Table {
+    let shells: String = (0..n)
+        .map(|i| match i % 2 {
+            0 => format!("#{} = OPEN_SHELL('', (#29));\n", 100 + i),
+            _ => format!("#{} = CLOSED_SHELL('', (#29, #30));\n", 100 + i),
+        })
+        .collect();
+    let step = format!("DATA;\n{SQUARE}{shells}ENDSEC;");
+    Table::from_data_section(&DataSection::from_str(&step).unwrap())
+}
+
+#[test]
+fn table_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Table>();
+    assert_send_sync::<StepConvertingError>();
+    assert_send_sync::<StepCompressedShell>();
+}
+
+#[test]
+fn compressed_shells_in_id_order() {
+    let table = many_shells(16);
+    let shells = table.to_compressed_shells();
+    let ids: Vec<u64> = shells.iter().map(|(idx, _)| *idx).collect();
+    assert_eq!(ids, (100..116).collect::<Vec<_>>());
+    shells.iter().for_each(|(idx, cshell)| {
+        let cshell = cshell.as_ref().unwrap();
+        let expected = table.to_compressed_shell(&table.shell[idx]).unwrap();
+        assert_eq!(
+            serde_json::to_string(cshell).unwrap(),
+            serde_json::to_string(&expected).unwrap(),
+        );
+        assert_eq!(cshell.faces.len(), (idx % 2 + 1) as usize);
+    });
+}
+
+#[test]
+fn triangulated_shells_in_id_order() {
+    let table = many_shells(16);
+    let meshed = table.triangulate_shells(|_| 0.01);
+    let ids: Vec<u64> = meshed.iter().map(|(idx, _)| *idx).collect();
+    assert_eq!(ids, (100..116).collect::<Vec<_>>());
+    meshed.iter().for_each(|(idx, shell)| {
+        let shell = shell.as_ref().unwrap();
+        assert_eq!(shell.faces.len(), (idx % 2 + 1) as usize);
+        shell.faces.iter().for_each(|face| {
+            let mesh = face.surface.as_ref().unwrap();
+            assert!(!mesh.faces().is_empty());
+        });
+    });
+}
+
+#[test]
+fn control_point_bounding_box_of_square() {
+    let table = many_shells(1);
+    let shell = table.to_compressed_shell(&table.shell[&100]).unwrap();
+    let bdd = control_point_bounding_box(&shell);
+    assert_near!(bdd.min(), Point3::new(0.0, 0.0, 0.0));
+    assert_near!(bdd.max(), Point3::new(1.0, 1.0, 0.0));
+}
+
+#[test]
+fn view_tolerance() {
+    let model = BoundingBox::from_iter([Point3::new(0.0, 0.0, 0.0), Point3::new(10.0, 0.0, 0.0)]);
+    let part = BoundingBox::from_iter([Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)]);
+
+    let small = ViewTolerance::new((256, 256), ViewCamera::Fit);
+    let large = ViewTolerance::new((1024, 1024), ViewCamera::Fit);
+    assert_near!(small.pixel_size(&model, &part), 10.0 / 256.0);
+    assert_near!(small.chord_tolerance(&model, &model), 10.0 / 512.0);
+    assert!(small.chord_tolerance(&model, &model) > large.chord_tolerance(&model, &model));
+
+    // the angle bounds the tolerance of the parts much smaller than the model.
+    assert_near!(
+        small.chord_tolerance(&model, &part),
+        0.5 * (1.0 - f64::cos(7.5_f64.to_radians())),
+    );
+
+    let camera = |z: f64| ViewCamera::Perspective {
+        position: Point3::new(0.5, 0.0, z),
+        fov: std::f64::consts::PI / 2.0,
+    };
+    let near = ViewTolerance {
+        angle: std::f64::consts::PI,
+        ..ViewTolerance::new((256, 256), camera(10.0))
+    };
+    let far = ViewTolerance {
+        camera: camera(100.0),
+        ..near
+    };
+    assert_near!(near.pixel_size(&model, &part), 2.0 * 9.5 / 256.0);
+    assert!(near.chord_tolerance(&model, &part) < far.chord_tolerance(&model, &part));
+
+    let parallel = ViewTolerance::new((128, 256), ViewCamera::Parallel { screen_size: 2.56 });
+    assert_near!(parallel.pixel_size(&model, &part), 0.01);
+}
+
+#[test]
+fn triangulate_for_view() {
+    let table = many_shells(4);
+    let coarse = ViewTolerance::new((16, 16), ViewCamera::Fit);
+    let meshed = table.triangulate_shells_for_view(&coarse);
+    let ids: Vec<u64> = meshed.iter().map(|(idx, _)| *idx).collect();
+    assert_eq!(ids, vec![100, 101, 102, 103]);
+    meshed.iter().for_each(|(idx, shell)| {
+        let shell = shell.as_ref().unwrap();
+        assert_eq!(shell.faces.len(), (idx % 2 + 1) as usize);
+    });
+}
@@ -0,0 +1,93 @@
+use ruststep::ast::DataSection;
+use std::str::FromStr;
+use truck_stepio::r#in::pmi::*;
+
+const STEP: &str = "DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 5.0));
+#2 = DIRECTION('', (0.0, 0.0, 2.0));
+#3 = DIRECTION('', (1.0, 0.0, 0.0));
+#4 = AXIS2_PLACEMENT_3D('', #1, #2, #3);
+#5 = PLANE('', #4);
+#6 = COORDINATES_LIST('', 5, (
+    (0.0, 0.0, 5.0), (4.0, 0.0, 5.0), (4.0, 1.0, 5.0), (0.0, 1.0, 5.0), (2.0, 2.0, 5.0)
+));
+#7 = TESSELLATED_CURVE_SET('', #6, ((1, 2, 3), (4, 5)));
+#8 = COMPLEX_TRIANGULATED_SURFACE_SET('', #6, 5, (), (), ((1, 2, 4, 3)), ((5, 4, 3)));
+#9 = TESSELLATED_GEOMETRIC_SET('', (#7, #8));
+#10 = TESSELLATED_ANNOTATION_OCCURRENCE('text', (), #9);
+#11 = DRAUGHTING_CALLOUT('Linear Size.1', (#10));
+#12 = ANNOTATION_PLANE('front', (), #5, (#11));
+#20 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );
+#21 = DIMENSIONAL_SIZE(#30, 'diameter');
+#22 = (
+    LENGTH_MEASURE_WITH_UNIT()
+    MEASURE_REPRESENTATION_ITEM()
+    MEASURE_WITH_UNIT(LENGTH_MEASURE(20.0), #20)
+    REPRESENTATION_ITEM('nominal value')
+);
+#23 = SHAPE_DIMENSION_REPRESENTATION('', (#22), #40);
+#24 = DIMENSIONAL_CHARACTERISTIC_REPRESENTATION(#21, #23);
+#25 = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(-0.1), #20);
+#26 = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(0.2), #20);
+#27 = TOLERANCE_VALUE(#25, #26);
+#28 = PLUS_MINUS_TOLERANCE(#27, #21);
+#31 = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(0.05), #20);
+#32 = (
+    GEOMETRIC_TOLERANCE('Position.1', '', #31, #30)
+    GEOMETRIC_TOLERANCE_WITH_DATUM_REFERENCE((#33))
+    MODIFIED_GEOMETRIC_TOLERANCE(.MAXIMUM_MATERIAL_CONDITION.)
+    POSITION_TOLERANCE()
+);
+#33 = DATUM('Datum.1', '', #30, .F., 'A');
+ENDSEC;";
+
+fn pmi() -> StepPmi { StepPmi::from_data_section(&DataSection::from_str(STEP).unwrap()) }
+
+#[test]
+fn tessellated_annotation() {
+    let pmi = pmi();
+    assert_eq!(pmi.annotations.len(), 1);
+    let annotation = &pmi.annotations[0];
+    assert_eq!(annotation.entity_id, 10);
+    assert_eq!(annotation.callout, Some(11));
+    assert_eq!(annotation.callout_name.as_deref(), Some("Linear Size.1"));
+    let plane = annotation.plane.as_ref().unwrap();
+    assert_eq!(plane.name, "front");
+    assert_eq!(plane.origin, [0.0, 0.0, 5.0]);
+    assert_eq!(plane.normal, [0.0, 0.0, 1.0]);
+
+    assert_eq!(annotation.polylines.len(), 2);
+    assert_eq!(annotation.polylines[0].len(), 3);
+    assert_eq!(
+        annotation.polylines[1],
+        vec![[0.0, 1.0, 5.0], [2.0, 2.0, 5.0]]
+    );
+    // two triangles of the strip and one of the fan
+    assert_eq!(annotation.triangles.len(), 3);
+    assert_eq!(
+        annotation.triangles[1],
+        [[0.0, 1.0, 5.0], [4.0, 0.0, 5.0], [4.0, 1.0, 5.0]]
+    );
+}
+
+#[test]
+fn semantic_pmi() {
+    let pmi = pmi();
+    assert_eq!(pmi.tolerances.len(), 1);
+    let tolerance = &pmi.tolerances[0];
+    assert_eq!(tolerance.kind, "position_tolerance");
+    assert_eq!(tolerance.name, "Position.1");
+    assert_eq!(tolerance.magnitude, Some(0.05));
+    assert_eq!(tolerance.unit.as_deref(), Some("millimetre"));
+
+    assert_eq!(pmi.dimensions.len(), 1);
+    let dimension = &pmi.dimensions[0];
+    assert_eq!(dimension.kind, "dimensional_size");
+    assert_eq!(dimension.name, "diameter");
+    assert_eq!(dimension.value, Some(20.0));
+    assert_eq!(dimension.unit.as_deref(), Some("millimetre"));
+    assert_eq!((dimension.lower, dimension.upper), (Some(-0.1), Some(0.2)));
+
+    assert_eq!(pmi.datums.len(), 1);
+    assert_eq!(pmi.datums[0].identification, "A");
+}
@@ -0,0 +1,148 @@
+use ruststep::ast::DataSection;
+use std::str::FromStr;
+use truck_stepio::r#in::{product::*, stream::*, *};
+
+const ASSEMBLY: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }'));
+ENDSEC;
+DATA;
+#1 = APPLICATION_CONTEXT('automotive design');
+#2 = PRODUCT_CONTEXT('', #1, 'mechanical');
+#3 = PRODUCT_DEFINITION_CONTEXT('part definition', #1, 'design');
+#4 = GEOMETRIC_REPRESENTATION_CONTEXT(3);
+#10 = PRODUCT('ASM-1', 'gearbox', 'the root', (#2));
+#11 = PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE('C', '', #10, .NOT_KNOWN.);
+#12 = PRODUCT_DEFINITION('design', '', #11, #3);
+#13 = PRODUCT_DEFINITION_SHAPE('', '', #12);
+#14 = SHAPE_DEFINITION_REPRESENTATION(#13, #15);
+#15 = SHAPE_REPRESENTATION('', (), #4);
+#20 = PRODUCT('P-1', 'housing', $, (#2));
+#21 = PRODUCT_DEFINITION_FORMATION('A', '', #20);
+#22 = PRODUCT_DEFINITION('design', '', #21, #3);
+#23 = PRODUCT_DEFINITION_SHAPE('', '', #22);
+#24 = SHAPE_DEFINITION_REPRESENTATION(#23, #25);
+#25 = SHAPE_REPRESENTATION('', (), #4);
+#26 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#27, #28), #4);
+#27 = MANIFOLD_SOLID_BREP('', #29);
+#28 = BREP_WITH_VOIDS('', #30, (#31));
+#29 = CLOSED_SHELL('', ());
+#30 = CLOSED_SHELL('', ());
+#31 = ORIENTED_CLOSED_SHELL('', *, #29, .F.);
+#32 = SHAPE_REPRESENTATION_RELATIONSHIP('', '', #25, #26);
+#40 = PRODUCT('P-2', '', '', (#2));
+#41 = PRODUCT_DEFINITION_FORMATION('', '', #40);
+#42 = PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS('design', '', #41, #3, ());
+#43 = PRODUCT_DEFINITION_SHAPE('', '', #42);
+#44 = SHAPE_DEFINITION_REPRESENTATION(#43, #45);
+#45 = MANIFOLD_SURFACE_SHAPE_REPRESENTATION('', (#46, #48), #4);
+#46 = SHELL_BASED_SURFACE_MODEL('', (#47));
+#47 = OPEN_SHELL('', ());
+#48 = TESSELLATED_SHELL('', (), $);
+#50 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('1', 'housing', '', #12, #22, $);
+#51 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('2', 'cover', '', #12, #42, $);
+#52 = PRODUCT_DEFINITION_SHAPE('', '', #50);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+fn assert_products(products: &[StepProduct]) {
+    let ids: Vec<u64> = products.iter().map(|product| product.entity_id).collect();
+    assert_eq!(ids, vec![10, 20, 40]);
+
+    let root = &products[0];
+    assert_eq!(root.id, "ASM-1");
+    assert_eq!(root.description, "the root");
+    assert_eq!(root.revision.as_deref(), Some("C"));
+    assert!(root.shells.is_empty());
+    assert!(root.top_level);
+    assert_eq!(root.caption(), "gearbox rev. C");
+
+    let housing = &products[1];
+    assert_eq!(housing.shells, vec![29, 30]);
+    assert!(housing.tessellated_shells.is_empty());
+    assert!(!housing.top_level);
+
+    let cover = &products[2];
+    assert_eq!(cover.revision, None);
+    assert_eq!(cover.shells, vec![47]);
+    assert_eq!(cover.tessellated_shells, vec![48]);
+    assert!(!cover.top_level);
+    assert_eq!(cover.caption(), "P-2");
+}
+
+#[test]
+fn read_products() {
+    let exchange = ruststep::parser::parse(ASSEMBLY).unwrap();
+    let table = Table::from_data_section(&exchange.data[0]);
+    assert_products(&table.products());
+    assert_eq!(table.top_level_product().unwrap().entity_id, 10);
+    // only the cover has the tessellated shape
+    assert_eq!(table.shells_with_tessellation(), vec![47]);
+}
+
+#[test]
+fn related_representations() {
+    let step = "DATA;
+#1 = PRODUCT('P-1', 'part', '', ());
+#2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+#3 = PRODUCT_DEFINITION('design', '', #2, #31);
+#4 = PRODUCT_DEFINITION_SHAPE('', '', #3);
+#5 = SHAPE_DEFINITION_REPRESENTATION(#4, #6);
+#6 = SHAPE_REPRESENTATION('', (), #30);
+#7 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#10), #30);
+#8 = ADVANCED_BREP_SHAPE_REPRESENTATION('', (#11), #30);
+#10 = MANIFOLD_SOLID_BREP('', #12);
+#11 = MANIFOLD_SOLID_BREP('', #13);
+#12 = CLOSED_SHELL('', ());
+#13 = CLOSED_SHELL('', ());
+#20 = SHAPE_REPRESENTATION_RELATIONSHIP('', '', #7, #6);
+#21 = SHAPE_REPRESENTATION_RELATIONSHIP('', '', #7, #8);
+#30 = GEOMETRIC_REPRESENTATION_CONTEXT(3);
+#31 = PRODUCT_DEFINITION_CONTEXT('part definition', #32, 'design');
+#32 = APPLICATION_CONTEXT('automotive design');
+ENDSEC;";
+    let table = Table::from_data_section(&DataSection::from_str(step).unwrap());
+    // the inverse relationship is followed from the product, but not the next one
+    assert_eq!(table.products()[0].shells, vec![12]);
+}
+
+#[test]
+fn shells_without_products() {
+    let step = "DATA;
+#1 = CLOSED_SHELL('', ());
+#2 = TESSELLATED_SHELL('', (), $);
+ENDSEC;";
+    let table = Table::from_data_section(&DataSection::from_str(step).unwrap());
+    assert_eq!(table.shells_with_tessellation(), vec![1]);
+    let step = "DATA;
+#1 = CLOSED_SHELL('', ());
+ENDSEC;";
+    let table = Table::from_data_section(&DataSection::from_str(step).unwrap());
+    assert!(table.shells_with_tessellation().is_empty());
+}
+
+#[test]
+fn read_products_from_stream() {
+    let options = StreamOptions::product_structure();
+    let table = Table::from_reader_with_options(ASSEMBLY.as_bytes(), &options).unwrap();
+    assert!(table.dummy.is_empty());
+    assert_products(&table.products());
+}
+
+#[test]
+fn single_part_is_top_level() {
+    let step = "DATA;
+#1 = PRODUCT('P-1', 'part', '', ());
+#2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+ENDSEC;";
+    let table = Table::from_data_section(&DataSection::from_str(step).unwrap());
+    let product = table.top_level_product().unwrap();
+    assert_eq!(product.caption(), "part");
+    assert_eq!(
+        serde_json::to_value(&product).unwrap()["name"],
+        serde_json::json!("part")
+    );
+}
@@ -0,0 +1,75 @@
+use truck_stepio::r#in::*;
+
+const HEADER: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+";
+
+fn step_file(data: &str) -> String { format!("{HEADER}DATA;\n{data}ENDSEC;\nEND-ISO-10303-21;\n") }
+
+#[test]
+fn clean_file() {
+    let step = step_file(
+        "#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = DIRECTION('', (0.0, 0.0, 1.0));
+#3 = VECTOR('', #2, 1.0);
+#4 = LINE('', #1, #3);
+",
+    );
+    let (table, report) = Table::from_step_with_report(&step).unwrap();
+    assert_eq!(table.line.len(), 1);
+    assert!(report.is_clean());
+    assert_eq!(report, StepReadReport::default());
+}
+
+#[test]
+fn broken_file() {
+    let step = step_file(
+        "#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = DIRECTION('', 'not a direction');
+#3 = VECTOR('', #2, 1.0);
+#4 = LINE('', #1, #30);
+#5 = PRESENTATION_LAYER_ASSIGNMENT('', '', (#4));
+#6 = PRESENTATION_LAYER_ASSIGNMENT('', '', (#1));
+#7 = (GEOMETRIC_REPRESENTATION_CONTEXT(3) GLOBAL_UNIT_ASSIGNED_CONTEXT((#8)) REPRESENTATION_CONTEXT('', ''));
+#8 = (LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI., .METRE.));
+#10 = VERTEX_POINT('', #1);
+#11 = EDGE_CURVE('', #10, #10, #4, .T.);
+#12 = ORIENTED_EDGE('', *, *, #11, .T.);
+#13 = EDGE_LOOP('', (#12));
+#14 = FACE_OUTER_BOUND('', #13, .T.);
+#15 = ADVANCED_FACE('', (#14), #31, .T.);
+#16 = CLOSED_SHELL('', (#15));
+",
+    );
+    let (table, report) = Table::from_step_with_report(&step).unwrap();
+    assert_eq!(table.cartesian_point.len(), 1);
+    assert!(!report.is_clean());
+
+    let failed_ids: Vec<u64> = report.failed_entities.iter().map(|(id, _)| *id).collect();
+    assert_eq!(failed_ids, vec![2]);
+    assert_eq!(report.dangling_references, vec![(4, 30), (15, 31)]);
+    assert_eq!(report.unsupported_entities.len(), 3);
+    assert_eq!(
+        report.unsupported_entities["PRESENTATION_LAYER_ASSIGNMENT"],
+        2
+    );
+    assert_eq!(
+        report.unsupported_entities["(LENGTH_UNIT NAMED_UNIT SI_UNIT)"],
+        1
+    );
+    let failed_shells: Vec<u64> = report.failed_shells.iter().map(|(id, _)| *id).collect();
+    assert_eq!(failed_shells, vec![16]);
+
+    let display = report.to_string();
+    assert!(display.contains("#4 refers to missing #30"));
+    assert!(display.contains("failed to convert shell #16"));
+}
+
+#[test]
+fn syntax_error() {
+    assert!(Table::from_step_with_report("ISO-10303-21;\nHEADER;").is_err());
+}
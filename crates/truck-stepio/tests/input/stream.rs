@@ -0,0 +1,170 @@
+use std::io::Read;
+use truck_stepio::r#in::{stream::*, *};
+
+const STEP: &str = "ISO-10303-21;
+HEADER;
+/* a comment with ; and ' */
+FILE_DESCRIPTION(('semicolon; in a string', 'it''s quoted; too'), '2;1');
+FILE_NAME('cube.step', '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('a;b', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('it''s', (1.0, 0.0, 0.0));/**/
+#3 = DIRECTION('', (1.0, 0.0, 0.0)); /* #4 = DIRECTION('', (0.0, 1.0, 0.0)); */
+#5 = VECTOR('', #3, 1.0);
+#6 = LINE('', #1, #5);
+#7 = PRESENTATION_LAYER_ASSIGNMENT('layer', '', (#6));
+#8 = (GEOMETRIC_REPRESENTATION_CONTEXT(3) GLOBAL_UNIT_ASSIGNED_CONTEXT((#9)) REPRESENTATION_CONTEXT('', ''));
+#10 = COORDINATES_LIST('', 3, ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)));
+#11 = TRIANGULATED_FACE('', #10, 0, (), $, (), ((1, 2, 3)));
+#12 = TESSELLATED_SHELL('', (#11), $);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+/// reader returning one byte for each call, to split the tokens at every position
+struct OneByteReader<'a>(&'a [u8]);
+
+impl<'a> Read for OneByteReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match (self.0.split_first(), buf.is_empty()) {
+            (Some((c, rest)), false) => {
+                buf[0] = *c;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn statements() {
+    let statements = StatementReader::new(STEP.as_bytes())
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(statements.len(), 19);
+    assert_eq!(
+        statements[2],
+        "FILE_DESCRIPTION(('semicolon; in a string', 'it''s quoted; too'), '2;1')"
+    );
+    assert_eq!(
+        statements[8],
+        "#2 = CARTESIAN_POINT('it''s', (1.0, 0.0, 0.0))"
+    );
+    assert_eq!(statements[9], "#3 = DIRECTION('', (1.0, 0.0, 0.0))");
+    assert_eq!(statements[10], "#5 = VECTOR('', #3, 1.0)");
+
+    let one_byte = StatementReader::new(OneByteReader(STEP.as_bytes()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(statements, one_byte);
+}
+
+#[test]
+fn same_as_from_step() {
+    let mut answer = Table::from_step(STEP).unwrap();
+    let options = StreamOptions {
+        keep_unsupported_records: true,
+        ..Default::default()
+    };
+    let table = Table::from_reader_with_options(STEP.as_bytes(), &options).unwrap();
+    assert_eq!(table, answer);
+    let table = Table::from_reader_with_options(OneByteReader(STEP.as_bytes()), &options).unwrap();
+    assert_eq!(table, answer);
+
+    // by default, only the type names of unsupported entities are kept.
+    let table = Table::from_reader(STEP.as_bytes()).unwrap();
+    assert_eq!(table.dummy[&7].record, "PRESENTATION_LAYER_ASSIGNMENT");
+    assert_eq!(
+        table.dummy[&8].record,
+        "(GEOMETRIC_REPRESENTATION_CONTEXT GLOBAL_UNIT_ASSIGNED_CONTEXT REPRESENTATION_CONTEXT)"
+    );
+    answer.dummy.clear();
+    let mut table = table;
+    table.dummy.clear();
+    assert_eq!(table, answer);
+}
+
+#[test]
+fn preview() {
+    let table =
+        Table::from_reader_with_options(STEP.as_bytes(), &StreamOptions::tessellated_preview())
+            .unwrap();
+    assert!(table.cartesian_point.is_empty());
+    assert!(table.dummy.is_empty());
+    assert_eq!(table.coordinates_list.len(), 1);
+    assert_eq!(table.triangulated_face.len(), 1);
+    let mesh = table
+        .to_polygon_mesh(&table.tessellated_shell[&12])
+        .unwrap();
+    assert_eq!(mesh.faces().len(), 1);
+
+    let options = StreamOptions {
+        max_entities: Some(3),
+        ..Default::default()
+    };
+    let table = Table::from_reader_with_options(STEP.as_bytes(), &options).unwrap();
+    assert_eq!(table.cartesian_point.len(), 2);
+    assert_eq!(table.direction.len(), 1);
+    assert!(table.vector.is_empty());
+}
+
+#[test]
+fn syntax_error() {
+    let step = STEP.replace("#5 = VECTOR('', #3, 1.0);", "#5 = VECTOR('', #3, 1.0;");
+    let error = Table::from_reader(step.as_bytes()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn compact_table() {
+    let store = CompactTable::from_reader(STEP.as_bytes()).unwrap();
+    assert_eq!(store.len(), 10);
+    assert_eq!(store.get(1), Some("CARTESIAN_POINT('a;b',(0.0,0.0,0.0))"));
+    assert_eq!(store.get(2), Some("CARTESIAN_POINT('it''s',(1.0,0.0,0.0))"));
+    assert_eq!(store.get(4), None);
+    assert_eq!(store.ids_of_types(&["CARTESIAN_POINT"]), vec![1, 2]);
+    assert_eq!(store.ids_of_types(&["REPRESENTATION_CONTEXT"]), vec![8]);
+
+    let (table, report) = store.to_table();
+    assert_eq!(table, Table::from_reader(STEP.as_bytes()).unwrap());
+    assert!(report.failed_entities.is_empty());
+
+    // only the line and the entities referred by it are read.
+    let (table, report) = store.table_of([6]);
+    assert_eq!(table.line.len(), 1);
+    assert_eq!(table.vector.len(), 1);
+    assert_eq!(table.direction.len(), 1);
+    assert_eq!(table.cartesian_point.len(), 1);
+    assert!(table.dummy.is_empty());
+    assert!(table.coordinates_list.is_empty());
+    assert!(report.is_clean());
+
+    // the references to the filtered entities are dangling.
+    let options = StreamOptions {
+        entity_types: Some(["LINE", "VECTOR"].iter().map(|s| s.to_string()).collect()),
+        ..Default::default()
+    };
+    let store = CompactTable::from_reader_with_options(STEP.as_bytes(), &options).unwrap();
+    assert_eq!(store.len(), 2);
+    let (table, report) = store.table_of([6]);
+    assert_eq!(table.line.len(), 1);
+    assert_eq!(report.dangling_references, vec![(5, 3), (6, 1)]);
+}
+
+#[test]
+fn report_failed_entities() {
+    let step = STEP.replace("#5 = VECTOR('', #3, 1.0);", "#5 = VECTOR('', #3);");
+    let (table, report) =
+        Table::from_reader_with_report(step.as_bytes(), &Default::default()).unwrap();
+    assert!(table.vector.is_empty());
+    assert_eq!(report.failed_entities.len(), 1);
+    assert_eq!(report.failed_entities[0].0, 5);
+
+    let store = CompactTable::from_reader(step.as_bytes()).unwrap();
+    let (_, report) = store.table_of([6]);
+    assert_eq!(report.failed_entities.len(), 1);
+    assert_eq!(report.failed_entities[0].0, 5);
+}
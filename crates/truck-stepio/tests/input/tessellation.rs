@@ -0,0 +1,87 @@
+use ruststep::{ast::DataSection, tables::EntityTable};
+use std::str::FromStr;
+use truck_stepio::r#in::{alias::*, *};
+
+const STEP: &str = "DATA;
+#1 = COORDINATES_LIST('', 6, (
+    (0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (1.0, 1.0, 0.0),
+    (0.0, 1.0, 0.0), (2.0, 0.0, 0.0), (2.0, 1.0, 0.0)
+));
+#2 = TRIANGULATED_FACE('plain', #1, 0, (), $, (), ((1, 2, 3), (1, 3, 4)));
+#3 = TRIANGULATED_FACE('indexed', #1, 4, ((0.0, 0.0, 1.0)), #100, (2, 5, 6, 3), ((1, 2, 3), (1, 3, 4)));
+#4 = COMPLEX_TRIANGULATED_FACE('strip', #1, 4, (), $, (1, 2, 4, 3), ((1, 2, 3, 4)), ());
+#5 = COMPLEX_TRIANGULATED_FACE('fan', #1, 5, (
+    (0.0, 0.0, 1.0), (0.0, 0.0, 1.0), (0.0, 0.0, 1.0), (0.0, 0.0, 1.0), (0.0, 0.0, 1.0)
+), $, (), (), ((3, 4, 1, 2, 5)));
+#6 = TESSELLATED_EDGE('', #1, $, (1, 2));
+#7 = TESSELLATED_SHELL('shell', (#2, #3, #6), $);
+#8 = TESSELLATED_SOLID('solid', (#4, #5), $);
+#100 = ADVANCED_FACE('', (), #101, .T.);
+ENDSEC;";
+
+fn table() -> Table { Table::from_data_section(&DataSection::from_str(STEP).unwrap()) }
+
+#[test]
+fn read_tessellated_faces() {
+    let table = table();
+    assert_eq!(table.coordinates_list.len(), 1);
+    assert_eq!(table.triangulated_face.len(), 2);
+    assert_eq!(table.complex_triangulated_face.len(), 2);
+    assert_eq!(table.tessellated_shell.len(), 2);
+
+    let face = EntityTable::<TriangulatedFaceHolder>::get_owned(&table, 2).unwrap();
+    let mesh = PolygonMesh::try_from(&face).unwrap();
+    assert_eq!(mesh.positions().len(), 4);
+    assert_eq!(mesh.faces().len(), 2);
+    assert!(mesh.normals().is_empty());
+
+    let face = EntityTable::<TriangulatedFaceHolder>::get_owned(&table, 3).unwrap();
+    let mesh = PolygonMesh::try_from(&face).unwrap();
+    assert_eq!(mesh.positions().len(), 4);
+    [
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(2.0, 1.0, 0.0),
+    ]
+    .iter()
+    .for_each(|p| assert!(mesh.positions().contains(p), "{p:?}"));
+    assert_eq!(mesh.normals(), &vec![Vector3::unit_z()]);
+
+    let face = EntityTable::<ComplexTriangulatedFaceHolder>::get_owned(&table, 4).unwrap();
+    let mesh = PolygonMesh::try_from(&face).unwrap();
+    assert_eq!(mesh.faces().len(), 2);
+    // every triangle of the strip is counterclockwise on the xy-plane.
+    mesh.faces().tri_faces().iter().for_each(|tri| {
+        let p = tri.map(|v| mesh.positions()[v.pos]);
+        assert!((p[1] - p[0]).cross(p[2] - p[0]).z > 0.0);
+    });
+
+    let face = EntityTable::<ComplexTriangulatedFaceHolder>::get_owned(&table, 5).unwrap();
+    let mesh = PolygonMesh::try_from(&face).unwrap();
+    assert_eq!(mesh.positions().len(), 5);
+    assert_eq!(mesh.faces().len(), 3);
+    assert_eq!(mesh.normals().len(), 5);
+}
+
+#[test]
+fn tessellated_shell_to_polygon_mesh() {
+    let table = table();
+    // the tessellated edge is skipped.
+    let mesh = table.to_polygon_mesh(&table.tessellated_shell[&7]).unwrap();
+    assert_eq!(mesh.positions().len(), 8);
+    assert_eq!(mesh.faces().len(), 4);
+    let mesh = table.to_polygon_mesh(&table.tessellated_shell[&8]).unwrap();
+    assert_eq!(mesh.faces().len(), 5);
+}
+
+#[test]
+fn broken_index() {
+    let step = "DATA;
+#1 = COORDINATES_LIST('', 3, ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (1.0, 1.0, 0.0)));
+#2 = TRIANGULATED_FACE('', #1, 0, (), $, (), ((1, 2, 4)));
+#3 = TESSELLATED_SHELL('', (#2), $);
+ENDSEC;";
+    let table = Table::from_data_section(&DataSection::from_str(step).unwrap());
+    assert!(table.to_polygon_mesh(&table.tessellated_shell[&3]).is_err());
+}
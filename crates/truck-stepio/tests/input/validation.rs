@@ -0,0 +1,92 @@
+use truck_stepio::r#in::{alias::*, validation::*, *};
+use truck_topology::compress::*;
+
+const STEP_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../resources/step/");
+
+fn cube_table() -> Table {
+    let step_string =
+        std::fs::read_to_string(STEP_DIRECTORY.to_string() + "occt-cube.step").unwrap();
+    Table::from_step(&step_string).unwrap()
+}
+
+fn cube() -> CompressedShell<Point3, Curve3D, Surface> {
+    let mut shells = cube_table().to_compressed_shells();
+    shells.pop().unwrap().1.unwrap()
+}
+
+#[test]
+fn valid_shell() {
+    let validations = cube_table().validate_shells(1.0e-6);
+    assert_eq!(validations.len(), 1);
+    let validation = validations[0].1.as_ref().unwrap();
+    assert!(validation.is_valid(), "{validation}");
+    assert!(validation.is_closed() && validation.is_manifold() && validation.is_oriented());
+    assert!(validation.edges.iter().all(|edge| edge.uses == 2));
+    assert_eq!(validation.faces.len(), 6);
+}
+
+#[test]
+fn missing_face() {
+    let mut shell = cube();
+    shell.faces.pop();
+    let validation = ShellValidation::new(&shell, 1.0e-6);
+    assert!(!validation.is_closed());
+    // the edges of the removed face are boundaries, which are not problems by themselves.
+    assert!(validation.is_valid());
+    assert_eq!(
+        validation
+            .edges
+            .iter()
+            .filter(|edge| edge.uses == 1)
+            .count(),
+        4
+    );
+    assert!(validation.to_string().contains("used by only one face"));
+}
+
+#[test]
+fn flipped_face() {
+    let mut shell = cube();
+    shell.faces[0].orientation = !shell.faces[0].orientation;
+    let validation = ShellValidation::new(&shell, 1.0e-6);
+    assert!(validation.is_closed());
+    assert!(!validation.is_oriented());
+    let inconsistent = validation
+        .edges
+        .iter()
+        .filter(|edge| edge.issues.contains(&EdgeIssue::InconsistentOrientation))
+        .count();
+    assert_eq!(inconsistent, 4);
+}
+
+#[test]
+fn broken_loop_and_geometry() {
+    let mut shell = cube();
+    // the first edge of the first face leaves its vertices and its surface.
+    let edge_idx = shell.faces[0].boundaries[0][0].index;
+    let vertex = shell.edges[edge_idx].vertices.0;
+    shell.vertices[vertex] += Vector3::new(0.0, 0.0, 0.5);
+    shell.faces[1].boundaries[0].reverse();
+    let validation = ShellValidation::new(&shell, 1.0e-6);
+    assert!(!validation.is_valid());
+    assert!(validation.edges.iter().any(|edge| edge.issues.iter().any(
+        |issue| matches!(issue, EdgeIssue::VertexOffCurve { vertex: v, .. } if *v == vertex)
+    )));
+    assert!(validation.faces[1]
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, FaceIssue::OpenLoop { boundary: 0, .. })));
+
+    let mut shell = cube();
+    shell.faces[0].boundaries[0].push(CompressedEdgeIndex {
+        index: shell.edges.len(),
+        orientation: true,
+    });
+    let validation = ShellValidation::new(&shell, 1.0e-6);
+    assert!(validation.faces[0]
+        .issues
+        .contains(&FaceIssue::MissingEdge {
+            boundary: 0,
+            edge: shell.edges.len(),
+        }));
+}
@@ -0,0 +1,90 @@
+use super::{cube, plate};
+use std::f64::consts::PI;
+use truck_modeling::*;
+use truck_stepio::{
+    out::*,
+    r#in::{product::product_instances, Table},
+};
+
+fn near_matrix(a: Matrix4, b: Matrix4) -> bool {
+    (0..4).all(|i| (0..4).all(|j| f64::abs(a[i][j] - b[i][j]) < 1.0e-6))
+}
+
+#[test]
+fn assembly_roundtrip() {
+    let (cube, plate) = (cube(), plate());
+    let translation = Matrix4::from_translation(Vector3::new(3.0, 0.0, 0.0));
+    let rotation = Matrix4::from_translation(Vector3::new(0.0, 2.0, 1.0))
+        * Matrix4::from_angle_z(Rad(PI / 2.0));
+
+    let mut assembly = StepAssembly::default();
+    let root = assembly.push_product("ASM", "assembly");
+    let cube_product = assembly.push_product("P-CUBE", "cube");
+    let plate_product = assembly.push_product("P-PLATE", "the 'plate'");
+    assembly.push_solid(cube_product, &cube);
+    assembly.push_shell(plate_product, &plate);
+    assembly.push_instance(root, cube_product, "cube-1", translation);
+    assembly.push_instance(root, cube_product, "cube-2", rotation);
+    assembly.push_instance(root, plate_product, "plate-1", Matrix4::identity());
+    let step_string = CompleteStepDisplay::new(assembly, Default::default()).to_string();
+    let table = Table::from_step(&step_string)
+        .unwrap_or_else(|| panic!("failed to parse step\n[STEP file]\n{step_string}"));
+
+    let products = table.products();
+    assert_eq!(products.len(), 3);
+    let names: Vec<_> = products
+        .iter()
+        .map(|product| product.name.as_str())
+        .collect();
+    assert_eq!(names, ["assembly", "cube", "the 'plate'"]);
+    let top_level: Vec<_> = products.iter().map(|product| product.top_level).collect();
+    assert_eq!(top_level, [true, false, false]);
+    assert!(products[0].shells.is_empty());
+    assert_eq!(products[1].shells.len(), 1);
+    assert_eq!(products[2].shells.len(), 1);
+
+    let cube_shell = table.shell.get(&products[1].shells[0]).unwrap();
+    let cube_shell = table.to_compressed_shell(cube_shell).unwrap();
+    assert_eq!(cube_shell.faces.len(), 6);
+
+    let occurrences = table.product_occurrences();
+    assert_eq!(occurrences.len(), 3);
+    let expected = [
+        ("cube-1", products[1].entity_id, translation),
+        ("cube-2", products[1].entity_id, rotation),
+        ("plate-1", products[2].entity_id, Matrix4::identity()),
+    ];
+    occurrences
+        .iter()
+        .zip(expected)
+        .for_each(|(occurrence, (name, child, transform))| {
+            assert_eq!(occurrence.name, name);
+            assert_eq!(occurrence.parent, products[0].entity_id);
+            assert_eq!(occurrence.child, child);
+            assert!(
+                near_matrix(occurrence.transform, transform),
+                "{:?}\n{:?}",
+                occurrence.transform,
+                transform,
+            );
+        });
+
+    let instances = product_instances(&products, &occurrences);
+    let placed: Vec<u64> = instances.iter().map(|(product, _)| *product).collect();
+    let (root, cube, plate) = (
+        products[0].entity_id,
+        products[1].entity_id,
+        products[2].entity_id,
+    );
+    assert_eq!(placed, [root, cube, cube, plate]);
+    assert!(near_matrix(instances[0].1, Matrix4::identity()));
+    assert!(near_matrix(instances[2].1, rotation));
+}
+
+#[test]
+#[should_panic]
+fn self_instance() {
+    let mut assembly = StepAssembly::<Point3, Curve, Surface>::default();
+    let root = assembly.push_product("ASM", "assembly");
+    assembly.push_instance(root, root, "loop", Matrix4::identity());
+}
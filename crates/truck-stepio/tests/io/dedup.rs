@@ -0,0 +1,127 @@
+use super::{cube, cube_at, plate};
+use truck_modeling::*;
+use truck_stepio::{out::*, r#in::Table};
+
+/// the sorted positions of the vertices of all the shells
+fn vertices(step_string: &str) -> Vec<[f64; 3]> {
+    let table = Table::from_step(step_string)
+        .unwrap_or_else(|| panic!("failed to parse step\n[STEP file]\n{step_string}"));
+    let mut vertices: Vec<[f64; 3]> = table
+        .shell
+        .values()
+        .flat_map(|step_shell| {
+            let shell = table.to_compressed_shell(step_shell).unwrap();
+            shell.vertices.into_iter().map(Into::into)
+        })
+        .collect();
+    vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    vertices
+}
+
+#[test]
+fn shared_geometry() {
+    let cubes = [cube(), cube_at(Point3::new(2.0, 0.0, 1.0e-9))];
+    let plate = plate();
+    let mut models = StepModels::default();
+    models.push_solid(&cubes[0]);
+    models.push_solid(&cubes[1]);
+    models.push_shell(&plate);
+    models.set_style(
+        2,
+        StepStyle {
+            name: "plate".to_string(),
+            colour: Some([0.0, 0.5, 1.0]),
+            transparency: None,
+        },
+    );
+    let verbatim = CompleteStepDisplay::new(models.clone(), Default::default()).to_string();
+    models.set_deduplication(Some(1.0e-6));
+    let shared = CompleteStepDisplay::new(models, Default::default()).to_string();
+
+    let count = |step_string: &str, keyword: &str| step_string.matches(keyword).count();
+    assert!(shared.len() < verbatim.len());
+    // the plate shares its plane and its lines with the bottom face of the first cube.
+    for keyword in ["CARTESIAN_POINT(", "DIRECTION(", "LINE(", "PLANE("] {
+        assert!(
+            count(&shared, keyword) < count(&verbatim, keyword),
+            "{keyword}"
+        );
+    }
+    for keyword in [
+        "VERTEX_POINT(",
+        "EDGE_CURVE(",
+        "FACE_SURFACE(",
+        "STYLED_ITEM(",
+    ] {
+        assert_eq!(
+            count(&shared, keyword),
+            count(&verbatim, keyword),
+            "{keyword}"
+        );
+    }
+
+    assert_eq!(vertices(&shared), vertices(&verbatim));
+    let table = Table::from_step(&shared).unwrap();
+    assert_eq!(table.manifold_solid_brep.len(), 2);
+    let (model_idx, _) = table
+        .shell_based_surface_model
+        .iter()
+        .find(|(_, model)| model.label.as_deref() == Some("plate"))
+        .unwrap();
+    let styled_items = table.styled_items();
+    assert_eq!(styled_items.len(), 1);
+    assert_eq!(styled_items[0].item, *model_idx);
+    assert_eq!(styled_items[0].colour, Some([0.0, 0.5, 1.0]));
+}
+
+#[test]
+fn no_deduplication_by_default() {
+    let cube = cube();
+    let mut models = StepModels::default();
+    models.push_solid(&cube);
+    let verbatim = CompleteStepDisplay::new(models.clone(), Default::default()).to_string();
+    models.set_deduplication(None);
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    // the time stamps may differ by the seconds.
+    let data = |step_string: &str| step_string.split("DATA;").nth(1).unwrap().to_string();
+    assert_eq!(data(&step_string), data(&verbatim));
+}
+
+#[test]
+fn share_models_pushed_after() {
+    let cube = cube();
+    let plate = plate();
+    let style = |name: &str| StepStyle {
+        name: name.to_string(),
+        colour: Some([1.0, 0.0, 0.0]),
+        transparency: None,
+    };
+    let mut before = StepModels::default();
+    before.push_solid(&cube);
+    before.push_shell(&plate);
+    before.set_style(0, style("cube"));
+    before.set_face_style(1, 0, style("face"));
+    before.set_deduplication(Some(1.0e-6));
+    let mut after = StepModels::default();
+    after.set_deduplication(Some(1.0e-6));
+    after.push_solid(&cube);
+    after.push_shell(&plate);
+    after.set_style(0, style("cube"));
+    after.set_face_style(1, 0, style("face"));
+
+    let before = CompleteStepDisplay::new(before, Default::default()).to_string();
+    let after = CompleteStepDisplay::new(after, Default::default()).to_string();
+    // the time stamps may differ by the seconds.
+    let data = |step_string: &str| step_string.split("DATA;").nth(1).unwrap().to_string();
+    assert_eq!(data(&before), data(&after));
+    assert!(before.matches("PLANE(").count() < 7);
+    let table = Table::from_step(&before).unwrap();
+    let (face_idx, _) = table
+        .face_surface
+        .iter()
+        .find(|(_, face)| face.label.as_deref() == Some("face"))
+        .unwrap();
+    let items: Vec<u64> = table.styled_items().iter().map(|item| item.item).collect();
+    assert_eq!(items.len(), 2);
+    assert!(items.contains(face_idx));
+}
@@ -0,0 +1,93 @@
+use truck_modeling::*;
+use truck_stepio::{out::*, r#in::Table};
+
+fn block(origin: Point3, height: f64) -> CompressedSolid<Point3, Curve, Surface> {
+    let v = builder::vertex(origin);
+    let e = builder::tsweep(&v, Vector3::unit_x());
+    let f = builder::tsweep(&e, Vector3::unit_y());
+    builder::tsweep(&f, Vector3::new(0.0, 0.0, height)).compress()
+}
+
+fn table(solids: &[CompressedSolid<Point3, Curve, Surface>]) -> Table {
+    let mut models = StepModels::default();
+    solids.iter().for_each(|solid| models.push_solid(solid));
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    Table::from_step(&step_string).unwrap()
+}
+
+#[test]
+fn same_models() {
+    let old = table(&[block(Point3::origin(), 1.0)]);
+    let new = table(&[block(Point3::origin(), 1.0)]);
+    let diff = old.diff(&new, 1.0e-6);
+    assert!(diff.is_empty(), "{diff}");
+    assert_eq!(diff.unchanged.len(), 1);
+    assert_eq!(diff.unchanged[0].faces.matched.len(), 6);
+}
+
+#[test]
+fn modified_and_added_solids() {
+    let old = table(&[
+        block(Point3::new(5.0, 0.0, 0.0), 1.0),
+        block(Point3::origin(), 1.0),
+    ]);
+    let new = table(&[
+        block(Point3::origin(), 1.0),
+        block(Point3::new(5.0, 0.0, 0.0), 2.0),
+        block(Point3::new(10.0, 0.0, 0.0), 1.0),
+    ]);
+    let diff = old.diff(&new, 1.0e-6);
+    assert!(!diff.is_empty());
+    assert_eq!(diff.unchanged.len(), 1);
+    assert!(diff.removed.is_empty());
+
+    assert_eq!(diff.modified.len(), 1);
+    let pair = &diff.modified[0];
+    assert!(f64::abs(diff.volume_change(pair) - 1.0) < 1.0e-2, "{diff}");
+    assert!(f64::abs(diff.area_change(pair) - 4.0) < 1.0e-2, "{diff}");
+    // only the bottom face stays, the top face moves and the side faces are stretched.
+    assert_eq!(pair.faces.matched.len(), 1);
+    assert_eq!(pair.faces.removed.len(), 5);
+    assert_eq!(pair.faces.added.len(), 5);
+
+    assert_eq!(diff.added.len(), 1);
+    let added = &diff.new[diff.added[0]];
+    assert!(f64::abs(added.bounding_box.min().x - 10.0) < 1.0e-2);
+    assert!(diff.to_string().contains("modified shell"));
+}
+
+#[test]
+fn failed_shells() {
+    let old = table(&[block(Point3::origin(), 1.0)]);
+    let new = Table::from_step(
+        "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = DIRECTION('', (0.0, 0.0, 1.0));
+#3 = VECTOR('', #2, 1.0);
+#4 = LINE('', #1, #3);
+#10 = VERTEX_POINT('', #1);
+#11 = EDGE_CURVE('', #10, #10, #4, .T.);
+#12 = ORIENTED_EDGE('', *, *, #11, .T.);
+#13 = EDGE_LOOP('', (#12));
+#14 = FACE_OUTER_BOUND('', #13, .T.);
+#15 = ADVANCED_FACE('', (#14), #31, .T.);
+#16 = CLOSED_SHELL('', (#15));
+ENDSEC;
+END-ISO-10303-21;
+",
+    )
+    .unwrap();
+    let diff = old.diff(&new, 1.0e-6);
+    assert!(diff.old_failed.is_empty());
+    let failed: Vec<u64> = diff.new_failed.iter().map(|(id, _)| *id).collect();
+    assert_eq!(failed, vec![16]);
+    assert!(diff.new.is_empty());
+    assert_eq!(diff.removed, vec![0]);
+    assert!(diff.to_string().contains("failed new shell #16"));
+}
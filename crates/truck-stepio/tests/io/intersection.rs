@@ -0,0 +1,157 @@
+use ruststep::{ast::DataSection, tables::EntityTable};
+use std::str::FromStr;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder, Curve, Solid, Surface};
+use truck_polymesh::PolylineCurve;
+use truck_stepio::{
+    out::*,
+    r#in::{
+        alias::{Curve3D, PCurve},
+        PcurveHolder, SurfaceCurveHolder, Table,
+    },
+};
+
+const TOL: f64 = 1.0e-5;
+
+/// the intersection of the plane `z = 0` and the surface `z = x^2 + y - 0.5`
+fn intersection_curve() -> IntersectionCurve<PolylineCurve<Point3>, BSplineSurface<Point3>> {
+    let plane = BSplineSurface::new(
+        (KnotVec::bezier_knot(1), KnotVec::bezier_knot(1)),
+        vec![
+            vec![Point3::new(-2.0, -2.0, 0.0), Point3::new(-2.0, 2.0, 0.0)],
+            vec![Point3::new(2.0, -2.0, 0.0), Point3::new(2.0, 2.0, 0.0)],
+        ],
+    );
+    let parabolic = BSplineSurface::new(
+        (KnotVec::bezier_knot(2), KnotVec::bezier_knot(1)),
+        vec![
+            vec![Point3::new(-1.0, -1.0, -0.5), Point3::new(-1.0, 1.0, 1.5)],
+            vec![Point3::new(0.0, -1.0, -2.5), Point3::new(0.0, 1.0, -0.5)],
+            vec![Point3::new(1.0, -1.0, -0.5), Point3::new(1.0, 1.0, 1.5)],
+        ],
+    );
+    let leader = (0..=10)
+        .map(|i| {
+            let x = -0.9 + 0.18 * i as f64;
+            Point3::new(x, 0.5 - x * x, 0.0)
+        })
+        .collect();
+    IntersectionCurve::new(plane, parabolic, PolylineCurve(leader))
+}
+
+fn on_intersection(p: Point3) -> bool {
+    p.z.abs() < 10.0 * TOL && (p.y - (0.5 - p.x * p.x)).abs() < 10.0 * TOL
+}
+
+#[test]
+fn approximation() {
+    let curve = intersection_curve();
+    let approx = IntersectionCurveApproximation::try_new(&curve, TOL).unwrap();
+    let bspline = approx.curve();
+    let (t0, t1) = bspline.range_tuple();
+    (0..=100).for_each(|i| {
+        let t = t0 + (t1 - t0) * i as f64 / 100.0;
+        let p = bspline.subs(t);
+        assert!(on_intersection(p), "{p:?}");
+        approx.pcurves().iter().for_each(|pcurve| {
+            let q = pcurve.subs(t);
+            assert!(p.distance(q) < 10.0 * TOL, "{p:?} {q:?}");
+        });
+    });
+    assert_near!(bspline.front(), curve.front());
+    assert_near!(bspline.back(), curve.back());
+}
+
+#[test]
+fn intersection_curve_roundtrip() {
+    let curve = intersection_curve();
+    let approx = IntersectionCurveApproximation::try_new(&curve, TOL).unwrap();
+    let step = format!("DATA;\n{}ENDSEC;", StepDisplay::new(&approx, 1));
+    let data_section = DataSection::from_str(&step).unwrap();
+    let table = Table::from_data_section(&data_section);
+    assert_eq!(table.pcurve.len(), 2);
+    let surface_curve = EntityTable::<SurfaceCurveHolder>::get_owned(&table, 1).unwrap();
+    let read = Curve3D::try_from(&surface_curve).unwrap();
+    let (t0, t1) = read.range_tuple();
+    (0..=100).for_each(|i| {
+        let p = read.subs(t0 + (t1 - t0) * i as f64 / 100.0);
+        assert!(on_intersection(p), "{p:?}");
+    });
+}
+
+fn cube(origin: Point3, size: f64) -> Solid {
+    let v = builder::vertex(origin);
+    let e = builder::tsweep(&v, Vector3::unit_x() * size);
+    let f = builder::tsweep(&e, Vector3::unit_y() * size);
+    builder::tsweep(&f, Vector3::unit_z() * size)
+}
+
+/// the cube `[0, 2]^3` without the corner `[1, 2]^3`, whose planes have the axes of length 2
+fn cut_cube() -> Solid {
+    let mut corner = cube(Point3::new(1.0, 1.0, 1.0), 2.0);
+    corner.not();
+    truck_shapeops::and(&cube(Point3::origin(), 2.0), &corner, 0.05).unwrap()
+}
+
+fn read_shells(step_string: &str) -> Table {
+    let table = Table::from_step(step_string)
+        .unwrap_or_else(|| panic!("failed to parse step\n[STEP file]\n{step_string}"));
+    table.shell.values().for_each(|shell| {
+        table.to_compressed_shell(shell).unwrap();
+    });
+    table
+}
+
+#[test]
+fn boolean_roundtrip() {
+    let solid = cut_cube().compress();
+    let step_string =
+        CompleteStepDisplay::new(StepModel::from(&solid), Default::default()).to_string();
+    let table = read_shells(&step_string);
+    assert!(!table.pcurve.is_empty(), "{step_string}");
+    // the pcurves on the planes are on the edges of the cut cube
+    let on_edge = |p: Point3| {
+        let on_grid =
+            |x: f64| (x - x.round()).abs() < 10.0 * TOL && (0.0..=2.0).contains(&x.round());
+        [p.x, p.y, p.z].into_iter().filter(|x| on_grid(*x)).count() >= 2
+    };
+    table.pcurve.keys().for_each(|idx| {
+        let pcurve = EntityTable::<PcurveHolder>::get_owned(&table, *idx).unwrap();
+        let read = Curve3D::PCurve(PCurve::try_from(&pcurve).unwrap());
+        let (t0, t1) = read.range_tuple();
+        (0..=10).for_each(|i| {
+            let p = read.subs(t0 + (t1 - t0) * i as f64 / 10.0);
+            assert!(on_edge(p), "#{idx}: {p:?}");
+        });
+    });
+}
+
+#[test]
+fn revolution_without_pcurves() {
+    let plane = Surface::Plane(Plane::new(
+        Point3::origin(),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(0.0, 2.0, 0.0),
+    ));
+    let line = Curve::Line(Line(
+        Point3::new(1.0, 0.0, -1.0),
+        Point3::new(1.0, 0.0, 1.0),
+    ));
+    let cylinder = Surface::RevolutedCurve(Processor::new(RevolutedCurve::by_revolution(
+        line,
+        Point3::origin(),
+        Vector3::unit_z(),
+    )));
+    let leader = (0..=10)
+        .map(|i| {
+            let t = 0.5 + 0.2 * i as f64;
+            Point3::new(f64::cos(t), f64::sin(t), 0.0)
+        })
+        .collect();
+    let curve = IntersectionCurve::new(plane, cylinder, PolylineCurve(leader));
+    let approx = IntersectionCurveApproximation::try_new(&curve, TOL).unwrap();
+    let step = StepDisplay::new(&approx, 1).to_string();
+    assert!(step.starts_with("#1 = B_SPLINE_CURVE_WITH_KNOTS"), "{step}");
+    assert!(!step.contains("PCURVE"), "{step}");
+    assert_eq!(approx.step_length(), approx.curve().step_length());
+}
@@ -1,2 +1,32 @@
+use truck_modeling::*;
+
+mod assembly;
+mod dedup;
+mod diff;
+mod intersection;
 mod ioi;
+mod mass;
+mod mesh;
 mod oi;
+mod style;
+mod units;
+mod writer;
+
+/// the unit cube whose corner of the minimum coordinates is `origin`
+pub fn cube_at(origin: Point3) -> CompressedSolid<Point3, Curve, Surface> {
+    let v = builder::vertex(origin);
+    let e = builder::tsweep(&v, Vector3::unit_x());
+    let f = builder::tsweep(&e, Vector3::unit_y());
+    builder::tsweep(&f, Vector3::unit_z()).compress()
+}
+
+/// the unit cube at the origin
+pub fn cube() -> CompressedSolid<Point3, Curve, Surface> { cube_at(Point3::origin()) }
+
+/// the unit square at the origin, the bottom of [`cube`]
+pub fn plate() -> CompressedShell<Point3, Curve, Surface> {
+    let v = builder::vertex(Point3::origin());
+    let e = builder::tsweep(&v, Vector3::unit_x());
+    let f = builder::tsweep(&e, Vector3::unit_y());
+    Shell::from(vec![f]).compress()
+}
@@ -0,0 +1,62 @@
+use truck_meshalgo::tessellation::MeshedShape;
+use truck_modeling::*;
+use truck_stepio::{
+    out::*,
+    r#in::{mass::MassProperties, Table},
+};
+
+fn block() -> CompressedSolid<Point3, Curve, Surface> {
+    let v = builder::vertex(Point3::new(5.0, -1.0, 2.0));
+    let e = builder::tsweep(&v, Vector3::new(0.0, 0.0, 3.0));
+    let f = builder::tsweep(&e, Vector3::new(0.0, 2.0, 0.0));
+    builder::tsweep(&f, Vector3::new(1.0, 0.0, 0.0)).compress()
+}
+
+#[test]
+fn block_mass_properties() {
+    let mut models = StepModels::default();
+    models.push_solid(&block());
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    let table = Table::from_step(&step_string).unwrap();
+    let meshed = table.triangulate_shells(|_| 0.01);
+    let shell = meshed[0].1.as_ref().unwrap();
+    let properties = MassProperties::from_meshed_shell(shell);
+
+    let near = |a: f64, b: f64| f64::abs(a - b) < 1.0e-8;
+    assert!(near(properties.volume, 6.0), "{properties:?}");
+    assert!(near(properties.area, 22.0), "{properties:?}");
+    let centre = properties.centre_of_mass;
+    assert!(near(centre[0], 5.5) && near(centre[1], 0.0) && near(centre[2], 3.5));
+    // the moments of the 1x2x3 block are V(b^2 + c^2)/12 and the like.
+    let moments = properties.principal_moments;
+    assert!(near(moments[0], 2.5) && near(moments[1], 5.0) && near(moments[2], 6.5));
+    let axes = properties.principal_axes;
+    assert!(near(axes[0][2].abs(), 1.0) && near(axes[1][1].abs(), 1.0));
+    assert!(near(axes[2][0].abs(), 1.0));
+
+    let scaled = properties.scaled(0.001);
+    assert!(near(scaled.volume, 6.0e-9) && near(scaled.centre_of_mass[0], 5.5e-3));
+}
+
+#[test]
+fn placed_mass_properties() {
+    let mut models = StepModels::default();
+    models.push_solid(&block());
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    let table = Table::from_step(&step_string).unwrap();
+    let meshed = table.triangulate_shells(|_| 0.01);
+    let mesh = meshed[0].1.as_ref().unwrap().to_polygon();
+    let translation = Matrix4::from_translation(Vector3::new(-5.5, 0.0, -3.5));
+    let rotation = Matrix4::from_angle_x(Rad(std::f64::consts::PI / 2.0));
+    let properties =
+        MassProperties::from_placed_meshes([(&mesh, translation), (&mesh, rotation * translation)]);
+
+    let near = |a: f64, b: f64| f64::abs(a - b) < 1.0e-8;
+    assert!(near(properties.volume, 12.0), "{properties:?}");
+    assert!(near(properties.area, 44.0), "{properties:?}");
+    let centre = properties.centre_of_mass;
+    assert!(near(centre[0], 0.0) && near(centre[1], 0.0) && near(centre[2], 0.0));
+    // the moments about the x axis are 6.5 for both, the others are 5.0 + 2.5.
+    let moments = properties.principal_moments;
+    assert!(near(moments[0], 7.5) && near(moments[1], 7.5) && near(moments[2], 13.0));
+}
@@ -0,0 +1,154 @@
+use ruststep::ast::{EntityInstance, Parameter};
+use truck_polymesh::*;
+use truck_stepio::{out::*, r#in::Table};
+
+fn vertex(pos: usize, nor: Option<usize>) -> StandardVertex {
+    StandardVertex { pos, uv: None, nor }
+}
+
+fn tetrahedron(with_normals: bool) -> PolygonMesh {
+    let positions = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.0, 1.0),
+    ];
+    let normals = vec![
+        -Vector3::unit_z(),
+        -Vector3::unit_y(),
+        -Vector3::unit_x(),
+        Vector3::new(1.0, 1.0, 1.0).normalize(),
+    ];
+    let triangles = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+    let tri_faces = triangles
+        .iter()
+        .enumerate()
+        .map(|(i, tri)| tri.map(|pos| vertex(pos, with_normals.then_some(i))))
+        .collect::<Vec<_>>();
+    let attrs = StandardAttributes {
+        positions,
+        normals: if with_normals { normals } else { Vec::new() },
+        ..Default::default()
+    };
+    PolygonMesh::new(attrs, Faces::from_tri_and_quad_faces(tri_faces, Vec::new()))
+}
+
+fn cube() -> PolygonMesh {
+    let positions = (0..8)
+        .map(|i| Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64))
+        .collect::<Vec<_>>();
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    let quad_faces = quads
+        .iter()
+        .map(|quad| quad.map(|pos| vertex(pos, None)))
+        .collect::<Vec<_>>();
+    let attrs = StandardAttributes {
+        positions,
+        ..Default::default()
+    };
+    PolygonMesh::new(
+        attrs,
+        Faces::from_tri_and_quad_faces(Vec::new(), quad_faces),
+    )
+}
+
+fn near(a: &PolygonMesh, b: &PolygonMesh) -> bool {
+    let triangles = |mesh: &PolygonMesh| {
+        mesh.faces()
+            .triangle_iter()
+            .map(|tri| tri.map(|v| mesh.positions()[v.pos]))
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (triangles(a), triangles(b));
+    a.len() == b.len()
+        && a.iter()
+            .zip(&b)
+            .all(|(a, b)| a.iter().zip(b).all(|(p, q)| p.distance(*q) < 1.0e-10))
+}
+
+#[test]
+fn tessellated_roundtrip() {
+    for with_normals in [false, true] {
+        let mesh = tetrahedron(with_normals);
+        let step_string =
+            CompleteStepDisplay::new(StepMeshes::from_iter([&mesh]), Default::default())
+                .to_string();
+        let table = Table::from_step(&step_string)
+            .unwrap_or_else(|| panic!("failed to parse step\n[STEP file]\n{step_string}"));
+        assert_eq!(table.tessellated_shell.len(), 1);
+        let face = table.triangulated_face.values().next().unwrap();
+        assert_eq!(face.pnindex.len(), if with_normals { 12 } else { 0 });
+        assert_eq!(face.pnmax, if with_normals { 12 } else { 4 });
+        let shell = table.tessellated_shell.values().next().unwrap();
+        let read = table.to_polygon_mesh(shell).unwrap();
+        assert!(near(&mesh, &read), "{step_string}");
+        match with_normals {
+            true => read
+                .faces()
+                .triangle_iter()
+                .enumerate()
+                .for_each(|(i, tri)| {
+                    tri.iter().for_each(|v| {
+                        let normal = read.normals()[v.nor.unwrap()];
+                        assert!(normal.distance(mesh.normals()[i]) < 1.0e-10);
+                    })
+                }),
+            false => assert!(read.normals().is_empty()),
+        }
+    }
+}
+
+#[test]
+fn tessellated_quads() {
+    let mesh = cube();
+    let step_string =
+        CompleteStepDisplay::new(StepMeshes::from_iter([&mesh]), Default::default()).to_string();
+    let table = Table::from_step(&step_string).unwrap();
+    let shell = table.tessellated_shell.values().next().unwrap();
+    let read = table.to_polygon_mesh(shell).unwrap();
+    assert_eq!(read.faces().len(), 12);
+    assert!(near(&mesh, &read));
+}
+
+#[test]
+fn faceted_brep() {
+    let (tetrahedron, cube) = (tetrahedron(true), cube());
+    let meshes = StepMeshes::new(StepMeshForm::FacetedBrep).with_meshes([&tetrahedron, &cube]);
+    let step_string = CompleteStepDisplay::new(meshes, Default::default()).to_string();
+    let exchange = ruststep::parser::parse(&step_string).unwrap_or_else(|e| {
+        panic!("failed to parse step\n[Error Message]\n{e}[STEP file]\n{step_string}")
+    });
+    let records = exchange.data[0]
+        .entities
+        .iter()
+        .filter_map(|instance| match instance {
+            EntityInstance::Simple { record, .. } => Some(record),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let count = |name: &str| records.iter().filter(|record| record.name == name).count();
+    assert_eq!(count("FACETED_BREP_SHAPE_REPRESENTATION"), 1);
+    assert_eq!(count("FACETED_BREP"), 2);
+    assert_eq!(count("CLOSED_SHELL"), 2);
+    assert_eq!(count("FACE"), 10);
+    assert_eq!(count("CARTESIAN_POINT"), 12);
+    let loop_lengths = records
+        .iter()
+        .filter(|record| record.name == "POLY_LOOP")
+        .map(|record| match &record.parameter {
+            Parameter::List(params) => match &params[1] {
+                Parameter::List(points) => points.len(),
+                _ => 0,
+            },
+            _ => 0,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(loop_lengths, [3, 3, 3, 3, 4, 4, 4, 4, 4, 4]);
+}
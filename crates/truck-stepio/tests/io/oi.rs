@@ -246,6 +246,18 @@ fn oi() {
             )),
         ))),
     ));
+    oitest_tryfrom::<alias::OffsetSurface<alias::Surface>, OffsetSurfaceHolder>(
+        alias::OffsetSurface::new(
+            alias::Surface::ElementarySurface(Box::new(alias::ElementarySurface::Plane(
+                truck::Plane::new(
+                    Point3::new(1.0, 2.0, 3.0),
+                    Point3::new(1.0, 2.0, 4.0),
+                    Point3::new(1.0, 1.0, 3.0),
+                ),
+            ))),
+            2.5,
+        ),
+    );
     oitest_tryfrom::<BSplineSurface<Point3>, BSplineSurfaceWithKnotsHolder>(BSplineSurface::new(
         (KnotVec::bezier_knot(3), KnotVec::bezier_knot(2)),
         vec![
@@ -0,0 +1,139 @@
+use super::{cube, plate};
+use truck_modeling::*;
+use truck_stepio::{out::*, r#in::Table};
+
+#[test]
+fn style_roundtrip() {
+    let (cube, plate) = (cube(), plate());
+    let mut models = StepModels::default();
+    models.push_solid(&cube);
+    models.push_shell(&plate);
+    models.set_style(
+        0,
+        StepStyle {
+            name: "red cube".to_string(),
+            colour: Some([1.0, 0.0, 0.0]),
+            transparency: None,
+        },
+    );
+    models.set_face_style(
+        0,
+        2,
+        StepStyle {
+            name: "side face".to_string(),
+            colour: Some([0.0, 0.5, 1.0]),
+            transparency: Some(0.25),
+        },
+    );
+    models.set_style(
+        1,
+        StepStyle {
+            name: "plate".to_string(),
+            colour: None,
+            transparency: Some(0.5),
+        },
+    );
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    let table = Table::from_step(&step_string)
+        .unwrap_or_else(|| panic!("failed to parse step\n[STEP file]\n{step_string}"));
+
+    let (solid_idx, _) = table
+        .manifold_solid_brep
+        .iter()
+        .find(|(_, brep)| brep.label.as_deref() == Some("red cube"))
+        .unwrap();
+    let (face_idx, _) = table
+        .face_surface
+        .iter()
+        .find(|(_, face)| face.label.as_deref() == Some("side face"))
+        .unwrap();
+    let (model_idx, _) = table
+        .shell_based_surface_model
+        .iter()
+        .find(|(_, model)| model.label.as_deref() == Some("plate"))
+        .unwrap();
+
+    let styled_items = table.styled_items();
+    assert_eq!(styled_items.len(), 3);
+    let find = |idx: &u64| styled_items.iter().find(|item| item.item == *idx).unwrap();
+    let solid_style = find(solid_idx);
+    assert_eq!(solid_style.colour, Some([1.0, 0.0, 0.0]));
+    assert_eq!(solid_style.transparency, None);
+    let face_style = find(face_idx);
+    assert_eq!(face_style.colour, Some([0.0, 0.5, 1.0]));
+    assert_eq!(face_style.transparency, Some(0.25));
+    let model_style = find(model_idx);
+    assert_eq!(model_style.colour, Some([1.0, 1.0, 1.0]));
+    assert_eq!(model_style.transparency, Some(0.5));
+}
+
+#[test]
+fn restyle() {
+    let cube = cube();
+    let mut model = StepModel::from(&cube);
+    model.set_style(StepStyle {
+        colour: Some([1.0, 0.0, 0.0]),
+        ..Default::default()
+    });
+    model.set_style(StepStyle {
+        colour: Some([0.0, 1.0, 0.0]),
+        ..Default::default()
+    });
+    let step_string = CompleteStepDisplay::new(model, Default::default()).to_string();
+    let table = Table::from_step(&step_string).unwrap();
+    let styled_items = table.styled_items();
+    assert_eq!(styled_items.len(), 1);
+    assert_eq!(styled_items[0].colour, Some([0.0, 1.0, 0.0]));
+}
+
+#[test]
+#[should_panic]
+fn no_face() {
+    let cube = cube();
+    let mut model = StepModel::from(&cube);
+    model.set_face_style(6, StepStyle::default());
+}
+
+#[test]
+fn shell_styles() {
+    let (cube, plate) = (cube(), plate());
+    let mut models = StepModels::default();
+    models.push_solid(&cube);
+    models.push_shell(&plate);
+    models.set_style(
+        0,
+        StepStyle {
+            colour: Some([1.0, 0.0, 0.0]),
+            ..Default::default()
+        },
+    );
+    models.set_face_style(
+        0,
+        2,
+        StepStyle {
+            colour: Some([0.0, 0.5, 1.0]),
+            ..Default::default()
+        },
+    );
+    models.set_style(
+        1,
+        StepStyle {
+            colour: Some([0.0, 1.0, 0.0]),
+            ..Default::default()
+        },
+    );
+    let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+    let table = Table::from_step(&step_string).unwrap();
+    let styles = table.shell_styles();
+    // the styles of the solid and the surface model are given to their shells, not the face.
+    assert_eq!(styles.len(), 2);
+    let mut colours: Vec<_> = styles
+        .iter()
+        .map(|(idx, style)| {
+            assert!(table.shell.contains_key(idx));
+            style.colour.unwrap()
+        })
+        .collect();
+    colours.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(colours, vec![[0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+}
@@ -0,0 +1,89 @@
+use super::cube;
+use std::f64::consts::PI;
+use truck_modeling::*;
+use truck_stepio::{
+    out::*,
+    r#in::{units::StepUnitContext, Table},
+};
+
+fn near(a: f64, b: f64) -> bool { f64::abs(a - b) <= f64::abs(b) * 1.0e-10 }
+
+#[test]
+fn default_units() {
+    let cube = cube();
+    let step_string =
+        CompleteStepDisplay::new(StepModel::from(&cube), Default::default()).to_string();
+    let units = StepUnitContext::from_step(&step_string).unwrap();
+    assert_eq!(units.length, 0.001);
+    assert_eq!(units.length_name, "millimetre");
+    assert_eq!(units.angle, 1.0);
+    assert_eq!(units.angle_name, "radian");
+    assert!(near(units.uncertainty.unwrap(), 1.0e-6));
+}
+
+#[test]
+fn units_roundtrip() {
+    let cube = cube();
+    let lengths = [
+        (StepLengthUnit::Millimetre, 0.001, "millimetre"),
+        (StepLengthUnit::Metre, 1.0, "metre"),
+        (StepLengthUnit::Inch, 0.0254, "inch"),
+    ];
+    let angles = [
+        (StepAngleUnit::Radian, 1.0, "radian"),
+        (StepAngleUnit::Degree, PI / 180.0, "degree"),
+    ];
+    for (length, length_factor, length_name) in lengths {
+        for (angle, angle_factor, angle_name) in angles {
+            let mut models = StepModels::default();
+            models.push_solid(&cube);
+            models.set_style(
+                0,
+                StepStyle {
+                    colour: Some([1.0, 0.0, 0.0]),
+                    ..Default::default()
+                },
+            );
+            models.set_units(StepUnits {
+                length,
+                angle,
+                uncertainty: 1.0e-4,
+            });
+            let step_string = CompleteStepDisplay::new(models, Default::default()).to_string();
+            let units = StepUnitContext::from_step(&step_string)
+                .unwrap_or_else(|| panic!("failed to read units\n[STEP file]\n{step_string}"));
+            assert!(near(units.length, length_factor), "{units:?}");
+            assert_eq!(units.length_name, length_name);
+            assert!(near(units.angle, angle_factor), "{units:?}");
+            assert_eq!(units.angle_name, angle_name);
+            assert!(near(units.uncertainty.unwrap(), 1.0e-4), "{units:?}");
+
+            // the geometry and the styles are kept
+            let table = Table::from_step(&step_string).unwrap();
+            assert_eq!(table.shell.len(), 1);
+            assert_eq!(table.styled_items().len(), 1);
+        }
+    }
+}
+
+#[test]
+fn assembly_units() {
+    let cube = cube();
+    let mut assembly = StepAssembly::default();
+    let root = assembly.push_product("ASM", "assembly");
+    let part = assembly.push_product("P-1", "cube");
+    assembly.push_solid(part, &cube);
+    assembly.push_instance(root, part, "cube-1", Matrix4::identity());
+    assembly.set_units(StepUnits {
+        length: StepLengthUnit::Inch,
+        angle: StepAngleUnit::Degree,
+        uncertainty: 1.0e-5,
+    });
+    let step_string = CompleteStepDisplay::new(assembly, Default::default()).to_string();
+    let units = StepUnitContext::from_step(&step_string).unwrap();
+    assert!(near(units.length, 0.0254));
+    assert!(near(units.angle, PI / 180.0));
+    assert!(near(units.uncertainty.unwrap(), 1.0e-5));
+    let table = Table::from_step(&step_string).unwrap();
+    assert_eq!(table.product_occurrences().len(), 1);
+}
@@ -0,0 +1,97 @@
+use super::{cube, plate};
+use std::io::{self, Write};
+use truck_modeling::*;
+use truck_stepio::{out::*, r#in::Table};
+
+fn header() -> StepHeaderDescriptor {
+    StepHeaderDescriptor {
+        time_stamp: "2024-01-01T00:00:00".to_string(),
+        ..Default::default()
+    }
+}
+
+/// accepts `capacity` bytes and then fails
+struct Limited {
+    capacity: usize,
+}
+
+impl Write for Limited {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.capacity {
+            0 => Err(io::Error::new(io::ErrorKind::WriteZero, "disk full")),
+            _ => {
+                let len = usize::min(buf.len(), self.capacity);
+                self.capacity -= len;
+                Ok(len)
+            }
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn stream_models() {
+    let (cube, plate) = (cube(), plate());
+    let mut models = StepModels::default();
+    models.push_solid(&cube);
+    models.push_shell(&plate);
+    models.set_style(
+        1,
+        StepStyle {
+            colour: Some([0.0, 0.5, 1.0]),
+            ..Default::default()
+        },
+    );
+
+    let mut progress = Vec::new();
+    let mut writer = StepWriter::new(Vec::new(), header());
+    writer.set_progress(|written, total| progress.push((written, total)));
+    writer.write_models(&models).unwrap();
+    let streamed = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(progress, vec![(1, 2), (2, 2)]);
+
+    let formatted = CompleteStepDisplay::new(models, header()).to_string();
+    assert_eq!(streamed, formatted);
+    let table = Table::from_step(&streamed).unwrap();
+    assert_eq!(table.manifold_solid_brep.len(), 1);
+    assert_eq!(table.shell_based_surface_model.len(), 1);
+}
+
+#[test]
+fn stream_deduplicated_models_and_model() {
+    let cube = cube();
+    let mut models = StepModels::default();
+    models.push_solid(&cube);
+    models.set_deduplication(Some(1.0e-6));
+    models.push_solid(&cube);
+    let mut progress = Vec::new();
+    let mut writer = StepWriter::new(Vec::new(), header());
+    writer.set_progress(|written, total| progress.push((written, total)));
+    writer.write_models(&models).unwrap();
+    let streamed = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(progress, vec![(1, 2), (2, 2)]);
+    assert_eq!(
+        streamed,
+        CompleteStepDisplay::new(models, header()).to_string()
+    );
+
+    let mut writer = StepWriter::new(Vec::new(), header());
+    writer.write_display(StepModel::from(&cube)).unwrap();
+    let streamed = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let formatted = CompleteStepDisplay::new(StepModel::from(&cube), header()).to_string();
+    assert_eq!(streamed, formatted);
+}
+
+#[test]
+fn io_errors() {
+    let cube = cube();
+    let mut models = StepModels::default();
+    (0..64).for_each(|_| models.push_solid(&cube));
+    let mut progress = 0;
+    let mut writer = StepWriter::new(Limited { capacity: 100_000 }, header());
+    writer.set_progress(|written, _| progress = written);
+    let error = writer.write_models(&models).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    drop(writer);
+    assert!(0 < progress && progress < 64, "{progress}");
+}